@@ -77,5 +77,5 @@ pub fn run() {
             .unwrap(),
         State::new(),
     )
-    .run();
+    .mount();
 }
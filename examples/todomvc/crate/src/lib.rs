@@ -361,5 +361,5 @@ pub fn run() {
             .unwrap(),
         State::new(),
     )
-    .run();
+    .mount();
 }
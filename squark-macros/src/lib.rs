@@ -9,7 +9,7 @@ extern crate proc_macro;
 use parser::{Parser as ViewParser, Rule};
 use pest::iterators::{Pair, Pairs};
 use pest::Parser;
-use proc_macro::{quote, Literal, TokenStream, TokenTree};
+use proc_macro::{quote, Delimiter, Literal, TokenStream, TokenTree};
 use std::iter::FromIterator;
 use std::str::FromStr;
 
@@ -96,7 +96,7 @@ fn get_token_stream(mut tag_pairs: Pairs<Rule>) -> TokenStream {
 
     quote! {
         _squark::View::new(
-            $_name.to_string(),
+            _squark::Tag::from_name($_name),
             vec![
                 $_attributes
             ],
@@ -123,3 +123,211 @@ pub fn view(arg: TokenStream) -> TokenStream {
         }
     }
 }
+
+fn is_punct(token: &TokenTree, c: char) -> bool {
+    match *token {
+        TokenTree::Punct(ref p) => p.as_char() == c,
+        _ => false,
+    }
+}
+
+// Splits at the commas outside of angle brackets.
+fn split_commas(stream: TokenStream) -> Vec<TokenStream> {
+    let mut parts = vec![vec![]];
+    let mut depth = 0;
+    let mut after_minus = false;
+    for token in stream {
+        if is_punct(&token, ',') && depth == 0 {
+            parts.push(vec![]);
+            continue;
+        }
+        if is_punct(&token, '<') {
+            depth += 1;
+        } else if is_punct(&token, '>') && !after_minus {
+            depth -= 1;
+        }
+        after_minus = is_punct(&token, '-');
+        parts.last_mut().unwrap().push(token);
+    }
+    parts
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .map(TokenStream::from_iter)
+        .collect()
+}
+
+fn camel_case(snake: &str) -> String {
+    snake
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(c) => c.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Implements `Clone` and `Default` for an app, cloning and defaulting it
+/// field by field.
+#[proc_macro_derive(SquarkApp)]
+pub fn derive_squark_app(item: TokenStream) -> TokenStream {
+    let tokens: Vec<TokenTree> = item.into_iter().collect();
+    let position = tokens
+        .iter()
+        .position(|t| t.to_string() == "struct")
+        .expect("SquarkApp can only be derived for structs");
+    let name = tokens[position + 1].to_string();
+    let (clone, default) = match tokens.get(position + 2) {
+        Some(TokenTree::Group(ref fields)) if fields.delimiter() == Delimiter::Brace => {
+            let names: Vec<String> = split_commas(fields.stream())
+                .into_iter()
+                .map(|field| {
+                    let tokens: Vec<TokenTree> = field.into_iter().collect();
+                    let colon = tokens.iter().position(|t| is_punct(t, ':')).unwrap();
+                    tokens[colon - 1].to_string()
+                })
+                .collect();
+            let clone: Vec<String> = names
+                .iter()
+                .map(|n| format!("{0}: ::std::clone::Clone::clone(&self.{0})", n))
+                .collect();
+            let default: Vec<String> = names
+                .iter()
+                .map(|n| format!("{}: ::std::default::Default::default()", n))
+                .collect();
+            (
+                format!("{} {{ {} }}", name, clone.join(", ")),
+                format!("{} {{ {} }}", name, default.join(", ")),
+            )
+        }
+        Some(TokenTree::Group(ref fields)) if fields.delimiter() == Delimiter::Parenthesis => {
+            let count = split_commas(fields.stream()).len();
+            let clone: Vec<String> = (0..count)
+                .map(|i| format!("::std::clone::Clone::clone(&self.{})", i))
+                .collect();
+            let default = vec!["::std::default::Default::default()"; count];
+            (
+                format!("{}({})", name, clone.join(", ")),
+                format!("{}({})", name, default.join(", ")),
+            )
+        }
+        _ => (name.clone(), name.clone()),
+    };
+    let code = format!(
+        "impl ::std::clone::Clone for {0} {{ fn clone(&self) -> {0} {{ {1} }} }}
+         impl ::std::default::Default for {0} {{ fn default() -> {0} {{ {2} }} }}",
+        name, clone, default
+    );
+    TokenStream::from_str(&code).unwrap()
+}
+
+/// Implements `App` for the type of an inherent impl holding its methods,
+/// given as `#[app(state = State, action = Action)]`.
+///
+/// Each variant of the action enum is reduced by a method named after it,
+/// `fn on_add_todo(&self, state: State, title: String) -> State` for
+/// `Action::AddTodo(String)`, taking the state and then the variant's
+/// fields. Methods named after those of `App`, such as `view` and
+/// `command`, are forwarded to.
+#[proc_macro_attribute]
+pub fn app(args: TokenStream, item: TokenStream) -> TokenStream {
+    let mut state = None;
+    let mut action = None;
+    for arg in split_commas(args) {
+        let tokens: Vec<TokenTree> = arg.into_iter().collect();
+        let value = tokens[2..]
+            .iter()
+            .map(|t| t.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        match &*tokens[0].to_string() {
+            "state" => state = Some(value),
+            "action" => action = Some(value),
+            other => panic!("unknown app argument `{}`", other),
+        }
+    }
+    let state = state.expect("app needs `state = ...`");
+    let action = action.expect("app needs `action = ...`");
+
+    let tokens: Vec<TokenTree> = item.clone().into_iter().collect();
+    let start = tokens.iter().position(|t| t.to_string() == "impl").unwrap() + 1;
+    let body = match tokens.last() {
+        Some(TokenTree::Group(ref body)) if body.delimiter() == Delimiter::Brace => body.stream(),
+        _ => panic!("app must be put on an impl block"),
+    };
+    let self_ty = tokens[start..tokens.len() - 1]
+        .iter()
+        .map(|t| t.to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut arms = vec![];
+    let mut forwards = vec![];
+    let body: Vec<TokenTree> = body.into_iter().collect();
+    for (i, token) in body.iter().enumerate() {
+        if token.to_string() != "fn" {
+            continue;
+        }
+        let name = body[i + 1].to_string();
+        let params = match body.get(i + 2) {
+            Some(TokenTree::Group(ref params)) => split_commas(params.stream()).len(),
+            _ => continue,
+        };
+        if let Some(variant) = name.strip_prefix("on_") {
+            let fields: Vec<String> = (0..params - 2).map(|i| format!("field{}", i)).collect();
+            let pattern = if fields.is_empty() {
+                String::new()
+            } else {
+                format!("({})", fields.join(", "))
+            };
+            arms.push(format!(
+                "{}::{}{} => self.{}(state{}),",
+                action,
+                camel_case(variant),
+                pattern,
+                name,
+                fields
+                    .iter()
+                    .map(|f| format!(", {}", f))
+                    .collect::<String>()
+            ));
+            continue;
+        }
+        let forward = match &*name {
+            "view" => "fn view(&self, state: {S}) -> _squark::View<{A}> { Self::view(self, state) }",
+            "command" => "fn command(&self, state: &{S}, action: &{A}) -> _squark::Cmd<{A}> { Self::command(self, state, action) }",
+            "subscriptions" => "fn subscriptions(&self, state: &{S}) -> Vec<_squark::Subscription<{A}>> { Self::subscriptions(self, state) }",
+            "translator" => "fn translator(&self, state: &{S}) -> Option<::std::rc::Rc<dyn _squark::Translator>> { Self::translator(self, state) }",
+            "roots" => "fn roots(&self, state: &{S}) -> Vec<(String, _squark::View<{A}>)> { Self::roots(self, state) }",
+            _ => continue,
+        };
+        forwards.push(forward.replace("{S}", &state).replace("{A}", &action));
+    }
+
+    let code = format!(
+        "const _: () = {{
+             extern crate squark as _squark;
+
+             impl _squark::App for {T} {{
+                 type State = {S};
+                 type Action = {A};
+
+                 fn reducer(&self, state: {S}, action: {A}) -> {S} {{
+                     match action {{ {arms} }}
+                 }}
+
+                 {forwards}
+             }}
+         }};",
+        T = self_ty,
+        S = state,
+        A = action,
+        arms = arms.concat(),
+        forwards = forwards.concat()
+    );
+    let mut stream = item;
+    stream.extend(TokenStream::from_str(&code).unwrap());
+    stream
+}
@@ -0,0 +1,68 @@
+extern crate squark;
+extern crate squark_macros;
+
+use squark::{App, Cmd, View};
+use squark_macros::{app, SquarkApp};
+
+#[derive(Clone, Debug)]
+enum Action {
+    Increment,
+    Add(i32),
+    Set(i32, String),
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct State {
+    count: i32,
+    label: String,
+}
+
+#[derive(SquarkApp)]
+struct Counter {
+    step: i32,
+}
+
+#[app(state = State, action = Action)]
+impl Counter {
+    fn on_increment(&self, state: State) -> State {
+        self.on_add(state, self.step.max(1))
+    }
+
+    fn on_add(&self, mut state: State, n: i32) -> State {
+        state.count += n;
+        state
+    }
+
+    fn on_set(&self, _state: State, count: i32, label: String) -> State {
+        State { count, label }
+    }
+
+    fn command(&self, _state: &State, action: &Action) -> Cmd<Action> {
+        match *action {
+            Action::Increment => Cmd::set_title("incremented"),
+            _ => Cmd::none(),
+        }
+    }
+
+    fn view(&self, state: State) -> View<Action> {
+        View::text(format!("{} {}", state.label, state.count))
+    }
+}
+
+#[test]
+fn reducers_are_wired_by_variant_name() {
+    let app = Counter::default().clone();
+    let state = app.reducer(State::default(), Action::Increment);
+    assert_eq!(state.count, 1);
+    let state = app.reducer(state, Action::Add(2));
+    assert_eq!(state.count, 3);
+    let state = app.reducer(state, Action::Set(7, "seven".to_string()));
+    assert_eq!(
+        state,
+        State {
+            count: 7,
+            label: "seven".to_string()
+        }
+    );
+    assert!(!app.command(&state, &Action::Increment).is_none());
+}
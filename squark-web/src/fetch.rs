@@ -0,0 +1,106 @@
+use std::future::{self, Future};
+use std::mem;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use js_sys::Array;
+use squark::{Fetch, FetchError, FetchFuture, Request, Response};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{window, RequestInit};
+
+pub struct WebFetch;
+
+enum State {
+    Head(JsFuture),
+    Body(u16, Vec<(String, String)>, JsFuture),
+    Done,
+}
+
+struct ResponseFuture {
+    state: State,
+}
+
+fn to_error(err: JsValue) -> FetchError {
+    FetchError::Network(err.as_string().unwrap_or_else(|| format!("{:?}", err)))
+}
+
+fn read_headers(res: &web_sys::Response) -> Vec<(String, String)> {
+    let iter = match js_sys::try_iter(res.headers().as_ref()) {
+        Ok(Some(iter)) => iter,
+        _ => return vec![],
+    };
+    iter.filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let entry: Array = entry.unchecked_into();
+            Some((entry.get(0).as_string()?, entry.get(1).as_string()?))
+        })
+        .collect()
+}
+
+impl Future for ResponseFuture {
+    type Output = Result<Response, FetchError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        loop {
+            match mem::replace(&mut self.state, State::Done) {
+                State::Head(mut head) => match Pin::new(&mut head).poll(cx) {
+                    Poll::Pending => {
+                        self.state = State::Head(head);
+                        return Poll::Pending;
+                    }
+                    Poll::Ready(Err(err)) => return Poll::Ready(Err(to_error(err))),
+                    Poll::Ready(Ok(res)) => {
+                        let res: web_sys::Response = res.unchecked_into();
+                        let text = match res.text() {
+                            Ok(text) => text,
+                            Err(err) => return Poll::Ready(Err(to_error(err))),
+                        };
+                        self.state = State::Body(res.status(), read_headers(&res), text.into());
+                    }
+                },
+                State::Body(status, headers, mut body) => match Pin::new(&mut body).poll(cx) {
+                    Poll::Pending => {
+                        self.state = State::Body(status, headers, body);
+                        return Poll::Pending;
+                    }
+                    Poll::Ready(Err(err)) => return Poll::Ready(Err(to_error(err))),
+                    Poll::Ready(Ok(body)) => {
+                        return Poll::Ready(Ok(Response {
+                            status,
+                            headers,
+                            body: body.as_string().unwrap_or_default(),
+                        }))
+                    }
+                },
+                State::Done => panic!("ResponseFuture polled after completion"),
+            }
+        }
+    }
+}
+
+impl Fetch for WebFetch {
+    fn request(&self, request: Request) -> FetchFuture {
+        let init = RequestInit::new();
+        init.set_method(request.method().as_str());
+        if let Some(body) = request.get_body() {
+            init.set_body(&body.into());
+        }
+
+        let web_request = match web_sys::Request::new_with_str_and_init(request.url(), &init) {
+            Ok(r) => r,
+            Err(err) => return Box::pin(future::ready(Err(to_error(err)))),
+        };
+        for (key, value) in request.headers() {
+            if let Err(err) = web_request.headers().set(key, value) {
+                return Box::pin(future::ready(Err(to_error(err))));
+            }
+        }
+
+        let promise = window().unwrap().fetch_with_request(&web_request);
+        Box::pin(ResponseFuture {
+            state: State::Head(promise.into()),
+        })
+    }
+}
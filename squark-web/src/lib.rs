@@ -4,32 +4,73 @@ extern crate wasm_bindgen;
 extern crate serde_json;
 extern crate js_sys;
 extern crate squark;
+extern crate wasm_bindgen_futures;
 extern crate web_sys;
 
-use std::cell::RefCell;
+mod fetch;
+mod storage;
+mod websocket;
+
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::rc::Rc;
 
 use squark::{
-    uuid, App, AttributeValue, Diff, Element as SquarkElement, Env, HandlerArg, Node as SquarkNode,
-    Runtime,
+    uuid, Addressing, App, AttributeValue, MediaControl, CUSTOM_ATTRIBUTE, REPORT_CARET, Politeness, FOCUS_TRAP, Diff, Element as SquarkElement, Env, Fetch, HandlerArg, KeyValueStorage,
+    Node as SquarkNode, Runtime, ScrollBehavior, SubscriptionKind, Task, OBSERVE_KEY, SCROLL_KEY,
 };
+use squark::log::{self, Level};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
-use web_sys::{window, Document, Element, EventTarget, HtmlElement, Node};
+use web_sys::{
+    window, Document, Element, EventTarget, HtmlElement, Node, ShadowRootInit, ShadowRootMode,
+};
+
+pub use fetch::WebFetch;
+pub use storage::WebStorage;
 
 trait ToHandlerArg: JsCast {
     fn to_handler_arg(self) -> HandlerArg;
 }
 
+// Events of media elements carry their playback state, see `MediaEvent`, and
+// those of selects the selected values, see `selected_values`.
 impl ToHandlerArg for web_sys::Event {
     fn to_handler_arg(self) -> HandlerArg {
-        json!{null}
+        let select = match self.target() {
+            Some(target) => target.dyn_into::<web_sys::HtmlSelectElement>().ok(),
+            None => None,
+        };
+        if let Some(select) = select {
+            if !select.multiple() {
+                return json!{select.value()};
+            }
+            let options = select.selected_options();
+            let values: Vec<String> = (0..options.length())
+                .filter_map(|i| options.item(i))
+                .map(|option| option.unchecked_into::<web_sys::HtmlOptionElement>().value())
+                .collect();
+            return json!{values};
+        }
+        let media = match self.target() {
+            Some(target) => target.dyn_into::<web_sys::HtmlMediaElement>().ok(),
+            None => None,
+        };
+        match media {
+            Some(media) => json!{{
+                "currentTime": media.current_time(),
+                "duration": media.duration(),
+                "paused": media.paused(),
+                "ended": media.ended(),
+            }},
+            None => json!{null},
+        }
     }
 }
 
 impl ToHandlerArg for web_sys::InputEvent {
     fn to_handler_arg(self) -> HandlerArg {
+        let composing = self.is_composing();
         let ev: web_sys::Event = self.into();
         let target = ev.target().unwrap();
         let js_val: &JsValue = target.as_ref();
@@ -37,22 +78,110 @@ impl ToHandlerArg for web_sys::InputEvent {
             return json!{""};
         }
         let input_el: &web_sys::HtmlInputElement = target.unchecked_ref();
+        if composing {
+            return json!{{ "value": input_el.value(), "composing": true }};
+        }
+        if input_el.has_attribute(REPORT_CARET) {
+            let caret = input_el.selection_start().ok().flatten();
+            return json!{{ "value": input_el.value(), "caret": caret }};
+        }
         json!{input_el.value()}
     }
 }
 
+impl ToHandlerArg for web_sys::CompositionEvent {
+    fn to_handler_arg(self) -> HandlerArg {
+        let data = self.data().unwrap_or_default();
+        let ev: web_sys::Event = self.into();
+        let value = ev
+            .target()
+            .map(|target| target.unchecked_into::<web_sys::HtmlInputElement>().value());
+        json!{{ "data": data, "value": value.unwrap_or_default() }}
+    }
+}
+
 impl ToHandlerArg for web_sys::KeyboardEvent {
     fn to_handler_arg(self) -> HandlerArg {
         json!{self.key()}
     }
 }
 
+// The ids of the event target and its ancestors scope shortcuts.
+fn key_down_arg(e: &web_sys::KeyboardEvent) -> HandlerArg {
+    let mut scopes = vec![];
+    let mut el = e.target().and_then(|t| t.dyn_into::<Element>().ok());
+    while let Some(current) = el {
+        let id = current.id();
+        if !id.is_empty() {
+            scopes.push(id);
+        }
+        el = current.parent_element();
+    }
+    json!{{
+        "key": e.key(),
+        "ctrl": e.ctrl_key(),
+        "alt": e.alt_key(),
+        "shift": e.shift_key(),
+        "meta": e.meta_key(),
+        "scopes": scopes,
+    }}
+}
+
 type AttachedMap = HashMap<String, HashMap<String, Closure<Fn(JsValue)>>>;
+// The trapping element, the element focused before and the Tab listener.
+type FocusTrap = (Element, Option<HtmlElement>, Closure<dyn FnMut(web_sys::KeyboardEvent)>);
+// Observers of elements tagged with `OBSERVE_KEY`, by subscription id.
+type ObserverMap = HashMap<String, (String, Observer)>;
+// Requests the next frame from within itself.
+type FrameLoop = Rc<RefCell<Option<Closure<dyn FnMut(f64)>>>>;
+type CustomHook = Rc<dyn Fn(&Element)>;
+type SubscriptionMap = HashMap<String, Box<dyn FnOnce()>>;
+type ScrollMap = HashMap<String, (i32, i32)>;
+
+// Out of sight but still read by screen readers.
+// Set on elements with `Addressing::StableIds`, so that removed ones are
+// forgotten.
+const NODE_ID: &str = "data-node-id";
+
+const VISUALLY_HIDDEN: &str =
+    "position:absolute;width:1px;height:1px;overflow:hidden;clip:rect(0 0 0 0);white-space:nowrap";
+
+// The clock animation frames are timed with, so that `Every` and
+// `AnimationFrame` ticks can be mixed.
+fn now() -> f64 {
+    window().unwrap().performance().unwrap().now()
+}
+
+fn request_animation_frame(closure: &Closure<dyn FnMut(f64)>) -> i32 {
+    window()
+        .unwrap()
+        .request_animation_frame(closure.as_ref().unchecked_ref())
+        .unwrap()
+}
 
 fn document() -> Document {
     window().unwrap().document().unwrap()
 }
 
+fn is_online() -> bool {
+    window().unwrap().navigator().on_line()
+}
+
+fn current_url() -> String {
+    let location = window().unwrap().location();
+    format!(
+        "{}{}{}",
+        location.pathname().unwrap_or_default(),
+        location.search().unwrap_or_default(),
+        location.hash().unwrap_or_default()
+    )
+}
+
+fn notify_url_change() {
+    let ev = web_sys::PopStateEvent::new("popstate").unwrap();
+    window().unwrap().dispatch_event(ev.as_ref()).unwrap();
+}
+
 fn handler_id(el: &HtmlElement) -> String {
     let dataset = el.dataset();
 
@@ -67,11 +196,49 @@ fn handler_id(el: &HtmlElement) -> String {
     id
 }
 
+enum Observer {
+    Intersection(web_sys::IntersectionObserver),
+    Resize(web_sys::ResizeObserver),
+}
+
+impl Observer {
+    fn observe(&self, el: &Element) {
+        match self {
+            Observer::Intersection(observer) => observer.observe(el),
+            Observer::Resize(observer) => observer.observe(el),
+        }
+    }
+
+    fn unobserve(&self, el: &Element) {
+        match self {
+            Observer::Intersection(observer) => observer.unobserve(el),
+            Observer::Resize(observer) => observer.unobserve(el),
+        }
+    }
+
+    fn disconnect(&self) {
+        match self {
+            Observer::Intersection(observer) => observer.disconnect(),
+            Observer::Resize(observer) => observer.disconnect(),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct WebRuntime<A: App> {
     env: Env<A>,
     root: Rc<Element>,
+    roots: Rc<RefCell<HashMap<String, Element>>>,
     attached_map: Rc<RefCell<AttachedMap>>,
+    subscriptions: Rc<RefCell<SubscriptionMap>>,
+    scroll_map: Rc<RefCell<ScrollMap>>,
+    style_sheet: Rc<RefCell<Option<Element>>>,
+    live_regions: Rc<RefCell<HashMap<Politeness, Element>>>,
+    focus_traps: Rc<RefCell<Vec<FocusTrap>>>,
+    observers: Rc<RefCell<ObserverMap>>,
+    custom_hooks: Rc<RefCell<HashMap<String, (CustomHook, CustomHook)>>>,
+    // Elements by `node_id`, kept with `Addressing::StableIds`.
+    nodes: Rc<RefCell<HashMap<u64, Element>>>,
 }
 
 fn insert_at(parent: &Node, i: usize, node: &Node) {
@@ -83,45 +250,247 @@ fn insert_at(parent: &Node, i: usize, node: &Node) {
     parent.insert_before(&node, ref_node.as_ref()).unwrap();
 }
 
+// Keys are kept on elements as a property, not rendered.
+const KEY_PROPERTY: &str = "__squarkKey";
+
+fn keyed_index(parent: &Node, key: &str) -> Option<usize> {
+    let children = parent.child_nodes();
+    (0..children.length()).map(|i| children.item(i).unwrap()).position(|child| {
+        js_sys::Reflect::get(child.as_ref(), &KEY_PROPERTY.into())
+            .ok()
+            .and_then(|value| value.as_string())
+            .as_deref()
+            == Some(key)
+    })
+}
+
+const FOCUSABLE: &str = "a[href], button:not([disabled]), input:not([disabled]), \
+    select:not([disabled]), textarea:not([disabled]), [tabindex]:not([tabindex='-1'])";
+
+fn focusable(el: &Element) -> Vec<HtmlElement> {
+    let list = el.query_selector_all(FOCUSABLE).unwrap();
+    (0..list.length())
+        .filter_map(|i| list.item(i))
+        .map(|node| node.unchecked_into())
+        .collect()
+}
+
+fn marked(node: &Node, attribute: &str) -> Vec<Element> {
+    if !node.is_instance_of::<Element>() {
+        return vec![];
+    }
+    let el: &Element = node.unchecked_ref();
+
+    let mut marked = vec![];
+    if el.has_attribute(attribute) {
+        marked.push(el.clone());
+    }
+    let children = el.query_selector_all(&format!("[{}]", attribute)).unwrap();
+    for i in 0..children.length() {
+        marked.push(children.item(i).unwrap().unchecked_into());
+    }
+    marked
+}
+
 fn set_attribute(el: &Element, name: &str, value: &AttributeValue) {
     match value {
         AttributeValue::Bool(b) => {
             js_sys::Reflect::set(el.as_ref(), &name.into(), &(*b).into()).unwrap();
-            el.set_attribute(name, &b.to_string()).unwrap();
+            if *b {
+                el.set_attribute(name, "true").unwrap();
+            } else {
+                el.remove_attribute(name).unwrap();
+            }
         }
         AttributeValue::String(s) => {
             js_sys::Reflect::set(el.as_ref(), &name.into(), &s.into()).unwrap();
             el.set_attribute(name, s).unwrap();
         }
+        AttributeValue::Property(v) => set_property(el, name, v),
+    }
+}
+
+fn set_property(el: &Element, name: &str, value: &HandlerArg) {
+    let value = js_sys::JSON::parse(&value.to_string()).unwrap();
+    js_sys::Reflect::set(el.as_ref(), &name.into(), &value).unwrap();
+}
+
+// Warnings go to the console, as stderr is nowhere to be seen.
+fn log_to_console(level: Level, message: &str) {
+    let message = JsValue::from_str(&format!("squark: {}", message));
+    match level {
+        Level::Debug => web_sys::console::debug_1(&message),
+        Level::Info => web_sys::console::info_1(&message),
+        Level::Warn => web_sys::console::warn_1(&message),
+        Level::Error => web_sys::console::error_1(&message),
     }
 }
 
 impl<A: App> WebRuntime<A> {
     pub fn new(root: Element, state: A::State) -> WebRuntime<A> {
+        if !log::has_sink() {
+            log::set_sink(log_to_console);
+        }
         WebRuntime {
             env: Env::new(state),
             root: Rc::new(root),
+            roots: Rc::new(RefCell::new(HashMap::new())),
             attached_map: Rc::new(RefCell::new(AttachedMap::new())),
+            subscriptions: Rc::new(RefCell::new(SubscriptionMap::new())),
+            scroll_map: Rc::new(RefCell::new(ScrollMap::new())),
+            style_sheet: Rc::new(RefCell::new(None)),
+            live_regions: Rc::new(RefCell::new(HashMap::new())),
+            focus_traps: Rc::new(RefCell::new(vec![])),
+            observers: Rc::new(RefCell::new(ObserverMap::new())),
+            custom_hooks: Rc::new(RefCell::new(HashMap::new())),
+            nodes: Rc::new(RefCell::new(HashMap::new())),
         }
     }
 
-    fn handle_diff_inner(&self, el: &Element, diff: Diff) {
+    /// Renders the additional root `id` of `App::roots` into `el`. Call
+    /// before the first `run`.
+    pub fn mount_root<S: Into<String>>(&self, id: S, el: Element) {
+        self.roots.borrow_mut().insert(id.into(), el);
+    }
+
+    /// Calls `mount` with every element of `View::custom` called `name` once
+    /// it is added to the document, and `unmount` before it is removed.
+    pub fn register_custom<S, M, U>(&self, name: S, mount: M, unmount: U)
+    where
+        S: Into<String>,
+        M: Fn(&Element) + 'static,
+        U: Fn(&Element) + 'static,
+    {
+        self.custom_hooks
+            .borrow_mut()
+            .insert(name.into(), (Rc::new(mount), Rc::new(unmount)));
+    }
+
+    // Returns false if the diff addresses children missing from the
+    // document, which then differs from the last render.
+    fn handle_diff_inner(&self, el: &Element, diff: Diff) -> bool {
+        let len = el.unchecked_ref::<Node>().child_nodes().length() as usize;
+        let in_range = match diff {
+            Diff::AddChild(i, _) | Diff::AddChildren(i, _) => i <= len,
+            Diff::ReplaceChild(i, _) | Diff::RemoveChild(i) | Diff::PatchChild(i, _) => i < len,
+            Diff::RemoveChildren(i, count) => i + count <= len,
+            _ => true,
+        };
+        if !in_range {
+            return false;
+        }
         match diff {
             Diff::AddChild(i, node) => self.add_child(el, i, node),
+            Diff::AddChildren(i, nodes) => self.add_children(el, i, &nodes),
             Diff::PatchChild(i, diffs) => {
                 let as_node: &Node = el.as_ref();
                 let child = as_node.child_nodes().item(i as u32).unwrap();
-                for diff in diffs {
-                    self.handle_diff_inner(child.unchecked_ref(), diff);
-                }
+                return diffs.into_iter().all(|diff| self.handle_diff_inner(child.unchecked_ref(), diff));
             }
             Diff::ReplaceChild(i, node) => self.replace_child(el, i, node),
-            Diff::SetAttribute(name, value) => set_attribute(el, &name, &value),
+            Diff::ReplaceRoot(node) => {
+                let as_node: &Node = el.as_ref();
+                // Anything else in the mount point was added by someone else.
+                while as_node.child_nodes().length() > 1 {
+                    let last = as_node.child_nodes().length() as usize - 1;
+                    self.remove_child(as_node, last);
+                }
+                match (as_node.has_child_nodes(), node) {
+                    (true, SquarkNode::Null) => self.remove_child(as_node, 0),
+                    (false, SquarkNode::Null) => {}
+                    (true, node) => self.replace_child(el, 0, node),
+                    (false, node) => self.add_child(el, 0, node),
+                }
+            }
+            Diff::PatchRoot(diffs) => return self.handle_diff_inner(el, Diff::PatchChild(0, diffs)),
+            Diff::PatchNode(id, diffs) => {
+                let target = match self.nodes.borrow().get(&id) {
+                    Some(target) => target.clone(),
+                    None => return false,
+                };
+                return diffs.into_iter().all(|diff| self.handle_diff_inner(&target, diff));
+            }
+            Diff::PatchKeyed(key, diffs) => match keyed_index(el.as_ref(), &key) {
+                Some(i) => return self.handle_diff_inner(el, Diff::PatchChild(i, diffs)),
+                None => return false,
+            },
+            Diff::ReplaceKeyed(key, node) => match keyed_index(el.as_ref(), &key) {
+                Some(i) => self.replace_child(el, i, node),
+                None => return false,
+            },
+            Diff::RemoveKeyed(key) => match keyed_index(el.as_ref(), &key) {
+                Some(i) => self.remove_child(el.as_ref(), i),
+                None => return false,
+            },
+            Diff::PatchPath(path, diffs) => {
+                let mut target = el.clone().unchecked_into::<Node>();
+                for &i in &path {
+                    match target.child_nodes().item(i as u32) {
+                        Some(child) => target = child,
+                        None => return false,
+                    }
+                }
+                return diffs.into_iter().all(|diff| self.handle_diff_inner(target.unchecked_ref(), diff));
+            }
+            Diff::SetText(text) => {
+                let node: &Node = el.as_ref();
+                node.set_text_content(Some(&text));
+            }
+            Diff::PatchShadow(diffs) => {
+                // Only child diffs are routed here, which need nothing beyond `Node`.
+                let shadow_root = match el.shadow_root() {
+                    Some(shadow_root) => shadow_root,
+                    None => return false,
+                };
+                return diffs.into_iter().all(|diff| self.handle_diff_inner(shadow_root.unchecked_ref(), diff));
+            }
+            Diff::SetAttribute(name, value) => {
+                set_attribute(el, &name, &value);
+                if name == FOCUS_TRAP {
+                    self.trap_focus(el);
+                }
+                if name == OBSERVE_KEY {
+                    self.unobserve_in(el);
+                    self.observe_in(el);
+                }
+            }
             Diff::RemoveAttribute(name) => {
+                if name == FOCUS_TRAP {
+                    self.release_focus(el);
+                }
+                if name == OBSERVE_KEY {
+                    self.unobserve_in(el);
+                }
                 el.remove_attribute(&name).unwrap();
+                // Properties like `checked` stop reflecting the attribute once changed.
+                let key: JsValue = name.as_str().into();
+                if js_sys::Reflect::get(el.as_ref(), &key).unwrap().as_bool().is_some() {
+                    js_sys::Reflect::set(el.as_ref(), &key, &false.into()).unwrap();
+                }
+            }
+            Diff::SetProperty(name, value) => set_property(el, &name, &value),
+            Diff::RemoveProperty(name) => {
+                js_sys::Reflect::set(el.as_ref(), &name.into(), &JsValue::UNDEFINED).unwrap();
             }
             Diff::RemoveChild(i) => self.remove_child(el.as_ref(), i),
+            Diff::RemoveChildren(i, count) => {
+                for _ in 0..count {
+                    self.remove_child(el.as_ref(), i);
+                }
+            }
+            Diff::ClearChildren => {
+                let as_node: &Node = el.as_ref();
+                while as_node.has_child_nodes() {
+                    let last = as_node.child_nodes().length() as usize - 1;
+                    self.remove_child(as_node, last);
+                }
+            }
             Diff::SetHandler(name, id) => self.set_handler(el.unchecked_ref(), &name, &id),
+            Diff::SaveScroll(key) => self.save_scroll(el, key),
+            Diff::RestoreScroll(key) => self.restore_scroll(el, &key),
+            Diff::InjectStyle(_, rule) => self.inject_style(&rule),
+            // Listeners are attached per element.
+            Diff::DelegateEvents(_) => (),
             Diff::RemoveHandler(name, _) => {
                 let attached = self
                     .attached_map
@@ -135,12 +504,214 @@ impl<A: App> WebRuntime<A> {
                     .unwrap();
             }
         }
+        true
     }
 
     fn replace_at(&self, parent: &Node, i: usize, node: &Node) {
         let current = parent.child_nodes().item(i as u32).unwrap();
+        self.save_scroll_in(&current);
+        self.release_focus_in(&current);
+        self.unobserve_in(&current);
+        self.unmount_customs_in(&current);
         self.remove_attached(&current);
+        self.forget_nodes_in(&current);
         parent.replace_child(&node, &current).unwrap();
+        self.restore_scroll_in(node);
+        self.trap_focus_in(node);
+        self.observe_in(node);
+        self.mount_customs_in(node);
+    }
+
+    fn inject_style(&self, rule: &str) {
+        let mut style_sheet = self.style_sheet.borrow_mut();
+        let style_el = style_sheet.get_or_insert_with(|| {
+            let style_el = document().create_element("style").unwrap();
+            let head = document().head().unwrap();
+            let head_node: &Node = head.as_ref();
+            head_node.append_child(style_el.as_ref()).unwrap();
+            style_el
+        });
+        let text = document().create_text_node(rule);
+        let node: &Node = style_el.as_ref();
+        node.append_child(text.as_ref()).unwrap();
+    }
+
+    fn live_region(&self, politeness: Politeness) -> Element {
+        let mut live_regions = self.live_regions.borrow_mut();
+        live_regions
+            .entry(politeness)
+            .or_insert_with(|| {
+                let el = document().create_element("div").unwrap();
+                el.set_attribute("aria-live", politeness.as_str()).unwrap();
+                el.set_attribute("aria-atomic", "true").unwrap();
+                el.set_attribute("style", VISUALLY_HIDDEN).unwrap();
+                let body = document().body().unwrap();
+                let body_node: &Node = body.as_ref();
+                body_node.append_child(el.as_ref()).unwrap();
+                el
+            })
+            .clone()
+    }
+
+    fn save_scroll(&self, el: &Element, key: String) {
+        self.scroll_map
+            .borrow_mut()
+            .insert(key, (el.scroll_left(), el.scroll_top()));
+    }
+
+    fn restore_scroll(&self, el: &Element, key: &str) {
+        let (left, top) = self
+            .scroll_map
+            .borrow()
+            .get(key)
+            .cloned()
+            .unwrap_or((0, 0));
+        el.set_scroll_left(left);
+        el.set_scroll_top(top);
+    }
+
+    fn save_scroll_in(&self, node: &Node) {
+        for el in marked(node, SCROLL_KEY) {
+            if let Some(key) = el.get_attribute(SCROLL_KEY) {
+                self.save_scroll(&el, key);
+            }
+        }
+    }
+
+    fn restore_scroll_in(&self, node: &Node) {
+        for el in marked(node, SCROLL_KEY) {
+            if let Some(key) = el.get_attribute(SCROLL_KEY) {
+                self.restore_scroll(&el, &key);
+            }
+        }
+    }
+
+    fn trap_focus(&self, el: &Element) {
+        let previous = document()
+            .active_element()
+            .map(|active| active.unchecked_into::<HtmlElement>());
+        let trap = el.clone();
+        let listener = Closure::wrap(Box::new(move |e: web_sys::KeyboardEvent| {
+            if e.key() != "Tab" {
+                return;
+            }
+            let items = focusable(&trap);
+            let active = document().active_element();
+            let (first, last) = match (items.first(), items.last()) {
+                (Some(first), Some(last)) => (first, last),
+                _ => return e.prevent_default(),
+            };
+            let is_active = |item: &HtmlElement| {
+                active.as_ref() == Some(item.unchecked_ref::<Element>())
+            };
+            if e.shift_key() && is_active(first) {
+                e.prevent_default();
+                last.focus().unwrap();
+            } else if !e.shift_key() && is_active(last) {
+                e.prevent_default();
+                first.focus().unwrap();
+            }
+        }) as Box<dyn FnMut(web_sys::KeyboardEvent)>);
+        let target: &EventTarget = el.as_ref();
+        target
+            .add_event_listener_with_callback("keydown", listener.as_ref().unchecked_ref())
+            .unwrap();
+        if let Some(first) = focusable(el).first() {
+            first.focus().unwrap();
+        }
+        self.focus_traps.borrow_mut().push((el.clone(), previous, listener));
+    }
+
+    // Traps are released innermost first, so focus ends up where it was
+    // before the outermost one.
+    fn release_focus(&self, el: &Element) {
+        let mut traps = self.focus_traps.borrow_mut();
+        if let Some(i) = traps.iter().position(|trap| trap.0 == *el) {
+            for (trap, previous, listener) in traps.drain(i..).rev() {
+                let target: &EventTarget = trap.as_ref();
+                target
+                    .remove_event_listener_with_callback(
+                        "keydown",
+                        listener.as_ref().unchecked_ref(),
+                    )
+                    .unwrap();
+                if let Some(previous) = previous {
+                    let _ = previous.focus();
+                }
+            }
+        }
+    }
+
+    fn trap_focus_in(&self, node: &Node) {
+        for el in marked(node, FOCUS_TRAP) {
+            self.trap_focus(&el);
+        }
+    }
+
+    fn release_focus_in(&self, node: &Node) {
+        for el in marked(node, FOCUS_TRAP) {
+            self.release_focus(&el);
+        }
+    }
+
+    // Starts an observer for the subscription `id`, watching the elements
+    // tagged with `key` now and later.
+    fn start_observer(&self, id: &str, key: &str, observer: Observer) -> Box<dyn FnOnce()> {
+        let tagged = document()
+            .query_selector_all(&format!("[{}=\"{}\"]", OBSERVE_KEY, key))
+            .unwrap();
+        for i in 0..tagged.length() {
+            observer.observe(tagged.item(i).unwrap().unchecked_ref());
+        }
+        self.observers
+            .borrow_mut()
+            .insert(id.to_string(), (key.to_string(), observer));
+        let observers = self.observers.clone();
+        let id = id.to_string();
+        Box::new(move || {
+            if let Some((_, observer)) = observers.borrow_mut().remove(&id) {
+                observer.disconnect();
+            }
+        })
+    }
+
+    fn observe_in(&self, node: &Node) {
+        for el in marked(node, OBSERVE_KEY) {
+            let key = el.get_attribute(OBSERVE_KEY);
+            for (observed, observer) in self.observers.borrow().values() {
+                if key.as_ref() == Some(observed) {
+                    observer.observe(&el);
+                }
+            }
+        }
+    }
+
+    fn unobserve_in(&self, node: &Node) {
+        for el in marked(node, OBSERVE_KEY) {
+            for (_, observer) in self.observers.borrow().values() {
+                observer.unobserve(&el);
+            }
+        }
+    }
+
+    fn mount_customs_in(&self, node: &Node) {
+        for el in marked(node, CUSTOM_ATTRIBUTE) {
+            let name = el.get_attribute(CUSTOM_ATTRIBUTE).unwrap();
+            let hooks = self.custom_hooks.borrow().get(&name).cloned();
+            if let Some((mount, _)) = hooks {
+                mount(&el);
+            }
+        }
+    }
+
+    fn unmount_customs_in(&self, node: &Node) {
+        for el in marked(node, CUSTOM_ATTRIBUTE) {
+            let name = el.get_attribute(CUSTOM_ATTRIBUTE).unwrap();
+            let hooks = self.custom_hooks.borrow().get(&name).cloned();
+            if let Some((_, unmount)) = hooks {
+                unmount(&el);
+            }
+        }
     }
 
     fn create_element(&self, el: &SquarkElement) -> Element {
@@ -148,63 +719,96 @@ impl<A: App> WebRuntime<A> {
         for (ref name, ref value) in el.attributes() {
             set_attribute(&web_el, name, value);
         }
+        if let Some(key) = el.key() {
+            js_sys::Reflect::set(web_el.as_ref(), &KEY_PROPERTY.into(), &key.into()).unwrap();
+        }
 
         for (ref name, id) in el.handlers() {
             self.set_handler(web_el.unchecked_ref(), name, &id);
         }
 
+        if self.env.addressing() == Addressing::StableIds {
+            let id = el.node_id();
+            web_el.set_attribute(NODE_ID, &id.to_string()).unwrap();
+            self.nodes.borrow_mut().insert(id, web_el.clone());
+        }
+
         {
-            let node: &Node = web_el.as_ref();
+            let node: &Node = if el.has_shadow_root() {
+                let init = ShadowRootInit::new(ShadowRootMode::Open);
+                &web_el.attach_shadow(&init).unwrap()
+            } else {
+                web_el.as_ref()
+            };
             for child in el.children() {
-                match child {
-                    SquarkNode::Element(el) => {
-                        let child = self.create_element(el);
-                        node.append_child(child.as_ref()).unwrap();
-                    }
-                    SquarkNode::Text(s) => {
-                        let child = document().create_text_node(s.as_str());
-                        node.append_child(child.as_ref()).unwrap();
-                    }
-                    _ => (),
-                };
+                if let Some(child) = self.create_node(child) {
+                    node.append_child(&child).unwrap();
+                }
             }
         }
 
         web_el
     }
 
-    fn add_child(&self, parent: &Element, i: usize, node: SquarkNode) {
+    fn create_node(&self, node: &SquarkNode) -> Option<Node> {
         match node {
-            SquarkNode::Element(el) => {
-                let child = self.create_element(&el);
-                insert_at(parent.as_ref(), i, child.as_ref());
+            SquarkNode::Element(el) => Some(self.create_element(el).into()),
+            SquarkNode::Text(s) => Some(document().create_text_node(s).into()),
+            SquarkNode::Raw(html) => {
+                let wrapper = document().create_element("span").unwrap();
+                wrapper.set_inner_html(html);
+                Some(wrapper.into())
             }
-            SquarkNode::Text(s) => {
-                let child = document().create_text_node(s.as_str());
-                insert_at(parent.as_ref(), i, child.as_ref());
+            SquarkNode::Custom(custom) => {
+                let el = document().create_element(custom.tag().name()).unwrap();
+                el.set_attribute(CUSTOM_ATTRIBUTE, custom.name()).unwrap();
+                Some(el.into())
             }
-            _ => (),
-        };
+            SquarkNode::Null => None,
+        }
+    }
+
+    fn add_child(&self, parent: &Element, i: usize, node: SquarkNode) {
+        if let Some(child) = self.create_node(&node) {
+            insert_at(parent.as_ref(), i, &child);
+            self.restore_scroll_in(&child);
+            self.trap_focus_in(&child);
+            self.observe_in(&child);
+            self.mount_customs_in(&child);
+        }
+    }
+
+    // Inserts the nodes at once through a fragment.
+    fn add_children(&self, parent: &Element, i: usize, nodes: &[SquarkNode]) {
+        let fragment = document().create_document_fragment();
+        let children: Vec<Node> = nodes.iter().filter_map(|node| self.create_node(node)).collect();
+        for child in &children {
+            fragment.append_child(child).unwrap();
+        }
+        insert_at(parent.as_ref(), i, fragment.as_ref());
+        for child in &children {
+            self.restore_scroll_in(child);
+            self.trap_focus_in(child);
+            self.observe_in(child);
+            self.mount_customs_in(child);
+        }
     }
 
     fn replace_child(&self, parent: &Element, i: usize, node: SquarkNode) {
-        match node {
-            SquarkNode::Element(el) => {
-                let child = self.create_element(&el);
-                self.replace_at(parent.as_ref(), i, child.as_ref());
-            }
-            SquarkNode::Text(s) => {
-                let child = document().create_text_node(s.as_str());
-                self.replace_at(parent.as_ref(), i, child.as_ref());
-            }
-            _ => (),
-        };
+        if let Some(child) = self.create_node(&node) {
+            self.replace_at(parent.as_ref(), i, &child);
+        }
     }
 
     fn remove_child(&self, parent: &Node, i: usize) {
         let current = parent.child_nodes().item(i as u32).unwrap();
 
+        self.save_scroll_in(&current);
+        self.release_focus_in(&current);
+        self.unobserve_in(&current);
+        self.unmount_customs_in(&current);
         self.remove_attached(current.unchecked_ref());
+        self.forget_nodes_in(&current);
         parent.remove_child(&current).unwrap();
     }
 
@@ -212,6 +816,9 @@ impl<A: App> WebRuntime<A> {
         let closure = match name {
             "keydown" => self._set_handler::<web_sys::KeyboardEvent>(el.as_ref(), "keydown", id),
             "input" => self._set_handler::<web_sys::InputEvent>(el.as_ref(), "input", id),
+            "compositionstart" | "compositionupdate" | "compositionend" => {
+                self._set_handler::<web_sys::CompositionEvent>(el.as_ref(), name, id)
+            }
             name => self._set_handler::<web_sys::Event>(el.as_ref(), name, id),
         };
 
@@ -243,6 +850,25 @@ impl<A: App> WebRuntime<A> {
         closure
     }
 
+    fn forget_nodes_in(&self, node: &Node) {
+        if !node.is_instance_of::<Element>() || self.nodes.borrow().is_empty() {
+            return;
+        }
+        let el: &Element = node.unchecked_ref();
+        let selector = format!("[{}]", NODE_ID);
+        let descendants = el.query_selector_all(&selector).unwrap();
+        let mut nodes = self.nodes.borrow_mut();
+        let ids = Some(el.clone())
+            .into_iter()
+            .chain((0..descendants.length()).map(|i| descendants.item(i).unwrap().unchecked_into()))
+            .filter_map(|el: Element| el.get_attribute(NODE_ID));
+        for id in ids {
+            if let Ok(id) = id.parse() {
+                nodes.remove(&id);
+            }
+        }
+    }
+
     fn remove_attached(&self, el: &Node) {
         if !el.is_instance_of::<Element>() {
             return;
@@ -269,7 +895,10 @@ impl<A: App> Runtime<A> for WebRuntime<A> {
     fn schedule_render(&self) {
         let this = self.clone();
         let closure = Closure::wrap(Box::new(move |_: JsValue| {
-            this.run();
+            // Animation frames may have rendered already.
+            if this.env.is_render_scheduled() {
+                this.run();
+            }
         }) as Box<FnMut(_)>);
         window()
             .unwrap()
@@ -279,6 +908,274 @@ impl<A: App> Runtime<A> for WebRuntime<A> {
     }
 
     fn handle_diff(&self, diff: Diff) {
-        self.handle_diff_inner(&self.root, diff);
+        if !self.handle_diff_inner(&self.root, diff) {
+            self.render_from_scratch();
+        }
+    }
+
+    fn handle_root_diff(&self, root: &str, diff: Diff) {
+        let el = self.roots.borrow().get(root).cloned();
+        if let Some(el) = el {
+            if !self.handle_diff_inner(&el, diff) {
+                self.render_from_scratch();
+            }
+        }
+    }
+
+    fn spawn(&self, task: Task) {
+        wasm_bindgen_futures::spawn_local(task);
+    }
+
+    fn fetcher(&self) -> Option<Rc<dyn Fetch>> {
+        Some(Rc::new(WebFetch))
+    }
+
+    fn storage(&self) -> Option<Rc<dyn KeyValueStorage>> {
+        Some(Rc::new(WebStorage))
+    }
+
+    fn subscribe(&self, id: &str, kind: &SubscriptionKind) {
+        let this = self.clone();
+        let id = id.to_string();
+        let teardown: Box<dyn FnOnce()> = match kind {
+            SubscriptionKind::WebSocket(url) => {
+                let sub_id = id.clone();
+                let conn = websocket::Connection::open(url, move |msg| {
+                    this.dispatch_subscription(&sub_id, json!{msg});
+                });
+                Box::new(move || conn.close())
+            }
+            SubscriptionKind::Every(interval) => {
+                let sub_id = id.clone();
+                let closure = Closure::wrap(Box::new(move || {
+                    this.dispatch_subscription(&sub_id, json!{now()});
+                }) as Box<dyn FnMut()>);
+                let handle = window()
+                    .unwrap()
+                    .set_interval_with_callback_and_timeout_and_arguments_0(
+                        closure.as_ref().unchecked_ref(),
+                        interval.as_millis() as i32,
+                    )
+                    .unwrap();
+                Box::new(move || {
+                    window().unwrap().clear_interval_with_handle(handle);
+                    drop(closure);
+                })
+            }
+            SubscriptionKind::UrlChange => {
+                let sub_id = id.clone();
+                let closure = Closure::wrap(Box::new(move |_: JsValue| {
+                    this.dispatch_subscription(&sub_id, json!{current_url()});
+                }) as Box<dyn FnMut(JsValue)>);
+                let target: &EventTarget = &window().unwrap();
+                target
+                    .add_event_listener_with_callback("popstate", closure.as_ref().unchecked_ref())
+                    .unwrap();
+                self.dispatch_subscription(&id, json!{current_url()});
+                Box::new(move || {
+                    let target: &EventTarget = &window().unwrap();
+                    target
+                        .remove_event_listener_with_callback(
+                            "popstate",
+                            closure.as_ref().unchecked_ref(),
+                        )
+                        .unwrap();
+                })
+            }
+            SubscriptionKind::Online => {
+                let sub_id = id.clone();
+                let closure = Closure::wrap(Box::new(move |_: JsValue| {
+                    this.dispatch_subscription(&sub_id, json!{is_online()});
+                }) as Box<dyn FnMut(JsValue)>);
+                let target: &EventTarget = &window().unwrap();
+                for kind in &["online", "offline"] {
+                    target
+                        .add_event_listener_with_callback(kind, closure.as_ref().unchecked_ref())
+                        .unwrap();
+                }
+                self.dispatch_subscription(&id, json!{is_online()});
+                Box::new(move || {
+                    let target: &EventTarget = &window().unwrap();
+                    for kind in &["online", "offline"] {
+                        target
+                            .remove_event_listener_with_callback(
+                                kind,
+                                closure.as_ref().unchecked_ref(),
+                            )
+                            .unwrap();
+                    }
+                })
+            }
+            SubscriptionKind::KeyDown => {
+                let sub_id = id.clone();
+                let closure = Closure::wrap(Box::new(move |e: web_sys::KeyboardEvent| {
+                    this.dispatch_subscription(&sub_id, key_down_arg(&e));
+                }) as Box<dyn FnMut(web_sys::KeyboardEvent)>);
+                let target: &EventTarget = &document();
+                target
+                    .add_event_listener_with_callback("keydown", closure.as_ref().unchecked_ref())
+                    .unwrap();
+                Box::new(move || {
+                    let target: &EventTarget = &document();
+                    target
+                        .remove_event_listener_with_callback(
+                            "keydown",
+                            closure.as_ref().unchecked_ref(),
+                        )
+                        .unwrap();
+                })
+            }
+            SubscriptionKind::Visible(key) => {
+                let sub_id = id.clone();
+                let closure = Closure::wrap(Box::new(move |entries: js_sys::Array| {
+                    for entry in entries.iter() {
+                        let entry: web_sys::IntersectionObserverEntry = entry.unchecked_into();
+                        this.dispatch_subscription(&sub_id, json!{entry.is_intersecting()});
+                    }
+                }) as Box<dyn FnMut(js_sys::Array)>);
+                let observer =
+                    web_sys::IntersectionObserver::new(closure.as_ref().unchecked_ref()).unwrap();
+                let stop = self.start_observer(&id, key, Observer::Intersection(observer));
+                Box::new(move || {
+                    stop();
+                    drop(closure);
+                })
+            }
+            SubscriptionKind::Resize(key) => {
+                let sub_id = id.clone();
+                let closure = Closure::wrap(Box::new(move |entries: js_sys::Array| {
+                    for entry in entries.iter() {
+                        let entry: web_sys::ResizeObserverEntry = entry.unchecked_into();
+                        let rect = entry.content_rect();
+                        this.dispatch_subscription(&sub_id, json!{[rect.width(), rect.height()]});
+                    }
+                }) as Box<dyn FnMut(js_sys::Array)>);
+                let observer =
+                    web_sys::ResizeObserver::new(closure.as_ref().unchecked_ref()).unwrap();
+                let stop = self.start_observer(&id, key, Observer::Resize(observer));
+                Box::new(move || {
+                    stop();
+                    drop(closure);
+                })
+            }
+            SubscriptionKind::AnimationFrame => {
+                let sub_id = id.clone();
+                let handle = Rc::new(Cell::new(0));
+                let tick: FrameLoop = Rc::new(RefCell::new(None));
+                {
+                    let handle = handle.clone();
+                    let next = tick.clone();
+                    *tick.borrow_mut() = Some(Closure::wrap(Box::new(move |timestamp: f64| {
+                        // Requested first, so unsubscribing by this tick cancels it.
+                        if let Some(ref closure) = *next.borrow() {
+                            handle.set(request_animation_frame(closure));
+                        }
+                        this.dispatch_frame(&sub_id, timestamp);
+                    }) as Box<dyn FnMut(f64)>));
+                }
+                handle.set(request_animation_frame(tick.borrow().as_ref().unwrap()));
+                Box::new(move || {
+                    window().unwrap().cancel_animation_frame(handle.get()).unwrap();
+                    // Breaks the cycle between the closure and itself, later as
+                    // it may be running right now.
+                    let drop_tick = Closure::once_into_js(move || {
+                        tick.borrow_mut().take();
+                    });
+                    window()
+                        .unwrap()
+                        .set_timeout_with_callback(drop_tick.unchecked_ref())
+                        .unwrap();
+                })
+            }
+        };
+        self.subscriptions.borrow_mut().insert(id, teardown);
+    }
+
+    fn push_url(&self, url: &str) {
+        let history = window().unwrap().history().unwrap();
+        history
+            .push_state_with_url(&JsValue::NULL, "", Some(url))
+            .unwrap();
+        notify_url_change();
+    }
+
+    fn announce(&self, message: &str, politeness: Politeness) {
+        // Set in the next frame so that repeating a message announces it again.
+        let region = self.live_region(politeness);
+        let node: &Node = region.as_ref();
+        node.set_text_content(None);
+        let message = message.to_string();
+        let closure = Closure::wrap(Box::new(move |_: JsValue| {
+            let node: &Node = region.as_ref();
+            node.set_text_content(Some(&message));
+        }) as Box<dyn FnMut(_)>);
+        window()
+            .unwrap()
+            .request_animation_frame(closure.as_ref().unchecked_ref())
+            .unwrap();
+        closure.forget();
+    }
+
+    fn control_media(&self, id: &str, control: MediaControl) {
+        let media = match document().get_element_by_id(id) {
+            Some(el) => el.unchecked_into::<web_sys::HtmlMediaElement>(),
+            None => return,
+        };
+        match control {
+            // Rejected when autoplay is blocked, which leaves it paused.
+            MediaControl::Play => drop(media.play()),
+            MediaControl::Pause => media.pause().unwrap(),
+            MediaControl::Seek(seconds) => media.set_current_time(seconds),
+        }
+    }
+
+    fn scroll_into_view(&self, path: &[usize], behavior: ScrollBehavior) {
+        let target = path.iter().fold(self.root.first_child(), |node, &i| {
+            node.and_then(|node| node.child_nodes().item(i as u32))
+        });
+        let el = match target.and_then(|node| node.dyn_into::<Element>().ok()) {
+            Some(el) => el,
+            None => return,
+        };
+        let options = web_sys::ScrollIntoViewOptions::new();
+        options.set_behavior(match behavior {
+            ScrollBehavior::Auto => web_sys::ScrollBehavior::Auto,
+            ScrollBehavior::Smooth => web_sys::ScrollBehavior::Smooth,
+        });
+        el.scroll_into_view_with_scroll_into_view_options(&options);
+    }
+
+    fn set_title(&self, title: &str) {
+        document().set_title(title);
+    }
+
+    fn set_favicon(&self, url: &str) {
+        let icon = match document().query_selector("link[rel~=icon]").unwrap() {
+            Some(icon) => icon,
+            None => {
+                let icon = document().create_element("link").unwrap();
+                icon.set_attribute("rel", "icon").unwrap();
+                let head = document().head().unwrap();
+                let head_node: &Node = head.as_ref();
+                head_node.append_child(icon.as_ref()).unwrap();
+                icon
+            }
+        };
+        icon.set_attribute("href", url).unwrap();
+    }
+
+    fn replace_url(&self, url: &str) {
+        let history = window().unwrap().history().unwrap();
+        history
+            .replace_state_with_url(&JsValue::NULL, "", Some(url))
+            .unwrap();
+        notify_url_change();
+    }
+
+    fn unsubscribe(&self, id: &str) {
+        let teardown = self.subscriptions.borrow_mut().remove(id);
+        if let Some(teardown) = teardown {
+            teardown();
+        }
     }
 }
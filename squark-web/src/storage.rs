@@ -0,0 +1,26 @@
+use squark::KeyValueStorage;
+use web_sys::{window, Storage};
+
+pub struct WebStorage;
+
+fn local_storage() -> Option<Storage> {
+    window()?.local_storage().ok()?
+}
+
+impl KeyValueStorage for WebStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        local_storage()?.get_item(key).ok()?
+    }
+
+    fn set(&self, key: &str, value: &str) {
+        if let Some(storage) = local_storage() {
+            let _ = storage.set_item(key, value);
+        }
+    }
+
+    fn remove(&self, key: &str) {
+        if let Some(storage) = local_storage() {
+            let _ = storage.remove_item(key);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use std::cell::{Cell, RefCell};
+use std::cmp;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{window, MessageEvent, WebSocket};
+
+const BACKOFF_BASE_MS: i32 = 500;
+const BACKOFF_MAX_MS: i32 = 30_000;
+
+type Listener = Closure<dyn FnMut(JsValue)>;
+
+struct Inner {
+    url: String,
+    on_message: Box<dyn Fn(String)>,
+    socket: RefCell<Option<WebSocket>>,
+    closures: RefCell<Vec<Listener>>,
+    attempts: Cell<u32>,
+    closed: Cell<bool>,
+}
+
+// WebSocket connection which reconnects with exponential backoff until closed.
+pub struct Connection {
+    inner: Rc<Inner>,
+}
+
+impl Connection {
+    pub fn open<F>(url: &str, on_message: F) -> Connection
+    where
+        F: Fn(String) + 'static,
+    {
+        let inner = Rc::new(Inner {
+            url: url.to_string(),
+            on_message: Box::new(on_message),
+            socket: RefCell::new(None),
+            closures: RefCell::new(vec![]),
+            attempts: Cell::new(0),
+            closed: Cell::new(false),
+        });
+        connect(&inner);
+        Connection { inner }
+    }
+
+    pub fn close(&self) {
+        self.inner.closed.set(true);
+        if let Some(socket) = self.inner.socket.borrow_mut().take() {
+            socket.set_onopen(None);
+            socket.set_onmessage(None);
+            socket.set_onclose(None);
+            let _ = socket.close();
+        }
+        self.inner.closures.borrow_mut().clear();
+    }
+}
+
+fn connect(inner: &Rc<Inner>) {
+    let socket = match WebSocket::new(&inner.url) {
+        Ok(socket) => socket,
+        Err(_) => return schedule_reconnect(inner),
+    };
+
+    let on_open = {
+        let inner = inner.clone();
+        Closure::wrap(Box::new(move |_: JsValue| {
+            inner.attempts.set(0);
+        }) as Box<dyn FnMut(JsValue)>)
+    };
+    let on_message = {
+        let inner = inner.clone();
+        Closure::wrap(Box::new(move |ev: JsValue| {
+            let ev: MessageEvent = ev.unchecked_into();
+            if let Some(text) = ev.data().as_string() {
+                (inner.on_message)(text);
+            }
+        }) as Box<dyn FnMut(JsValue)>)
+    };
+    let on_close = {
+        let inner = inner.clone();
+        Closure::wrap(Box::new(move |_: JsValue| {
+            if !inner.closed.get() {
+                schedule_reconnect(&inner);
+            }
+        }) as Box<dyn FnMut(JsValue)>)
+    };
+
+    socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+    socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+    *inner.closures.borrow_mut() = vec![on_open, on_message, on_close];
+    *inner.socket.borrow_mut() = Some(socket);
+}
+
+fn schedule_reconnect(inner: &Rc<Inner>) {
+    let attempts = inner.attempts.get();
+    inner.attempts.set(attempts + 1);
+    let delay = cmp::min(
+        BACKOFF_BASE_MS.saturating_mul(1 << cmp::min(attempts, 16)),
+        BACKOFF_MAX_MS,
+    );
+
+    let inner = inner.clone();
+    let retry = Closure::once_into_js(move || {
+        if !inner.closed.get() {
+            connect(&inner);
+        }
+    });
+    window()
+        .unwrap()
+        .set_timeout_with_callback_and_timeout_and_arguments_0(retry.unchecked_ref(), delay)
+        .unwrap();
+}
@@ -0,0 +1,248 @@
+use std::fmt;
+use {Aria, AttributeValue, Child, Element, Node, View};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Issue {
+    MissingAlt,
+    MissingAccessibleName,
+    UnknownRole(String),
+    UnknownAriaAttribute(String),
+    UnsupportedAriaAttribute(String, Aria),
+    NonInteractiveClickHandler,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct A11yWarning {
+    /// Child indices from the audited node down to the offending element.
+    pub path: Vec<usize>,
+    pub element: String,
+    pub issue: Issue,
+}
+
+impl fmt::Display for A11yWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<{}> at {:?}: ", self.element, self.path)?;
+        match self.issue {
+            Issue::MissingAlt => write!(f, "image has no alt text"),
+            Issue::MissingAccessibleName => write!(f, "button has no accessible name"),
+            Issue::UnknownRole(ref role) => write!(f, "unknown role \"{}\"", role),
+            Issue::UnknownAriaAttribute(ref name) => write!(f, "unknown attribute \"{}\"", name),
+            Issue::UnsupportedAriaAttribute(ref role, aria) => write!(
+                f,
+                "\"{}\" is not supported by role \"{}\"",
+                aria.name(),
+                role
+            ),
+            Issue::NonInteractiveClickHandler => write!(
+                f,
+                "click handler on non-interactive element without role and tabindex"
+            ),
+        }
+    }
+}
+
+const ROLES: &[&str] = &[
+    "alert", "alertdialog", "application", "article", "banner", "button", "cell", "checkbox",
+    "columnheader", "combobox", "complementary", "contentinfo", "definition", "dialog",
+    "directory", "document", "feed", "figure", "form", "grid", "gridcell", "group", "heading",
+    "img", "link", "list", "listbox", "listitem", "log", "main", "marquee", "math", "menu",
+    "menubar", "menuitem", "menuitemcheckbox", "menuitemradio", "navigation", "none", "note",
+    "option", "presentation", "progressbar", "radio", "radiogroup", "region", "row", "rowgroup",
+    "rowheader", "scrollbar", "search", "searchbox", "separator", "slider", "spinbutton",
+    "status", "switch", "tab", "table", "tablist", "tabpanel", "term", "textbox", "timer",
+    "toolbar", "tooltip", "tree", "treegrid", "treeitem",
+];
+
+// Role restricted attributes and the roles which support them. Global
+// attributes are accepted on any role.
+fn supported_roles(aria: Aria) -> Option<&'static [&'static str]> {
+    let roles: &'static [&'static str] = match aria {
+        Aria::Checked => &[
+            "checkbox", "menuitemcheckbox", "menuitemradio", "option", "radio", "switch",
+            "treeitem",
+        ],
+        Aria::Pressed => &["button"],
+        Aria::Selected => &["gridcell", "option", "row", "tab", "columnheader", "rowheader", "treeitem"],
+        Aria::Expanded => &[
+            "button", "combobox", "document", "link", "section", "sectionhead", "window",
+            "alertdialog", "application", "checkbox", "columnheader", "dialog", "grid",
+            "gridcell", "group", "listbox", "menu", "menubar", "menuitem", "menuitemcheckbox",
+            "menuitemradio", "radiogroup", "row", "rowheader", "switch", "tab", "tablist",
+            "toolbar", "tree", "treegrid", "treeitem",
+        ],
+        Aria::Level => &["heading", "listitem", "row", "treeitem", "grid", "treegrid"],
+        Aria::Modal => &["dialog", "alertdialog", "window"],
+        Aria::MultiLine => &["textbox", "searchbox"],
+        Aria::MultiSelectable => &["grid", "listbox", "tablist", "tree", "treegrid"],
+        Aria::ValueMax | Aria::ValueMin | Aria::ValueNow | Aria::ValueText => &[
+            "meter", "progressbar", "scrollbar", "separator", "slider", "spinbutton",
+        ],
+        Aria::Sort => &["columnheader", "rowheader"],
+        _ => return None,
+    };
+    Some(roles)
+}
+
+fn is_interactive(el: &Element) -> bool {
+    match el.name() {
+        "a" | "button" | "input" | "select" | "textarea" | "option" | "summary" | "label"
+        | "details" | "audio" | "video" => true,
+        _ => has_attribute(el, "role") || has_attribute(el, "tabindex"),
+    }
+}
+
+fn has_attribute(el: &Element, name: &str) -> bool {
+    el.attributes().iter().any(|(k, _)| k == name)
+}
+
+fn string_attribute<'a>(el: &'a Element, name: &str) -> Option<&'a str> {
+    el.attributes()
+        .iter()
+        .find(|(k, _)| k == name)
+        .and_then(|(_, v)| match v {
+            AttributeValue::String(s) => Some(s.as_str()),
+            _ => None,
+        })
+}
+
+fn has_text_attribute(el: &Element, name: &str) -> bool {
+    match string_attribute(el, name) {
+        Some(v) => !v.trim().is_empty(),
+        None => false,
+    }
+}
+
+fn has_accessible_name(el: &Element) -> bool {
+    ["aria-label", "aria-labelledby", "title"]
+        .iter()
+        .any(|name| has_text_attribute(el, name))
+        || (el.name() == "img" && has_text_attribute(el, "alt"))
+        || el.children().iter().any(|child| match child {
+            Node::Text(s) => !s.trim().is_empty(),
+            Node::Element(el) => has_accessible_name(el),
+            Node::Raw(html) => !html.trim().is_empty(),
+            Node::Custom(_) | Node::Null => false,
+        })
+}
+
+fn audit_element(el: &Element, path: &mut Vec<usize>, warnings: &mut Vec<A11yWarning>) {
+    let mut warn = |issue| {
+        warnings.push(A11yWarning {
+            path: path.clone(),
+            element: el.name().to_string(),
+            issue,
+        })
+    };
+
+    if el.name() == "img" && !has_attribute(el, "alt") {
+        warn(Issue::MissingAlt);
+    }
+
+    let role = string_attribute(el, "role");
+    if let Some(role) = role {
+        if !ROLES.contains(&role) {
+            warn(Issue::UnknownRole(role.to_string()));
+        }
+    }
+    let is_button = el.name() == "button" || role == Some("button");
+    if is_button && !has_accessible_name(el) {
+        warn(Issue::MissingAccessibleName);
+    }
+
+    for (name, _) in el.attributes() {
+        if !name.starts_with("aria-") {
+            continue;
+        }
+        let aria = match Aria::from_name(name) {
+            Some(aria) => aria,
+            None => {
+                warn(Issue::UnknownAriaAttribute(name.clone()));
+                continue;
+            }
+        };
+        if let (Some(role), Some(roles)) = (role, supported_roles(aria)) {
+            if !roles.contains(&role) {
+                warn(Issue::UnsupportedAriaAttribute(role.to_string(), aria));
+            }
+        }
+    }
+
+    if el.handlers().iter().any(|(kind, _)| kind == "click") && !is_interactive(el) {
+        warn(Issue::NonInteractiveClickHandler);
+    }
+
+    for (i, child) in el.children().iter().enumerate() {
+        if let Node::Element(child) = child {
+            path.push(i);
+            audit_element(child, path, warnings);
+            path.pop();
+        }
+    }
+}
+
+/// Checks a rendered tree for common accessibility problems.
+pub fn audit(node: &Node) -> Vec<A11yWarning> {
+    let mut warnings = vec![];
+    if let Node::Element(el) = node {
+        audit_element(el, &mut vec![], &mut warnings);
+    }
+    warnings
+}
+
+/// How urgently assistive technology reads out a live region.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Politeness {
+    /// Read once the user is idle.
+    Polite,
+    /// Read immediately, interrupting.
+    Assertive,
+}
+
+impl Politeness {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Politeness::Polite => "polite",
+            Politeness::Assertive => "assertive",
+        }
+    }
+
+    fn role(&self) -> &'static str {
+        match self {
+            Politeness::Polite => "status",
+            Politeness::Assertive => "alert",
+        }
+    }
+}
+
+/// A region whose changing content is announced by screen readers. It must
+/// be rendered before its content changes; for one-off messages use
+/// `Cmd::announce`.
+pub fn live_region<A>(politeness: Politeness, children: Vec<Child<A>>) -> View<A> {
+    View::new(
+        "div",
+        vec![
+            ("role".to_string(), politeness.role().into()),
+            ("aria-live".to_string(), politeness.as_str().into()),
+            ("aria-atomic".to_string(), "true".into()),
+        ],
+        vec![],
+        children,
+    )
+}
+
+/// Marks an element which keeps Tab focus among its descendants while mounted.
+/// Runtimes focus its first focusable descendant when it is added and return
+/// focus to the element focused before once it is removed.
+pub const FOCUS_TRAP: &str = "data-focus-trap";
+
+/// Traps focus within `view`, for modal dialogs. Panics if `view` is not an
+/// element.
+pub fn focus_trap<A>(mut view: View<A>) -> View<A> {
+    match view.node {
+        Node::Element(ref mut el) => el
+            .attributes_mut()
+            .push((FOCUS_TRAP.to_string(), true.into())),
+        _ => panic!("focus_trap needs an element"),
+    }
+    view
+}
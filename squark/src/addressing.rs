@@ -0,0 +1,62 @@
+//! How diffs address the children they change.
+
+use std::cell::Cell;
+use Diff;
+
+/// How diffs address the children they change. See `Env::set_addressing`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Addressing {
+    /// By index among the rendered children.
+    #[default]
+    Index,
+    /// Keyed children by key, as `PatchKeyed`, `ReplaceKeyed` and
+    /// `RemoveKeyed`, so that nodes added by someone else, e.g. browser
+    /// extensions, don't shift them. Other children are still addressed by
+    /// index.
+    Keyed,
+    /// Patched elements by `Element::node_id`, as `PatchNode`, which
+    /// runtimes map to the nodes they created for them. Children are still
+    /// added, replaced and removed by index.
+    StableIds,
+}
+
+thread_local! {
+    static CURRENT: Cell<Addressing> = const { Cell::new(Addressing::Index) };
+    static NEXT_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+pub(crate) fn next_node_id() -> u64 {
+    NEXT_ID.with(|id| {
+        id.set(id.get() + 1);
+        id.get()
+    })
+}
+
+pub(crate) fn with_addressing<T, F: FnOnce() -> T>(addressing: Addressing, f: F) -> T {
+    let previous = CURRENT.with(|c| c.replace(addressing));
+    let result = f();
+    CURRENT.with(|c| c.set(previous));
+    result
+}
+
+// The diffs of the element at `i` with `id`.
+pub(crate) fn patch(i: usize, id: u64, diffs: Vec<Diff>) -> Diff {
+    match CURRENT.with(|c| c.get()) {
+        Addressing::StableIds => Diff::PatchNode(id, diffs),
+        _ => Diff::PatchChild(i, diffs),
+    }
+}
+
+// The diff of the child with `key`, addressed as the current diff requires.
+pub(crate) fn keyed(key: Option<String>, diff: Diff) -> Diff {
+    let key = match key {
+        Some(key) if CURRENT.with(|c| c.get()) == Addressing::Keyed => key,
+        _ => return diff,
+    };
+    match diff {
+        Diff::PatchChild(_, diffs) => Diff::PatchKeyed(key, diffs),
+        Diff::ReplaceChild(_, node) => Diff::ReplaceKeyed(key, node),
+        Diff::RemoveChild(_) => Diff::RemoveKeyed(key),
+        diff => diff,
+    }
+}
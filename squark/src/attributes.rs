@@ -0,0 +1,195 @@
+use url::Url;
+use {Attribute, AttributeValue};
+
+pub fn data<V: Into<AttributeValue>>(name: &str, value: V) -> Attribute {
+    (format!("data-{}", name), value.into())
+}
+
+pub fn aria<V: Into<AttributeValue>>(attribute: Aria, value: V) -> Attribute {
+    (attribute.name().to_string(), value.into())
+}
+
+pub fn href(url: Url) -> Attribute {
+    ("href".to_string(), url.into())
+}
+
+pub fn src(url: Url) -> Attribute {
+    ("src".to_string(), url.into())
+}
+
+/// The attribute when `condition` holds. Chain it onto the others, e.g.
+/// `attrs.into_iter().chain(attr_if(done, "class", "done")).collect()`.
+pub fn attr_if<K, V>(condition: bool, name: K, value: V) -> Option<Attribute>
+where
+    K: Into<String>,
+    V: Into<AttributeValue>,
+{
+    if condition {
+        Some((name.into(), value.into()))
+    } else {
+        None
+    }
+}
+
+/// States and properties of WAI-ARIA 1.1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Aria {
+    ActiveDescendant,
+    Atomic,
+    AutoComplete,
+    Busy,
+    Checked,
+    ColCount,
+    ColIndex,
+    ColSpan,
+    Controls,
+    Current,
+    DescribedBy,
+    Details,
+    Disabled,
+    DropEffect,
+    ErrorMessage,
+    Expanded,
+    FlowTo,
+    Grabbed,
+    HasPopup,
+    Hidden,
+    Invalid,
+    KeyShortcuts,
+    Label,
+    LabelledBy,
+    Level,
+    Live,
+    Modal,
+    MultiLine,
+    MultiSelectable,
+    Orientation,
+    Owns,
+    Placeholder,
+    PosInSet,
+    Pressed,
+    ReadOnly,
+    Relevant,
+    Required,
+    RoleDescription,
+    RowCount,
+    RowIndex,
+    RowSpan,
+    Selected,
+    SetSize,
+    Sort,
+    ValueMax,
+    ValueMin,
+    ValueNow,
+    ValueText,
+}
+
+impl Aria {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Aria::ActiveDescendant => "aria-activedescendant",
+            Aria::Atomic => "aria-atomic",
+            Aria::AutoComplete => "aria-autocomplete",
+            Aria::Busy => "aria-busy",
+            Aria::Checked => "aria-checked",
+            Aria::ColCount => "aria-colcount",
+            Aria::ColIndex => "aria-colindex",
+            Aria::ColSpan => "aria-colspan",
+            Aria::Controls => "aria-controls",
+            Aria::Current => "aria-current",
+            Aria::DescribedBy => "aria-describedby",
+            Aria::Details => "aria-details",
+            Aria::Disabled => "aria-disabled",
+            Aria::DropEffect => "aria-dropeffect",
+            Aria::ErrorMessage => "aria-errormessage",
+            Aria::Expanded => "aria-expanded",
+            Aria::FlowTo => "aria-flowto",
+            Aria::Grabbed => "aria-grabbed",
+            Aria::HasPopup => "aria-haspopup",
+            Aria::Hidden => "aria-hidden",
+            Aria::Invalid => "aria-invalid",
+            Aria::KeyShortcuts => "aria-keyshortcuts",
+            Aria::Label => "aria-label",
+            Aria::LabelledBy => "aria-labelledby",
+            Aria::Level => "aria-level",
+            Aria::Live => "aria-live",
+            Aria::Modal => "aria-modal",
+            Aria::MultiLine => "aria-multiline",
+            Aria::MultiSelectable => "aria-multiselectable",
+            Aria::Orientation => "aria-orientation",
+            Aria::Owns => "aria-owns",
+            Aria::Placeholder => "aria-placeholder",
+            Aria::PosInSet => "aria-posinset",
+            Aria::Pressed => "aria-pressed",
+            Aria::ReadOnly => "aria-readonly",
+            Aria::Relevant => "aria-relevant",
+            Aria::Required => "aria-required",
+            Aria::RoleDescription => "aria-roledescription",
+            Aria::RowCount => "aria-rowcount",
+            Aria::RowIndex => "aria-rowindex",
+            Aria::RowSpan => "aria-rowspan",
+            Aria::Selected => "aria-selected",
+            Aria::SetSize => "aria-setsize",
+            Aria::Sort => "aria-sort",
+            Aria::ValueMax => "aria-valuemax",
+            Aria::ValueMin => "aria-valuemin",
+            Aria::ValueNow => "aria-valuenow",
+            Aria::ValueText => "aria-valuetext",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Aria> {
+        ALL.iter().find(|a| a.name() == name).cloned()
+    }
+}
+
+const ALL: [Aria; 48] = [
+    Aria::ActiveDescendant,
+    Aria::Atomic,
+    Aria::AutoComplete,
+    Aria::Busy,
+    Aria::Checked,
+    Aria::ColCount,
+    Aria::ColIndex,
+    Aria::ColSpan,
+    Aria::Controls,
+    Aria::Current,
+    Aria::DescribedBy,
+    Aria::Details,
+    Aria::Disabled,
+    Aria::DropEffect,
+    Aria::ErrorMessage,
+    Aria::Expanded,
+    Aria::FlowTo,
+    Aria::Grabbed,
+    Aria::HasPopup,
+    Aria::Hidden,
+    Aria::Invalid,
+    Aria::KeyShortcuts,
+    Aria::Label,
+    Aria::LabelledBy,
+    Aria::Level,
+    Aria::Live,
+    Aria::Modal,
+    Aria::MultiLine,
+    Aria::MultiSelectable,
+    Aria::Orientation,
+    Aria::Owns,
+    Aria::Placeholder,
+    Aria::PosInSet,
+    Aria::Pressed,
+    Aria::ReadOnly,
+    Aria::Relevant,
+    Aria::Required,
+    Aria::RoleDescription,
+    Aria::RowCount,
+    Aria::RowIndex,
+    Aria::RowSpan,
+    Aria::Selected,
+    Aria::SetSize,
+    Aria::Sort,
+    Aria::ValueMax,
+    Aria::ValueMin,
+    Aria::ValueNow,
+    Aria::ValueText,
+];
@@ -0,0 +1,169 @@
+//! Standard workloads for benchmarking diffing and runtimes, after
+//! js-framework-benchmark: a table of keyed rows created, partly updated,
+//! reordered and cleared.
+
+use {Diff, Node, View};
+
+/// The number of rows the standard workloads are run with.
+pub const ROWS: usize = 10_000;
+
+const ADJECTIVES: &[&str] = &[
+    "pretty", "large", "big", "small", "tall", "short", "long", "plain", "quaint", "clean", "easy",
+    "angry", "crazy", "mushy", "odd", "cheap", "fancy",
+];
+const COLOURS: &[&str] = &[
+    "red", "yellow", "blue", "green", "pink", "brown", "purple", "white", "black", "orange",
+];
+const NOUNS: &[&str] = &[
+    "table", "chair", "house", "bbq", "desk", "car", "pony", "cookie", "sandwich", "burger",
+    "pizza", "mouse", "keyboard",
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Row {
+    pub id: usize,
+    pub label: String,
+}
+
+/// `count` rows with ids from 1 and labels mixed as if at random, the same
+/// on every call.
+pub fn rows(count: usize) -> Vec<Row> {
+    (1..=count)
+        .map(|id| Row {
+            id,
+            label: format!(
+                "{} {} {}",
+                ADJECTIVES[id * 7 % ADJECTIVES.len()],
+                COLOURS[id * 3 % COLOURS.len()],
+                NOUNS[id % NOUNS.len()]
+            ),
+        })
+        .collect()
+}
+
+/// A `tr` keyed by the id, holding cells with the id and the label.
+pub fn row<A>(row: &Row) -> View<A> {
+    View::new(
+        "tr",
+        vec![("key".to_string(), row.id.to_string().into())],
+        vec![],
+        vec![
+            cell(row.id.to_string()).into(),
+            cell(row.label.clone()).into(),
+        ],
+    )
+}
+
+fn cell<A>(text: String) -> View<A> {
+    View::new("td", vec![], vec![], vec![View::text(text).into()])
+}
+
+/// A `table` whose `tbody` holds the rows.
+pub fn table<A>(rows: &[Row]) -> View<A> {
+    let rows: Vec<View<A>> = rows.iter().map(row).collect();
+    let body = View::new("tbody", vec![], vec![], vec![rows.into()]);
+    View::new("table", vec![], vec![], vec![body.into()])
+}
+
+/// A change of the rows of a table.
+pub struct Workload {
+    pub name: &'static str,
+    pub before: Vec<Row>,
+    pub after: Vec<Row>,
+    /// The diff turning the table of `before` into that of `after`.
+    pub expected: Option<Diff>,
+}
+
+impl Workload {
+    /// The tables before and after the change.
+    pub fn views<A>(&self) -> (View<A>, View<A>) {
+        (table(&self.before), table(&self.after))
+    }
+}
+
+fn row_node(r: &Row) -> Node {
+    row::<()>(r).node().clone()
+}
+
+fn patch_body(diffs: Vec<Diff>) -> Option<Diff> {
+    if diffs.is_empty() {
+        return None;
+    }
+    Some(Diff::PatchRoot(vec![Diff::PatchChild(0, diffs)]))
+}
+
+/// Fills an empty table with `count` rows.
+pub fn create(count: usize) -> Workload {
+    let after = rows(count);
+    let adds = match after.len() {
+        0 => vec![],
+        1 => vec![Diff::AddChild(0, row_node(&after[0]))],
+        _ => vec![Diff::AddChildren(0, after.iter().map(row_node).collect())],
+    };
+    Workload {
+        name: "create",
+        before: vec![],
+        expected: patch_body(adds),
+        after,
+    }
+}
+
+/// Appends to the label of every tenth of `count` rows.
+pub fn update_every_tenth(count: usize) -> Workload {
+    let before = rows(count);
+    let mut after = before.clone();
+    let mut diffs = vec![];
+    for (i, r) in after.iter_mut().enumerate().step_by(10) {
+        r.label.push_str(" !!!");
+        let text = Diff::ReplaceChild(0, Node::Text(r.label.clone().into()));
+        diffs.push(Diff::PatchChild(i, vec![Diff::PatchChild(1, vec![text])]));
+    }
+    Workload {
+        name: "update every tenth row",
+        before,
+        after,
+        expected: patch_body(diffs),
+    }
+}
+
+/// Swaps the second and the second to last of `count` rows.
+pub fn swap(count: usize) -> Workload {
+    let before = rows(count);
+    let mut after = before.clone();
+    let mut diffs = vec![];
+    if count >= 4 {
+        let (a, b) = (1, count - 2);
+        after.swap(a, b);
+        diffs.push(Diff::ReplaceChild(a, row_node(&after[a])));
+        diffs.push(Diff::ReplaceChild(b, row_node(&after[b])));
+    }
+    Workload {
+        name: "swap rows",
+        before,
+        after,
+        expected: patch_body(diffs),
+    }
+}
+
+/// Removes all of `count` rows.
+pub fn clear(count: usize) -> Workload {
+    Workload {
+        name: "clear",
+        before: rows(count),
+        after: vec![],
+        expected: patch_body(match count {
+            0 => vec![],
+            _ => vec![Diff::ClearChildren],
+        }),
+    }
+}
+
+/// The workloads above, each with `count` rows.
+pub fn workloads(count: usize) -> Vec<Workload> {
+    vec![
+        create(count),
+        update_every_tenth(count),
+        swap(count),
+        clear(count),
+    ]
+}
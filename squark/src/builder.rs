@@ -0,0 +1,143 @@
+//! Configuring an `Env` in one place.
+
+use addressing::Addressing;
+use id::{IdGen, UuidIds};
+use log::{self, Level};
+use std::rc::Rc;
+use storage::KeyValueStorage;
+use {App, Devtools, DiffFilter, Env, Middleware, Normalize};
+
+/// When a state change is rendered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum RenderSchedule {
+    /// Once the runtime gets to it, see `Runtime::schedule_render`.
+    #[default]
+    Deferred,
+    /// Right after the actions are reduced, before their commands run.
+    Immediate,
+    /// Only when `Runtime::run` is called.
+    Manual,
+}
+
+type LogSink = Rc<dyn Fn(Level, &str)>;
+
+/// Options of an `Env`, from `Env::builder()`.
+pub struct EnvBuilder<A: App> {
+    id_gen: Option<Rc<dyn IdGen>>,
+    normalize: Normalize,
+    placeholders: bool,
+    addressing: Addressing,
+    middleware: Vec<Middleware<A>>,
+    diff_filters: Vec<Rc<dyn DiffFilter>>,
+    storage: Option<Rc<dyn KeyValueStorage>>,
+    devtools: Option<Devtools<A>>,
+    global_log_sink: Option<LogSink>,
+    schedule: RenderSchedule,
+}
+
+impl<A: App> EnvBuilder<A> {
+    pub(crate) fn new() -> EnvBuilder<A> {
+        EnvBuilder {
+            id_gen: None,
+            normalize: Normalize::default(),
+            placeholders: false,
+            addressing: Addressing::default(),
+            middleware: vec![],
+            diff_filters: vec![],
+            storage: None,
+            devtools: None,
+            global_log_sink: None,
+            schedule: RenderSchedule::default(),
+        }
+    }
+
+    /// The strategy for handler and subscription ids, random uuids by
+    /// default.
+    pub fn id_gen(mut self, id_gen: Rc<dyn IdGen>) -> EnvBuilder<A> {
+        self.id_gen = Some(id_gen);
+        self
+    }
+
+    /// Makes the ids reproducible. See `UuidIds::seeded`.
+    pub fn seed_ids(self, seed: u64) -> EnvBuilder<A> {
+        self.id_gen(Rc::new(UuidIds::seeded(seed)))
+    }
+
+    pub fn normalize(mut self, normalize: Normalize) -> EnvBuilder<A> {
+        self.normalize = normalize;
+        self
+    }
+
+    /// See `Env::set_placeholders`.
+    pub fn placeholders(mut self, placeholders: bool) -> EnvBuilder<A> {
+        self.placeholders = placeholders;
+        self
+    }
+
+    pub fn addressing(mut self, addressing: Addressing) -> EnvBuilder<A> {
+        self.addressing = addressing;
+        self
+    }
+
+    /// Runs every dispatched action through `f` before the reducer, which
+    /// gets what `f` returns. Returning `None` drops the action. Middleware
+    /// runs in the order it is added.
+    pub fn middleware<F>(mut self, f: F) -> EnvBuilder<A>
+    where
+        F: Fn(&A::State, A::Action) -> Option<A::Action> + 'static,
+    {
+        self.middleware.push(Rc::new(f));
+        self
+    }
+
+    /// See `Env::add_diff_filter`. Filters run in the order they are added.
+    pub fn diff_filter<F: DiffFilter + 'static>(mut self, filter: F) -> EnvBuilder<A> {
+        self.diff_filters.push(Rc::new(filter));
+        self
+    }
+
+    /// Storage for `Cmd` storage commands, in place of the runtime's.
+    pub fn storage(mut self, storage: Rc<dyn KeyValueStorage>) -> EnvBuilder<A> {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Records every action, state change and render diff. See
+    /// `Env::attach_devtools`.
+    pub fn devtools(mut self, bridge: Devtools<A>) -> EnvBuilder<A> {
+        self.devtools = Some(bridge);
+        self
+    }
+
+    /// Sets where warnings go when built, as `log::set_sink` does. The sink
+    /// is not kept by the env: it takes every warning of the thread, whichever
+    /// env logs it, until another one is set.
+    pub fn global_log_sink<F: Fn(Level, &str) + 'static>(mut self, sink: F) -> EnvBuilder<A> {
+        self.global_log_sink = Some(Rc::new(sink));
+        self
+    }
+
+    pub fn schedule(mut self, schedule: RenderSchedule) -> EnvBuilder<A> {
+        self.schedule = schedule;
+        self
+    }
+
+    pub fn build(self, state: A::State) -> Env<A> {
+        let mut env = Env::new(state);
+        if let Some(id_gen) = self.id_gen {
+            env.set_id_gen(id_gen);
+        }
+        env.set_normalize(self.normalize);
+        env.set_placeholders(self.placeholders);
+        env.set_addressing(self.addressing);
+        env.middleware = Rc::new(self.middleware);
+        *env.diff_filters.borrow_mut() = self.diff_filters;
+        env.storage = self.storage;
+        *env.devtools.borrow_mut() = self.devtools;
+        if let Some(sink) = self.global_log_sink {
+            log::set_sink(move |level, message: &str| sink(level, message));
+        }
+        env.schedule = self.schedule;
+        env
+    }
+}
@@ -0,0 +1,252 @@
+use a11y::Politeness;
+use fetch::{FetchError, Request, Response};
+use media::MediaControl;
+use router::{Route, ScrollBehavior};
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+#[cfg(all(debug_assertions, feature = "debug-checks"))]
+use warn;
+
+pub type Task = Pin<Box<dyn Future<Output = ()>>>;
+
+type Callback<T, A> = Box<dyn FnOnce(T) -> Option<A>>;
+
+/// Single effect requested by an `App`, interpreted by the runtime.
+pub enum Command<A> {
+    Fetch(Request, Callback<Result<Response, FetchError>, A>),
+    StorageGet(String, Callback<Option<String>, A>),
+    StorageSet(String, String),
+    StorageRemove(String),
+    PushUrl(String),
+    ReplaceUrl(String),
+    Announce(String, Politeness),
+    Media(String, MediaControl),
+    ScrollIntoView(String, ScrollBehavior),
+    SetTitle(String),
+    SetFavicon(String),
+    /// Marks the resource as pending until the command completes.
+    Tracked(String, Box<Command<A>>),
+}
+
+impl<A: 'static> Command<A> {
+    fn map<B: 'static>(self, f: Rc<dyn Fn(A) -> B>) -> Command<B> {
+        match self {
+            Command::Fetch(request, callback) => {
+                Command::Fetch(request, Box::new(move |res| callback(res).map(|a| f(a))))
+            }
+            Command::StorageGet(key, callback) => {
+                Command::StorageGet(key, Box::new(move |v| callback(v).map(|a| f(a))))
+            }
+            Command::StorageSet(key, value) => Command::StorageSet(key, value),
+            Command::StorageRemove(key) => Command::StorageRemove(key),
+            Command::PushUrl(url) => Command::PushUrl(url),
+            Command::ReplaceUrl(url) => Command::ReplaceUrl(url),
+            Command::Announce(message, politeness) => Command::Announce(message, politeness),
+            Command::Media(id, control) => Command::Media(id, control),
+            Command::ScrollIntoView(key, behavior) => Command::ScrollIntoView(key, behavior),
+            Command::SetTitle(title) => Command::SetTitle(title),
+            Command::SetFavicon(url) => Command::SetFavicon(url),
+            Command::Tracked(key, command) => Command::Tracked(key, Box::new(command.map(f))),
+        }
+    }
+}
+
+pub struct Cmd<A> {
+    commands: Vec<Command<A>>,
+}
+
+impl<A: 'static> Cmd<A> {
+    pub fn none() -> Cmd<A> {
+        Cmd { commands: vec![] }
+    }
+
+    pub fn batch<I>(cmds: I) -> Cmd<A>
+    where
+        I: IntoIterator<Item = Cmd<A>>,
+    {
+        Cmd {
+            commands: cmds.into_iter().flat_map(|c| c.commands).collect(),
+        }
+    }
+
+    pub fn fetch<F>(request: Request, f: F) -> Cmd<A>
+    where
+        F: FnOnce(Result<Response, FetchError>) -> Option<A> + 'static,
+    {
+        Command::Fetch(request, Box::new(f)).into()
+    }
+
+    /// Sends `request` for a change the reducer has already made, tracked
+    /// under `key` as with `track`. Dispatches `undo` to roll the change back
+    /// if the request fails or its response is not a success, otherwise the
+    /// action `f` returns.
+    pub fn optimistic<K, F>(key: K, request: Request, undo: A, f: F) -> Cmd<A>
+    where
+        K: Into<String>,
+        F: FnOnce(Response) -> Option<A> + 'static,
+    {
+        Cmd::fetch(request, move |res| match res {
+            Ok(ref res) if !res.is_success() => Some(undo),
+            Ok(res) => f(res),
+            Err(_) => Some(undo),
+        })
+        .track(key)
+    }
+
+    pub fn storage_get<K, F>(key: K, f: F) -> Cmd<A>
+    where
+        K: Into<String>,
+        F: FnOnce(Option<String>) -> A + 'static,
+    {
+        Command::StorageGet(key.into(), Box::new(move |v| Some(f(v)))).into()
+    }
+
+    pub fn storage_set<K: Into<String>, V: Into<String>>(key: K, value: V) -> Cmd<A> {
+        Command::StorageSet(key.into(), value.into()).into()
+    }
+
+    pub fn storage_remove<K: Into<String>>(key: K) -> Cmd<A> {
+        Command::StorageRemove(key.into()).into()
+    }
+
+    pub fn push_url<S: Into<String>>(url: S) -> Cmd<A> {
+        Command::PushUrl(url.into()).into()
+    }
+
+    pub fn replace_url<S: Into<String>>(url: S) -> Cmd<A> {
+        Command::ReplaceUrl(url.into()).into()
+    }
+
+    pub fn navigate<R: Route>(route: &R) -> Cmd<A> {
+        Cmd::push_url(route.to_url())
+    }
+
+    /// Has screen readers read out `message`, e.g. "3 results loaded".
+    pub fn announce<S: Into<String>>(message: S, politeness: Politeness) -> Cmd<A> {
+        Command::Announce(message.into(), politeness).into()
+    }
+
+    /// Starts playing the audio or video element with id `id`.
+    pub fn play<S: Into<String>>(id: S) -> Cmd<A> {
+        Command::Media(id.into(), MediaControl::Play).into()
+    }
+
+    pub fn pause<S: Into<String>>(id: S) -> Cmd<A> {
+        Command::Media(id.into(), MediaControl::Pause).into()
+    }
+
+    /// Moves the playback position of `id` to `seconds`.
+    pub fn seek<S: Into<String>>(id: S, seconds: f64) -> Cmd<A> {
+        Command::Media(id.into(), MediaControl::Seek(seconds)).into()
+    }
+
+    /// Scrolls the first element keyed `key` into view once rendered, like a
+    /// new message at the end of a chat.
+    pub fn scroll_into_view<K: Into<String>>(key: K, behavior: ScrollBehavior) -> Cmd<A> {
+        Command::ScrollIntoView(key.into(), behavior).into()
+    }
+
+    /// Sets the document's title.
+    pub fn set_title<S: Into<String>>(title: S) -> Cmd<A> {
+        Command::SetTitle(title.into()).into()
+    }
+
+    /// Points the document's icon at `url`.
+    pub fn set_favicon<S: Into<String>>(url: S) -> Cmd<A> {
+        Command::SetFavicon(url.into()).into()
+    }
+
+    /// Has `pending(key, ..)` views show their fallback until these commands
+    /// complete. Only fetches and storage reads report back; other commands
+    /// are done once run, so they are left untracked, with a warning in debug
+    /// builds.
+    pub fn track<K: Into<String>>(self, key: K) -> Cmd<A> {
+        let key = key.into();
+        Cmd {
+            commands: self
+                .commands
+                .into_iter()
+                .map(|c| match c {
+                    Command::Fetch(..) | Command::StorageGet(..) | Command::Tracked(..) => {
+                        Command::Tracked(key.clone(), Box::new(c))
+                    }
+                    c => {
+                        #[cfg(all(debug_assertions, feature = "debug-checks"))]
+                        warn(&format!(
+                            "only fetches and storage reads are tracked by \"{}\"",
+                            key
+                        ));
+                        c
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    pub fn is_none(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub fn map<B: 'static, F>(self, f: F) -> Cmd<B>
+    where
+        F: Fn(A) -> B + 'static,
+    {
+        let f: Rc<dyn Fn(A) -> B> = Rc::new(f);
+        Cmd {
+            commands: self
+                .commands
+                .into_iter()
+                .map(|c| c.map(f.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl<A> From<Command<A>> for Cmd<A> {
+    fn from(command: Command<A>) -> Cmd<A> {
+        Cmd {
+            commands: vec![command],
+        }
+    }
+}
+
+impl<A> IntoIterator for Cmd<A> {
+    type Item = Command<A>;
+    type IntoIter = ::std::vec::IntoIter<Command<A>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.commands.into_iter()
+    }
+}
+
+struct Then<T> {
+    future: Pin<Box<dyn Future<Output = T>>>,
+    f: Option<Box<dyn FnOnce(T)>>,
+}
+
+impl<T> Future for Then<T> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        match self.future.as_mut().poll(cx) {
+            Poll::Ready(v) => {
+                let f = self.f.take().expect("Then polled after completion");
+                f(v);
+                Poll::Ready(())
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+pub(crate) fn then<T: 'static, F>(future: Pin<Box<dyn Future<Output = T>>>, f: F) -> Task
+where
+    F: FnOnce(T) + 'static,
+{
+    Box::pin(Then {
+        future,
+        f: Some(Box::new(f)),
+    })
+}
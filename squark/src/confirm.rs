@@ -0,0 +1,58 @@
+//! Renders which runtimes apply later than they are diffed.
+
+use Node;
+
+/// Identifies a render a runtime applies later. See `Runtime::handle_render`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RenderToken(pub(crate) u64);
+
+#[derive(Default)]
+pub(crate) struct Confirmations {
+    // The tree the runtime confirmed last, while later renders are pending.
+    base: Option<Node>,
+    // The trees of the renders not confirmed yet, in order.
+    pending: Vec<(RenderToken, Node)>,
+}
+
+impl Confirmations {
+    pub(crate) fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    pub(crate) fn stage(&mut self, token: RenderToken, before: Node, after: Node) {
+        if self.base.is_none() {
+            self.base = Some(before);
+        }
+        self.pending.push((token, after));
+    }
+
+    // Confirms the render and those before it, returning whether any was
+    // pending.
+    pub(crate) fn confirm(&mut self, token: RenderToken) -> bool {
+        let confirmed = self.pending.iter().take_while(|(t, _)| *t <= token).count();
+        if confirmed == 0 {
+            return false;
+        }
+        let (_, node) = self.pending.drain(..confirmed).next_back().unwrap();
+        self.base = if self.pending.is_empty() {
+            None
+        } else {
+            Some(node)
+        };
+        true
+    }
+
+    // Drops the render and those after it, which were diffed against it,
+    // returning the tree the runtime still shows. Earlier renders count as
+    // confirmed.
+    pub(crate) fn reject(&mut self, token: RenderToken) -> Option<Node> {
+        if !self.pending.iter().any(|(t, _)| *t == token) {
+            return None;
+        }
+        if let Some(&(previous, _)) = self.pending.iter().take_while(|(t, _)| *t < token).last() {
+            self.confirm(previous);
+        }
+        self.pending.clear();
+        self.base.take()
+    }
+}
@@ -0,0 +1,42 @@
+use super::{Node, Tag, View};
+use std::collections::HashMap;
+
+/// Marks the elements of `Node::Custom`, holding their name.
+pub const CUSTOM_ATTRIBUTE: &str = "data-squark-custom";
+
+/// An element whose contents belong to user code, like a canvas drawn on
+/// directly. Runtimes create the element and hand it to hooks registered for
+/// its name when it is mounted and unmounted. Squark never diffs inside it;
+/// changing the tag or name replaces it.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Custom {
+    tag: Tag,
+    name: String,
+}
+
+impl Custom {
+    pub(crate) fn new(tag: Tag, name: String) -> Custom {
+        Custom { tag, name }
+    }
+
+    pub fn tag(&self) -> &Tag {
+        &self.tag
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<A> View<A> {
+    /// A `<tag>` managed by the hooks registered for `name`.
+    pub fn custom<T: Into<Tag>, S: Into<String>>(tag: T, name: S) -> View<A> {
+        View {
+            node: Node::Custom(Custom {
+                tag: tag.into(),
+                name: name.into(),
+            }),
+            handler_map: HashMap::new(),
+        }
+    }
+}
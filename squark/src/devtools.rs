@@ -0,0 +1,25 @@
+use super::Diff;
+use std::sync::mpsc::Sender;
+
+/// What an `Env` reports to an attached inspector.
+#[derive(Clone, Debug)]
+pub enum DevtoolsEvent<S, A> {
+    /// An action was dispatched.
+    Action(A),
+    /// The state after an action changed it.
+    State(S),
+    /// A diff handed to the runtime on render.
+    Diff(Diff),
+}
+
+/// Receives the events of an `Env`, e.g. to forward them to an external inspector.
+pub trait DevtoolsBridge<S, A> {
+    fn send(&self, event: DevtoolsEvent<S, A>);
+}
+
+impl<S, A> DevtoolsBridge<S, A> for Sender<DevtoolsEvent<S, A>> {
+    fn send(&self, event: DevtoolsEvent<S, A>) {
+        // A closed inspector must not break the app.
+        let _ = Sender::send(self, event);
+    }
+}
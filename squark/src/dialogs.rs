@@ -0,0 +1,107 @@
+//! Modal dialogs stacked above the page.
+
+use a11y::focus_trap;
+use shortcuts::Shortcuts;
+use {handler, keyed_list, View};
+
+/// Open dialogs, kept in the state, with the topmost last. `D` describes a
+/// dialog, typically an enum of the app's dialogs and their data.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dialogs<D> {
+    stack: Vec<(u64, D)>,
+    next_id: u64,
+}
+
+impl<D> Default for Dialogs<D> {
+    fn default() -> Self {
+        Dialogs::new()
+    }
+}
+
+impl<D> Dialogs<D> {
+    pub fn new() -> Dialogs<D> {
+        Dialogs {
+            stack: vec![],
+            next_id: 0,
+        }
+    }
+
+    /// Opens `dialog` above the others and returns its id.
+    pub fn open(&mut self, dialog: D) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.stack.push((id, dialog));
+        id
+    }
+
+    /// Closes the dialog `id` along with the dialogs opened above it.
+    pub fn close(&mut self, id: u64) -> Option<D> {
+        let index = self.stack.iter().position(|&(i, _)| i == id)?;
+        self.stack.drain(index..).next().map(|(_, dialog)| dialog)
+    }
+
+    pub fn top(&self) -> Option<(u64, &D)> {
+        self.stack.last().map(|&(id, ref dialog)| (id, dialog))
+    }
+
+    pub fn is_open(&self) -> bool {
+        !self.stack.is_empty()
+    }
+
+    /// Open dialogs by id, bottom first.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, &D)> {
+        self.stack.iter().map(|&(id, ref dialog)| (id, dialog))
+    }
+
+    /// Binds `escape` to `close` with the topmost dialog's id while a dialog
+    /// is open.
+    pub fn bind_escape<A, F>(&self, shortcuts: Shortcuts<A>, close: F) -> Shortcuts<A>
+    where
+        A: Clone + 'static,
+        F: Fn(u64) -> A,
+    {
+        match self.top() {
+            Some((id, _)) => shortcuts.bind("escape", close(id)),
+            None => shortcuts,
+        }
+    }
+}
+
+/// The open dialogs, each rendered by `render` inside a modal element that
+/// traps focus, over a backdrop calling `close` with its id when clicked.
+/// Dialogs below the topmost one are inert. Meant for an `App::roots` mount
+/// point so that it stacks above the page.
+pub fn dialog_stack<A, D, F, C>(dialogs: &Dialogs<D>, render: F, close: C) -> View<A>
+where
+    A: 'static,
+    F: Fn(&D) -> View<A>,
+    C: Fn(u64) -> A + Clone + 'static,
+{
+    let top = dialogs.top().map(|(id, _)| id);
+    let layers = keyed_list(dialogs.iter(), |(id, dialog)| {
+        let close = close.clone();
+        let backdrop = View::new(
+            "div",
+            vec![("data-backdrop".to_string(), true.into())],
+            vec![("click".to_string(), handler(move |_| close(id)))],
+            vec![],
+        );
+        let modal = focus_trap(View::new(
+            "div",
+            vec![
+                ("role".to_string(), "dialog".into()),
+                ("aria-modal".to_string(), "true".into()),
+            ],
+            vec![],
+            vec![render(dialog).into()],
+        ));
+        let layer = View::new(
+            "div",
+            vec![("inert".to_string(), (Some(id) != top).into())],
+            vec![],
+            vec![backdrop.into(), modal.into()],
+        );
+        (id, layer)
+    });
+    View::new("div", vec![], vec![], vec![layers])
+}
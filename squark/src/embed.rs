@@ -0,0 +1,81 @@
+use std::rc::Rc;
+use {App, Cmd, Subscription, View};
+
+type Mapper<C, A> = Rc<dyn Fn(<C as App>::Action) -> A>;
+type Getter<C, S> = Rc<dyn Fn(&S) -> <C as App>::State>;
+type Updater<C, S> = Rc<dyn Fn(S, <C as App>::State) -> S>;
+
+/// Mounts a child `App` inside a parent by projecting its state slice out of the
+/// parent state and mapping its actions into parent actions.
+pub struct Embed<C: App, S, A> {
+    app: C,
+    mapper: Mapper<C, A>,
+    selector: Getter<C, S>,
+    updater: Updater<C, S>,
+}
+
+impl<C: App, S, A> Clone for Embed<C, S, A> {
+    fn clone(&self) -> Self {
+        Embed {
+            app: self.app.clone(),
+            mapper: self.mapper.clone(),
+            selector: self.selector.clone(),
+            updater: self.updater.clone(),
+        }
+    }
+}
+
+impl<C: App, S, A: 'static> Embed<C, S, A> {
+    pub fn app(&self) -> &C {
+        &self.app
+    }
+
+    pub fn state(&self, state: &S) -> C::State {
+        (self.selector)(state)
+    }
+
+    pub fn view(&self, state: &S) -> View<A> {
+        let mapper = self.mapper.clone();
+        self.app
+            .view(self.state(state))
+            .map(move |action| mapper(action))
+    }
+
+    pub fn command(&self, state: &S, action: &C::Action) -> Cmd<A> {
+        let mapper = self.mapper.clone();
+        self.app
+            .command(&self.state(state), action)
+            .map(move |action| mapper(action))
+    }
+
+    pub fn subscriptions(&self, state: &S) -> Vec<Subscription<A>> {
+        self.app
+            .subscriptions(&self.state(state))
+            .into_iter()
+            .map(|sub| {
+                let mapper = self.mapper.clone();
+                sub.map(move |action| mapper(action))
+            })
+            .collect()
+    }
+
+    pub fn reducer(&self, state: S, action: C::Action) -> S {
+        let child_state = self.app.reducer(self.state(&state), action);
+        (self.updater)(state, child_state)
+    }
+}
+
+pub fn embed<C, S, A, M, F, U>(mapper: M, selector: F, updater: U) -> Embed<C, S, A>
+where
+    C: App,
+    M: Fn(C::Action) -> A + 'static,
+    F: Fn(&S) -> C::State + 'static,
+    U: Fn(S, C::State) -> S + 'static,
+{
+    Embed {
+        app: C::default(),
+        mapper: Rc::new(mapper),
+        selector: Rc::new(selector),
+        updater: Rc::new(updater),
+    }
+}
@@ -0,0 +1,111 @@
+use std::future::Future;
+use std::pin::Pin;
+
+pub type FetchFuture = Pin<Box<dyn Future<Output = Result<Response, FetchError>>>>;
+
+/// Capability to perform HTTP requests, supplied by the runtime.
+pub trait Fetch {
+    fn request(&self, request: Request) -> FetchFuture;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Method {
+    Get,
+    Post,
+    Put,
+    Patch,
+    Delete,
+    Head,
+}
+
+impl Method {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Get => "GET",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Patch => "PATCH",
+            Method::Delete => "DELETE",
+            Method::Head => "HEAD",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Request {
+    method: Method,
+    url: String,
+    headers: Vec<(String, String)>,
+    body: Option<String>,
+}
+
+impl Request {
+    pub fn new<S: Into<String>>(method: Method, url: S) -> Request {
+        Request {
+            method,
+            url: url.into(),
+            headers: vec![],
+            body: None,
+        }
+    }
+
+    pub fn get<S: Into<String>>(url: S) -> Request {
+        Request::new(Method::Get, url)
+    }
+
+    pub fn post<S: Into<String>, B: Into<String>>(url: S, body: B) -> Request {
+        Request::new(Method::Post, url).body(body)
+    }
+
+    pub fn header<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Request {
+        self.headers.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn body<B: Into<String>>(mut self, body: B) -> Request {
+        self.body = Some(body.into());
+        self
+    }
+
+    pub fn method(&self) -> Method {
+        self.method
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    pub fn get_body(&self) -> Option<&str> {
+        self.body.as_deref()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Response {
+    pub fn is_success(&self) -> bool {
+        self.status >= 200 && self.status < 300
+    }
+
+    pub fn header(&self, key: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FetchError {
+    Network(String),
+    Unavailable,
+}
@@ -0,0 +1,129 @@
+//! Hooks between diffing and the runtime, for sandboxing what a render may
+//! change or recording the ops it makes.
+
+use std::mem;
+use std::rc::Rc;
+#[cfg(all(debug_assertions, feature = "debug-checks"))]
+use warn;
+use Diff;
+
+/// A step from the mount point towards the element a diff op applies to.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PatchStep {
+    Child(usize),
+    Keyed(String),
+    /// The element with the id, wherever it is. See `Addressing::StableIds`.
+    Node(u64),
+    Shadow,
+}
+
+/// Inspects, rewrites or drops the ops of every diff before the runtime gets
+/// it. See `Env::add_diff_filter`.
+pub trait DiffFilter {
+    /// The op to apply in place of `diff`, or `None` to drop it. `path` leads
+    /// to the element the op applies to. Ops patching children come without
+    /// the ops they hold, which are filtered after them.
+    ///
+    /// Only ops setting attributes, handlers, properties or text, and those
+    /// not touching the tree, may be dropped or rewritten, into ops of these
+    /// kinds. Ops adding, removing, replacing or patching nodes are kept as
+    /// they are, with a warning in debug builds if the filter returns
+    /// something else, as the runtime's tree would no longer match what later
+    /// diffs address.
+    fn filter(&self, path: &[PatchStep], diff: Diff) -> Option<Diff>;
+}
+
+impl<F: Fn(&[PatchStep], Diff) -> Option<Diff>> DiffFilter for F {
+    fn filter(&self, path: &[PatchStep], diff: Diff) -> Option<Diff> {
+        self(path, diff)
+    }
+}
+
+pub(crate) type DiffFilters = Vec<Rc<dyn DiffFilter>>;
+
+// Runs the diff through every filter in order.
+pub(crate) fn apply(filters: &[Rc<dyn DiffFilter>], diff: Diff) -> Option<Diff> {
+    filters
+        .iter()
+        .try_fold(diff, |diff, f| filter(&**f, &mut vec![], diff))
+}
+
+// Whether the op changes which nodes the runtime's tree has, or holds ops
+// which may.
+fn is_structural(diff: &Diff) -> bool {
+    matches!(
+        diff,
+        Diff::AddChild(..)
+            | Diff::AddChildren(..)
+            | Diff::ReplaceChild(..)
+            | Diff::RemoveChild(_)
+            | Diff::RemoveChildren(..)
+            | Diff::ClearChildren
+            | Diff::PatchChild(..)
+            | Diff::PatchNode(..)
+            | Diff::PatchKeyed(..)
+            | Diff::ReplaceKeyed(..)
+            | Diff::RemoveKeyed(_)
+            | Diff::ReplaceRoot(_)
+            | Diff::PatchRoot(_)
+            | Diff::PatchShadow(_)
+            | Diff::PatchPath(..)
+    )
+}
+
+// The steps into the element whose ops `diff` holds, and those ops.
+fn nested(diff: &mut Diff) -> Option<(Vec<PatchStep>, &mut Vec<Diff>)> {
+    match *diff {
+        Diff::PatchRoot(ref mut diffs) => Some((vec![], diffs)),
+        Diff::PatchChild(i, ref mut diffs) => Some((vec![PatchStep::Child(i)], diffs)),
+        Diff::PatchNode(id, ref mut diffs) => Some((vec![PatchStep::Node(id)], diffs)),
+        Diff::PatchKeyed(ref key, ref mut diffs) => {
+            Some((vec![PatchStep::Keyed(key.clone())], diffs))
+        }
+        Diff::PatchShadow(ref mut diffs) => Some((vec![PatchStep::Shadow], diffs)),
+        Diff::PatchPath(ref indices, ref mut diffs) => Some((
+            indices.iter().map(|&i| PatchStep::Child(i)).collect(),
+            diffs,
+        )),
+        _ => None,
+    }
+}
+
+fn filter(f: &dyn DiffFilter, path: &mut Vec<PatchStep>, mut diff: Diff) -> Option<Diff> {
+    // The held ops are filtered on their own below, so only the op without
+    // them is copied to tell whether the filter changed it.
+    let held = nested(&mut diff).map(|(_, diffs)| mem::take(diffs));
+    let structural = is_structural(&diff);
+    let filtered = f.filter(path, diff.clone());
+    let mut diff = match filtered {
+        Some(ref filtered) if *filtered == diff => diff,
+        Some(filtered) if !structural && !is_structural(&filtered) => filtered,
+        None if !structural => return None,
+        _ => {
+            #[cfg(all(debug_assertions, feature = "debug-checks"))]
+            warn(&format!(
+                "diff filters may not drop or rewrite ops changing the tree, kept one at {:?}",
+                path
+            ));
+            diff
+        }
+    };
+    let (steps, diffs) = match (nested(&mut diff), held) {
+        (Some((steps, diffs)), Some(held)) => {
+            *diffs = held;
+            (steps, diffs)
+        }
+        _ => return Some(diff),
+    };
+    let depth = path.len();
+    path.extend(steps);
+    *diffs = mem::take(diffs)
+        .into_iter()
+        .filter_map(|diff| filter(f, path, diff))
+        .collect();
+    path.truncate(depth);
+    if diffs.is_empty() {
+        return None;
+    }
+    Some(diff)
+}
@@ -0,0 +1,86 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+thread_local! {
+    static TRANSLATOR: RefCell<Option<Rc<dyn Translator>>> = RefCell::new(None);
+}
+
+/// Resolves message keys into localized text.
+pub trait Translator {
+    fn locale(&self) -> &str;
+
+    fn translate(&self, key: &str, args: &[(&str, String)]) -> Option<String>;
+}
+
+/// A translator backed by a table of messages for a single locale.
+///
+/// Messages may reference arguments as `{name}`.
+#[derive(Clone, Debug, Default)]
+pub struct Catalog {
+    locale: String,
+    messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    pub fn new<S: Into<String>>(locale: S) -> Catalog {
+        Catalog {
+            locale: locale.into(),
+            messages: HashMap::new(),
+        }
+    }
+
+    pub fn message<K: Into<String>, V: Into<String>>(mut self, key: K, text: V) -> Catalog {
+        self.messages.insert(key.into(), text.into());
+        self
+    }
+}
+
+impl Translator for Catalog {
+    fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    fn translate(&self, key: &str, args: &[(&str, String)]) -> Option<String> {
+        self.messages
+            .get(key)
+            .map(|template| interpolate(template, args))
+    }
+}
+
+fn interpolate(template: &str, args: &[(&str, String)]) -> String {
+    let mut result = template.to_string();
+    for &(name, ref value) in args {
+        result = result.replace(&format!("{{{}}}", name), value);
+    }
+    result
+}
+
+pub(crate) fn with_translator<T, F>(translator: Option<Rc<dyn Translator>>, f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    let previous = TRANSLATOR.with(|t| t.replace(translator));
+    let result = f();
+    TRANSLATOR.with(|t| *t.borrow_mut() = previous);
+    result
+}
+
+/// Looks `key` up in the translator of the view being built. Unknown keys
+/// and views built without a translator fall back to the key itself.
+pub fn translate(key: &str, args: &[(&str, String)]) -> String {
+    TRANSLATOR
+        .with(|t| t.borrow().as_ref().and_then(|t| t.translate(key, args)))
+        .unwrap_or_else(|| interpolate(key, args))
+}
+
+/// Translates a message key, e.g. `t!("greeting", name = user.name)`.
+#[macro_export]
+macro_rules! t {
+    ($key:expr) => {
+        $crate::translate($key, &[])
+    };
+    ($key:expr, $($name:ident = $value:expr),+ $(,)*) => {
+        $crate::translate($key, &[$((stringify!($name), $value.to_string())),+])
+    };
+}
@@ -0,0 +1,154 @@
+use rand::prelude::*;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::rc::Rc;
+use uuid::{Builder, Uuid, Variant, Version};
+
+thread_local! {
+    static RNG: RefCell<SmallRng> = RefCell::new(SmallRng::from_entropy());
+    static CURRENT: RefCell<Option<Rc<dyn IdGen>>> = RefCell::new(None);
+    static PREFIX: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Strategy for the ids an `Env` gives handlers and subscriptions.
+pub trait IdGen {
+    /// `content` describes what is identified, such as the type of a
+    /// handler closure or the kind of a subscription.
+    fn next_id(&self, content: &str) -> String;
+
+    /// Called before every render.
+    fn reset(&self) {}
+}
+
+/// Random UUIDs, the default.
+#[derive(Default)]
+pub struct UuidIds {
+    rng: Option<RefCell<SmallRng>>,
+}
+
+impl UuidIds {
+    pub fn new() -> UuidIds {
+        UuidIds::default()
+    }
+
+    /// UUIDs which are the same on every run.
+    pub fn seeded(seed: u64) -> UuidIds {
+        UuidIds {
+            rng: Some(RefCell::new(SmallRng::seed_from_u64(seed))),
+        }
+    }
+}
+
+impl IdGen for UuidIds {
+    fn next_id(&self, _content: &str) -> String {
+        match self.rng {
+            Some(ref rng) => random_uuid(rng.borrow_mut().gen()).to_string(),
+            None => uuid(),
+        }
+    }
+}
+
+/// `0`, `1`, `2`, ... in order of generation.
+#[derive(Default)]
+pub struct SequentialIds {
+    next: Cell<u64>,
+}
+
+impl SequentialIds {
+    pub fn new() -> SequentialIds {
+        SequentialIds::default()
+    }
+}
+
+impl IdGen for SequentialIds {
+    fn next_id(&self, _content: &str) -> String {
+        let id = self.next.get();
+        self.next.set(id + 1);
+        id.to_string()
+    }
+}
+
+/// A hash of the content plus its occurrence within the render, so a view
+/// producing the same handlers gets the same short ids on every render.
+#[derive(Default)]
+pub struct ContentIds {
+    seen: RefCell<HashMap<u32, u32>>,
+}
+
+impl ContentIds {
+    pub fn new() -> ContentIds {
+        ContentIds::default()
+    }
+}
+
+impl IdGen for ContentIds {
+    fn next_id(&self, content: &str) -> String {
+        let hash = content.bytes().fold(0x811c_9dc5, |hash: u32, b| {
+            (hash ^ u32::from(b)).wrapping_mul(0x0100_0193)
+        });
+        let mut seen = self.seen.borrow_mut();
+        let count = seen.entry(hash).or_insert(0);
+        let mut id = format!("{:x}", hash);
+        if *count > 0 {
+            let _ = write!(id, "-{}", count);
+        }
+        *count += 1;
+        id
+    }
+
+    fn reset(&self) {
+        self.seen.borrow_mut().clear();
+    }
+}
+
+pub(crate) fn with_id_gen<T, F>(id_gen: Rc<dyn IdGen>, f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    let previous = CURRENT.with(|c| c.replace(Some(id_gen)));
+    let result = f();
+    CURRENT.with(|c| *c.borrow_mut() = previous);
+    result
+}
+
+// Prefixes the ids generated by `f`, nesting within the current prefix.
+pub(crate) fn with_prefix<T, F>(prefix: &str, f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    let nested = match PREFIX.with(|p| p.borrow().clone()) {
+        Some(outer) => format!("{}/{}", outer, prefix),
+        None => prefix.to_string(),
+    };
+    let previous = PREFIX.with(|p| p.replace(Some(nested)));
+    let result = f();
+    PREFIX.with(|p| *p.borrow_mut() = previous);
+    result
+}
+
+// An id from the generator of the env currently rendering, if any.
+pub(crate) fn next_id(content: &str) -> String {
+    let current = CURRENT.with(|c| c.borrow().clone());
+    let id = match current {
+        Some(id_gen) => id_gen.next_id(content),
+        None => uuid(),
+    };
+    match PREFIX.with(|p| p.borrow().clone()) {
+        Some(prefix) => format!("{}/{}", prefix, id),
+        None => id,
+    }
+}
+
+pub fn uuid() -> String {
+    RNG.with(|rng| random_uuid(rng.borrow_mut().gen()))
+        .to_string()
+}
+
+// A version 4 UUID made of the bytes.
+fn random_uuid(bytes: [u8; 16]) -> Uuid {
+    Builder::from_bytes(bytes)
+        .set_variant(Variant::RFC4122)
+        .set_version(Version::Random)
+        .build()
+}
@@ -0,0 +1,139 @@
+//! Handlers for text inputs.
+
+use std::rc::Rc;
+use {handler, Attribute, AttributeValue, HandlerArg, HandlerFunction, IntoActions, View};
+
+/// Payload of `compositionstart`, `compositionupdate` and `compositionend`,
+/// fired while an input method editor composes text, e.g. for Japanese.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompositionEvent {
+    /// The text being composed, or committed on `compositionend`.
+    pub data: String,
+    /// The whole value of the input.
+    pub value: String,
+}
+
+impl CompositionEvent {
+    /// Reads `{"data", "value"}`.
+    pub fn from_arg(arg: &HandlerArg) -> Option<CompositionEvent> {
+        Some(CompositionEvent {
+            data: arg["data"].as_str()?.to_string(),
+            value: arg["value"].as_str()?.to_string(),
+        })
+    }
+}
+
+/// `input` handlers receive the value as a string, except while text is
+/// composed, when it is `{"value", "composing": true}`.
+pub fn is_composing(arg: &HandlerArg) -> bool {
+    arg["composing"].as_bool() == Some(true)
+}
+
+/// Handlers for `input` and `compositionend` calling `f` with the value of
+/// the input, skipping text which is still being composed. Some browsers
+/// fire both for a committed composition, so `f` may see a value twice.
+pub fn composed_input<A, R, F>(f: F) -> Vec<(String, (String, HandlerFunction<A>))>
+where
+    R: IntoActions<A>,
+    F: Fn(String) -> R + 'static,
+{
+    let f = Rc::new(f);
+    let on_end = f.clone();
+    vec![
+        (
+            "input".to_string(),
+            handler(move |arg| match arg.as_str() {
+                Some(value) => f(value.to_string()).into_actions(),
+                None => vec![],
+            }),
+        ),
+        (
+            "compositionend".to_string(),
+            handler(move |arg| match CompositionEvent::from_arg(&arg) {
+                Some(event) => on_end(event.value).into_actions(),
+                None => vec![],
+            }),
+        ),
+    ]
+}
+
+/// Has runtimes report `input` events of an element as `{"value", "caret"}`,
+/// with the caret in UTF-16 units like the DOM.
+pub const REPORT_CARET: &str = "data-report-caret";
+
+/// Value and caret of a masked input, kept in the state so that the caret
+/// survives the formatted value being set.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Masked {
+    pub value: String,
+    /// In UTF-16 units.
+    pub caret: usize,
+}
+
+impl Masked {
+    /// Formats `raw`, keeping the caret after as many letters and digits as
+    /// preceded it.
+    pub fn format<F: Fn(&str) -> String>(raw: &str, caret: usize, format: F) -> Masked {
+        let mut units = 0;
+        let significant = raw
+            .chars()
+            .take_while(|c| {
+                units += c.len_utf16();
+                units <= caret
+            })
+            .filter(|c| c.is_alphanumeric())
+            .count();
+        let value = format(raw);
+        let mut seen = 0;
+        let mut caret = 0;
+        for c in value.chars() {
+            if seen == significant {
+                break;
+            }
+            if c.is_alphanumeric() {
+                seen += 1;
+            }
+            caret += c.len_utf16();
+        }
+        if seen < significant {
+            caret = value.encode_utf16().count();
+        }
+        Masked { value, caret }
+    }
+}
+
+/// An `<input>` showing `masked`, which passes every edit through `format`
+/// before handing it to `f`. The caret is restored through the
+/// `selectionStart` and `selectionEnd` properties.
+pub fn masked_input<A, F, G>(
+    attributes: Vec<Attribute>,
+    masked: &Masked,
+    format: F,
+    f: G,
+) -> View<A>
+where
+    F: Fn(&str) -> String + 'static,
+    G: Fn(Masked) -> A + 'static,
+{
+    let caret = HandlerArg::from(masked.caret);
+    let mut attributes = attributes;
+    attributes.extend(vec![
+        ("value".to_string(), AttributeValue::Property(masked.value.clone().into())),
+        ("selectionStart".to_string(), AttributeValue::Property(caret.clone())),
+        ("selectionEnd".to_string(), AttributeValue::Property(caret)),
+        (REPORT_CARET.to_string(), true.into()),
+    ]);
+    let on_input = handler(move |arg: HandlerArg| {
+        if is_composing(&arg) {
+            return None;
+        }
+        // Without a caret, as for some input types, it is at the end.
+        let raw = arg.as_str().or_else(|| arg["value"].as_str())?;
+        let caret = match arg["caret"].as_u64() {
+            Some(caret) => caret as usize,
+            None => raw.encode_utf16().count(),
+        };
+        Some(f(Masked::format(raw, caret, &format)))
+    });
+    View::new("input", attributes, vec![("input".to_string(), on_input)], vec![])
+}
@@ -0,0 +1,36 @@
+//! Which attribute `View::new` takes the keys of elements from.
+
+use std::cell::RefCell;
+use {Attribute, AttributeValue};
+
+thread_local! {
+    static KEY_ATTRIBUTE: RefCell<Option<String>> = RefCell::new(Some("key".to_string()));
+}
+
+/// Sets the attribute `View::new` takes the key of an element from, `"key"`
+/// by default. The key is never rendered as an attribute. With `None`,
+/// every attribute is rendered and keys are only set with `View::with_key`
+/// and `keyed_list`.
+pub fn set_key_attribute(name: Option<&str>) {
+    KEY_ATTRIBUTE.with(|k| *k.borrow_mut() = name.map(String::from));
+}
+
+pub(crate) fn is_key_attribute(name: &str) -> bool {
+    KEY_ATTRIBUTE.with(|k| k.borrow().as_deref() == Some(name))
+}
+
+// Removes the key attribute, returning its last value.
+pub(crate) fn take_key(attributes: &mut Vec<Attribute>) -> Option<String> {
+    let mut key = None;
+    attributes.retain(|(name, value)| {
+        if !is_key_attribute(name) {
+            return true;
+        }
+        key = match *value {
+            AttributeValue::String(ref value) => Some(value.clone()),
+            _ => None,
+        };
+        false
+    });
+    key
+}
@@ -0,0 +1,92 @@
+//! Updating fields deep inside the state without rebuilding every struct on
+//! the way.
+
+use std::rc::Rc;
+
+type Getter<S, T> = Rc<dyn Fn(&S) -> &T>;
+type Setter<S, T> = Rc<dyn Fn(&mut S) -> &mut T>;
+
+/// A path to a `T` inside an `S`, usually built by `lens!`. Reducers take
+/// the state by value, so `set` and `update` change it in place and hand
+/// it back.
+pub struct Lens<S, T> {
+    get: Getter<S, T>,
+    get_mut: Setter<S, T>,
+}
+
+impl<S, T> Clone for Lens<S, T> {
+    fn clone(&self) -> Lens<S, T> {
+        Lens {
+            get: self.get.clone(),
+            get_mut: self.get_mut.clone(),
+        }
+    }
+}
+
+impl<S: 'static, T: 'static> Lens<S, T> {
+    pub fn new<G, M>(get: G, get_mut: M) -> Lens<S, T>
+    where
+        G: Fn(&S) -> &T + 'static,
+        M: Fn(&mut S) -> &mut T + 'static,
+    {
+        Lens {
+            get: Rc::new(get),
+            get_mut: Rc::new(get_mut),
+        }
+    }
+
+    pub fn get<'a>(&self, state: &'a S) -> &'a T {
+        (self.get)(state)
+    }
+
+    pub fn set(&self, state: S, value: T) -> S {
+        self.update(state, |target| *target = value)
+    }
+
+    pub fn update<F: FnOnce(&mut T)>(&self, mut state: S, f: F) -> S {
+        f((self.get_mut)(&mut state));
+        state
+    }
+
+    /// The lens focusing on a part of what this one does.
+    pub fn then<U: 'static>(&self, inner: Lens<T, U>) -> Lens<S, U> {
+        let (get, get_mut) = (self.get.clone(), self.get_mut.clone());
+        let (inner_get, inner_get_mut) = (inner.get, inner.get_mut);
+        Lens::new(
+            move |state| inner_get(get(state)),
+            move |state| inner_get_mut(get_mut(state)),
+        )
+    }
+}
+
+/// A `Lens` on the fields and indices following a state type, e.g.
+/// `lens!(State, todos[i].done)`. Indices are captured when the lens is
+/// built.
+#[macro_export]
+macro_rules! lens {
+    ($state:ty, $($path:tt)+) => {
+        $crate::Lens::new(
+            move |state: &$state| &state.$($path)+,
+            move |state: &mut $state| &mut state.$($path)+,
+        )
+    };
+}
+
+/// The state with one field assigned, e.g.
+/// `update!(state.todos[i].done = true)`, for reducers taking the state by
+/// value. The value is evaluated first, so it may read the old state.
+#[macro_export]
+macro_rules! update {
+    (@path $state:ident [$($path:tt)*] = $value:expr) => {{
+        let value = $value;
+        let mut state = $state;
+        state $($path)* = value;
+        state
+    }};
+    (@path $state:ident [$($path:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::update!(@path $state [$($path)* $next] $($rest)*)
+    };
+    ($state:ident $($rest:tt)+) => {
+        $crate::update!(@path $state [] $($rest)+)
+    };
+}
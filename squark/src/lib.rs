@@ -1,68 +1,198 @@
+#[cfg(feature = "im")]
+extern crate im_rc;
+#[cfg(feature = "markdown")]
+extern crate pulldown_cmark;
 extern crate rand;
+#[macro_use]
 extern crate serde_json;
 extern crate uuid;
 
-use rand::prelude::*;
-use std::cell::{Cell, RefCell};
-use std::collections::{HashMap, HashSet};
-use std::fmt::Debug;
+use std::borrow::Cow;
+use std::cell::{Cell, Ref, RefCell};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::{Debug, Display};
+use std::hash::{Hash, Hasher};
 use std::iter::FromIterator;
 use std::rc::Rc;
-use uuid::Uuid;
 
-thread_local! {
-    static RNG: RefCell<SmallRng> = RefCell::new(SmallRng::from_entropy());
-}
+pub mod a11y;
+mod addressing;
+mod attributes;
+pub mod bench;
+mod builder;
+mod cmd;
+mod confirm;
+mod custom;
+mod devtools;
+mod dialogs;
+mod embed;
+mod fetch;
+mod filter;
+mod i18n;
+mod id;
+mod input;
+mod key;
+mod lens;
+pub mod log;
+#[cfg(feature = "markdown")]
+mod markdown;
+mod media;
+mod merge;
+mod normalize;
+mod outbox;
+mod paginate;
+mod pending;
+pub mod persistent;
+pub mod proptest_support;
+mod pretty;
+mod query;
+mod regions;
+pub mod remote;
+mod router;
+mod sanitize;
+mod select;
+mod selector;
+pub mod session;
+mod shortcuts;
+pub mod ssr;
+mod storage;
+mod style;
+mod subscription;
+mod table;
+mod tag;
+mod template;
+mod toasts;
+mod transform;
+mod url;
+pub mod testing;
+pub mod validate;
+mod wire;
+
+pub use a11y::{focus_trap, Politeness, FOCUS_TRAP};
+pub use addressing::Addressing;
+pub use attributes::{aria, attr_if, data, href, src, Aria};
+pub use builder::{EnvBuilder, RenderSchedule};
+pub use cmd::{Cmd, Command, Task};
+pub use confirm::RenderToken;
+pub use custom::{Custom, CUSTOM_ATTRIBUTE};
+pub use devtools::{DevtoolsBridge, DevtoolsEvent};
+pub use dialogs::{dialog_stack, Dialogs};
+pub use embed::{embed, Embed};
+pub use filter::{DiffFilter, PatchStep};
+pub use fetch::{Fetch, FetchError, FetchFuture, Method, Request, Response};
+pub use i18n::{translate, Catalog, Translator};
+pub use id::{uuid, ContentIds, IdGen, SequentialIds, UuidIds};
+pub use input::{
+    composed_input, is_composing, masked_input, CompositionEvent, Masked, REPORT_CARET,
+};
+pub use key::set_key_attribute;
+pub use lens::Lens;
+pub use router::{
+    decode_component, encode_component, scroll_key, Location, QueryParams, QuerySync, Route,
+    RouteLoader, RouteStatus, Routing, ScrollBehavior, SCROLL_KEY,
+};
+#[cfg(feature = "sanitize")]
+pub use sanitize::AllowlistSanitizer;
+pub use sanitize::Sanitizer;
+pub use select::{multi_select, select, selected_values};
+pub use selector::Selector;
+pub use shortcuts::Shortcuts;
+pub use storage::{KeyValueStorage, MemoryStorage};
+pub use style::css;
+pub use media::{media_handler, MediaControl, MediaEvent};
+pub use merge::{merge, Conflict};
+pub use normalize::Normalize;
+pub use outbox::Outbox;
+pub use paginate::{load_more_sentinel, pager, Paginated};
+pub use pending::{is_pending, pending, PLACEHOLDER_ATTRIBUTE};
+pub use pretty::format_diffs;
+pub use query::{query, query_all};
+pub use regions::StateDiff;
+pub use subscription::{Subscription, SubscriptionKind, OBSERVE_KEY};
+pub use table::{Column, DataTable, Sort, SortDirection};
+pub use tag::Tag;
+pub use template::{Binding, Holes, Template};
+pub use toasts::{toast_stack, Toast, ToastState, Toasts};
+pub use url::{set_blocked_url_schemes, BlockedUrl, Url, URL_ATTRIBUTES};
 
 pub use serde_json::Value as HandlerArg;
 
-type Attribute = (String, AttributeValue);
+// Debug checks run in debug builds with the `debug-checks` feature, which is
+// on by default, and cost nothing otherwise.
+#[cfg(all(debug_assertions, feature = "debug-checks"))]
+pub(crate) fn warn(message: &str) {
+    log::log(log::Level::Warn, message);
+}
+
+pub type Attribute = (String, AttributeValue);
 
+// Diffs come in the order of the new attributes, then removals in that of
+// the old ones.
 fn diff_attributes(a: &mut Vec<Attribute>, b: &[Attribute]) -> Vec<Diff> {
     let mut result = vec![];
 
-    let mut old_map = HashMap::<String, AttributeValue>::from_iter(a.drain(..));
+    // `Bool(false)` attributes are never materialized.
+    let old: Vec<Attribute> = a
+        .drain(..)
+        .filter(|(_, value)| !value.is_absent())
+        .collect();
+    let mut old_map = HashMap::<&str, &AttributeValue>::from_iter(
+        old.iter().map(|(key, value)| (key.as_str(), value)),
+    );
     for &(ref new_key, ref new_val) in b {
-        match old_map.remove(new_key) {
-            Some(old_val) => {
-                if &old_val != new_val {
-                    result.push(Diff::SetAttribute(new_key.clone(), new_val.clone()))
-                }
+        match old_map.remove(new_key.as_str()) {
+            Some(old_val) if old_val == new_val => {}
+            Some(old_val) if new_val.is_absent() => {
+                result.push(Diff::remove(new_key.clone(), old_val));
             }
-            None => result.push(Diff::SetAttribute(new_key.clone(), new_val.clone())),
+            None if new_val.is_absent() => {}
+            Some(old_val) if old_val.is_property() != new_val.is_property() => {
+                result.push(Diff::remove(new_key.clone(), old_val));
+                result.push(Diff::set(new_key.clone(), new_val.clone()));
+            }
+            _ => result.push(Diff::set(new_key.clone(), new_val.clone())),
         }
     }
 
-    for (old_key, _) in old_map.drain() {
-        result.push(Diff::RemoveAttribute(old_key));
+    for (old_key, old_val) in &old {
+        if old_map.contains_key(old_key.as_str()) {
+            result.push(Diff::remove(old_key.clone(), old_val));
+        }
     }
 
     result
 }
 
-type HandlerFunction<A> = Box<Fn(HandlerArg) -> Option<A>>;
+type HandlerFunction<A> = Box<dyn Fn(HandlerArg) -> Vec<A>>;
 type Handler = (String, String);
 
 fn diff_handlers(a: &mut Vec<Handler>, b: &[Handler]) -> Vec<Diff> {
     let mut result = vec![];
 
-    let mut old_map = HashMap::<String, String>::from_iter(a.drain(..));
     for &(ref new_key, ref new_id) in b {
-        old_map.remove(new_key);
         result.push(Diff::SetHandler(new_key.clone(), new_id.clone()));
     }
 
-    for (old_key, old_id) in old_map.drain() {
-        result.push(Diff::RemoveHandler(old_key, old_id));
+    // Few enough to search, and removed in their order.
+    for (old_key, old_id) in a.drain(..) {
+        if !b.iter().any(|(key, _)| *key == old_key) {
+            result.push(Diff::RemoveHandler(old_key, old_id));
+        }
     }
 
     result
 }
 
-#[derive(Clone, Debug)]
+/// Equality and hashing are structural and ignore handler ids, which
+/// change on every render. See `Node::eq_with_handler_ids`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Node {
-    Text(String),
+    Text(Cow<'static, str>),
     Element(Element),
+    /// Sanitized markup, materialized by runtimes as a single `<span>` holding it.
+    Raw(String),
+    /// Left to user code, see `View::custom`.
+    Custom(Custom),
     Null,
 }
 
@@ -81,6 +211,17 @@ impl Node {
                 }
                 Some(Diff::ReplaceChild(*i, b.clone()))
             }
+            (&mut Node::Raw(ref mut html_a), &Node::Raw(ref html_b)) => {
+                if html_a == html_b {
+                    return None;
+                }
+                Some(Diff::ReplaceChild(*i, b.clone()))
+            }
+            (&mut Node::Custom(ref custom_a), &Node::Custom(ref custom_b))
+                if custom_a == custom_b =>
+            {
+                None
+            }
             (&mut Node::Null, &Node::Null) => None,
             (&mut Node::Null, _) => Some(Diff::AddChild(*i, b.clone())),
             (_, &Node::Null) => Some(Diff::RemoveChild(*i)),
@@ -88,6 +229,23 @@ impl Node {
         }
     }
 
+    // The diff runtimes receive when the tree at a mount point changes.
+    pub(crate) fn diff_root(a: &mut Node, b: &Node) -> Option<Diff> {
+        Node::diff(a, b, &mut 0).map(Diff::at_root)
+    }
+
+    /// Like `==`, but handlers must have the same ids too.
+    pub fn eq_with_handler_ids(&self, other: &Node) -> bool {
+        match (self, other) {
+            (Node::Element(a), Node::Element(b)) => a.eq_by(b, true),
+            (a, b) => a == b,
+        }
+    }
+
+    fn is_null(&self) -> bool {
+        matches!(self, Node::Null)
+    }
+
     fn get_key(&self) -> Option<String> {
         match self {
             Node::Element(ref el) => el.get_key(),
@@ -96,6 +254,28 @@ impl Node {
     }
 }
 
+/// The diffs turning what is rendered from `old` at a mount point into what
+/// is rendered from `new`, as runtimes receive them: none if nothing
+/// changes, `AddChild(0, new)` if `old` is null, `ReplaceRoot` if the root
+/// is of another kind, tag or key and `PatchRoot` otherwise. Children are
+/// addressed by index. Patched elements set their handlers anew, and
+/// elements of `new` take over the ids of those they patch, see
+/// `Element::node_id`.
+pub fn diff(old: &Node, new: &Node) -> Vec<Diff> {
+    Node::diff_root(&mut old.clone(), new).into_iter().collect()
+}
+
+fn collect_event_kinds(node: &Node, kinds: &mut BTreeSet<String>) {
+    if let Node::Element(el) = node {
+        for (kind, _) in el.handlers() {
+            kinds.insert(kind.clone());
+        }
+        for child in el.children() {
+            collect_event_kinds(child, kinds);
+        }
+    }
+}
+
 fn get_nodelist_key_set(nodelist: &[Node]) -> HashSet<String> {
     HashSet::from_iter(nodelist.iter().filter_map(|c| c.get_key()))
 }
@@ -109,14 +289,17 @@ fn diff_children(a: &mut Vec<Node>, b: &[Node], i: &mut usize) -> Vec<Diff> {
             Some(k) => {
                 let is_survived = b_key_set.contains(&k);
                 if !is_survived {
-                    result.push(Diff::RemoveChild(*i));
+                    result.push(addressing::keyed(Some(k), Diff::RemoveChild(*i)));
                     return false;
                 }
                 *i += 1;
                 true
             }
             None => {
-                *i += 1;
+                // Null nodes take no place in the rendered children.
+                if !c.is_null() {
+                    *i += 1;
+                }
                 true
             }
         })
@@ -128,117 +311,414 @@ fn diff_children(a: &mut Vec<Node>, b: &[Node], i: &mut usize) -> Vec<Diff> {
     for new_child in b.iter() {
         match a.pop() {
             None => {
+                if new_child.is_null() {
+                    continue;
+                }
                 result.push(Diff::AddChild(i, new_child.clone()));
-                i += 1;
             }
             Some(mut old_child) => {
+                let key = old_child.get_key();
                 if let Some(diff) = Node::diff(&mut old_child, new_child, &mut i) {
-                    result.push(diff.clone());
-                    if let Diff::RemoveChild(_) = diff {
+                    let removed = matches!(diff, Diff::RemoveChild(_));
+                    result.push(addressing::keyed(key, diff));
+                    if removed {
                         continue;
                     }
                 }
+                if new_child.is_null() {
+                    continue;
+                }
             }
         }
         i += 1;
     }
 
-    for _ in a.iter() {
-        result.push(Diff::RemoveChild(i));
+    for old_child in a.iter() {
+        if !old_child.is_null() {
+            result.push(addressing::keyed(old_child.get_key(), Diff::RemoveChild(i)));
+        }
     }
 
+    batch(result)
+}
+
+// Merges children added next to each other, as when appending to a list,
+// and those removed one after another.
+fn batch(diffs: Vec<Diff>) -> Vec<Diff> {
+    let mut result: Vec<Diff> = Vec::with_capacity(diffs.len());
+    for diff in diffs {
+        let previous = result.pop();
+        let merged = match (previous, diff) {
+            (Some(Diff::AddChild(j, a)), Diff::AddChild(i, b)) if j + 1 == i => {
+                Diff::AddChildren(j, vec![a, b])
+            }
+            (Some(Diff::AddChildren(j, mut nodes)), Diff::AddChild(i, b))
+                if j + nodes.len() == i =>
+            {
+                nodes.push(b);
+                Diff::AddChildren(j, nodes)
+            }
+            (Some(Diff::RemoveChild(j)), Diff::RemoveChild(i)) if j == i => {
+                Diff::RemoveChildren(i, 2)
+            }
+            (Some(Diff::RemoveChildren(j, count)), Diff::RemoveChild(i)) if j == i => {
+                Diff::RemoveChildren(i, count + 1)
+            }
+            (previous, diff) => {
+                result.extend(previous);
+                diff
+            }
+        };
+        result.push(merged);
+    }
+    result
+}
+
+// Keeps the first position and the last value of repeated names.
+fn dedup_attributes(attributes: Vec<Attribute>) -> Vec<Attribute> {
+    let mut result: Vec<Attribute> = Vec::with_capacity(attributes.len());
+    for (name, value) in attributes {
+        match result.iter_mut().find(|(k, _)| *k == name) {
+            Some(attribute) => attribute.1 = value,
+            None => result.push((name, value)),
+        }
+    }
     result
 }
 
 #[derive(Clone, Debug)]
-pub struct Element {
-    name: String,
+struct ElementData {
+    name: Tag,
     attributes: Vec<Attribute>,
     handlers: Vec<Handler>,
     children: Vec<Node>,
+    shadow: bool,
+    key: Option<String>,
+    template: Option<template::Slots>,
+    // Set by `View::pinned`.
+    pin: Option<u64>,
+    // Set by `View::external`.
+    external: bool,
+    // Carried over to the element a render diffs against this one.
+    id: Cell<u64>,
+}
+
+/// Cloning is cheap: the data is shared and copied on the first write.
+#[derive(Clone, Debug)]
+pub struct Element {
+    data: Rc<ElementData>,
 }
 
 impl Element {
     fn new(
-        name: String,
+        name: Tag,
         attributes: Vec<Attribute>,
         handlers: Vec<Handler>,
         children: Vec<Node>,
     ) -> Element {
         Element {
-            name,
-            attributes,
-            handlers,
-            children,
+            data: Rc::new(ElementData {
+                name,
+                attributes: dedup_attributes(attributes),
+                handlers,
+                children,
+                shadow: false,
+                key: None,
+                template: None,
+                pin: None,
+                external: false,
+                id: Cell::new(addressing::next_node_id()),
+            }),
         }
     }
 
     pub fn name(&self) -> &str {
-        &self.name
+        self.data.name.name()
+    }
+
+    pub fn tag(&self) -> &Tag {
+        &self.data.name
     }
 
     pub fn attributes(&self) -> &[Attribute] {
-        &self.attributes
+        &self.data.attributes
     }
 
     pub fn handlers(&self) -> &[Handler] {
-        &self.handlers
+        &self.data.handlers
+    }
+
+    /// The id of the handler for events of `kind`.
+    pub fn handler(&self, kind: &str) -> Option<&str> {
+        self.data
+            .handlers
+            .iter()
+            .find(|(k, _)| k == kind)
+            .map(|(_, id)| id.as_str())
     }
 
     pub fn children(&self) -> &[Node] {
-        &self.children
+        &self.data.children
+    }
+
+    /// Identifies the element among its siblings, so that diffs keep it
+    /// when they are reordered or some are removed. Never rendered. See
+    /// `View::with_key`.
+    pub fn key(&self) -> Option<&str> {
+        self.data.key.as_deref()
+    }
+
+    /// Identifies the element across renders: it is given at creation and
+    /// passed on to the element of the next render that patches it. Elements
+    /// sharing data, like a `View::constant` rendered twice, share it too.
+    pub fn node_id(&self) -> u64 {
+        self.data.id.get()
+    }
+
+    /// Whether the children are rendered into a shadow root attached to this element.
+    pub fn has_shadow_root(&self) -> bool {
+        self.data.shadow
+    }
+
+    /// Whether code outside squark manages the children. See `View::external`.
+    pub fn is_external(&self) -> bool {
+        self.data.external
     }
 
     fn diff(a: &mut Element, b: &Element, i: usize) -> Option<Diff> {
-        if let (Some(a_key), Some(b_key)) = (a.get_key(), b.get_key()) {
-            if a_key != b_key {
-                return Some(Diff::ReplaceChild(i, Node::Element(b.clone())));
-            }
+        if a.data.key != b.data.key {
+            return Some(Diff::ReplaceChild(i, Node::Element(b.clone())));
         }
 
-        if a.name != b.name {
+        if Rc::ptr_eq(&a.data, &b.data) {
+            return None;
+        }
+
+        let id = a.node_id();
+        if a.data.pin.is_some() && a.data.pin == b.data.pin {
+            b.data.id.set(id);
+            return None;
+        }
+
+        if a.data.name != b.data.name
+            || a.data.shadow != b.data.shadow
+            || a.data.external != b.data.external
+        {
             return Some(Diff::ReplaceChild(i, Node::Element(b.clone())));
         }
+        b.data.id.set(id);
+
+        if let (Some(a_slots), Some(b_slots)) = (&a.data.template, &b.data.template) {
+            if Rc::ptr_eq(a_slots, b_slots) {
+                let result = template::diff_holes(a, b, b_slots);
+                if result.is_empty() {
+                    return None;
+                }
+                return Some(addressing::patch(i, id, result));
+            }
+        }
 
         let mut result = vec![];
 
+        let scroll_key = match (
+            a.get_string_attribute(SCROLL_KEY),
+            b.get_string_attribute(SCROLL_KEY),
+        ) {
+            (Some(old_key), Some(new_key)) if old_key != new_key => {
+                result.push(Diff::SaveScroll(old_key));
+                Some(new_key)
+            }
+            _ => None,
+        };
+
+        let a = Rc::make_mut(&mut a.data);
+        let b = &b.data;
         result.append(&mut diff_attributes(&mut a.attributes, &b.attributes));
         result.append(&mut diff_handlers(&mut a.handlers, &b.handlers));
-        result.append(&mut diff_children(&mut a.children, &b.children, &mut 0));
+        if !b.external {
+            let had_children = a.children.iter().any(|child| !child.is_null());
+            let mut children = diff_children(&mut a.children, &b.children, &mut 0);
+            if had_children && b.children.iter().all(Node::is_null) {
+                children = vec![Diff::ClearChildren];
+            }
+            if b.shadow && !children.is_empty() {
+                result.push(Diff::PatchShadow(children));
+            } else {
+                result.append(&mut children);
+            }
+        }
+
+        if let Some(new_key) = scroll_key {
+            result.push(Diff::RestoreScroll(new_key));
+        }
 
         if result.is_empty() {
             return None;
         }
-        Some(Diff::PatchChild(i, result))
+        Some(addressing::patch(i, id, result))
+    }
+
+    // Structural equality, comparing handler ids only when `ids` is set.
+    fn eq_by(&self, other: &Element, ids: bool) -> bool {
+        if Rc::ptr_eq(&self.data, &other.data) {
+            return true;
+        }
+        let (a, b) = (&self.data, &other.data);
+        let same_handlers = if ids {
+            a.handlers == b.handlers
+        } else {
+            a.handlers.len() == b.handlers.len()
+                && a.handlers.iter().zip(&b.handlers).all(|(a, b)| a.0 == b.0)
+        };
+        a.name == b.name
+            && a.shadow == b.shadow
+            && a.external == b.external
+            && a.key == b.key
+            && a.attributes == b.attributes
+            && same_handlers
+            && a.children.len() == b.children.len()
+            && a.children.iter().zip(&b.children).all(|(a, b)| {
+                if ids {
+                    a.eq_with_handler_ids(b)
+                } else {
+                    a == b
+                }
+            })
     }
 
     fn get_key(&self) -> Option<String> {
-        self.attributes
+        self.data.key.clone()
+    }
+
+    fn get_string_attribute(&self, name: &str) -> Option<String> {
+        self.data
+            .attributes
             .iter()
-            .find(|&&(ref k, _)| k == "key")
+            .find(|(k, _)| k == name)
             .and_then(|&(_, ref v)| match v {
                 AttributeValue::String(ref s) => Some(s.clone()),
-                AttributeValue::Bool(_) => None,
+                _ => None,
             })
     }
 }
 
-#[derive(Debug, Clone)]
+impl PartialEq for Element {
+    fn eq(&self, other: &Element) -> bool {
+        self.eq_by(other, false)
+    }
+}
+
+impl Eq for Element {}
+
+impl Hash for Element {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.data.name.hash(state);
+        self.data.shadow.hash(state);
+        self.data.external.hash(state);
+        self.data.key.hash(state);
+        self.data.attributes.hash(state);
+        for (kind, _) in &self.data.handlers {
+            kind.hash(state);
+        }
+        self.data.children.hash(state);
+    }
+}
+
+/// At the mount point a tree arrives as `AddChild(0, node)` when nothing
+/// is rendered there, and changes as `ReplaceRoot` or `PatchRoot`, so child
+/// indices only ever address children of rendered elements.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Diff {
     SetAttribute(String, AttributeValue),
     RemoveAttribute(String),
     AddChild(usize, Node),
+    /// Adds the nodes as children from the index on, in order.
+    AddChildren(usize, Vec<Node>),
     ReplaceChild(usize, Node),
     RemoveChild(usize),
+    /// Removes `count` children from the index on, as that many
+    /// `RemoveChild` of the index would.
+    RemoveChildren(usize, usize),
+    /// Removes every child.
+    ClearChildren,
     PatchChild(usize, Vec<Diff>),
+    /// Diffs to apply to the element with the id, wherever it is. See
+    /// `Addressing::StableIds`.
+    PatchNode(u64, Vec<Diff>),
+    /// Like `PatchChild`, for the child with the key. See `Addressing::Keyed`.
+    PatchKeyed(String, Vec<Diff>),
+    /// Like `ReplaceChild`, for the child with the key.
+    ReplaceKeyed(String, Node),
+    /// Like `RemoveChild`, for the child with the key.
+    RemoveKeyed(String),
+    /// Replaces what is rendered at the mount point, leaving it empty if the
+    /// node is null.
+    ReplaceRoot(Node),
+    /// Diffs to apply to the element rendered at the mount point.
+    PatchRoot(Vec<Diff>),
+    /// Child diffs to apply inside the shadow root of the patched element.
+    PatchShadow(Vec<Diff>),
+    /// Diffs to apply to the descendant at the given child indices.
+    PatchPath(Vec<usize>, Vec<Diff>),
+    /// Replaces the content of the patched text node.
+    SetText(String),
     SetHandler(String, String),
     RemoveHandler(String, String),
+    SaveScroll(String),
+    RestoreScroll(String),
+    InjectStyle(String, String),
+    /// Every event kind handled somewhere in the tree, emitted whenever the
+    /// set changes, for runtimes listening once at the root. See `Env::handler_at`.
+    DelegateEvents(Vec<String>),
+    SetProperty(String, HandlerArg),
+    RemoveProperty(String),
+}
+
+impl Diff {
+    // The diff of the mount point, for that of its only child.
+    fn at_root(self) -> Diff {
+        match self {
+            Diff::ReplaceChild(0, node) => Diff::ReplaceRoot(node),
+            Diff::RemoveChild(0) => Diff::ReplaceRoot(Node::Null),
+            Diff::PatchChild(0, diffs) => Diff::PatchRoot(diffs),
+            diff => diff,
+        }
+    }
+
+    fn set(name: String, value: AttributeValue) -> Diff {
+        match value {
+            AttributeValue::Property(value) => Diff::SetProperty(name, value),
+            value => Diff::SetAttribute(name, value),
+        }
+    }
+
+    fn remove(name: String, old_value: &AttributeValue) -> Diff {
+        if old_value.is_property() {
+            return Diff::RemoveProperty(name);
+        }
+        Diff::RemoveAttribute(name)
+    }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum AttributeValue {
     String(String),
     Bool(bool),
+    /// Assigned as a DOM property instead of an attribute, so custom elements
+    /// receive structured values as-is.
+    Property(HandlerArg),
+}
+
+impl AttributeValue {
+    pub fn is_property(&self) -> bool {
+        matches!(self, AttributeValue::Property(_))
+    }
+
+    /// `Bool(false)`, which means the attribute is left out: diffs remove it
+    /// rather than set it and runtimes skip it when creating elements.
+    pub fn is_absent(&self) -> bool {
+        *self == AttributeValue::Bool(false)
+    }
 }
 
 impl From<String> for AttributeValue {
@@ -259,8 +739,17 @@ impl From<bool> for AttributeValue {
     }
 }
 
+impl From<HandlerArg> for AttributeValue {
+    fn from(v: HandlerArg) -> AttributeValue {
+        AttributeValue::Property(v)
+    }
+}
+
 type HandlerMap<A> = HashMap<String, HandlerFunction<A>>;
 
+// By the id of the runtime source, the subscriptions of its kind.
+type SubscriptionMap<A> = HashMap<String, Vec<Subscription<A>>>;
+
 pub struct View<A> {
     node: Node,
     handler_map: HandlerMap<A>,
@@ -289,13 +778,77 @@ impl<A> FromIterator<View<A>> for Child<A> {
     }
 }
 
+impl<A> From<Vec<View<A>>> for Child<A> {
+    fn from(views: Vec<View<A>>) -> Child<A> {
+        Child::ViewList(views)
+    }
+}
+
+impl<A> From<Option<Vec<View<A>>>> for Child<A> {
+    fn from(views: Option<Vec<View<A>>>) -> Child<A> {
+        Child::ViewList(views.unwrap_or_default())
+    }
+}
+
+/// A list of element views keyed by the first item `f` returns for each
+/// item, which is set as their `key` attribute. Null views are kept as is.
+///
+/// Panics on duplicate keys and on text views, which cannot carry a key.
+pub fn keyed_list<A, I, K, F>(items: I, mut f: F) -> Child<A>
+where
+    I: IntoIterator,
+    K: ToString,
+    F: FnMut(I::Item) -> (K, View<A>),
+{
+    let mut keys = HashSet::new();
+    let mut views = vec![];
+    for item in items {
+        let (key, mut view) = f(item);
+        let key = key.to_string();
+        if !keys.insert(key.clone()) {
+            panic!("duplicate key `{}` in keyed_list", key);
+        }
+        match view.node {
+            Node::Element(_) => view = view.with_key(key),
+            Node::Null => (),
+            _ => panic!("keyed_list item `{}` is not an element", key),
+        }
+        views.push(view);
+    }
+    Child::ViewList(views)
+}
+
+/// Extending a single view turns it into a list starting with it.
+impl<A> Extend<View<A>> for Child<A> {
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = View<A>>,
+    {
+        let mut views = match std::mem::replace(self, Child::ViewList(vec![])) {
+            Child::View(view) => vec![view],
+            Child::ViewList(views) => views,
+        };
+        views.extend(iter);
+        *self = Child::ViewList(views);
+    }
+}
+
 impl<A> View<A> {
-    pub fn new(
-        name: String,
-        attributes: Vec<Attribute>,
+    /// `attributes` may be any collection of pairs, such as a map. Of
+    /// repeated names the last value wins.
+    pub fn new<N, I>(
+        name: N,
+        attributes: I,
         handlers: Vec<(String, (String, HandlerFunction<A>))>,
         children: Vec<Child<A>>,
-    ) -> View<A> {
+    ) -> View<A>
+    where
+        N: Into<Tag>,
+        I: IntoIterator<Item = Attribute>,
+    {
+        let name = name.into();
+        let mut attributes = attributes.into_iter().collect();
+        let key = key::take_key(&mut attributes);
         let mut handler_map = HashMap::new();
         let handlers = handlers
             .into_iter()
@@ -307,30 +860,72 @@ impl<A> View<A> {
             .collect();
 
         let mut children_vec = vec![];
+        let mut child_handlers = HashMap::new();
         for child in children {
             match child {
                 Child::View(v) => {
-                    handler_map.extend(v.handler_map);
+                    child_handlers.extend(v.handler_map);
                     children_vec.push(v.node);
                 }
                 Child::ViewList(child_vec) => {
                     for v in child_vec {
-                        handler_map.extend(v.handler_map);
+                        child_handlers.extend(v.handler_map);
                         children_vec.push(v.node);
                     }
                 }
             }
         }
 
+        #[cfg(all(debug_assertions, feature = "debug-checks"))]
+        for violation in validate::check_children(name.name(), &children_vec) {
+            validate::warn_once(violation);
+        }
+        if name.is_void() {
+            children_vec.clear();
+        } else {
+            handler_map.extend(child_handlers);
+        }
+
+        let mut el = Element::new(name, attributes, handlers, children_vec);
+        if key.is_some() {
+            Rc::make_mut(&mut el.data).key = key;
+        }
         View {
-            node: Node::Element(Element::new(name, attributes, handlers, children_vec)),
+            node: Node::Element(el),
             handler_map,
         }
     }
 
-    pub fn text(s: String) -> View<A> {
+    /// Sets the key of an element, see `Element::key`. Other nodes have
+    /// none and are returned as they are.
+    pub fn with_key<S: Into<String>>(mut self, key: S) -> View<A> {
+        // Templates are kept, keys are not among their holes.
+        if let Node::Element(ref mut el) = self.node {
+            Rc::make_mut(&mut el.data).key = Some(key.into());
+        }
+        self
+    }
+
+    pub fn text<S: Into<Cow<'static, str>>>(s: S) -> View<A> {
         View {
-            node: Node::Text(s),
+            node: Node::Text(s.into()),
+            handler_map: HashMap::new(),
+        }
+    }
+
+    /// A text node borrowing a literal, so rendering it does not allocate.
+    pub fn static_text(s: &'static str) -> View<A> {
+        View::text(s)
+    }
+
+    /// A text node showing `value`, e.g. `View::text_of(state.count)`.
+    pub fn text_of<T: Display>(value: T) -> View<A> {
+        View::text(value.to_string())
+    }
+
+    pub fn raw_html<S: Sanitizer>(html: &str, sanitizer: &S) -> View<A> {
+        View {
+            node: Node::Raw(sanitizer.sanitize(html)),
             handler_map: HashMap::new(),
         }
     }
@@ -341,6 +936,65 @@ impl<A> View<A> {
             handler_map: HashMap::new(),
         }
     }
+
+    /// The view built by `f` when `condition` holds, otherwise nothing.
+    pub fn when<F>(condition: bool, f: F) -> View<A>
+    where
+        F: FnOnce() -> View<A>,
+    {
+        if condition {
+            f()
+        } else {
+            View::null()
+        }
+    }
+
+    pub fn node(&self) -> &Node {
+        &self.node
+    }
+
+    /// A `div` with the id whose children belong to code outside squark, like
+    /// an imperative charting library. Squark creates and removes it and
+    /// patches its attributes, but never diffs into it.
+    pub fn external<S: Into<String>>(id: S) -> View<A> {
+        let attributes = vec![("id".to_string(), id.into().into())];
+        let mut view = View::new("div", attributes, vec![], vec![]);
+        if let Node::Element(ref mut el) = view.node {
+            Rc::make_mut(&mut el.data).external = true;
+        }
+        view
+    }
+
+    /// Renders the children of this element into an attached shadow root.
+    pub fn shadow_root(mut self) -> View<A> {
+        if let Node::Element(ref mut el) = self.node {
+            Rc::make_mut(&mut el.data).shadow = true;
+        }
+        self
+    }
+
+    pub fn map<B, F>(self, f: F) -> View<B>
+    where
+        A: 'static,
+        F: Fn(A) -> B + 'static,
+    {
+        let f = Rc::new(f);
+        let handler_map = self
+            .handler_map
+            .into_iter()
+            .map(|(id, handler)| {
+                let f = f.clone();
+                let mapped: HandlerFunction<B> =
+                    Box::new(move |arg| handler(arg).into_iter().map(|a| f(a)).collect());
+                (id, mapped)
+            })
+            .collect();
+
+        View {
+            node: self.node,
+            handler_map,
+        }
+    }
 }
 
 impl<A> From<()> for View<A> {
@@ -361,6 +1015,20 @@ impl<'a, A> From<&'a str> for View<A> {
     }
 }
 
+macro_rules! impl_from_display {
+    ($($t:ty),*) => {
+        $(
+            impl<A> From<$t> for View<A> {
+                fn from(value: $t) -> View<A> {
+                    View::text_of(value)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_display!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64, char);
+
 impl<A, T> From<Option<T>> for View<A>
 where
     T: Into<View<A>>,
@@ -376,23 +1044,111 @@ pub trait App: 'static + Clone + Default {
 
     fn reducer(&self, state: Self::State, action: Self::Action) -> Self::State;
 
+    fn command(&self, _state: &Self::State, _action: &Self::Action) -> Cmd<Self::Action> {
+        Cmd::none()
+    }
+
+    fn subscriptions(&self, _state: &Self::State) -> Vec<Subscription<Self::Action>> {
+        vec![]
+    }
+
+    /// Translator used by `t!` while the view is built. Keep the locale in
+    /// the state so that switching it re-renders every text.
+    fn translator(&self, _state: &Self::State) -> Option<Rc<dyn Translator>> {
+        None
+    }
+
+    /// Views for further mount points driven by the same state, by root id.
+    /// Their diffs reach the runtime through `Runtime::handle_root_diff`.
+    fn roots(&self, _state: &Self::State) -> Vec<(String, View<Self::Action>)> {
+        vec![]
+    }
+
+    /// Names the fields that differ between two states, so that only the
+    /// `View::region`s showing them are rebuilt. `None`, the default,
+    /// rebuilds every region. States implementing `StateDiff` return
+    /// `Some(new.changed_fields(old))`.
+    fn changed_fields(&self, _old: &Self::State, _new: &Self::State) -> Option<Vec<&'static str>> {
+        None
+    }
+
     fn view(&self, state: Self::State) -> View<Self::Action>;
 }
 
-pub fn handler<A, F>(f: F) -> (String, HandlerFunction<A>)
+/// What a handler may return: no action, one, or several dispatched as a
+/// batch and rendered once.
+pub trait IntoActions<A> {
+    fn into_actions(self) -> Vec<A>;
+}
+
+impl<A> IntoActions<A> for A {
+    fn into_actions(self) -> Vec<A> {
+        vec![self]
+    }
+}
+
+impl<A> IntoActions<A> for Option<A> {
+    fn into_actions(self) -> Vec<A> {
+        self.into_iter().collect()
+    }
+}
+
+impl<A> IntoActions<A> for Vec<A> {
+    fn into_actions(self) -> Vec<A> {
+        self
+    }
+}
+
+pub fn handler<A, R, F>(f: F) -> (String, HandlerFunction<A>)
 where
-    F: Fn(HandlerArg) -> Option<A> + 'static,
+    R: IntoActions<A>,
+    F: Fn(HandlerArg) -> R + 'static,
 {
-    (uuid(), Box::new(f))
+    let id = id::next_id(std::any::type_name::<F>());
+    (id, Box::new(move |arg| f(arg).into_actions()))
 }
 
+type Devtools<A> = Rc<dyn DevtoolsBridge<<A as App>::State, <A as App>::Action>>;
+type Middleware<A> =
+    Rc<dyn Fn(&<A as App>::State, <A as App>::Action) -> Option<<A as App>::Action>>;
+type RootViews<A> = Vec<(String, View<A>)>;
+// Handlers replaced by the render of each generation.
+type RetiredHandlers<A> = Vec<(u64, HandlerMap<<A as App>::Action>)>;
+
 #[derive(Clone)]
 pub struct Env<A: App> {
     app: A,
     state: Rc<RefCell<A::State>>,
     node: Rc<RefCell<Node>>,
+    roots: Rc<RefCell<BTreeMap<String, Node>>>,
     handler_map: Rc<RefCell<HandlerMap<A::Action>>>,
+    live_handlers: Rc<RefCell<HashSet<String>>>,
+    pops_handlers: Rc<Cell<bool>>,
+    generation: Rc<Cell<u64>>,
+    retired_handlers: Rc<RefCell<RetiredHandlers<A>>>,
+    confirmations: Rc<RefCell<confirm::Confirmations>>,
+    subscriptions: Rc<RefCell<SubscriptionMap<A::Action>>>,
+    styles: style::StyleRegistry,
+    // Rules of `styles` and of those shared by every env already injected.
+    injected_styles: Rc<Cell<(usize, usize)>>,
+    event_kinds: Rc<RefCell<BTreeSet<String>>>,
+    constants: template::ConstantCache,
+    regions: regions::RegionCache,
+    normalize: Rc<Cell<Normalize>>,
+    addressing: Rc<Cell<Addressing>>,
+    pending: pending::PendingMap,
+    placeholders: Rc<Cell<bool>>,
+    scrolls: Rc<RefCell<Vec<(String, ScrollBehavior)>>>,
+    id_gen: Rc<RefCell<Rc<dyn IdGen>>>,
+    devtools: Rc<RefCell<Option<Devtools<A>>>>,
+    middleware: Rc<Vec<Middleware<A>>>,
+    diff_filters: Rc<RefCell<filter::DiffFilters>>,
+    storage: Option<Rc<dyn KeyValueStorage>>,
+    schedule: RenderSchedule,
+    unmounted: Rc<Cell<bool>>,
     scheduled: Rc<Cell<bool>>,
+    // Set by `force_full_render`.
+    full_render: Rc<Cell<bool>>,
 }
 
 impl<A: App> Env<A> {
@@ -401,16 +1157,272 @@ impl<A: App> Env<A> {
             app: A::default(),
             state: Rc::new(RefCell::new(state)),
             node: Rc::new(RefCell::new(Node::Null)),
+            roots: Rc::new(RefCell::new(BTreeMap::new())),
             handler_map: Rc::new(RefCell::new(HashMap::new())),
+            live_handlers: Rc::new(RefCell::new(HashSet::new())),
+            pops_handlers: Rc::new(Cell::new(false)),
+            generation: Rc::new(Cell::new(0)),
+            retired_handlers: Rc::new(RefCell::new(vec![])),
+            confirmations: Rc::new(RefCell::new(confirm::Confirmations::default())),
+            subscriptions: Rc::new(RefCell::new(HashMap::new())),
+            styles: Rc::new(RefCell::new(style::Registry::default())),
+            injected_styles: Rc::new(Cell::new((0, 0))),
+            event_kinds: Rc::new(RefCell::new(BTreeSet::new())),
+            constants: Rc::new(RefCell::new(HashMap::new())),
+            regions: Rc::new(RefCell::new(regions::Regions::default())),
+            normalize: Rc::new(Cell::new(Normalize::default())),
+            addressing: Rc::new(Cell::new(Addressing::default())),
+            pending: Rc::new(RefCell::new(HashMap::new())),
+            placeholders: Rc::new(Cell::new(false)),
+            scrolls: Rc::new(RefCell::new(vec![])),
+            id_gen: Rc::new(RefCell::new(Rc::new(UuidIds::new()))),
+            devtools: Rc::new(RefCell::new(None)),
+            middleware: Rc::new(vec![]),
+            diff_filters: Rc::new(RefCell::new(vec![])),
+            storage: None,
+            schedule: RenderSchedule::default(),
+            unmounted: Rc::new(Cell::new(false)),
             scheduled: Rc::new(Cell::new(false)),
+            full_render: Rc::new(Cell::new(false)),
         }
     }
 
+    /// Configures an env before creating it.
+    pub fn builder() -> EnvBuilder<A> {
+        EnvBuilder::new()
+    }
+
+    /// Replaces the strategy for handler and subscription ids.
+    pub fn set_id_gen(&self, id_gen: Rc<dyn IdGen>) {
+        *self.id_gen.borrow_mut() = id_gen;
+    }
+
+    /// Normalizes the text of every render before it is diffed.
+    pub fn set_normalize(&self, normalize: Normalize) {
+        self.normalize.set(normalize);
+    }
+
+    /// Marks the fallbacks `pending` renders with `PLACEHOLDER_ATTRIBUTE`, as
+    /// `ssr::prerender_pending` does, so a client can hydrate its markup.
+    pub fn set_placeholders(&self, placeholders: bool) {
+        self.placeholders.set(placeholders);
+    }
+
+    /// How the diffs of later renders address children.
+    pub fn set_addressing(&self, addressing: Addressing) {
+        self.addressing.set(addressing);
+    }
+
+    pub fn addressing(&self) -> Addressing {
+        self.addressing.get()
+    }
+
+    /// Makes the ids generated for this env reproducible. See `UuidIds::seeded`.
+    pub fn seed_ids(&self, seed: u64) {
+        self.set_id_gen(Rc::new(UuidIds::seeded(seed)));
+    }
+
+    /// Detaches the app: later dispatches, renders and command results are
+    /// ignored. Returns the diffs removing the main tree and the ids of
+    /// the subscriptions to cancel. The state stays readable. Use
+    /// `Runtime::unmount` rather than calling this directly.
+    pub fn unmount(&self) -> (Vec<Diff>, Vec<String>) {
+        self.unmounted.set(true);
+        self.scheduled.set(false);
+
+        let mut diffs = vec![];
+        let node = self.node.replace(Node::Null);
+        if !node.is_null() {
+            diffs.push(Diff::ReplaceRoot(Node::Null));
+        }
+        if !self.event_kinds.borrow().is_empty() {
+            self.event_kinds.borrow_mut().clear();
+            diffs.push(Diff::DelegateEvents(vec![]));
+        }
+        self.swap_handlers(HashMap::new());
+        self.retired_handlers.borrow_mut().clear();
+        self.constants.borrow_mut().clear();
+        let subscriptions = self.subscriptions.borrow_mut().drain().map(|(id, _)| id).collect();
+        (diffs, subscriptions)
+    }
+
+    /// Makes the next render replace the main tree and the additional roots
+    /// whole rather than patch them, for when the runtime failed to apply a
+    /// diff and what it shows is unknown. Renders awaiting confirmation are
+    /// dropped. See `Runtime::render_from_scratch`.
+    pub fn force_full_render(&self) {
+        self.full_render.set(true);
+        self.set_node(Node::Null);
+        *self.confirmations.borrow_mut() = confirm::Confirmations::default();
+        self.regions.borrow_mut().discard();
+    }
+
+    pub fn is_unmounted(&self) -> bool {
+        self.unmounted.get()
+    }
+
+    /// Whether a command tracked under `key` is in flight. See `Cmd::track`.
+    pub fn is_pending(&self, key: &str) -> bool {
+        self.pending.borrow().contains_key(key)
+    }
+
+    /// Counts a command under `key` as in flight until `end_pending`, like
+    /// one the server was still running when it rendered.
+    pub fn begin_pending(&self, key: &str) {
+        pending::begin(&self.pending, key);
+    }
+
+    pub fn end_pending(&self, key: &str) {
+        pending::end(&self.pending, key);
+    }
+
+    /// Whether state changed since the last render.
+    pub fn is_render_scheduled(&self) -> bool {
+        self.scheduled.get()
+    }
+
+    /// The tree as of the last render.
+    pub fn snapshot_node(&self) -> Node {
+        self.get_node()
+    }
+
+    /// The tree of an additional root as of the last render. See `App::roots`.
+    pub fn root_node(&self, root: &str) -> Option<Node> {
+        self.roots.borrow().get(root).cloned()
+    }
+
+    /// The trees of the additional roots as of the last render, by root id.
+    pub fn root_nodes(&self) -> Vec<(String, Node)> {
+        self.roots
+            .borrow()
+            .iter()
+            .map(|(id, node)| (id.clone(), node.clone()))
+            .collect()
+    }
+
+    /// Ids of the handlers of the last render which the runtime has not taken yet.
+    pub fn handler_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.handler_map.borrow().keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    /// Borrows the current state without cloning it. Release it before dispatching.
+    pub fn state_ref(&self) -> Ref<'_, A::State> {
+        self.state.borrow()
+    }
+
+    /// Reports every action, state change and render diff to `bridge`.
+    pub fn attach_devtools(&self, bridge: Devtools<A>) {
+        *self.devtools.borrow_mut() = Some(bridge);
+    }
+
+    fn apply_middleware(&self, action: A::Action) -> Option<A::Action> {
+        let state = self.state_ref();
+        self.middleware
+            .iter()
+            .try_fold(action, |action, f| f(&state, action))
+    }
+
+    /// Runs every diff through `filter` before the runtime gets it, after
+    /// the filters added before. Filters may drop ops on attributes,
+    /// handlers, properties and text but not those changing the tree, see
+    /// `DiffFilter::filter`. The env keeps diffing against the trees as
+    /// rendered, so dropped ops are not made again.
+    pub fn add_diff_filter(&self, filter: Rc<dyn DiffFilter>) {
+        self.diff_filters.borrow_mut().push(filter);
+    }
+
+    fn filter_diff(&self, diff: Diff) -> Option<Diff> {
+        let filters = self.diff_filters.borrow();
+        if filters.is_empty() {
+            return Some(diff);
+        }
+        filter::apply(&filters, diff)
+    }
+
+    /// The storage given to `EnvBuilder::storage`, used instead of the
+    /// runtime's.
+    pub fn storage(&self) -> Option<Rc<dyn KeyValueStorage>> {
+        self.storage.clone()
+    }
+
+    fn report(&self, event: DevtoolsEvent<A::State, A::Action>) {
+        if let Some(ref bridge) = *self.devtools.borrow() {
+            bridge.send(event);
+        }
+    }
+
+    fn with_ids<T, F: FnOnce() -> T>(&self, f: F) -> T {
+        let id_gen = self.id_gen.borrow().clone();
+        id::with_id_gen(id_gen, f)
+    }
+
+    // The main view and those of the additional roots.
+    fn build_view(&self) -> (View<A::Action>, RootViews<A::Action>) {
+        let state = self.get_state();
+        let translator = self.app.translator(&state);
+        let placeholders = self.placeholders.get();
+        self.id_gen.borrow().reset();
+        self.with_ids(|| {
+            regions::with_regions(self.regions.clone(), || {
+                style::with_registry(self.styles.clone(), || {
+                    template::with_constants(self.constants.clone(), || {
+                        i18n::with_translator(translator, || {
+                            pending::with_pending(self.pending.clone(), placeholders, || {
+                                let normalize = self.normalize.get();
+                                let mut roots = self.app.roots(&state);
+                                for (_, root) in &mut roots {
+                                    root.node = normalize
+                                        .apply(std::mem::replace(&mut root.node, Node::Null));
+                                }
+                                let mut view = self.app.view(state);
+                                view.node = normalize.apply(view.node);
+                                (view, roots)
+                            })
+                        })
+                    })
+                })
+            })
+        })
+    }
+
+    // Diffs every additional root against its last render. Roots no longer
+    // returned are removed.
+    fn swap_roots(&self, roots: Vec<(String, Node)>, full: bool) -> Vec<(String, Diff)> {
+        let mut old_roots = self.roots.borrow_mut();
+        let mut diffs = vec![];
+        let mut new_roots = BTreeMap::new();
+        for (id, node) in roots {
+            let mut old_node = old_roots.remove(&id).unwrap_or(Node::Null);
+            if full {
+                diffs.push((id.clone(), Diff::ReplaceRoot(node.clone())));
+            } else if let Some(diff) = Node::diff_root(&mut old_node, &node) {
+                diffs.push((id.clone(), diff));
+            }
+            new_roots.insert(id, node);
+        }
+        for (id, node) in old_roots.iter() {
+            if !node.is_null() {
+                diffs.push((id.clone(), Diff::ReplaceRoot(Node::Null)));
+            }
+        }
+        *old_roots = new_roots;
+        diffs
+    }
+
     fn get_state(&self) -> A::State {
         self.state.borrow().clone()
     }
 
     fn set_state(&self, state: A::State) {
+        self.set_state_changing(state, None);
+    }
+
+    // Sets the state, rebuilding the regions showing `fields` on the next
+    // render, all of them with `None`.
+    fn set_state_changing(&self, state: A::State, fields: Option<Vec<&'static str>>) {
+        self.regions.borrow_mut().mark_dirty(fields);
         *self.state.borrow_mut() = state;
     }
 
@@ -422,9 +1434,281 @@ impl<A: App> Env<A> {
         *self.node.borrow_mut() = node;
     }
 
+    // Replaces the handlers with those of a new render. Those no longer
+    // used are kept until acknowledged, as events may still refer to them.
+    fn swap_handlers(&self, handler_map: HandlerMap<A::Action>) {
+        let generation = self.generation.get() + 1;
+        self.generation.set(generation);
+        *self.live_handlers.borrow_mut() = handler_map.keys().cloned().collect();
+        let old = self.handler_map.replace(handler_map);
+        let current = self.handler_map.borrow();
+        let retired: HandlerMap<A::Action> = old
+            .into_iter()
+            .filter(|(id, _)| !current.contains_key(id))
+            .collect();
+        if !retired.is_empty() {
+            self.retired_handlers
+                .borrow_mut()
+                .push((generation, retired));
+        }
+    }
+
+    /// The number of renders whose handlers the env has taken.
+    pub fn handler_generation(&self) -> u64 {
+        self.generation.get()
+    }
+
+    /// Drops the handlers replaced by renders up to `generation`, once the
+    /// runtime has applied their diffs and no event can refer to them. See
+    /// `Runtime::acknowledges_handlers`.
+    pub fn acknowledge_handlers(&self, generation: u64) {
+        self.retired_handlers
+            .borrow_mut()
+            .retain(|&(retired, _)| retired > generation);
+    }
+
+    /// The token of the last render, for `Runtime::handle_render`.
+    pub fn render_token(&self) -> RenderToken {
+        RenderToken(self.generation.get())
+    }
+
+    /// Marks the render and those before it as applied by the runtime.
+    /// Handlers they replaced are dropped.
+    pub fn confirm_render(&self, token: RenderToken) {
+        if self.confirmations.borrow_mut().confirm(token) {
+            self.acknowledge_handlers(token.0);
+        }
+    }
+
+    /// Marks the render as failed to apply, leaving the tree as it was
+    /// before it so that the next `run` diffs against what the runtime
+    /// shows. Later renders are dropped too. Returns whether the render
+    /// was pending.
+    pub fn reject_render(&self, token: RenderToken) -> bool {
+        let node = self.confirmations.borrow_mut().reject(token);
+        match node {
+            Some(node) => {
+                self.set_node(node);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether a render handed to the runtime awaits confirmation.
+    pub fn has_pending_renders(&self) -> bool {
+        self.confirmations.borrow().is_pending()
+    }
+
+    fn has_handler(&self, id: &str) -> bool {
+        self.handler_map.borrow().contains_key(id)
+            || self
+                .retired_handlers
+                .borrow()
+                .iter()
+                .any(|(_, handlers)| handlers.contains_key(id))
+    }
+
     fn pop_handler(&self, id: &str) -> Option<HandlerFunction<A::Action>> {
+        self.pops_handlers.set(true);
+        #[cfg(all(debug_assertions, feature = "debug-checks"))]
+        {
+            if !self.live_handlers.borrow().contains(id) {
+                warn(&format!("handler {} was requested after a later render", id));
+            }
+        }
         self.handler_map.borrow_mut().remove(id)
     }
+
+    /// Handlers of the last render which the runtime has not taken with
+    /// `pop_handler`. For a runtime taking handlers when it attaches
+    /// listeners these are elements still listening with an older handler.
+    pub fn orphaned_handlers(&self) -> Vec<String> {
+        let live = self.live_handlers.borrow();
+        let mut ids: Vec<String> = self
+            .handler_map
+            .borrow()
+            .keys()
+            .filter(|id| live.contains(*id))
+            .cloned()
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    // Calls a handler still held by the env, leaving it in place. Handlers
+    // of earlier renders are called until acknowledged.
+    fn call_handler(&self, id: &str, arg: HandlerArg) -> Vec<A::Action> {
+        if let Some(f) = self.handler_map.borrow().get(id) {
+            return f(arg);
+        }
+        let retired = self.retired_handlers.borrow();
+        match retired.iter().rev().find_map(|(_, handlers)| handlers.get(id)) {
+            Some(f) => f(arg),
+            None => vec![],
+        }
+    }
+
+    /// The first element of the current tree matching `selector`, searching
+    /// the additional roots after the main one. See `query`.
+    pub fn query(&self, selector: &str) -> Option<Element> {
+        if let Some(el) = query::query(&self.node.borrow(), selector) {
+            return Some(el.clone());
+        }
+        self.roots
+            .borrow()
+            .values()
+            .filter_map(|node| query::query(node, selector))
+            .next()
+            .cloned()
+    }
+
+    fn take_styles(&self) -> Vec<Diff> {
+        let (own, shared) = self.injected_styles.get();
+        let shared_rules = style::shared_rules_since(shared);
+        let own_rules = self.styles.borrow().rules_since(own);
+        self.injected_styles
+            .set((own + own_rules.len(), shared + shared_rules.len()));
+        shared_rules
+            .into_iter()
+            .chain(own_rules)
+            .map(|(name, rule)| Diff::InjectStyle(name, rule))
+            .collect()
+    }
+
+    fn swap_event_kinds(&self, node: &Node) -> Option<Diff> {
+        let mut kinds = BTreeSet::new();
+        collect_event_kinds(node, &mut kinds);
+        if *self.event_kinds.borrow() == kinds {
+            return None;
+        }
+        let diff = Diff::DelegateEvents(kinds.iter().cloned().collect());
+        *self.event_kinds.borrow_mut() = kinds;
+        Some(diff)
+    }
+
+    /// Resolves the handler id a delegated root listener should call for an
+    /// event of `kind` whose target is at `path`, the indices of rendered
+    /// children (null nodes skipped) from the root node of the view. As with
+    /// bubbling, the deepest element on the path handling `kind` wins.
+    pub fn handler_at(&self, path: &[usize], kind: &str) -> Option<String> {
+        let find = |el: &Element| el.handler(kind).map(|id| id.to_string());
+
+        let node = self.node.borrow();
+        let mut el = match *node {
+            Node::Element(ref el) => el,
+            _ => return None,
+        };
+        let mut found = find(el);
+        for &i in path {
+            let child = el
+                .children()
+                .iter()
+                .filter(|c| !matches!(c, Node::Null))
+                .nth(i);
+            el = match child {
+                Some(Node::Element(child)) => child,
+                _ => break,
+            };
+            if let Some(id) = find(el) {
+                found = Some(id);
+            }
+        }
+        found
+    }
+
+    /// The path of the first element keyed `key`, in the form `handler_at`
+    /// takes.
+    pub fn path_of_key(&self, key: &str) -> Option<Vec<usize>> {
+        fn find(el: &Element, key: &str, path: &mut Vec<usize>) -> bool {
+            let children = el.children().iter().filter(|c| !matches!(c, Node::Null));
+            for (i, child) in children.enumerate() {
+                if let Node::Element(ref child) = *child {
+                    path.push(i);
+                    if child.get_key().as_deref() == Some(key) || find(child, key, path) {
+                        return true;
+                    }
+                    path.pop();
+                }
+            }
+            false
+        }
+
+        let node = self.node.borrow();
+        let el = match *node {
+            Node::Element(ref el) => el,
+            _ => return None,
+        };
+        let mut path = vec![];
+        if el.get_key().as_deref() == Some(key) || find(el, key, &mut path) {
+            Some(path)
+        } else {
+            None
+        }
+    }
+
+    fn get_subscriptions(&self, id: &str) -> Vec<Subscription<A::Action>> {
+        self.subscriptions
+            .borrow()
+            .get(id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    // Groups the subscriptions by kind, each kind under one id fed by one
+    // runtime source. Keeps the id of every kind still requested so that the
+    // source survives, and returns the (added, removed) changes.
+    fn swap_subscriptions(
+        &self,
+        subscriptions: Vec<Subscription<A::Action>>,
+    ) -> (Vec<(String, SubscriptionKind)>, Vec<String>) {
+        let mut old = self.subscriptions.borrow_mut();
+        let mut new: SubscriptionMap<A::Action> = HashMap::new();
+        let mut added = vec![];
+        for sub in subscriptions {
+            if let Some(subs) = new.values_mut().find(|subs| subs[0].kind() == sub.kind()) {
+                subs.push(sub);
+                continue;
+            }
+            let existing = old
+                .iter()
+                .find(|&(_, subs)| subs[0].kind() == sub.kind())
+                .map(|(id, _)| id.clone());
+            let id = match existing {
+                Some(id) => {
+                    old.remove(&id);
+                    id
+                }
+                None => {
+                    let id = id::next_id(&format!("{:?}", sub.kind()));
+                    added.push((id.clone(), sub.kind().clone()));
+                    id
+                }
+            };
+            new.insert(id, vec![sub]);
+        }
+        let removed = old.drain().map(|(id, _)| id).collect();
+        *old = new;
+        (added, removed)
+    }
+}
+
+// The env's storage if it was given one, otherwise the runtime's.
+fn storage_of<A: App, R: Runtime<A>>(runtime: &R) -> Option<Rc<dyn KeyValueStorage>> {
+    runtime.get_env().storage().or_else(|| runtime.storage())
+}
+
+// Renders as the env schedules it unless a render is pending already.
+fn request_render<A: App, R: Runtime<A>>(runtime: &R) {
+    let env = runtime.get_env();
+    if !env.scheduled.get() {
+        env.scheduled.set(true);
+        match env.schedule {
+            RenderSchedule::Deferred => runtime.schedule_render(),
+            RenderSchedule::Immediate => runtime.run(),
+            RenderSchedule::Manual => {}
+        }
+    }
 }
 
 pub trait Runtime<A: App>: Clone + 'static {
@@ -432,51 +1716,392 @@ pub trait Runtime<A: App>: Clone + 'static {
 
     fn handle_diff(&self, diff: Diff);
 
+    /// Applies the diff of the main tree of a render. Runtimes applying it
+    /// later return `Env::render_token()` and report the outcome with
+    /// `Env::confirm_render` or `Env::reject_render`; until then the env
+    /// keeps the handlers the render replaced and the tree to go back to.
+    fn handle_render(&self, diff: Diff) -> Option<RenderToken> {
+        self.handle_diff(diff);
+        None
+    }
+
     fn schedule_render(&self);
 
+    /// Whether the runtime calls `Env::acknowledge_handlers` once it has
+    /// applied a render. Otherwise handlers replaced by a render are kept
+    /// until the next one, for events already under way.
+    fn acknowledges_handlers(&self) -> bool {
+        false
+    }
+
+    /// Applies a diff to the mount point of an additional root. See
+    /// `App::roots`; runtimes without such mount points ignore these.
+    fn handle_root_diff(&self, _root: &str, _diff: Diff) {}
+
     fn run(&self) {
         let env = self.get_env();
+        if env.is_unmounted() {
+            return;
+        }
         env.scheduled.set(false);
         let mut old_node = env.get_node();
-        let view = env.app.view(env.get_state());
-        *env.handler_map.borrow_mut() = view.handler_map;
-        if let Some(diff) = Node::diff(&mut old_node, &view.node, &mut 0) {
-            env.set_node(view.node);
+        let (mut view, roots) = env.build_view();
+        let mut root_nodes = vec![];
+        for (id, root) in roots {
+            view.handler_map.extend(root.handler_map);
+            root_nodes.push((id, root.node));
+        }
+        #[cfg(all(debug_assertions, feature = "debug-checks"))]
+        for node in Some(&view.node).into_iter().chain(root_nodes.iter().map(|(_, node)| node)) {
+            for violation in validate::check_duplicate_ids(node) {
+                validate::warn_once(violation);
+            }
+        }
+        for diff in env.take_styles() {
+            if let Some(diff) = env.filter_diff(diff) {
+                self.handle_diff(diff);
+            }
+        }
+        if let Some(diff) = env.swap_event_kinds(&view.node).and_then(|d| env.filter_diff(d)) {
             self.handle_diff(diff);
         }
+        for id in env.regions.borrow_mut().take_carried() {
+            if let Some(f) = env.handler_map.borrow_mut().remove(&id) {
+                view.handler_map.insert(id, f);
+            }
+        }
+        env.swap_handlers(view.handler_map);
+        env.regions.borrow_mut().rendered();
+        let full = env.full_render.replace(false);
+        let addressing = env.addressing.get();
+        let diff = if full {
+            Some(Diff::ReplaceRoot(view.node.clone()))
+        } else {
+            let node = &view.node;
+            addressing::with_addressing(addressing, || Node::diff_root(&mut old_node, node))
+        };
+        if let Some(diff) = diff {
+            let before = env.get_node();
+            env.set_node(view.node.clone());
+            if let Some(diff) = env.filter_diff(diff) {
+                env.report(DevtoolsEvent::Diff(diff.clone()));
+                if let Some(token) = self.handle_render(diff) {
+                    let mut confirmations = env.confirmations.borrow_mut();
+                    confirmations.stage(token, before, view.node);
+                }
+            }
+        }
+        if !self.acknowledges_handlers() && !env.has_pending_renders() {
+            env.acknowledge_handlers(env.handler_generation() - 1);
+        }
+        let root_diffs =
+            addressing::with_addressing(addressing, || env.swap_roots(root_nodes, full));
+        for (id, diff) in root_diffs {
+            if let Some(diff) = env.filter_diff(diff) {
+                self.handle_root_diff(&id, diff);
+            }
+        }
+        #[cfg(all(debug_assertions, feature = "debug-checks"))]
+        {
+            let orphaned = env.orphaned_handlers();
+            if env.pops_handlers.get() && !orphaned.is_empty() {
+                warn(&format!("handlers never attached: {}", orphaned.join(", ")));
+            }
+        }
+        let scrolls: Vec<_> = env.scrolls.borrow_mut().drain(..).collect();
+        for (key, behavior) in scrolls {
+            if let Some(path) = env.path_of_key(&key) {
+                self.scroll_into_view(&path, behavior);
+            }
+        }
+        self.update_subscriptions();
     }
 
-    fn pop_handler(&self, id: &str) -> Option<Box<Fn(HandlerArg)>> {
+    fn subscribe(&self, id: &str, kind: &SubscriptionKind);
+
+    fn unsubscribe(&self, id: &str);
+
+    fn update_subscriptions(&self) {
         let env = self.get_env();
-        let handler = env.pop_handler(id)?;
-        let app = env.app.clone();
+        let subscriptions = env.app.subscriptions(&env.get_state());
+        let (added, removed) = env.with_ids(|| env.swap_subscriptions(subscriptions));
+        for id in removed {
+            self.unsubscribe(&id);
+        }
+        for (id, kind) in added {
+            self.subscribe(&id, &kind);
+        }
+    }
 
-        let this = self.clone();
-        let f = move |arg: HandlerArg| {
-            let action = match handler(arg) {
-                Some(a) => a,
-                None => return,
+    /// Dispatches the actions of every subscription fed by the source `id`,
+    /// in the order `App::subscriptions` returned them.
+    fn dispatch_subscription(&self, id: &str, arg: HandlerArg) {
+        for sub in self.get_env().get_subscriptions(id) {
+            if let Some(action) = sub.handle(arg.clone()) {
+                self.dispatch(action);
+            }
+        }
+    }
+
+    /// Dispatches an `AnimationFrame` tick and renders within the same frame,
+    /// so ticks are reduced no faster than frames are rendered. Runtimes call
+    /// this instead of `dispatch_subscription` and skip their own scheduled
+    /// render once `Env::is_render_scheduled` is false again.
+    fn dispatch_frame(&self, id: &str, timestamp: f64) {
+        self.dispatch_subscription(id, serde_json::Value::from(timestamp));
+        if self.get_env().is_render_scheduled() {
+            self.run();
+        }
+    }
+
+    /// Runs a task spawned by a command. Runtimes without an executor keep
+    /// this default, which drops the task, so such commands have no effect.
+    fn spawn(&self, _task: Task) {
+        #[cfg(all(debug_assertions, feature = "debug-checks"))]
+        warn("this runtime does not run tasks, dropping one");
+    }
+
+    fn fetcher(&self) -> Option<Rc<dyn Fetch>> {
+        None
+    }
+
+    fn storage(&self) -> Option<Rc<dyn KeyValueStorage>> {
+        None
+    }
+
+    /// Runtimes without a history ignore navigation. Implementors must notify
+    /// `UrlChange` subscriptions after the url is changed.
+    fn push_url(&self, _url: &str) {}
+
+    fn replace_url(&self, _url: &str) {}
+
+    /// Runtimes without assistive technology to speak to ignore announcements.
+    fn announce(&self, _message: &str, _politeness: Politeness) {}
+
+    /// Plays, pauses or seeks the media element with id `id`. Runtimes
+    /// without media elements ignore these.
+    fn control_media(&self, _id: &str, _control: MediaControl) {}
+
+    /// Scrolls the element at `path`, as taken by `Env::handler_at`, into
+    /// view.
+    fn scroll_into_view(&self, _path: &[usize], _behavior: ScrollBehavior) {}
+
+    fn set_title(&self, _title: &str) {}
+
+    fn set_favicon(&self, _url: &str) {}
+
+    /// Renders for the first time, into mount points the runtime has left
+    /// empty: the tree arrives whole as `AddChild(0, node)`, never as
+    /// patches of an earlier one, and so do those of the additional roots.
+    /// Styles and delegated events come first. Runtimes should start with
+    /// this rather than `run`.
+    fn mount(&self) {
+        let env = self.get_env();
+        if env.is_unmounted() {
+            return;
+        }
+        env.set_node(Node::Null);
+        env.roots.borrow_mut().clear();
+        self.run();
+    }
+
+    /// Renders for the first time over markup from `ssr::prerender` of the
+    /// same state: only handlers are attached and the markers left by
+    /// `ssr::strip_handlers` removed.
+    fn hydrate(&self) {
+        let env = self.get_env();
+        let (view, _) = env.build_view();
+        // The handlers of pinned views are attached like the others rather
+        // than taken for those of a previous render.
+        env.set_node(regions::unpin(ssr::strip_handlers(view.node)));
+        env.regions.borrow_mut().discard();
+        self.run();
+    }
+
+    /// Renders the whole tree anew in place of what is shown, for runtimes
+    /// that failed to apply a diff, e.g. as the document was changed by
+    /// someone else. See `Env::force_full_render`.
+    fn render_from_scratch(&self) {
+        let env = self.get_env();
+        if env.is_unmounted() {
+            return;
+        }
+        env.force_full_render();
+        env.scheduled.set(true);
+        self.schedule_render();
+    }
+
+    /// Removes the rendered tree, cancels subscriptions and drops handlers.
+    /// Tasks still running complete, but their actions are ignored.
+    fn unmount(&self) {
+        let (diffs, subscriptions) = self.get_env().unmount();
+        for id in subscriptions {
+            self.unsubscribe(&id);
+        }
+        let env = self.get_env();
+        for diff in diffs.into_iter().filter_map(|diff| env.filter_diff(diff)) {
+            self.handle_diff(diff);
+        }
+        for (id, diff) in env.swap_roots(vec![], false) {
+            if let Some(diff) = env.filter_diff(diff) {
+                self.handle_root_diff(&id, diff);
+            }
+        }
+    }
+
+    fn dispatch(&self, action: A::Action) {
+        self.dispatch_all(vec![action]);
+    }
+
+    /// Dispatches the result of a command left in flight under `key`, which
+    /// replaces the placeholder rendered for it. See `Env::begin_pending`.
+    fn fill_placeholder(&self, key: &str, action: A::Action) {
+        self.get_env().end_pending(key);
+        self.dispatch(action);
+    }
+
+    /// Reduces the actions in order and renders once afterwards. Their
+    /// commands run after the last action is reduced.
+    fn dispatch_all(&self, actions: Vec<A::Action>) {
+        let env = self.get_env();
+        if env.is_unmounted() {
+            return;
+        }
+
+        let mut changed = false;
+        let mut cmds = vec![];
+        for action in actions {
+            let action = match env.apply_middleware(action) {
+                Some(action) => action,
+                None => continue,
             };
+            env.report(DevtoolsEvent::Action(action.clone()));
+            // A single clone per action, which `persistent` collections make
+            // cheap, as they do comparing states sharing most of their data.
+            let new_state = env.app.reducer(env.get_state(), action.clone());
+            cmds.push(env.app.command(&new_state, &action));
+            if *env.state_ref() != new_state {
+                env.report(DevtoolsEvent::State(new_state.clone()));
+                let fields = env.app.changed_fields(&env.state_ref(), &new_state);
+                env.set_state_changing(new_state, fields);
+                changed = true;
+            }
+        }
+        if changed {
+            request_render(self);
+        }
 
-            let env = this.get_env();
+        for cmd in cmds {
+            for command in cmd {
+                self.run_command(command);
+            }
+        }
+    }
 
-            let old_state = env.get_state();
-            let new_state = app.reducer(old_state.clone(), action);
-            if old_state == new_state {
-                return;
+    fn run_command(&self, command: Command<A::Action>) {
+        match command {
+            Command::Fetch(request, callback) => {
+                let fetch = match self.fetcher() {
+                    Some(fetch) => fetch,
+                    None => {
+                        if let Some(action) = callback(Err(FetchError::Unavailable)) {
+                            self.dispatch(action);
+                        }
+                        return;
+                    }
+                };
+                let this = self.clone();
+                self.spawn(cmd::then(fetch.request(request), move |res| {
+                    if let Some(action) = callback(res) {
+                        this.dispatch(action);
+                    }
+                }));
+            }
+            Command::StorageGet(key, callback) => {
+                let value = storage_of(self).and_then(|storage| storage.get(&key));
+                if let Some(action) = callback(value) {
+                    self.dispatch(action);
+                }
+            }
+            Command::StorageSet(key, value) => {
+                if let Some(storage) = storage_of(self) {
+                    storage.set(&key, &value);
+                }
+            }
+            Command::StorageRemove(key) => {
+                if let Some(storage) = storage_of(self) {
+                    storage.remove(&key);
+                }
+            }
+            Command::PushUrl(url) => self.push_url(&url),
+            Command::ReplaceUrl(url) => self.replace_url(&url),
+            Command::Announce(message, politeness) => self.announce(&message, politeness),
+            Command::Media(id, control) => self.control_media(&id, control),
+            Command::ScrollIntoView(key, behavior) => {
+                self.get_env().scrolls.borrow_mut().push((key, behavior));
+                request_render(self);
             }
-            env.set_state(new_state);
-            if env.scheduled.get() {
-                return;
+            Command::SetTitle(title) => self.set_title(&title),
+            Command::SetFavicon(url) => self.set_favicon(&url),
+            Command::Tracked(key, command) => {
+                let env = self.get_env();
+                pending::begin(&env.pending, &key);
+                request_render(self);
+                let this = self.clone();
+                let end = move || {
+                    pending::end(&this.get_env().pending, &key);
+                    request_render(&this);
+                };
+                match *command {
+                    Command::Fetch(request, callback) => self.run_command(Command::Fetch(
+                        request,
+                        Box::new(move |res| {
+                            end();
+                            callback(res)
+                        }),
+                    )),
+                    Command::StorageGet(name, callback) => self.run_command(Command::StorageGet(
+                        name,
+                        Box::new(move |value| {
+                            end();
+                            callback(value)
+                        }),
+                    )),
+                    command => {
+                        self.run_command(command);
+                        end();
+                    }
+                }
             }
-            env.scheduled.set(true);
-            this.schedule_render();
+        }
+    }
+
+    /// Invokes the `kind` handler of the first element matching `selector`
+    /// with `arg`, for runtimes which keep handlers in the env such as
+    /// `TestRuntime`. Returns whether a handler was found.
+    fn trigger(&self, selector: &str, kind: &str, arg: HandlerArg) -> bool {
+        let env = self.get_env();
+        let id = match env.query(selector) {
+            Some(el) => match el.handler(kind) {
+                Some(id) => id.to_string(),
+                None => return false,
+            },
+            None => return false,
         };
+        if !env.has_handler(&id) {
+            return false;
+        }
+        let actions = env.call_handler(&id, arg);
+        self.dispatch_all(actions);
+        true
+    }
+
+    fn pop_handler(&self, id: &str) -> Option<Box<dyn Fn(HandlerArg)>> {
+        let handler = self.get_env().pop_handler(id)?;
+
+        let this = self.clone();
+        let f = move |arg: HandlerArg| this.dispatch_all(handler(arg));
         Some(Box::new(f))
     }
 }
 
-pub fn uuid() -> String {
-    RNG.with(|rng| Uuid::from_random_bytes(rng.borrow_mut().gen()))
-        .to_string()
-}
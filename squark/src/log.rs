@@ -0,0 +1,48 @@
+//! Where the warnings of debug checks go: nowhere until a sink is set, as
+//! there is no stderr to write to in the browser. Runtimes set one unless
+//! the app did: the web runtime logs to the console and `TestRuntime` to
+//! stderr. Elsewhere, such as when rendering on a server, set one like
+//! `log_to_stderr`.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+type Sink = Rc<dyn Fn(Level, &str)>;
+
+thread_local! {
+    static SINK: RefCell<Option<Sink>> = const { RefCell::new(None) };
+}
+
+/// Sends messages to `sink`.
+pub fn set_sink<F: Fn(Level, &str) + 'static>(sink: F) {
+    SINK.with(|s| *s.borrow_mut() = Some(Rc::new(sink)));
+}
+
+/// Drops the sink, discarding messages again.
+pub fn reset_sink() {
+    SINK.with(|s| *s.borrow_mut() = None);
+}
+
+pub fn has_sink() -> bool {
+    SINK.with(|s| s.borrow().is_some())
+}
+
+pub fn log(level: Level, message: &str) {
+    // Cloned out so that the sink may log or replace itself.
+    if let Some(sink) = SINK.with(|s| s.borrow().clone()) {
+        sink(level, message);
+    }
+}
+
+/// A sink writing to stderr, for runtimes outside the browser.
+pub fn log_to_stderr(_level: Level, message: &str) {
+    eprintln!("squark: {}", message);
+}
@@ -0,0 +1,251 @@
+use pulldown_cmark::{Alignment, CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use super::{Attribute, Child, Node, View};
+use url;
+
+const URL_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+fn is_safe_url(url: &str) -> bool {
+    url::scheme(url).map_or(true, |scheme| URL_SCHEMES.contains(&scheme.as_str()))
+}
+
+fn text_content(node: &Node, out: &mut String) {
+    match *node {
+        Node::Text(ref text) => out.push_str(text),
+        Node::Element(ref el) => {
+            for child in el.children() {
+                text_content(child, out);
+            }
+        }
+        _ => (),
+    }
+}
+
+struct Frame<A> {
+    name: &'static str,
+    attributes: Vec<Attribute>,
+    children: Vec<Child<A>>,
+}
+
+impl<A> Frame<A> {
+    fn new(name: &'static str, attributes: Vec<Attribute>) -> Frame<A> {
+        Frame {
+            name,
+            attributes,
+            children: vec![],
+        }
+    }
+
+    fn into_view(self) -> View<A> {
+        let mut attributes = self.attributes;
+        if self.name != "img" {
+            return View::new(self.name.to_string(), attributes, vec![], self.children);
+        }
+
+        // Image descriptions become the alt text.
+        let mut alt = String::new();
+        for child in self.children {
+            if let Child::View(v) = child {
+                text_content(v.node(), &mut alt);
+            }
+        }
+        attributes.push(("alt".to_string(), alt.into()));
+        View::new("img".to_string(), attributes, vec![], vec![])
+    }
+}
+
+struct Builder<A> {
+    frames: Vec<Frame<A>>,
+    // Number of frames opened by each pending start tag.
+    depths: Vec<usize>,
+    alignments: Vec<Alignment>,
+    in_head: bool,
+    column: usize,
+}
+
+impl<A> Builder<A> {
+    fn push(&mut self, view: View<A>) {
+        self.frames
+            .last_mut()
+            .expect("markdown root frame")
+            .children
+            .push(Child::View(view));
+    }
+
+    fn open(&mut self, name: &'static str, attributes: Vec<Attribute>) {
+        self.frames.push(Frame::new(name, attributes));
+    }
+
+    fn close(&mut self) {
+        let view = self.frames.pop().expect("markdown frame").into_view();
+        self.push(view);
+    }
+
+    fn start(&mut self, tag: Tag) {
+        let mut depth = self.frames.len();
+        match tag {
+            Tag::Paragraph => self.open("p", vec![]),
+            Tag::Heading { level, .. } => {
+                let name = ["h1", "h2", "h3", "h4", "h5", "h6"][level as usize - 1];
+                self.open(name, vec![]);
+            }
+            Tag::BlockQuote(_) => self.open("blockquote", vec![]),
+            Tag::CodeBlock(kind) => {
+                let mut attributes = vec![];
+                if let CodeBlockKind::Fenced(info) = kind {
+                    if let Some(lang) = info.split_whitespace().next() {
+                        attributes.push(("class".to_string(), format!("language-{}", lang).into()));
+                    }
+                }
+                self.open("pre", vec![]);
+                self.open("code", attributes);
+            }
+            Tag::List(Some(start)) => {
+                let mut attributes = vec![];
+                if start != 1 {
+                    attributes.push(("start".to_string(), start.to_string().into()));
+                }
+                self.open("ol", attributes);
+            }
+            Tag::List(None) => self.open("ul", vec![]),
+            Tag::Item => self.open("li", vec![]),
+            Tag::Table(alignments) => {
+                self.alignments = alignments;
+                self.open("table", vec![]);
+            }
+            Tag::TableHead => {
+                self.in_head = true;
+                self.column = 0;
+                self.open("thead", vec![]);
+                self.open("tr", vec![]);
+            }
+            Tag::TableRow => {
+                // Rows after the head get the `tbody` browsers would insert
+                // anyway. It is closed together with the table.
+                if self.frames.last().map(|f| f.name) == Some("table") {
+                    self.open("tbody", vec![]);
+                    depth += 1;
+                    if let Some(table) = self.depths.last_mut() {
+                        *table += 1;
+                    }
+                }
+                self.column = 0;
+                self.open("tr", vec![]);
+            }
+            Tag::TableCell => {
+                let mut attributes = vec![];
+                let align = match self.alignments.get(self.column) {
+                    Some(&Alignment::Left) => Some("left"),
+                    Some(&Alignment::Center) => Some("center"),
+                    Some(&Alignment::Right) => Some("right"),
+                    _ => None,
+                };
+                if let Some(align) = align {
+                    attributes.push((
+                        "style".to_string(),
+                        format!("text-align: {}", align).into(),
+                    ));
+                }
+                self.column += 1;
+                self.open(if self.in_head { "th" } else { "td" }, attributes);
+            }
+            Tag::Emphasis => self.open("em", vec![]),
+            Tag::Strong => self.open("strong", vec![]),
+            Tag::Strikethrough => self.open("del", vec![]),
+            Tag::Link {
+                dest_url, title, ..
+            } => {
+                let mut attributes = vec![];
+                if is_safe_url(&dest_url) {
+                    attributes.push(("href".to_string(), dest_url.to_string().into()));
+                }
+                if !title.is_empty() {
+                    attributes.push(("title".to_string(), title.to_string().into()));
+                }
+                self.open("a", attributes);
+            }
+            Tag::Image {
+                dest_url, title, ..
+            } => {
+                let mut attributes = vec![];
+                if is_safe_url(&dest_url) {
+                    attributes.push(("src".to_string(), dest_url.to_string().into()));
+                }
+                if !title.is_empty() {
+                    attributes.push(("title".to_string(), title.to_string().into()));
+                }
+                self.open("img", attributes);
+            }
+            // Raw HTML blocks and extensions we don't enable keep their
+            // content in the parent.
+            _ => (),
+        }
+        let opened = self.frames.len() - depth;
+        self.depths.push(opened);
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        if let TagEnd::TableHead = tag {
+            self.in_head = false;
+        }
+        for _ in 0..self.depths.pop().unwrap_or(0) {
+            self.close();
+        }
+    }
+
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => self.push(View::text(text.to_string())),
+            Event::Code(code) => {
+                let view = View::new(
+                    "code".to_string(),
+                    vec![],
+                    vec![],
+                    vec![View::text(code.to_string()).into()],
+                );
+                self.push(view);
+            }
+            Event::SoftBreak => self.push(View::static_text("\n")),
+            Event::HardBreak => self.push(View::new("br".to_string(), vec![], vec![], vec![])),
+            Event::Rule => self.push(View::new("hr".to_string(), vec![], vec![], vec![])),
+            Event::TaskListMarker(checked) => {
+                let mut attributes = vec![
+                    ("type".to_string(), "checkbox".into()),
+                    ("disabled".to_string(), true.into()),
+                ];
+                if checked {
+                    attributes.push(("checked".to_string(), true.into()));
+                }
+                self.push(View::new("input".to_string(), attributes, vec![], vec![]));
+            }
+            // Embedded HTML is dropped rather than rendered.
+            _ => (),
+        }
+    }
+}
+
+impl<A> View<A> {
+    /// Renders CommonMark (with tables, strikethrough and task lists) into a
+    /// `div` of ordinary elements. Embedded HTML is dropped and link targets
+    /// are limited to http, https, mailto and relative URLs.
+    pub fn from_markdown(markdown: &str) -> View<A> {
+        let options = Options::ENABLE_TABLES
+            | Options::ENABLE_STRIKETHROUGH
+            | Options::ENABLE_TASKLISTS;
+        let mut builder = Builder {
+            frames: vec![Frame::new("div", vec![])],
+            depths: vec![],
+            alignments: vec![],
+            in_head: false,
+            column: 0,
+        };
+        for event in Parser::new_ext(markdown, options) {
+            builder.event(event);
+        }
+        while builder.frames.len() > 1 {
+            builder.close();
+        }
+        builder.frames.pop().expect("markdown root frame").into_view()
+    }
+}
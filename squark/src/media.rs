@@ -0,0 +1,48 @@
+//! Audio and video elements, whose playback has no attribute to set.
+
+use {handler, HandlerArg, HandlerFunction, IntoActions};
+
+/// What `Cmd::play`, `Cmd::pause` and `Cmd::seek` do to a media element.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MediaControl {
+    Play,
+    Pause,
+    /// Jumps to the position in seconds.
+    Seek(f64),
+}
+
+/// Playback state carried by events of media elements such as `timeupdate`,
+/// `ended`, `play` and `pause`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MediaEvent {
+    /// Position in seconds.
+    pub current_time: f64,
+    /// Length in seconds, unknown until the metadata is loaded.
+    pub duration: Option<f64>,
+    pub paused: bool,
+    pub ended: bool,
+}
+
+impl MediaEvent {
+    /// Reads `{"currentTime", "duration", "paused", "ended"}`.
+    pub fn from_arg(arg: &HandlerArg) -> Option<MediaEvent> {
+        Some(MediaEvent {
+            current_time: arg["currentTime"].as_f64()?,
+            duration: arg["duration"].as_f64(),
+            paused: arg["paused"].as_bool()?,
+            ended: arg["ended"].as_bool()?,
+        })
+    }
+}
+
+/// A handler for media events, ignoring events without playback state.
+pub fn media_handler<A, R, F>(f: F) -> (String, HandlerFunction<A>)
+where
+    R: IntoActions<A>,
+    F: Fn(MediaEvent) -> R + 'static,
+{
+    handler(move |arg| match MediaEvent::from_arg(&arg) {
+        Some(event) => f(event).into_actions(),
+        None => vec![],
+    })
+}
@@ -0,0 +1,261 @@
+//! Three-way merges of trees rendered from a common one, such as an interim
+//! UI rendered locally and the one a server pushes.
+
+use std::collections::HashSet;
+use std::rc::Rc;
+use {AttributeValue, Element, Handler, Node};
+
+/// A change both sides made differently. The merge keeps that of `theirs`.
+/// Paths are child indices from the root of the merged tree, null children
+/// left out.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Conflict {
+    /// Both changed the node, or one removed it while the other changed it,
+    /// in which case that side has `Node::Null`.
+    Node {
+        path: Vec<usize>,
+        mine: Node,
+        theirs: Node,
+    },
+    /// Both set the attribute to different values, `None` if removed.
+    Attribute {
+        path: Vec<usize>,
+        name: String,
+        mine: Option<AttributeValue>,
+        theirs: Option<AttributeValue>,
+    },
+    /// Both reordered the children of the element differently, or changed
+    /// how many unkeyed children it has.
+    Children { path: Vec<usize> },
+}
+
+/// Merges the changes `mine` and `theirs` each made to `base`. Changes to
+/// different attributes and children are combined, matching keyed children
+/// by key and others by position. Handlers come with the nodes they are
+/// taken from, those of `theirs` where both have an element. Null children
+/// are dropped.
+pub fn merge(base: &Node, mine: &Node, theirs: &Node) -> (Node, Vec<Conflict>) {
+    let mut merger = Merger {
+        path: vec![],
+        conflicts: vec![],
+    };
+    let node = merger.node(base, mine, theirs);
+    (node, merger.conflicts)
+}
+
+struct Merger {
+    path: Vec<usize>,
+    conflicts: Vec<Conflict>,
+}
+
+fn same_element(a: &Element, b: &Element) -> bool {
+    a.data.name == b.data.name
+        && a.data.shadow == b.data.shadow
+        && a.data.external == b.data.external
+        && a.data.key == b.data.key
+}
+
+fn rendered(children: &[Node]) -> Vec<&Node> {
+    children.iter().filter(|child| !child.is_null()).collect()
+}
+
+fn keys(children: &[&Node]) -> Option<Vec<String>> {
+    children.iter().map(|child| child.get_key()).collect()
+}
+
+fn find<'a>(children: &[&'a Node], key: &str) -> Option<&'a Node> {
+    children
+        .iter()
+        .find(|child| child.get_key().as_deref() == Some(key))
+        .cloned()
+}
+
+// Whether the keys `a` and `b` share are in another order.
+fn reordered(a: &[String], b: &[String]) -> bool {
+    let (in_a, in_b): (HashSet<&String>, HashSet<&String>) =
+        (a.iter().collect(), b.iter().collect());
+    let a_shared = a.iter().filter(|key| in_b.contains(key));
+    let b_shared = b.iter().filter(|key| in_a.contains(key));
+    !a_shared.eq(b_shared)
+}
+
+// Merges values by name, in the order of `theirs` and then of `mine`.
+fn merge_named<V, F>(
+    base: &[(String, V)],
+    mine: &[(String, V)],
+    theirs: &[(String, V)],
+    mut conflict: F,
+) -> Vec<(String, V)>
+where
+    V: Clone + PartialEq,
+    F: FnMut(&str, Option<&V>, Option<&V>),
+{
+    let get = |list: &[(String, V)], name: &str| {
+        list.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone())
+    };
+    let mut names: Vec<&String> = theirs.iter().map(|(k, _)| k).collect();
+    names.extend(
+        mine.iter()
+            .map(|(k, _)| k)
+            .filter(|k| get(theirs, k).is_none()),
+    );
+    let mut result = vec![];
+    for name in names {
+        let (b, m, t) = (get(base, name), get(mine, name), get(theirs, name));
+        let value = if m == t || m == b {
+            t
+        } else if t == b {
+            m
+        } else {
+            conflict(name, m.as_ref(), t.as_ref());
+            t
+        };
+        result.extend(value.map(|value| (name.clone(), value)));
+    }
+    result
+}
+
+impl Merger {
+    fn node(&mut self, base: &Node, mine: &Node, theirs: &Node) -> Node {
+        if mine == theirs || mine == base {
+            return theirs.clone();
+        }
+        if theirs == base {
+            return mine.clone();
+        }
+        if let (Node::Element(b), Node::Element(m), Node::Element(t)) = (base, mine, theirs) {
+            if same_element(b, m) && same_element(b, t) {
+                return Node::Element(self.element(b, m, t));
+            }
+        }
+        self.conflicts.push(Conflict::Node {
+            path: self.path.clone(),
+            mine: mine.clone(),
+            theirs: theirs.clone(),
+        });
+        theirs.clone()
+    }
+
+    fn element(&mut self, base: &Element, mine: &Element, theirs: &Element) -> Element {
+        let path = &self.path;
+        let conflicts = &mut self.conflicts;
+        let attributes = merge_named(
+            base.attributes(),
+            mine.attributes(),
+            theirs.attributes(),
+            |name, m, t| {
+                conflicts.push(Conflict::Attribute {
+                    path: path.clone(),
+                    name: name.to_string(),
+                    mine: m.cloned(),
+                    theirs: t.cloned(),
+                })
+            },
+        );
+        // Ids differ between renders, so only which kinds are handled is merged.
+        let kinds = |handlers: &[Handler]| -> Vec<(String, ())> {
+            handlers
+                .iter()
+                .map(|(kind, _)| (kind.clone(), ()))
+                .collect()
+        };
+        let handlers = merge_named(
+            &kinds(base.handlers()),
+            &kinds(mine.handlers()),
+            &kinds(theirs.handlers()),
+            |_, _, _| (),
+        )
+        .into_iter()
+        .filter_map(|(kind, _)| {
+            let id = theirs.handler(&kind).or_else(|| mine.handler(&kind))?;
+            Some((kind, id.to_string()))
+        })
+        .collect();
+        let children = self.children(base.children(), mine.children(), theirs.children());
+
+        let mut el = Element::new(theirs.data.name.clone(), attributes, handlers, children);
+        {
+            let data = Rc::make_mut(&mut el.data);
+            data.shadow = theirs.data.shadow;
+            data.external = theirs.data.external;
+            data.key = theirs.data.key.clone();
+        }
+        el
+    }
+
+    fn children(&mut self, base: &[Node], mine: &[Node], theirs: &[Node]) -> Vec<Node> {
+        let (b, m, t) = (rendered(base), rendered(mine), rendered(theirs));
+        if let (Some(kb), Some(km), Some(kt)) = (keys(&b), keys(&m), keys(&t)) {
+            return self.keyed(&b, &m, &t, &kb, &km, &kt);
+        }
+        if b.len() == m.len() && b.len() == t.len() {
+            let mut result = vec![];
+            for i in 0..b.len() {
+                self.path.push(i);
+                result.push(self.node(b[i], m[i], t[i]));
+                self.path.pop();
+            }
+            return result;
+        }
+        let cloned = |list: &[&Node]| list.iter().map(|&node| node.clone()).collect();
+        if m == b || m == t {
+            return cloned(&t);
+        }
+        if t == b {
+            return cloned(&m);
+        }
+        self.conflicts.push(Conflict::Children {
+            path: self.path.clone(),
+        });
+        cloned(&t)
+    }
+
+    fn keyed(
+        &mut self,
+        b: &[&Node],
+        m: &[&Node],
+        t: &[&Node],
+        kb: &[String],
+        km: &[String],
+        kt: &[String],
+    ) -> Vec<Node> {
+        let (mine_reordered, theirs_reordered) = (reordered(kb, km), reordered(kb, kt));
+        if mine_reordered && theirs_reordered && reordered(km, kt) {
+            self.conflicts.push(Conflict::Children {
+                path: self.path.clone(),
+            });
+        }
+        let (first, second) = if mine_reordered && !theirs_reordered {
+            (km, kt)
+        } else {
+            (kt, km)
+        };
+
+        // Keys of the other side go after the key they follow there.
+        let mut order: Vec<&String> = first.iter().collect();
+        for (i, key) in second.iter().enumerate() {
+            if !order.contains(&key) {
+                let at = second[..i]
+                    .iter()
+                    .rev()
+                    .find_map(|k| order.iter().position(|o| *o == k))
+                    .map_or(0, |p| p + 1);
+                order.insert(at, key);
+            }
+        }
+
+        let mut result = vec![];
+        for key in order {
+            let base = find(b, key).unwrap_or(&Node::Null);
+            let mine = find(m, key).unwrap_or(&Node::Null);
+            let theirs = find(t, key).unwrap_or(&Node::Null);
+            self.path.push(result.len());
+            let node = self.node(base, mine, theirs);
+            self.path.pop();
+            if !node.is_null() {
+                result.push(node);
+            }
+        }
+        result
+    }
+}
@@ -0,0 +1,103 @@
+use super::{Element, Node};
+use std::mem;
+
+// Elements whose whitespace is content, kept by `trim_whitespace`.
+const PRESERVES_WHITESPACE: &[&str] = &["pre", "textarea", "listing", "plaintext"];
+
+/// How text is normalized before diffing and server rendering, so trees
+/// match what a browser parses from the markup: it merges adjacent text and
+/// pages often skip whitespace between elements.
+///
+/// Both sides of a hydrated app must use the same options. See
+/// `Env::set_normalize` and `ssr::prerender_with`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Normalize {
+    /// Merges text nodes which are siblings, ignoring null nodes in between.
+    pub coalesce_text: bool,
+    /// Drops text children consisting only of whitespace, except within
+    /// elements where it is content, like `pre` and `textarea`.
+    pub trim_whitespace: bool,
+}
+
+impl Normalize {
+    /// Both options.
+    pub fn all() -> Normalize {
+        Normalize {
+            coalesce_text: true,
+            trim_whitespace: true,
+        }
+    }
+
+    /// Normalizes `node`, copying only the elements that change.
+    pub fn apply(&self, mut node: Node) -> Node {
+        if *self == Normalize::default() {
+            return node;
+        }
+        self.normalize(&mut node);
+        node
+    }
+
+    fn normalize(&self, node: &mut Node) {
+        let el = match *node {
+            Node::Element(ref mut el) => el,
+            _ => return,
+        };
+        if self.is_normal(el) {
+            return;
+        }
+        let this = self.within(el);
+        let data = el.data_mut();
+        let mut children: Vec<Node> = vec![];
+        for mut child in mem::take(&mut data.children) {
+            if let Node::Text(ref text) = child {
+                if this.trim_whitespace && text.trim().is_empty() {
+                    continue;
+                }
+                if this.coalesce_text {
+                    let previous = children.iter_mut().rev().find(|c| !c.is_null());
+                    if let Some(&mut Node::Text(ref mut previous)) = previous {
+                        previous.to_mut().push_str(text);
+                        continue;
+                    }
+                }
+            }
+            this.normalize(&mut child);
+            children.push(child);
+        }
+        data.children = children;
+    }
+
+    // The options for the children of `el`.
+    fn within(&self, el: &Element) -> Normalize {
+        if PRESERVES_WHITESPACE.contains(&el.name()) {
+            Normalize {
+                trim_whitespace: false,
+                ..*self
+            }
+        } else {
+            *self
+        }
+    }
+
+    fn is_normal(&self, el: &Element) -> bool {
+        let this = self.within(el);
+        let mut after_text = false;
+        el.children().iter().all(|child| match *child {
+            Node::Null => true,
+            Node::Text(ref text) => {
+                let blank = this.trim_whitespace && text.trim().is_empty();
+                let merged = this.coalesce_text && after_text;
+                after_text = true;
+                !blank && !merged
+            }
+            Node::Element(ref child) => {
+                after_text = false;
+                this.is_normal(child)
+            }
+            Node::Raw(_) | Node::Custom(_) => {
+                after_text = false;
+                true
+            }
+        })
+    }
+}
@@ -0,0 +1,103 @@
+//! Work deferred while offline.
+
+use cmd::Cmd;
+use HandlerArg;
+
+/// Items queued while offline, kept in the state and saved to storage under
+/// a key so that they survive reloads. Items are JSON values, typically the
+/// payloads of the requests to send.
+///
+/// Feed `Subscription::online` into `set_online`. Coming back online takes
+/// the queue off to `flushing`, which `App::command` for the same action
+/// sends, as it does items pushed while online.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Outbox {
+    key: String,
+    queue: Vec<HandlerArg>,
+    flushing: Vec<HandlerArg>,
+    online: bool,
+}
+
+impl Outbox {
+    /// An empty outbox saved under `key`, taken to be online.
+    pub fn new<S: Into<String>>(key: S) -> Outbox {
+        Outbox {
+            key: key.into(),
+            queue: vec![],
+            flushing: vec![],
+            online: true,
+        }
+    }
+
+    pub fn is_online(&self) -> bool {
+        self.online
+    }
+
+    pub fn queue(&self) -> &[HandlerArg] {
+        &self.queue
+    }
+
+    /// Items taken off the queue by the last reconnect.
+    pub fn flushing(&self) -> &[HandlerArg] {
+        &self.flushing
+    }
+
+    pub fn set_online(&mut self, online: bool) {
+        if online && !self.online {
+            self.flushing = self.queue.drain(..).collect();
+        } else {
+            self.flushing.clear();
+        }
+        self.online = online;
+    }
+
+    /// Queues `item` while offline and returns whether it was, in which case
+    /// `save` persists it.
+    pub fn push(&mut self, item: HandlerArg) -> bool {
+        if self.online {
+            return false;
+        }
+        self.queue.push(item);
+        true
+    }
+
+    /// Loads the queue saved by an earlier session, handing it to `f` for
+    /// `restored`.
+    pub fn restore<A, F>(&self, f: F) -> Cmd<A>
+    where
+        A: 'static,
+        F: FnOnce(Vec<HandlerArg>) -> A + 'static,
+    {
+        Cmd::storage_get(self.key.clone(), move |saved| {
+            let items = saved
+                .and_then(|saved| ::serde_json::from_str(&saved).ok())
+                .unwrap_or_default();
+            f(items)
+        })
+    }
+
+    /// Queues items loaded by `restore` before those queued since.
+    pub fn restored(&mut self, items: Vec<HandlerArg>) {
+        self.queue.splice(0..0, items);
+    }
+
+    pub fn save<A: 'static>(&self) -> Cmd<A> {
+        if self.queue.is_empty() {
+            Cmd::storage_remove(self.key.clone())
+        } else {
+            Cmd::storage_set(
+                self.key.clone(),
+                HandlerArg::from(self.queue.clone()).to_string(),
+            )
+        }
+    }
+
+    /// Sends the items being flushed with `send` and saves the queue.
+    pub fn flush<A, F>(&self, send: F) -> Cmd<A>
+    where
+        A: 'static,
+        F: Fn(&HandlerArg) -> Cmd<A>,
+    {
+        Cmd::batch(self.flushing.iter().map(send).chain(Some(self.save())))
+    }
+}
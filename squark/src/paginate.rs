@@ -0,0 +1,147 @@
+//! Lists loaded a page at a time.
+
+use subscription::{Subscription, OBSERVE_KEY};
+use {handler, Attribute, View};
+
+/// Pages of items loaded so far, kept in the state. Pages are numbered from
+/// zero and a page shorter than `page_size` is taken to be the last one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Paginated<T> {
+    items: Vec<T>,
+    page_size: usize,
+    loading: bool,
+    exhausted: bool,
+}
+
+impl<T> Paginated<T> {
+    pub fn new(page_size: usize) -> Paginated<T> {
+        assert!(page_size > 0, "page size must not be zero");
+        Paginated {
+            items: vec![],
+            page_size,
+            loading: false,
+            exhausted: false,
+        }
+    }
+
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// The items of page `page`, empty if it is not loaded.
+    pub fn page(&self, page: usize) -> &[T] {
+        let start = (page * self.page_size).min(self.items.len());
+        let end = (start + self.page_size).min(self.items.len());
+        &self.items[start..end]
+    }
+
+    /// Pages loaded so far.
+    pub fn loaded_pages(&self) -> usize {
+        (self.items.len() + self.page_size - 1) / self.page_size
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.loading
+    }
+
+    pub fn has_more(&self) -> bool {
+        !self.exhausted
+    }
+
+    /// Marks the next page as loading and returns its number, or `None`
+    /// while a page is loading or after the last one. Call from the reducer
+    /// and start the fetch from `App::command`.
+    pub fn request_next(&mut self) -> Option<usize> {
+        if self.loading || self.exhausted {
+            return None;
+        }
+        self.loading = true;
+        Some(self.loaded_pages())
+    }
+
+    /// Adds the page requested last.
+    pub fn receive(&mut self, items: Vec<T>) {
+        self.exhausted = items.len() < self.page_size;
+        self.items.extend(items);
+        self.loading = false;
+    }
+
+    /// Lets the page requested last be requested again.
+    pub fn fail(&mut self) {
+        self.loading = false;
+    }
+
+    /// Fires `action` once the sentinel tagged with `key` scrolls into view,
+    /// while there is more to load. See `load_more_sentinel`.
+    pub fn load_more<A: Clone + 'static>(&self, key: &str, action: A) -> Option<Subscription<A>> {
+        if self.loading || self.exhausted {
+            return None;
+        }
+        Some(Subscription::on_visible(key, action))
+    }
+}
+
+/// An empty element to put after an infinite list; see `Paginated::load_more`.
+pub fn load_more_sentinel<A>(key: &str) -> View<A> {
+    View::new(
+        "div",
+        vec![
+            (OBSERVE_KEY.to_string(), key.into()),
+            ("aria-hidden".to_string(), "true".into()),
+        ],
+        vec![],
+        vec![],
+    )
+}
+
+/// Previous and next buttons around one button per page, calling `f` with
+/// the page chosen. Pages are numbered from zero but shown from one.
+pub fn pager<A, F>(current: usize, page_count: usize, f: F) -> View<A>
+where
+    A: 'static,
+    F: Fn(usize) -> A + Clone + 'static,
+{
+    let button = |label: String, page: usize, attributes: Vec<Attribute>| {
+        let f = f.clone();
+        View::new(
+            "button",
+            attributes,
+            vec![("click".to_string(), handler(move |_| f(page)))],
+            vec![View::text(label).into()],
+        )
+    };
+    let mut buttons = vec![button(
+        "Previous".to_string(),
+        current.saturating_sub(1),
+        vec![
+            ("aria-label".to_string(), "Previous page".into()),
+            ("disabled".to_string(), (current == 0).into()),
+        ],
+    )];
+    for page in 0..page_count {
+        let current = if page == current {
+            Some(("aria-current".to_string(), "page".into()))
+        } else {
+            None
+        };
+        buttons.push(button(
+            (page + 1).to_string(),
+            page,
+            current.into_iter().collect(),
+        ));
+    }
+    buttons.push(button(
+        "Next".to_string(),
+        (current + 1).min(page_count.saturating_sub(1)),
+        vec![
+            ("aria-label".to_string(), "Next page".into()),
+            ("disabled".to_string(), (current + 1 >= page_count).into()),
+        ],
+    ));
+    View::new(
+        "nav",
+        vec![("aria-label".to_string(), "Pagination".into())],
+        vec![],
+        vec![buttons.into()],
+    )
+}
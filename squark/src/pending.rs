@@ -0,0 +1,83 @@
+//! Views of resources whose commands are still in flight.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+use {Node, View};
+
+/// Marks the fallbacks of `pending` as placeholders, holding the resource
+/// key. See `Env::set_placeholders`.
+pub const PLACEHOLDER_ATTRIBUTE: &str = "data-squark-placeholder";
+
+/// Commands in flight by resource key. See `Cmd::track`.
+pub(crate) type PendingMap = Rc<RefCell<HashMap<String, usize>>>;
+
+thread_local! {
+    static PENDING: RefCell<Option<PendingMap>> = const { RefCell::new(None) };
+    static PLACEHOLDERS: Cell<bool> = const { Cell::new(false) };
+}
+
+pub(crate) fn with_pending<T, F>(pending: PendingMap, placeholders: bool, f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    let previous = PENDING.with(|p| p.replace(Some(pending)));
+    let marked = PLACEHOLDERS.with(|p| p.replace(placeholders));
+    let result = f();
+    PENDING.with(|p| *p.borrow_mut() = previous);
+    PLACEHOLDERS.with(|p| p.set(marked));
+    result
+}
+
+pub(crate) fn begin(pending: &PendingMap, key: &str) {
+    *pending.borrow_mut().entry(key.to_string()).or_insert(0) += 1;
+}
+
+pub(crate) fn end(pending: &PendingMap, key: &str) {
+    let mut pending = pending.borrow_mut();
+    let done = match pending.get_mut(key) {
+        Some(count) => {
+            *count -= 1;
+            *count == 0
+        }
+        None => false,
+    };
+    if done {
+        pending.remove(key);
+    }
+}
+
+/// Whether a command tracked under `key` is in flight, for the view being
+/// built. False outside of views.
+pub fn is_pending(key: &str) -> bool {
+    PENDING.with(|p| {
+        p.borrow()
+            .as_ref()
+            .is_some_and(|pending| pending.borrow().contains_key(key))
+    })
+}
+
+/// `fallback` while a command tracked under `key` is in flight, the view
+/// built by `content` otherwise.
+pub fn pending<A, F>(key: &str, fallback: View<A>, content: F) -> View<A>
+where
+    F: FnOnce() -> View<A>,
+{
+    if !is_pending(key) {
+        content()
+    } else if PLACEHOLDERS.with(Cell::get) {
+        placeholder(key, fallback)
+    } else {
+        fallback
+    }
+}
+
+// Marks an element fallback, wrapping others in a `span`.
+fn placeholder<A>(key: &str, mut fallback: View<A>) -> View<A> {
+    let mark = (PLACEHOLDER_ATTRIBUTE.to_string(), key.into());
+    if let Node::Element(ref mut el) = fallback.node {
+        el.attributes_mut().push(mark);
+        return fallback;
+    }
+    View::new("span", vec![mark], vec![], vec![fallback.into()])
+}
@@ -0,0 +1,68 @@
+//! State that is cheap to clone.
+//!
+//! Every dispatch clones the state for the reducer and compares the result
+//! with the current one, which costs O(n) with a large `Vec`. With the `im`
+//! feature, the persistent collections of `im-rc` are re-exported here:
+//! their clones share structure, and so do the states reduced from them,
+//! which makes both steps cheap. `Shared` does the same for data the
+//! reducers seldom change.
+
+use std::fmt;
+use std::ops::Deref;
+use std::rc::Rc;
+
+#[cfg(feature = "im")]
+pub use im_rc::{HashMap, HashSet, OrdMap, OrdSet, Vector};
+
+/// A value behind an `Rc`, cloned by reference and compared by identity
+/// before value. `make_mut` clones it when it is shared.
+#[derive(Default, Eq)]
+pub struct Shared<T>(Rc<T>);
+
+impl<T> Shared<T> {
+    pub fn new(value: T) -> Shared<T> {
+        Shared(Rc::new(value))
+    }
+
+    pub fn ptr_eq(&self, other: &Shared<T>) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<T: Clone> Shared<T> {
+    pub fn make_mut(&mut self) -> &mut T {
+        Rc::make_mut(&mut self.0)
+    }
+}
+
+impl<T> Clone for Shared<T> {
+    fn clone(&self) -> Shared<T> {
+        Shared(self.0.clone())
+    }
+}
+
+impl<T> Deref for Shared<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: PartialEq> PartialEq for Shared<T> {
+    fn eq(&self, other: &Shared<T>) -> bool {
+        self.ptr_eq(other) || self.0 == other.0
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Shared<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T> From<T> for Shared<T> {
+    fn from(value: T) -> Shared<T> {
+        Shared::new(value)
+    }
+}
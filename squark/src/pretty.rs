@@ -0,0 +1,318 @@
+//! Readable renderings of trees and diffs for logs and test failures.
+
+use super::{AttributeValue, Diff, Element, Node};
+use std::fmt::{self, Write};
+
+fn indent(f: &mut dyn Write, depth: usize) -> fmt::Result {
+    for _ in 0..depth {
+        f.write_str("  ")?;
+    }
+    Ok(())
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// `<name attributes @kinds>`, omitting `Bool(false)` attributes like runtimes do.
+fn open_tag(f: &mut dyn Write, el: &Element) -> fmt::Result {
+    write!(f, "<{}", el.name())?;
+    if let Some(key) = el.key() {
+        write!(f, " :key=\"{}\"", escape(key))?;
+    }
+    if el.is_external() {
+        f.write_str(" :external")?;
+    }
+    for (name, value) in el.attributes() {
+        match *value {
+            AttributeValue::Bool(false) => (),
+            AttributeValue::Bool(true) => write!(f, " {}", name)?,
+            AttributeValue::String(ref s) => write!(f, " {}=\"{}\"", name, escape(s))?,
+            AttributeValue::Property(ref v) => write!(f, " .{}={}", name, v)?,
+        }
+    }
+    for (kind, _) in el.handlers() {
+        write!(f, " @{}", kind)?;
+    }
+    f.write_char('>')
+}
+
+fn write_node(f: &mut dyn Write, node: &Node, depth: usize) -> fmt::Result {
+    match *node {
+        Node::Null => return Ok(()),
+        Node::Text(ref text) => {
+            indent(f, depth)?;
+            f.write_str(&escape(text))?;
+        }
+        Node::Raw(ref html) => {
+            indent(f, depth)?;
+            write!(f, "<span>{}</span>", html)?;
+        }
+        Node::Custom(ref custom) => {
+            indent(f, depth)?;
+            write!(f, "<{} #custom={}></{}>", custom.tag(), custom.name(), custom.tag())?;
+        }
+        Node::Element(ref el) => {
+            indent(f, depth)?;
+            open_tag(f, el)?;
+            let children: Vec<&Node> = el.children().iter().filter(|c| !c.is_null()).collect();
+            if el.tag().is_void() {
+                // Written without a closing tag.
+            } else if children.is_empty() && !el.has_shadow_root() {
+                write!(f, "</{}>", el.name())?;
+            } else {
+                f.write_char('\n')?;
+                let mut inner = depth + 1;
+                if el.has_shadow_root() {
+                    indent(f, inner)?;
+                    f.write_str("#shadow-root\n")?;
+                    inner += 1;
+                }
+                for child in children {
+                    write_node(f, child, inner)?;
+                }
+                indent(f, depth)?;
+                write!(f, "</{}>", el.name())?;
+            }
+        }
+    }
+    f.write_char('\n')
+}
+
+// A single line standing for a whole node.
+fn summary(f: &mut dyn Write, node: &Node) -> fmt::Result {
+    match *node {
+        Node::Null => f.write_str("null"),
+        Node::Text(ref text) => write!(f, "{:?}", text),
+        Node::Raw(ref html) => write!(f, "raw {:?}", html),
+        Node::Custom(ref custom) => write!(f, "custom {:?}", custom.name()),
+        Node::Element(ref el) => {
+            open_tag(f, el)?;
+            if el.tag().is_void() {
+                return Ok(());
+            }
+            if !el.children().is_empty() {
+                f.write_str("…")?;
+            }
+            write!(f, "</{}>", el.name())
+        }
+    }
+}
+
+fn summaries(f: &mut dyn Write, nodes: &[Node]) -> fmt::Result {
+    for (i, node) in nodes.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        summary(f, node)?;
+    }
+    Ok(())
+}
+
+fn write_nested(f: &mut dyn Write, diffs: &[Diff], depth: usize) -> fmt::Result {
+    for diff in diffs {
+        write_diff(f, diff, depth)?;
+    }
+    Ok(())
+}
+
+fn write_diff(f: &mut dyn Write, diff: &Diff, depth: usize) -> fmt::Result {
+    indent(f, depth)?;
+    match *diff {
+        Diff::SetAttribute(ref name, AttributeValue::String(ref s)) => {
+            write!(f, "set {}={:?}", name, s)?
+        }
+        Diff::SetAttribute(ref name, AttributeValue::Property(ref v))
+        | Diff::SetProperty(ref name, ref v) => write!(f, "set .{}={}", name, v)?,
+        Diff::SetAttribute(ref name, AttributeValue::Bool(b)) => write!(f, "set {}={}", name, b)?,
+        Diff::RemoveAttribute(ref name) => write!(f, "remove {}", name)?,
+        Diff::RemoveProperty(ref name) => write!(f, "remove .{}", name)?,
+        Diff::AddChild(i, ref node) => {
+            write!(f, "add {} ", i)?;
+            summary(f, node)?
+        }
+        Diff::AddChildren(i, ref nodes) => {
+            write!(f, "add {}.. ", i)?;
+            summaries(f, nodes)?
+        }
+        Diff::ReplaceChild(i, ref node) => {
+            write!(f, "replace {} ", i)?;
+            summary(f, node)?
+        }
+        Diff::RemoveChild(i) => write!(f, "remove child {}", i)?,
+        Diff::RemoveChildren(i, count) => write!(f, "remove children {}..{}", i, i + count)?,
+        Diff::ClearChildren => f.write_str("clear children")?,
+        Diff::PatchChild(i, ref diffs) => {
+            writeln!(f, "patch {}", i)?;
+            return write_nested(f, diffs, depth + 1);
+        }
+        Diff::PatchNode(id, ref diffs) => {
+            writeln!(f, "patch @{}", id)?;
+            return write_nested(f, diffs, depth + 1);
+        }
+        Diff::ReplaceKeyed(ref key, ref node) => {
+            write!(f, "replace #{} ", key)?;
+            summary(f, node)?
+        }
+        Diff::RemoveKeyed(ref key) => write!(f, "remove child #{}", key)?,
+        Diff::PatchKeyed(ref key, ref diffs) => {
+            writeln!(f, "patch #{}", key)?;
+            return write_nested(f, diffs, depth + 1);
+        }
+        Diff::ReplaceRoot(ref node) => {
+            write!(f, "replace root ")?;
+            summary(f, node)?
+        }
+        Diff::PatchRoot(ref diffs) => {
+            writeln!(f, "patch root")?;
+            return write_nested(f, diffs, depth + 1);
+        }
+        Diff::PatchShadow(ref diffs) => {
+            writeln!(f, "patch #shadow-root")?;
+            return write_nested(f, diffs, depth + 1);
+        }
+        Diff::PatchPath(ref path, ref diffs) => {
+            let path: Vec<String> = path.iter().map(|i| i.to_string()).collect();
+            writeln!(f, "patch {}", path.join("/"))?;
+            return write_nested(f, diffs, depth + 1);
+        }
+        Diff::SetText(ref text) => write!(f, "text {:?}", text)?,
+        Diff::SetHandler(ref kind, ref id) => write!(f, "on {} {}", kind, id)?,
+        Diff::RemoveHandler(ref kind, ref id) => write!(f, "off {} {}", kind, id)?,
+        Diff::SaveScroll(ref key) => write!(f, "save scroll {}", key)?,
+        Diff::RestoreScroll(ref key) => write!(f, "restore scroll {}", key)?,
+        Diff::InjectStyle(ref name, _) => write!(f, "inject style {}", name)?,
+        Diff::DelegateEvents(ref kinds) => write!(f, "delegate {}", kinds.join(", "))?,
+    }
+    f.write_char('\n')
+}
+
+/// Indented HTML-like markup, with handlers as `@kind`, properties as
+/// `.name=json`, the key as `:key` and external elements as `:external`.
+/// Null nodes are left out.
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_node(f, self, 0)
+    }
+}
+
+/// One line per operation, nested diffs indented below the patched child.
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_diff(f, self, 0)
+    }
+}
+
+// One line per leaf operation, prefixed by the patches leading to it.
+fn explain(out: &mut Vec<String>, path: &mut Vec<String>, diff: &Diff) {
+    let mut nested = |out: &mut Vec<String>, step: String, diffs: &[Diff]| {
+        path.push(step);
+        for diff in diffs {
+            explain(out, path, diff);
+        }
+        path.pop();
+    };
+    let mut leaf = String::new();
+    match *diff {
+        Diff::PatchChild(i, ref diffs) => return nested(out, format!("PatchChild[{}]", i), diffs),
+        Diff::PatchNode(id, ref diffs) => return nested(out, format!("PatchNode[@{}]", id), diffs),
+        Diff::PatchKeyed(ref key, ref diffs) => {
+            return nested(out, format!("PatchKeyed[{:?}]", key), diffs)
+        }
+        Diff::PatchRoot(ref diffs) => return nested(out, "PatchRoot".to_string(), diffs),
+        Diff::PatchShadow(ref diffs) => return nested(out, "PatchShadow".to_string(), diffs),
+        Diff::PatchPath(ref indices, ref diffs) => {
+            let indices: Vec<String> = indices.iter().map(|i| i.to_string()).collect();
+            return nested(out, format!("PatchPath[{}]", indices.join("/")), diffs);
+        }
+        Diff::SetAttribute(ref name, AttributeValue::String(ref s)) => {
+            let _ = write!(leaf, "SetAttribute {}={:?}", name, s);
+        }
+        Diff::SetAttribute(ref name, AttributeValue::Bool(b)) => {
+            let _ = write!(leaf, "SetAttribute {}={}", name, b);
+        }
+        Diff::SetAttribute(ref name, AttributeValue::Property(ref v))
+        | Diff::SetProperty(ref name, ref v) => {
+            let _ = write!(leaf, "SetProperty {}={}", name, v);
+        }
+        Diff::RemoveAttribute(ref name) => {
+            let _ = write!(leaf, "RemoveAttribute {}", name);
+        }
+        Diff::RemoveProperty(ref name) => {
+            let _ = write!(leaf, "RemoveProperty {}", name);
+        }
+        Diff::AddChild(i, ref node) => {
+            let _ = write!(leaf, "AddChild[{}] ", i);
+            let _ = summary(&mut leaf, node);
+        }
+        Diff::AddChildren(i, ref nodes) => {
+            let _ = write!(leaf, "AddChildren[{}..] ", i);
+            let _ = summaries(&mut leaf, nodes);
+        }
+        Diff::ReplaceChild(i, ref node) => {
+            let _ = write!(leaf, "ReplaceChild[{}] ", i);
+            let _ = summary(&mut leaf, node);
+        }
+        Diff::RemoveChild(i) => {
+            let _ = write!(leaf, "RemoveChild[{}]", i);
+        }
+        Diff::RemoveChildren(i, count) => {
+            let _ = write!(leaf, "RemoveChildren[{}..{}]", i, i + count);
+        }
+        Diff::ClearChildren => leaf.push_str("ClearChildren"),
+        Diff::ReplaceKeyed(ref key, ref node) => {
+            let _ = write!(leaf, "ReplaceKeyed[{:?}] ", key);
+            let _ = summary(&mut leaf, node);
+        }
+        Diff::RemoveKeyed(ref key) => {
+            let _ = write!(leaf, "RemoveKeyed[{:?}]", key);
+        }
+        Diff::ReplaceRoot(ref node) => {
+            leaf.push_str("ReplaceRoot ");
+            let _ = summary(&mut leaf, node);
+        }
+        Diff::SetText(ref text) => {
+            let _ = write!(leaf, "SetText {:?}", text);
+        }
+        Diff::SetHandler(ref kind, ref id) => {
+            let _ = write!(leaf, "SetHandler {}={}", kind, id);
+        }
+        Diff::RemoveHandler(ref kind, ref id) => {
+            let _ = write!(leaf, "RemoveHandler {}={}", kind, id);
+        }
+        Diff::SaveScroll(ref key) => {
+            let _ = write!(leaf, "SaveScroll {}", key);
+        }
+        Diff::RestoreScroll(ref key) => {
+            let _ = write!(leaf, "RestoreScroll {}", key);
+        }
+        Diff::InjectStyle(ref name, _) => {
+            let _ = write!(leaf, "InjectStyle {}", name);
+        }
+        Diff::DelegateEvents(ref kinds) => {
+            let _ = write!(leaf, "DelegateEvents {}", kinds.join(","));
+        }
+    }
+    path.push(leaf);
+    out.push(path.join(" -> "));
+    path.pop();
+}
+
+impl Diff {
+    /// Every operation on its own line with the patches leading to it, e.g.
+    /// `PatchRoot -> PatchChild[3] -> SetAttribute class="active"`.
+    pub fn explain(&self) -> String {
+        let mut lines = vec![];
+        explain(&mut lines, &mut vec![], self);
+        lines.join("\n")
+    }
+}
+
+/// `diffs` printed one after the other, as a runtime receives them.
+pub fn format_diffs(diffs: &[Diff]) -> String {
+    let mut out = String::new();
+    write_nested(&mut out, diffs, 0).unwrap();
+    out
+}
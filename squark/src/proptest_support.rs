@@ -0,0 +1,286 @@
+//! Random trees and a reference patcher for fuzzing diffing and runtimes.
+
+use super::{key, Attribute, AttributeValue, Diff, Element, Handler, Node};
+use rand::Rng;
+use std::rc::Rc;
+
+const NAMES: &[&str] = &["div", "span", "p", "ul", "li", "button"];
+const ATTRIBUTES: &[&str] = &["class", "id", "title", "key"];
+const VALUES: &[&str] = &["a", "b", "c"];
+const EVENTS: &[&str] = &["click", "input"];
+const TEXTS: &[&str] = &["", "foo", "bar", "baz"];
+
+fn pick<'a, R: Rng>(rng: &mut R, items: &[&'a str]) -> &'a str {
+    items[rng.gen_range(0, items.len())]
+}
+
+/// A random node at most `depth` elements deep.
+pub fn arbitrary_node<R: Rng>(rng: &mut R, depth: usize) -> Node {
+    match rng.gen_range(0, if depth == 0 { 4 } else { 8 }) {
+        0 => Node::Null,
+        1 => Node::Raw(format!("<b>{}</b>", pick(rng, TEXTS))),
+        2 | 3 => Node::Text(pick(rng, TEXTS).into()),
+        _ => Node::Element(arbitrary_element(rng, depth)),
+    }
+}
+
+/// A random element whose children are at most `depth` elements deep.
+pub fn arbitrary_element<R: Rng>(rng: &mut R, depth: usize) -> Element {
+    let mut attributes: Vec<Attribute> = vec![];
+    for name in ATTRIBUTES {
+        if !rng.gen_bool(0.4) {
+            continue;
+        }
+        let value = match rng.gen_range(0, 4) {
+            0 => AttributeValue::Bool(rng.gen()),
+            1 => AttributeValue::Property(pick(rng, VALUES).into()),
+            _ => AttributeValue::String(pick(rng, VALUES).to_string()),
+        };
+        attributes.push((name.to_string(), value));
+    }
+
+    let mut handlers: Vec<Handler> = vec![];
+    for kind in EVENTS {
+        if rng.gen_bool(0.3) {
+            handlers.push((kind.to_string(), pick(rng, VALUES).to_string()));
+        }
+    }
+
+    let children = if depth == 0 {
+        vec![]
+    } else {
+        let len = rng.gen_range(0, 5);
+        (0..len).map(|_| arbitrary_node(rng, depth - 1)).collect()
+    };
+
+    let key = key::take_key(&mut attributes);
+    let mut el = Element::new(pick(rng, NAMES).into(), attributes, handlers, children);
+    Rc::make_mut(&mut el.data).key = key;
+    Rc::make_mut(&mut el.data).shadow = rng.gen_bool(0.1);
+    el
+}
+
+/// The diff runtimes receive when the tree rendered from `a` becomes `b`.
+pub fn diff(a: &Node, b: &Node) -> Option<Diff> {
+    Node::diff_root(&mut a.clone(), b)
+}
+
+// What a runtime materializes: null nodes have no counterpart.
+fn rendered(node: &Node) -> Node {
+    match *node {
+        Node::Element(ref el) => {
+            let mut el = el.clone();
+            let data = Rc::make_mut(&mut el.data);
+            data.attributes.retain(|(_, value)| !value.is_absent());
+            data.children = data
+                .children
+                .iter()
+                .filter(|child| !child.is_null())
+                .map(rendered)
+                .collect();
+            Node::Element(el)
+        }
+        ref node => node.clone(),
+    }
+}
+
+fn patch(node: &mut Node, diff: &Diff) {
+    match *diff {
+        Diff::SetText(ref text) => {
+            *node = Node::Text(text.clone().into());
+            return;
+        }
+        // Shadow children are modelled as ordinary children.
+        Diff::PatchShadow(ref diffs) => {
+            for diff in diffs {
+                patch(node, diff);
+            }
+            return;
+        }
+        _ => (),
+    }
+    let el = match *node {
+        Node::Element(ref mut el) => Rc::make_mut(&mut el.data),
+        _ => panic!("{:?} applied to a non-element", diff),
+    };
+    match *diff {
+        Diff::SetAttribute(ref name, ref value) => upsert(&mut el.attributes, name, value.clone()),
+        Diff::SetProperty(ref name, ref value) => upsert(
+            &mut el.attributes,
+            name,
+            AttributeValue::Property(value.clone()),
+        ),
+        Diff::RemoveAttribute(ref name) | Diff::RemoveProperty(ref name) => {
+            el.attributes.retain(|(k, _)| k != name)
+        }
+        Diff::SetHandler(ref kind, ref id) => {
+            el.handlers.retain(|(k, _)| k != kind);
+            el.handlers.push((kind.clone(), id.clone()));
+        }
+        Diff::RemoveHandler(ref kind, _) => el.handlers.retain(|(k, _)| k != kind),
+        Diff::AddChild(i, ref child) => {
+            if child.is_null() {
+                return;
+            }
+            let child = rendered(child);
+            if i < el.children.len() {
+                el.children.insert(i, child);
+            } else {
+                el.children.push(child);
+            }
+        }
+        Diff::AddChildren(i, ref children) => {
+            let children = children.iter().filter(|child| !child.is_null()).map(rendered);
+            let i = i.min(el.children.len());
+            el.children.splice(i..i, children);
+        }
+        Diff::ReplaceChild(i, ref child) => el.children[i] = rendered(child),
+        Diff::RemoveChild(i) => {
+            el.children.remove(i);
+        }
+        Diff::RemoveChildren(i, count) => {
+            el.children.drain(i..i + count);
+        }
+        Diff::ClearChildren => el.children.clear(),
+        Diff::PatchChild(i, ref diffs) => {
+            for diff in diffs {
+                patch(&mut el.children[i], diff);
+            }
+        }
+        Diff::PatchNode(id, ref diffs) => {
+            let target = find_node(&mut el.children, id)
+                .unwrap_or_else(|| panic!("no element with id {}", id));
+            for diff in diffs {
+                patch(target, diff);
+            }
+        }
+        Diff::PatchKeyed(ref key, ref diffs) => {
+            let i = keyed_index(&el.children, key);
+            for diff in diffs {
+                patch(&mut el.children[i], diff);
+            }
+        }
+        Diff::ReplaceKeyed(ref key, ref child) => {
+            let i = keyed_index(&el.children, key);
+            el.children[i] = rendered(child);
+        }
+        Diff::RemoveKeyed(ref key) => {
+            let i = keyed_index(&el.children, key);
+            el.children.remove(i);
+        }
+        Diff::PatchPath(ref path, ref diffs) => {
+            let mut target = &mut el.children[path[0]];
+            for &i in &path[1..] {
+                target = match *target {
+                    Node::Element(ref mut el) => &mut Rc::make_mut(&mut el.data).children[i],
+                    _ => panic!("path leads through a non-element"),
+                };
+            }
+            for diff in diffs {
+                patch(target, diff);
+            }
+        }
+        _ => (),
+    }
+}
+
+fn find_node(children: &mut [Node], id: u64) -> Option<&mut Node> {
+    for child in children.iter_mut() {
+        let found = match *child {
+            Node::Element(ref el) => el.node_id() == id,
+            _ => continue,
+        };
+        if found {
+            return Some(child);
+        }
+        if let Node::Element(ref mut el) = *child {
+            if let Some(node) = find_node(&mut Rc::make_mut(&mut el.data).children, id) {
+                return Some(node);
+            }
+        }
+    }
+    None
+}
+
+fn keyed_index(children: &[Node], key: &str) -> usize {
+    children
+        .iter()
+        .position(|child| child.get_key().as_deref() == Some(key))
+        .unwrap_or_else(|| panic!("no child with key {:?}", key))
+}
+
+fn upsert(attributes: &mut Vec<Attribute>, name: &str, value: AttributeValue) {
+    match attributes.iter_mut().find(|(k, _)| k == name) {
+        Some(attribute) => attribute.1 = value,
+        None => attributes.push((name.to_string(), value)),
+    }
+}
+
+/// Applies `diff`, as returned by `diff(node, _)`, to the rendered `node`
+/// like a runtime would. Panics when the diff addresses missing nodes.
+pub fn apply(node: &Node, diff: &Diff) -> Node {
+    match *diff {
+        Diff::ReplaceRoot(ref node) => return rendered(node),
+        Diff::PatchRoot(ref diffs) => {
+            let mut node = rendered(node);
+            for diff in diffs {
+                patch(&mut node, diff);
+            }
+            return node;
+        }
+        _ => (),
+    }
+    let mut children = vec![];
+    if !node.is_null() {
+        children.push(rendered(node));
+    }
+    let mut container = Node::Element(Element::new("".into(), vec![], vec![], children));
+    patch(&mut container, diff);
+    match container {
+        Node::Element(el) => el.children().first().cloned().unwrap_or(Node::Null),
+        _ => unreachable!(),
+    }
+}
+
+fn same_set<T: PartialEq>(a: &[T], b: &[T]) -> bool {
+    a.len() == b.len() && a.iter().all(|item| b.contains(item))
+}
+
+/// Compares trees the way a document would, ignoring attribute and handler order.
+pub fn equivalent(a: &Node, b: &Node) -> bool {
+    match (a, b) {
+        (Node::Element(a), Node::Element(b)) => {
+            a.name() == b.name()
+                && a.has_shadow_root() == b.has_shadow_root()
+                && a.is_external() == b.is_external()
+                && same_set(a.attributes(), b.attributes())
+                && same_set(a.handlers(), b.handlers())
+                && a.children().len() == b.children().len()
+                && a.children()
+                    .iter()
+                    .zip(b.children())
+                    .all(|(a, b)| equivalent(a, b))
+        }
+        (Node::Text(a), Node::Text(b)) => a == b,
+        (Node::Raw(a), Node::Raw(b)) => a == b,
+        (Node::Custom(a), Node::Custom(b)) => a == b,
+        (Node::Null, Node::Null) => true,
+        _ => false,
+    }
+}
+
+/// Checks that patching the rendered `a` with `diff(a, b)` yields the rendered `b`.
+pub fn check_diff(a: &Node, b: &Node) -> Result<(), String> {
+    let patched = match diff(a, b) {
+        Some(diff) => apply(a, &diff),
+        None => rendered(a),
+    };
+    let expected = rendered(b);
+    if equivalent(&patched, &expected) {
+        return Ok(());
+    }
+    Err(format!(
+        "patching {:?}\nresulted in {:?}\ninstead of {:?}",
+        a, patched, expected
+    ))
+}
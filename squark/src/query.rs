@@ -0,0 +1,213 @@
+use super::{key, AttributeValue, Element, Node};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+enum Condition {
+    Id(String),
+    Class(String),
+    Has(String),
+    Equals(String, String),
+}
+
+struct Compound {
+    name: Option<String>,
+    conditions: Vec<Condition>,
+}
+
+struct Part {
+    combinator: Combinator,
+    compound: Compound,
+}
+
+fn is_ident(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+fn take_ident(chars: &[char], i: &mut usize) -> String {
+    let start = *i;
+    while *i < chars.len() && is_ident(chars[*i]) {
+        *i += 1;
+    }
+    chars[start..*i].iter().collect()
+}
+
+fn parse_attribute(chars: &[char], i: &mut usize) -> Option<Condition> {
+    let name = take_ident(chars, i);
+    if name.is_empty() {
+        return None;
+    }
+    match chars.get(*i) {
+        Some(']') => {
+            *i += 1;
+            return Some(Condition::Has(name));
+        }
+        Some('=') => *i += 1,
+        _ => return None,
+    }
+    let value = match chars.get(*i) {
+        Some(&quote) if quote == '"' || quote == '\'' => {
+            let start = *i + 1;
+            let end = start + chars[start..].iter().position(|&c| c == quote)?;
+            *i = end + 1;
+            chars[start..end].iter().collect()
+        }
+        _ => take_ident(chars, i),
+    };
+    if chars.get(*i) != Some(&']') {
+        return None;
+    }
+    *i += 1;
+    Some(Condition::Equals(name, value))
+}
+
+fn parse(selector: &str) -> Option<Vec<Part>> {
+    let chars: Vec<char> = selector.trim().chars().collect();
+    let mut parts = vec![];
+    let mut combinator = Combinator::Descendant;
+    let mut i = 0;
+    while i < chars.len() {
+        let mut compound = Compound {
+            name: None,
+            conditions: vec![],
+        };
+        let universal = chars[i] == '*';
+        if universal {
+            i += 1;
+        } else if is_ident(chars[i]) {
+            compound.name = Some(take_ident(&chars, &mut i).to_ascii_lowercase());
+        }
+        while i < chars.len() {
+            let condition = match chars[i] {
+                '#' => {
+                    i += 1;
+                    Condition::Id(take_ident(&chars, &mut i))
+                }
+                '.' => {
+                    i += 1;
+                    Condition::Class(take_ident(&chars, &mut i))
+                }
+                '[' => {
+                    i += 1;
+                    parse_attribute(&chars, &mut i)?
+                }
+                _ => break,
+            };
+            compound.conditions.push(condition);
+        }
+        if compound.name.is_none() && compound.conditions.is_empty() && !universal {
+            return None;
+        }
+        parts.push(Part {
+            combinator,
+            compound,
+        });
+
+        combinator = Combinator::Descendant;
+        while i < chars.len() && (chars[i].is_whitespace() || chars[i] == '>') {
+            if chars[i] == '>' {
+                combinator = Combinator::Child;
+            }
+            i += 1;
+        }
+    }
+    if parts.is_empty() {
+        return None;
+    }
+    Some(parts)
+}
+
+// The key is matched as the attribute it is written as. See `set_key_attribute`.
+fn string_attribute<'a>(el: &'a Element, name: &str) -> Option<&'a str> {
+    el.attributes()
+        .iter()
+        .find(|(k, _)| k == name)
+        .and_then(|(_, v)| match v {
+            AttributeValue::String(s) => Some(s.as_str()),
+            _ => None,
+        })
+        .or_else(|| el.key().filter(|_| key::is_key_attribute(name)))
+}
+
+impl Compound {
+    fn matches(&self, el: &Element) -> bool {
+        if let Some(ref name) = self.name {
+            if !el.name().eq_ignore_ascii_case(name) {
+                return false;
+            }
+        }
+        self.conditions.iter().all(|condition| match condition {
+            Condition::Id(id) => string_attribute(el, "id") == Some(id.as_str()),
+            Condition::Class(class) => string_attribute(el, "class")
+                .is_some_and(|classes| classes.split_whitespace().any(|c| c == class)),
+            Condition::Has(name) => {
+                el.attributes()
+                    .iter()
+                    .any(|(k, v)| k == name && !v.is_absent())
+                    || el.key().is_some() && key::is_key_attribute(name)
+            }
+            Condition::Equals(name, value) => string_attribute(el, name) == Some(value.as_str()),
+        })
+    }
+}
+
+fn matches(parts: &[Part], ancestors: &[&Element], el: &Element) -> bool {
+    let (last, rest) = match parts.split_last() {
+        Some(split) => split,
+        None => return true,
+    };
+    if !last.compound.matches(el) {
+        return false;
+    }
+    if rest.is_empty() {
+        return true;
+    }
+    match last.combinator {
+        Combinator::Child => ancestors
+            .split_last()
+            .is_some_and(|(parent, up)| matches(rest, up, parent)),
+        Combinator::Descendant => {
+            (0..ancestors.len()).any(|i| matches(rest, &ancestors[..i], ancestors[i]))
+        }
+    }
+}
+
+fn collect<'a>(
+    parts: &[Part],
+    el: &'a Element,
+    ancestors: &mut Vec<&'a Element>,
+    found: &mut Vec<&'a Element>,
+) {
+    if matches(parts, ancestors, el) {
+        found.push(el);
+    }
+    ancestors.push(el);
+    for child in el.children() {
+        if let Node::Element(child) = child {
+            collect(parts, child, ancestors, found);
+        }
+    }
+    ancestors.pop();
+}
+
+/// Every element under `node`, itself included, matching a CSS selector, in
+/// document order. Supports type, `#id`, `.class`, `[attr]` and
+/// `[attr=value]` selectors joined by descendant and `>` combinators.
+///
+/// Panics on selectors outside that subset.
+pub fn query_all<'a>(node: &'a Node, selector: &str) -> Vec<&'a Element> {
+    let parts = parse(selector).unwrap_or_else(|| panic!("unsupported selector `{}`", selector));
+    let mut found = vec![];
+    if let Node::Element(el) = node {
+        collect(&parts, el, &mut vec![], &mut found);
+    }
+    found
+}
+
+/// The first element `query_all` would return.
+pub fn query<'a>(node: &'a Node, selector: &str) -> Option<&'a Element> {
+    query_all(node, selector).into_iter().next()
+}
@@ -0,0 +1,278 @@
+//! Subtrees rebuilt only when the fields of the state they show change.
+
+use id;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use std::rc::Rc;
+#[cfg(all(debug_assertions, feature = "debug-checks"))]
+use warn;
+use {Node, View};
+
+/// Names the fields that differ between two states, for
+/// `App::changed_fields`. See `state_diff!`.
+pub trait StateDiff {
+    fn changed_fields(&self, old: &Self) -> Vec<&'static str>;
+}
+
+/// Implements `StateDiff` for a struct by comparing the listed fields, e.g.
+/// `state_diff!(State { todos, filter })`.
+#[macro_export]
+macro_rules! state_diff {
+    ($state:ty { $($field:ident),* $(,)* }) => {
+        impl $crate::StateDiff for $state {
+            fn changed_fields(&self, old: &Self) -> Vec<&'static str> {
+                let mut fields = vec![];
+                $(
+                    if self.$field != old.$field {
+                        fields.push(stringify!($field));
+                    }
+                )*
+                fields
+            }
+        }
+    };
+}
+
+struct Region {
+    node: Node,
+    handler_ids: Vec<String>,
+    nested: Vec<String>,
+    // The version of a pinned view.
+    version: Option<u64>,
+}
+
+#[derive(Default)]
+pub(crate) struct Regions {
+    cache: HashMap<String, Region>,
+    // Fields changed since the last render, all of them if `None`.
+    dirty: Option<HashSet<&'static str>>,
+    used: Vec<String>,
+    carried: Vec<String>,
+    // Whether the last build was rendered, so that the cache holds what is
+    // on screen.
+    live: bool,
+}
+
+impl Regions {
+    pub(crate) fn mark_dirty(&mut self, fields: Option<Vec<&'static str>>) {
+        match (self.dirty.as_mut(), fields) {
+            (Some(dirty), Some(fields)) => dirty.extend(fields),
+            _ => self.dirty = None,
+        }
+    }
+
+    // Called once the handlers of a render are in place, which reused
+    // regions rely on.
+    pub(crate) fn rendered(&mut self) {
+        self.dirty = Some(HashSet::new());
+        self.live = true;
+    }
+
+    // Drops the cache at the next build, once what is on screen is unknown.
+    pub(crate) fn discard(&mut self) {
+        self.live = false;
+    }
+
+    // Ids of the handlers of regions reused by the last build, to be kept
+    // from the previous render.
+    pub(crate) fn take_carried(&mut self) -> Vec<String> {
+        self.carried.drain(..).collect()
+    }
+
+    fn reuse(&mut self, key: &str, fields: &[&str]) -> Option<Node> {
+        let dirty = self.dirty.as_ref()?;
+        if fields.iter().any(|field| dirty.contains(field)) {
+            return None;
+        }
+        if self.cache.get(key)?.version.is_some() {
+            return None;
+        }
+        self.carry(key)
+    }
+
+    fn reuse_pinned(&mut self, key: &str, version: u64) -> Option<Node> {
+        if self.cache.get(key)?.version != Some(version) {
+            return None;
+        }
+        self.carry(key)
+    }
+
+    // Drops the regions holding any of the handlers, once these are renamed.
+    fn forget_handlers(&mut self, ids: &HashSet<String>) {
+        self.cache
+            .retain(|_, region| !region.handler_ids.iter().any(|id| ids.contains(id)));
+    }
+
+    fn carry(&mut self, key: &str) -> Option<Node> {
+        let region = self.cache.get(key)?;
+        self.used.push(key.to_string());
+        self.used.extend(region.nested.iter().cloned());
+        self.carried.extend(region.handler_ids.iter().cloned());
+        Some(region.node.clone())
+    }
+}
+
+pub(crate) type RegionCache = Rc<RefCell<Regions>>;
+
+thread_local! {
+    static REGIONS: RefCell<Option<RegionCache>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn with_regions<T, F>(regions: RegionCache, f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    {
+        let mut regions = regions.borrow_mut();
+        regions.used.clear();
+        regions.carried.clear();
+        if !mem::replace(&mut regions.live, false) {
+            regions.cache.clear();
+        }
+    }
+    let previous = REGIONS.with(|r| r.replace(Some(regions.clone())));
+    let result = f();
+    REGIONS.with(|r| *r.borrow_mut() = previous);
+    let mut regions = regions.borrow_mut();
+    let used: HashSet<String> = regions.used.iter().cloned().collect();
+    regions.cache.retain(|key, _| used.contains(key));
+    result
+}
+
+impl<A> View<A> {
+    /// Builds a subtree showing `fields` of the state, reusing the one of the
+    /// last render while none of them changed, as told by
+    /// `App::changed_fields`. Diffing skips reused subtrees without walking
+    /// them. `key` must identify the region within the view; it prefixes
+    /// the ids of the handlers inside, which stay attached when reused.
+    pub fn region<F>(key: &str, fields: &[&str], build: F) -> View<A>
+    where
+        F: FnOnce() -> View<A>,
+    {
+        let regions = match REGIONS.with(|r| r.borrow().clone()) {
+            Some(regions) => regions,
+            None => return build(),
+        };
+        if let Some(node) = regions.borrow_mut().reuse(key, fields) {
+            return View {
+                node,
+                handler_map: HashMap::new(),
+            };
+        }
+
+        let (used, carried) = {
+            let mut regions = regions.borrow_mut();
+            regions.used.push(key.to_string());
+            (regions.used.len(), regions.carried.len())
+        };
+        let view = id::with_prefix(key, build);
+        let mut regions = regions.borrow_mut();
+        let mut handler_ids: Vec<String> = view.handler_map.keys().cloned().collect();
+        handler_ids.extend(regions.carried[carried..].iter().cloned());
+        let region = Region {
+            node: view.node.clone(),
+            handler_ids,
+            nested: regions.used[used..].to_vec(),
+            version: None,
+        };
+        regions.cache.insert(key.to_string(), region);
+        view
+    }
+
+    /// Marks a keyed view as unchanged while `version` stays the same: the
+    /// subtree of the last render is reused and diffing compares only the
+    /// versions. A cheap alternative to `region` for views tracking their own
+    /// versions. Views without a key are diffed as usual, with a warning in
+    /// debug builds.
+    pub fn pinned(version: u64, mut view: View<A>) -> View<A> {
+        let key = match view.node.get_key() {
+            Some(key) => key,
+            None => {
+                #[cfg(all(debug_assertions, feature = "debug-checks"))]
+                warn("pinned views need a key, diffing one without as usual");
+                return view;
+            }
+        };
+        if let Node::Element(ref mut el) = view.node {
+            Rc::make_mut(&mut el.data).pin = Some(version);
+        }
+        let regions = match REGIONS.with(|r| r.borrow().clone()) {
+            Some(regions) => regions,
+            None => return view,
+        };
+        let cache_key = format!("pinned:{}", key);
+        if let Some(node) = regions.borrow_mut().reuse_pinned(&cache_key, version) {
+            return View {
+                node,
+                handler_map: HashMap::new(),
+            };
+        }
+
+        // The handlers built by this render are renamed after the version,
+        // so that later renders reusing the subtree cannot give their ids
+        // to others.
+        let prefix = format!("{}@{}", key, version);
+        let renamed: HashMap<String, String> = view
+            .handler_map
+            .keys()
+            .map(|id| (id.clone(), format!("{}/{}", prefix, id)))
+            .collect();
+        rename_handlers(&mut view.node, &renamed);
+        view.handler_map = view
+            .handler_map
+            .into_iter()
+            .map(|(id, handler)| (renamed[&id].clone(), handler))
+            .collect();
+
+        let mut regions = regions.borrow_mut();
+        regions.forget_handlers(&renamed.keys().cloned().collect());
+        let mut handler_ids = vec![];
+        collect_handler_ids(&view.node, &mut handler_ids);
+        regions.used.push(cache_key.clone());
+        let region = Region {
+            node: view.node.clone(),
+            handler_ids,
+            nested: vec![],
+            version: Some(version),
+        };
+        regions.cache.insert(cache_key, region);
+        view
+    }
+}
+
+// Clears the versions of pinned views, so that diffing walks them.
+pub(crate) fn unpin(node: Node) -> Node {
+    node.map_transform(|node| match node {
+        Node::Element(mut el) => {
+            if el.data.pin.is_some() {
+                el.data_mut().pin = None;
+            }
+            Node::Element(el)
+        }
+        node => node,
+    })
+}
+
+fn rename_handlers(node: &mut Node, renamed: &HashMap<String, String>) {
+    if let Node::Element(ref mut el) = *node {
+        let data = Rc::make_mut(&mut el.data);
+        for &mut (_, ref mut id) in data.handlers.iter_mut() {
+            if let Some(new_id) = renamed.get(id) {
+                *id = new_id.clone();
+            }
+        }
+        for child in data.children.iter_mut() {
+            rename_handlers(child, renamed);
+        }
+    }
+}
+
+fn collect_handler_ids(node: &Node, ids: &mut Vec<String>) {
+    if let Node::Element(ref el) = *node {
+        ids.extend(el.handlers().iter().map(|(_, id)| id.clone()));
+        for child in el.children() {
+            collect_handler_ids(child, ids);
+        }
+    }
+}
@@ -0,0 +1,632 @@
+//! Running an app away from the document it renders to, exchanging diffs and
+//! handler invocations with the peer holding the document as datagrams.
+
+use serde_json::Value;
+use std::cell::{Cell, RefCell};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+use {App, Diff, Env, HandlerArg, Node, Runtime, SubscriptionKind, Task};
+
+/// An unreliable, unordered link carrying text datagrams, like a WebRTC data
+/// channel with retransmits turned off. See `DatagramChannel`.
+pub trait DatagramTransport {
+    fn send(&self, datagram: &str);
+}
+
+/// Ordered, acknowledged delivery of JSON messages over a
+/// `DatagramTransport`.
+///
+/// Messages are numbered as sent. The receiving channel holds back those
+/// arriving early, delivers them in order and acknowledges the ones
+/// delivered so far with every datagram it gets, so that the sender can drop
+/// them. Lost messages are sent again by `retransmit`, which hosts call from
+/// a timer.
+pub struct DatagramChannel<T> {
+    transport: T,
+    next_seq: Cell<u64>,
+    unacked: RefCell<BTreeMap<u64, String>>,
+    expected: Cell<u64>,
+    early: RefCell<BTreeMap<u64, HandlerArg>>,
+}
+
+impl<T: DatagramTransport> DatagramChannel<T> {
+    pub fn new(transport: T) -> DatagramChannel<T> {
+        DatagramChannel {
+            transport,
+            next_seq: Cell::new(0),
+            unacked: RefCell::new(BTreeMap::new()),
+            expected: Cell::new(0),
+            early: RefCell::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    pub fn send(&self, message: HandlerArg) {
+        let seq = self.next_seq.get();
+        self.next_seq.set(seq + 1);
+        let datagram = json!({ "seq": seq, "body": message }).to_string();
+        self.transport.send(&datagram);
+        self.unacked.borrow_mut().insert(seq, datagram);
+    }
+
+    /// Handles a datagram from the peer, returning the messages it makes
+    /// deliverable in order. Malformed datagrams are ignored.
+    pub fn receive(&self, datagram: &str) -> Vec<HandlerArg> {
+        let datagram: Value = match ::serde_json::from_str(datagram) {
+            Ok(datagram) => datagram,
+            Err(_) => return vec![],
+        };
+        if let Some(ack) = datagram["ack"].as_u64() {
+            self.unacked.borrow_mut().retain(|&seq, _| seq >= ack);
+            return vec![];
+        }
+        let seq = match datagram["seq"].as_u64() {
+            Some(seq) => seq,
+            None => return vec![],
+        };
+        let mut delivered = vec![];
+        {
+            let mut early = self.early.borrow_mut();
+            if seq >= self.expected.get() {
+                early.insert(seq, datagram["body"].clone());
+            }
+            while let Some(message) = early.remove(&self.expected.get()) {
+                delivered.push(message);
+                self.expected.set(self.expected.get() + 1);
+            }
+        }
+        // Also for duplicates, whose acknowledgement may have been lost.
+        let ack = json!({ "ack": self.expected.get() }).to_string();
+        self.transport.send(&ack);
+        delivered
+    }
+
+    /// Sends every unacknowledged message again.
+    pub fn retransmit(&self) {
+        for datagram in self.unacked.borrow().values() {
+            self.transport.send(datagram);
+        }
+    }
+
+    /// Messages sent but not acknowledged yet.
+    pub fn unacknowledged(&self) -> usize {
+        self.unacked.borrow().len()
+    }
+}
+
+/// What runtimes and the peers holding their documents exchange.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    /// Diffs to apply in order, to the main mount point or to an
+    /// additional root, numbered from 0 so that peers notice missed ones.
+    /// See `PatchSequence`.
+    Patch(u64, Option<String>, Vec<Diff>),
+    /// Invokes the handler with this id.
+    Event(String, HandlerArg),
+    /// Asks the runtime for a `FullTree`.
+    RequestFullTree,
+    /// The main tree and those of the additional roots, to replace the
+    /// peer's, and the number of the patch following them.
+    FullTree(u64, Node, Vec<(String, Node)>),
+}
+
+impl Message {
+    pub fn to_json(&self) -> HandlerArg {
+        match *self {
+            Message::Patch(seq, ref root, ref diffs) => {
+                let diffs: Vec<Value> = diffs.iter().map(Diff::to_json).collect();
+                json!({ "patch": diffs, "root": root, "seq": seq })
+            }
+            Message::Event(ref id, ref arg) => json!({ "event": id, "arg": arg }),
+            Message::RequestFullTree => json!({ "request": "tree" }),
+            Message::FullTree(seq, ref node, ref roots) => {
+                let roots: Vec<Value> = roots
+                    .iter()
+                    .map(|(id, node)| json!([id, node.to_json()]))
+                    .collect();
+                json!({ "tree": node.to_json(), "roots": roots, "seq": seq })
+            }
+        }
+    }
+
+    pub fn from_json(value: &HandlerArg) -> Option<Message> {
+        if let Some(diffs) = value.get("patch") {
+            let root = value["root"].as_str().map(|root| root.to_string());
+            let diffs = diffs
+                .as_array()?
+                .iter()
+                .map(Diff::from_json)
+                .collect::<Option<_>>()?;
+            return Some(Message::Patch(value.get("seq")?.as_u64()?, root, diffs));
+        }
+        if let Some(node) = value.get("tree") {
+            let roots = value
+                .get("roots")?
+                .as_array()?
+                .iter()
+                .map(|pair| Some((pair[0].as_str()?.to_string(), Node::from_json(&pair[1])?)))
+                .collect::<Option<_>>()?;
+            let seq = value.get("seq")?.as_u64()?;
+            return Some(Message::FullTree(seq, Node::from_json(node)?, roots));
+        }
+        if value["request"] == "tree" {
+            return Some(Message::RequestFullTree);
+        }
+        let id = value.get("event")?.as_str()?;
+        Some(Message::Event(id.to_string(), value["arg"].clone()))
+    }
+}
+
+/// What a peer should do with a message from the runtime, see
+/// `PatchSequence`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Received {
+    /// Apply the diffs as they are.
+    Apply(Option<String>, Vec<Diff>),
+    /// Replace the main tree and the additional roots.
+    Replace(Node, Vec<(String, Node)>),
+    /// Patches were missed, send `Message::RequestFullTree`.
+    Resync,
+    /// Drop the message.
+    Ignore,
+}
+
+/// Checks the numbers of the patches a peer receives. After a gap, patches
+/// are ignored until the `Message::FullTree` asked for arrives.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PatchSequence {
+    next: u64,
+    resyncing: bool,
+}
+
+impl PatchSequence {
+    pub fn new() -> PatchSequence {
+        PatchSequence::default()
+    }
+
+    pub fn is_resyncing(&self) -> bool {
+        self.resyncing
+    }
+
+    pub fn receive(&mut self, message: Message) -> Received {
+        match message {
+            Message::Patch(seq, root, diffs) => {
+                if self.resyncing || seq < self.next {
+                    Received::Ignore
+                } else if seq > self.next {
+                    self.resyncing = true;
+                    Received::Resync
+                } else {
+                    self.next += 1;
+                    Received::Apply(root, diffs)
+                }
+            }
+            Message::FullTree(seq, node, roots) => {
+                if seq < self.next && !self.resyncing {
+                    return Received::Ignore;
+                }
+                self.next = seq;
+                self.resyncing = false;
+                Received::Replace(node, roots)
+            }
+            _ => Received::Ignore,
+        }
+    }
+}
+
+thread_local! {
+    static CLIENT: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// The id of the client whose event is being handled, inside handlers run
+/// by a `RemoteRuntime`.
+pub fn client() -> Option<u64> {
+    CLIENT.with(Cell::get)
+}
+
+// A connected peer, with its own numbering and compression of patches.
+struct Client<T> {
+    channel: DatagramChannel<T>,
+    seq: Cell<u64>,
+    compressor: Option<RefCell<PatchCompressor>>,
+}
+
+impl<T: DatagramTransport> Client<T> {
+    fn send_full_tree(&self, node: Node, roots: Vec<(String, Node)>) {
+        if let Some(ref compressor) = self.compressor {
+            *compressor.borrow_mut() = PatchCompressor::new();
+        }
+        self.channel
+            .send(Message::FullTree(self.seq.get(), node, roots).to_json());
+    }
+
+    fn send_patch(&self, root: Option<String>, diff: Diff) {
+        let seq = self.seq.get();
+        self.seq.set(seq + 1);
+        let message = Message::Patch(seq, root, vec![diff]).to_json();
+        match self.compressor {
+            Some(ref compressor) => self.channel.send(compressor.borrow_mut().compress(message)),
+            None => self.channel.send(message),
+        }
+    }
+}
+
+/// Renders synchronously on every state change and sends the diffs as
+/// `Message::Patch` to each connected client over its own
+/// `DatagramChannel`, invoking handlers for the `Message::Event`s coming
+/// back. Handlers can tell which client an event came from with `client`.
+///
+/// Clients get a `Message::FullTree` when they connect and whenever they
+/// send `Message::RequestFullTree`, which also starts compression over when
+/// it is on.
+///
+/// Handlers stay in the env, so clients only need their ids.
+/// Subscriptions are not forwarded, and spawned tasks are kept for the
+/// host's executor, see `take_tasks`.
+pub struct RemoteRuntime<A: App, T> {
+    env: Env<A>,
+    clients: Rc<RefCell<BTreeMap<u64, Rc<Client<T>>>>>,
+    next_client: Rc<Cell<u64>>,
+    compress: bool,
+    tasks: Rc<RefCell<Vec<Task>>>,
+    observer: Rc<RefCell<Option<Observer<A::State>>>>,
+}
+
+// Told the state after each render scheduled by a dispatch.
+type Observer<S> = Rc<dyn Fn(S)>;
+
+impl<A: App, T> Clone for RemoteRuntime<A, T> {
+    fn clone(&self) -> RemoteRuntime<A, T> {
+        RemoteRuntime {
+            env: self.env.clone(),
+            clients: self.clients.clone(),
+            next_client: self.next_client.clone(),
+            compress: self.compress,
+            tasks: self.tasks.clone(),
+            observer: self.observer.clone(),
+        }
+    }
+}
+
+impl<A: App, T: DatagramTransport + 'static> RemoteRuntime<A, T> {
+    /// Creates the runtime for a single client, 0, and sends it the initial
+    /// render as patches.
+    pub fn new(state: A::State, transport: T) -> RemoteRuntime<A, T> {
+        RemoteRuntime::start(state, Some(transport), false)
+    }
+
+    /// Like `new`, but patches go through a `PatchCompressor`, so the client
+    /// reads them with a `PatchDecompressor`.
+    pub fn compressed(state: A::State, transport: T) -> RemoteRuntime<A, T> {
+        RemoteRuntime::start(state, Some(transport), true)
+    }
+
+    /// Creates the runtime without clients, see `connect`. With `compress`,
+    /// patches go through a `PatchCompressor` for each client.
+    pub fn serve(state: A::State, compress: bool) -> RemoteRuntime<A, T> {
+        RemoteRuntime::start(state, None, compress)
+    }
+
+    fn start(state: A::State, transport: Option<T>, compress: bool) -> RemoteRuntime<A, T> {
+        let runtime = RemoteRuntime {
+            env: Env::new(state),
+            clients: Rc::new(RefCell::new(BTreeMap::new())),
+            next_client: Rc::new(Cell::new(0)),
+            compress,
+            tasks: Rc::new(RefCell::new(vec![])),
+            observer: Rc::new(RefCell::new(None)),
+        };
+        if let Some(transport) = transport {
+            runtime.add_client(transport);
+        }
+        runtime.mount();
+        runtime
+    }
+
+    fn add_client(&self, transport: T) -> (u64, Rc<Client<T>>) {
+        let id = self.next_client.get();
+        self.next_client.set(id + 1);
+        let client = Rc::new(Client {
+            channel: DatagramChannel::new(transport),
+            seq: Cell::new(0),
+            compressor: if self.compress {
+                Some(RefCell::new(PatchCompressor::new()))
+            } else {
+                None
+            },
+        });
+        self.clients.borrow_mut().insert(id, client.clone());
+        (id, client)
+    }
+
+    /// Adds a client, sends it the current trees and returns its id.
+    pub fn connect(&self, transport: T) -> u64 {
+        let (id, client) = self.add_client(transport);
+        client.send_full_tree(self.env.get_node(), self.env.root_nodes());
+        id
+    }
+
+    /// Stops sending to the client, returning whether it was connected.
+    pub fn disconnect(&self, client: u64) -> bool {
+        self.clients.borrow_mut().remove(&client).is_some()
+    }
+
+    /// Ids of the connected clients.
+    pub fn clients(&self) -> Vec<u64> {
+        self.clients.borrow().keys().cloned().collect()
+    }
+
+    pub fn state(&self) -> A::State {
+        self.env.get_state()
+    }
+
+    pub fn node(&self) -> Node {
+        self.env.get_node()
+    }
+
+    /// Resends what the clients have not acknowledged, see
+    /// `DatagramChannel::retransmit`.
+    pub fn retransmit(&self) {
+        for client in self.clients.borrow().values() {
+            client.channel.retransmit();
+        }
+    }
+
+    /// Messages sent to the client but not acknowledged yet.
+    pub fn unacknowledged(&self, client: u64) -> usize {
+        self.clients
+            .borrow()
+            .get(&client)
+            .map_or(0, |c| c.channel.unacknowledged())
+    }
+
+    /// Handles a datagram from the client.
+    pub fn receive(&self, client: u64, datagram: &str) {
+        let sender = match self.clients.borrow().get(&client) {
+            Some(sender) => sender.clone(),
+            None => return,
+        };
+        for message in sender.channel.receive(datagram) {
+            match Message::from_json(&message) {
+                Some(Message::Event(id, arg)) => {
+                    let previous = CLIENT.with(|c| c.replace(Some(client)));
+                    let actions = self.env.call_handler(&id, arg);
+                    CLIENT.with(|c| c.set(previous));
+                    self.dispatch_all(actions);
+                }
+                Some(Message::RequestFullTree) => {
+                    sender.send_full_tree(self.env.get_node(), self.env.root_nodes())
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Tasks spawned since the last call.
+    pub fn take_tasks(&self) -> Vec<Task> {
+        self.tasks.borrow_mut().drain(..).collect()
+    }
+
+    pub(crate) fn observe<F: Fn(A::State) + 'static>(&self, f: F) {
+        *self.observer.borrow_mut() = Some(Rc::new(f));
+    }
+
+    // Renders a state set from outside, without telling the observer.
+    pub(crate) fn replace_state(&self, state: A::State) {
+        self.env.set_state(state);
+        self.run();
+    }
+
+    fn send_patch(&self, root: Option<String>, diff: Diff) {
+        for client in self.clients.borrow().values() {
+            client.send_patch(root.clone(), diff.clone());
+        }
+    }
+}
+
+impl<A: App, T: DatagramTransport + 'static> Runtime<A> for RemoteRuntime<A, T> {
+    fn get_env(&self) -> &Env<A> {
+        &self.env
+    }
+
+    fn handle_diff(&self, diff: Diff) {
+        self.send_patch(None, diff);
+    }
+
+    fn handle_root_diff(&self, root: &str, diff: Diff) {
+        self.send_patch(Some(root.to_string()), diff);
+    }
+
+    fn schedule_render(&self) {
+        self.run();
+        let observer = self.observer.borrow().clone();
+        if let Some(observer) = observer {
+            observer(self.env.get_state());
+        }
+    }
+
+    fn subscribe(&self, _id: &str, _kind: &SubscriptionKind) {}
+
+    fn unsubscribe(&self, _id: &str) {}
+
+    fn spawn(&self, task: Task) {
+        self.tasks.borrow_mut().push(task);
+    }
+}
+
+// Visits the name and path positions of a message in the wire format in
+// the same order on both ends, see `PatchCompressor`.
+trait Codec {
+    /// Encodes or decodes the name in place, returning it in plain form.
+    fn name(&mut self, value: &mut Value) -> Option<String>;
+
+    fn path(&mut self, value: &mut Value) -> Option<()>;
+
+    fn message(&mut self, message: &mut Value) -> Option<()> {
+        match message.get_mut("patch") {
+            Some(diffs) => self.diffs(diffs),
+            None => Some(()),
+        }
+    }
+
+    fn diffs(&mut self, diffs: &mut Value) -> Option<()> {
+        for diff in diffs.as_array_mut()? {
+            self.diff(diff)?;
+        }
+        Some(())
+    }
+
+    fn diff(&mut self, diff: &mut Value) -> Option<()> {
+        let diff = diff.as_array_mut()?;
+        match &*self.name(diff.get_mut(0)?)? {
+            "SetAttribute" | "RemoveAttribute" | "SetProperty" | "RemoveProperty"
+            | "SetHandler" | "RemoveHandler" => self.name(diff.get_mut(1)?).map(drop),
+            "AddChild" | "ReplaceChild" | "ReplaceKeyed" => self.node(diff.get_mut(2)?),
+            "AddChildren" => {
+                for node in diff.get_mut(2)?.as_array_mut()? {
+                    self.node(node)?;
+                }
+                Some(())
+            }
+            "PatchChild" | "PatchNode" | "PatchKeyed" => self.diffs(diff.get_mut(2)?),
+            "ReplaceRoot" => self.node(diff.get_mut(1)?),
+            "PatchShadow" | "PatchRoot" => self.diffs(diff.get_mut(1)?),
+            "PatchPath" => {
+                self.path(diff.get_mut(1)?)?;
+                self.diffs(diff.get_mut(2)?)
+            }
+            _ => Some(()),
+        }
+    }
+
+    fn node(&mut self, node: &mut Value) -> Option<()> {
+        let node = match node.as_object_mut() {
+            Some(node) if node.contains_key("tag") => node,
+            _ => return Some(()),
+        };
+        self.name(node.get_mut("tag")?)?;
+        if let Some(name) = node.get_mut("custom") {
+            return self.name(name).map(drop);
+        }
+        for key in &["attributes", "handlers"] {
+            for pair in node.get_mut(*key)?.as_array_mut()? {
+                self.name(pair.get_mut(0)?)?;
+            }
+        }
+        for child in node.get_mut("children")?.as_array_mut()? {
+            self.node(child)?;
+        }
+        Some(())
+    }
+}
+
+fn path_of(value: &Value) -> Option<Vec<u64>> {
+    value.as_array()?.iter().map(Value::as_u64).collect()
+}
+
+/// Shrinks `Message::Patch`es before they are sent. Names of tags,
+/// attributes, handlers and diffs are sent once and then referred to by
+/// number, and paths are sent as the length of the prefix they share with
+/// the previous path followed by the rest.
+///
+/// Both ends keep state, so messages must reach the `PatchDecompressor` in
+/// order and without loss, as over a `DatagramChannel`. Both start over
+/// with a `Message::FullTree`, which itself is sent as is.
+#[derive(Default)]
+pub struct PatchCompressor {
+    names: HashMap<String, u64>,
+    last_path: Vec<u64>,
+}
+
+impl PatchCompressor {
+    pub fn new() -> PatchCompressor {
+        PatchCompressor::default()
+    }
+
+    /// Compresses a message in the form of `Message::to_json`.
+    pub fn compress(&mut self, mut message: HandlerArg) -> HandlerArg {
+        self.message(&mut message)
+            .expect("compressing a malformed message");
+        message
+    }
+}
+
+impl Codec for PatchCompressor {
+    fn name(&mut self, value: &mut Value) -> Option<String> {
+        let name = value.as_str()?.to_string();
+        let next = self.names.len() as u64;
+        match self.names.entry(name.clone()) {
+            Entry::Occupied(entry) => *value = Value::from(*entry.get()),
+            Entry::Vacant(entry) => {
+                entry.insert(next);
+            }
+        }
+        Some(name)
+    }
+
+    fn path(&mut self, value: &mut Value) -> Option<()> {
+        let path = path_of(value)?;
+        let shared = path
+            .iter()
+            .zip(&self.last_path)
+            .take_while(|&(a, b)| a == b)
+            .count();
+        let mut delta = vec![shared as u64];
+        delta.extend(&path[shared..]);
+        *value = Value::from(delta);
+        self.last_path = path;
+        Some(())
+    }
+}
+
+/// Restores messages shrunk by a `PatchCompressor`.
+#[derive(Default)]
+pub struct PatchDecompressor {
+    names: Vec<String>,
+    last_path: Vec<u64>,
+}
+
+impl PatchDecompressor {
+    pub fn new() -> PatchDecompressor {
+        PatchDecompressor::default()
+    }
+
+    /// The message in the form of `Message::to_json`, `None` if it is
+    /// malformed.
+    pub fn decompress(&mut self, mut message: HandlerArg) -> Option<HandlerArg> {
+        if message.get("tree").is_some() {
+            *self = PatchDecompressor::new();
+        }
+        self.message(&mut message)?;
+        Some(message)
+    }
+}
+
+impl Codec for PatchDecompressor {
+    fn name(&mut self, value: &mut Value) -> Option<String> {
+        let name = match value.as_u64() {
+            Some(i) => self.names.get(i as usize)?.clone(),
+            None => {
+                let name = value.as_str()?.to_string();
+                self.names.push(name.clone());
+                name
+            }
+        };
+        *value = Value::from(name.as_str());
+        Some(name)
+    }
+
+    fn path(&mut self, value: &mut Value) -> Option<()> {
+        let delta = path_of(value)?;
+        let shared = *delta.first()? as usize;
+        let mut path = self.last_path.get(..shared)?.to_vec();
+        path.extend(&delta[1..]);
+        *value = Value::from(path.clone());
+        self.last_path = path;
+        Some(())
+    }
+}
@@ -0,0 +1,330 @@
+use cmd::Cmd;
+use std::fmt::Write;
+use View;
+
+/// Attribute tagging a scrollable container. When its value changes the diff
+/// saves the scroll offset under the old value and restores the new one, and
+/// runtimes restore it for newly created containers.
+pub const SCROLL_KEY: &str = "data-scroll-key";
+
+/// How `Cmd::scroll_into_view` scrolls.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ScrollBehavior {
+    /// Jumps straight to the element.
+    Auto,
+    Smooth,
+}
+
+/// Scroll key of a container which is remembered separately for each route path.
+pub fn scroll_key(container: &str, location: &Location) -> String {
+    format!("{}@{}", container, location.path())
+}
+
+/// Parsed form of a URL as reported by the runtime's history.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Location {
+    path: String,
+    query: Vec<(String, String)>,
+    fragment: Option<String>,
+}
+
+/// Application route which can be parsed from and serialized to a `Location`.
+pub trait Route: Sized {
+    fn from_location(location: &Location) -> Option<Self>;
+
+    fn to_url(&self) -> String;
+}
+
+fn strip_origin(url: &str) -> &str {
+    match url.find("://") {
+        Some(i) => {
+            let rest = &url[i + 3..];
+            match rest.find(&['/', '?', '#'][..]) {
+                Some(j) => &rest[j..],
+                None => "",
+            }
+        }
+        None => url,
+    }
+}
+
+fn from_hex(b: u8) -> Option<u8> {
+    match b {
+        b'0'..=b'9' => Some(b - b'0'),
+        b'a'..=b'f' => Some(b - b'a' + 10),
+        b'A'..=b'F' => Some(b - b'A' + 10),
+        _ => None,
+    }
+}
+
+pub fn decode_component(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                match (from_hex(bytes[i + 1]), from_hex(bytes[i + 2])) {
+                    (Some(h), Some(l)) => {
+                        out.push(h * 16 + l);
+                        i += 2;
+                    }
+                    _ => out.push(b'%'),
+                }
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+pub fn encode_component(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(b as char)
+            }
+            b => {
+                let _ = write!(out, "%{:02X}", b);
+            }
+        }
+    }
+    out
+}
+
+impl Location {
+    pub fn parse(url: &str) -> Location {
+        let url = strip_origin(url);
+        let (rest, fragment) = match url.find('#') {
+            Some(i) => (&url[..i], Some(decode_component(&url[i + 1..]))),
+            None => (url, None),
+        };
+        let (path, query) = match rest.find('?') {
+            Some(i) => (&rest[..i], &rest[i + 1..]),
+            None => (rest, ""),
+        };
+        let query = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| match pair.find('=') {
+                Some(i) => (decode_component(&pair[..i]), decode_component(&pair[i + 1..])),
+                None => (decode_component(pair), String::new()),
+            })
+            .collect();
+
+        Location {
+            path: if path.is_empty() { "/" } else { path }.to_string(),
+            query,
+            fragment,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn segments(&self) -> Vec<&str> {
+        self.path.split('/').filter(|s| !s.is_empty()).collect()
+    }
+
+    pub fn query(&self) -> &[(String, String)] {
+        &self.query
+    }
+
+    pub fn query_value(&self, key: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn fragment(&self) -> Option<&str> {
+        self.fragment.as_deref()
+    }
+
+    pub fn to_url(&self) -> String {
+        let mut url = self.path.clone();
+        for (i, (k, v)) in self.query.iter().enumerate() {
+            url.push(if i == 0 { '?' } else { '&' });
+            url.push_str(&encode_component(k));
+            url.push('=');
+            url.push_str(&encode_component(v));
+        }
+        if let Some(ref fragment) = self.fragment {
+            url.push('#');
+            url.push_str(&encode_component(fragment));
+        }
+        url
+    }
+}
+
+/// Route whose view needs something loaded first, such as a code chunk or
+/// the data it shows. See `Routing`.
+pub trait RouteLoader: Route {
+    type Data;
+
+    /// Loads what the view needs, failing with a message to show instead.
+    /// `None` for routes which render at once.
+    fn loader(&self) -> Option<Cmd<Result<Self::Data, String>>>;
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RouteStatus<D> {
+    Loading,
+    /// Loaded, with the data if the route has a loader.
+    Ready(Option<D>),
+    Failed(String),
+}
+
+/// The current route and the state of its loader, kept in the state.
+///
+/// Navigating from the reducer switches the route, and `load` in
+/// `App::command` for the same action starts its loader. Results of
+/// loaders for routes navigated away from meanwhile are ignored.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Routing<R: RouteLoader> {
+    route: R,
+    status: RouteStatus<R::Data>,
+    generation: u64,
+}
+
+impl<R: RouteLoader> Routing<R> {
+    /// Starts at `route`, which is loaded by the first `load`.
+    pub fn new(route: R) -> Routing<R> {
+        let mut routing = Routing {
+            route,
+            status: RouteStatus::Loading,
+            generation: 0,
+        };
+        routing.reset();
+        routing
+    }
+
+    pub fn route(&self) -> &R {
+        &self.route
+    }
+
+    pub fn status(&self) -> &RouteStatus<R::Data> {
+        &self.status
+    }
+
+    pub fn navigate(&mut self, route: R) {
+        self.route = route;
+        self.generation += 1;
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        self.status = match self.route.loader() {
+            Some(_) => RouteStatus::Loading,
+            None => RouteStatus::Ready(None),
+        };
+    }
+
+    /// The current route's loader, its result mapped by `f` to an action
+    /// handing it to `receive` along with the generation.
+    pub fn load<A, F>(&self, f: F) -> Cmd<A>
+    where
+        A: 'static,
+        R::Data: 'static,
+        F: Fn(u64, Result<R::Data, String>) -> A + 'static,
+    {
+        if !matches!(self.status, RouteStatus::Loading) {
+            return Cmd::none();
+        }
+        let generation = self.generation;
+        match self.route.loader() {
+            Some(loader) => loader.map(move |result| f(generation, result)),
+            None => Cmd::none(),
+        }
+    }
+
+    pub fn receive(&mut self, generation: u64, result: Result<R::Data, String>) {
+        if generation != self.generation || !matches!(self.status, RouteStatus::Loading) {
+            return;
+        }
+        self.status = match result {
+            Ok(data) => RouteStatus::Ready(Some(data)),
+            Err(message) => RouteStatus::Failed(message),
+        };
+    }
+
+    /// `loading` until the route has loaded, `failed` with the message if
+    /// its loader failed, otherwise the view built by `ready`.
+    pub fn view<A, E, F>(&self, loading: View<A>, failed: E, ready: F) -> View<A>
+    where
+        E: FnOnce(&str) -> View<A>,
+        F: FnOnce(&R, Option<&R::Data>) -> View<A>,
+    {
+        match self.status {
+            RouteStatus::Loading => loading,
+            RouteStatus::Failed(ref message) => failed(message),
+            RouteStatus::Ready(ref data) => ready(&self.route, data.as_ref()),
+        }
+    }
+}
+
+/// Part of the state kept in the query string, like a search term and the
+/// filters of a list.
+pub trait QueryParams: Sized {
+    /// Reads the parameters, defaulting missing or malformed ones.
+    fn from_query(location: &Location) -> Self;
+
+    /// The parameters to write, leaving out those at their default.
+    fn to_query(&self) -> Vec<(String, String)>;
+}
+
+/// A `QueryParams` value kept in the state along with the location it is
+/// read from, so that the two stay in sync both ways.
+///
+/// Navigating parses the value from the new location, while `set` writes it
+/// to the location's query, keeping unrelated parameters; `replace_url` in
+/// `App::command` for the same action then updates the URL without adding a
+/// history entry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuerySync<T> {
+    location: Location,
+    value: T,
+}
+
+impl<T: QueryParams> QuerySync<T> {
+    pub fn new(location: Location) -> QuerySync<T> {
+        QuerySync {
+            value: T::from_query(&location),
+            location,
+        }
+    }
+
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+
+    pub fn location(&self) -> &Location {
+        &self.location
+    }
+
+    pub fn navigate(&mut self, location: Location) {
+        *self = QuerySync::new(location);
+    }
+
+    pub fn set(&mut self, value: T) {
+        let new = value.to_query();
+        let keys: Vec<String> = self
+            .value
+            .to_query()
+            .into_iter()
+            .chain(new.clone())
+            .map(|(k, _)| k)
+            .collect();
+        self.location.query.retain(|(k, _)| !keys.contains(k));
+        self.location.query.extend(new);
+        self.value = value;
+    }
+
+    pub fn replace_url<A: 'static>(&self) -> Cmd<A> {
+        Cmd::replace_url(self.location.to_url())
+    }
+}
@@ -0,0 +1,219 @@
+/// Cleans untrusted markup before it is stored in a `Node::Raw`.
+pub trait Sanitizer {
+    fn sanitize(&self, html: &str) -> String;
+}
+
+impl<F> Sanitizer for F
+where
+    F: Fn(&str) -> String,
+{
+    fn sanitize(&self, html: &str) -> String {
+        self(html)
+    }
+}
+
+#[cfg(feature = "sanitize")]
+pub use self::allowlist::AllowlistSanitizer;
+
+#[cfg(feature = "sanitize")]
+mod allowlist {
+    use super::*;
+    use std::collections::HashSet;
+    use url;
+
+    // Elements which are dropped together with their contents.
+    const DROP_WITH_CONTENT: &[&str] = &[
+        "script", "style", "iframe", "object", "embed", "template", "noscript", "textarea",
+        "title",
+    ];
+
+    const VOID: &[&str] = &["br", "hr", "img", "wbr"];
+
+    /// Keeps only allowlisted tags and attributes. Everything else is stripped
+    /// while its text content is kept.
+    pub struct AllowlistSanitizer {
+        tags: HashSet<String>,
+        attributes: HashSet<String>,
+        url_schemes: HashSet<String>,
+    }
+
+    fn to_set(items: &[&str]) -> HashSet<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    impl Default for AllowlistSanitizer {
+        fn default() -> AllowlistSanitizer {
+            AllowlistSanitizer {
+                tags: to_set(&[
+                    "a", "abbr", "b", "blockquote", "br", "code", "dd", "del", "dl", "dt", "em",
+                    "h1", "h2", "h3", "h4", "h5", "h6", "hr", "i", "img", "ins", "kbd", "li", "ol",
+                    "p", "pre", "q", "s", "small", "span", "strong", "sub", "sup", "table",
+                    "tbody", "td", "tfoot", "th", "thead", "tr", "u", "ul",
+                ]),
+                attributes: to_set(&["alt", "class", "href", "src", "title"]),
+                url_schemes: to_set(&["http", "https", "mailto"]),
+            }
+        }
+    }
+
+    fn escape_attribute(value: &str) -> String {
+        value
+            .replace('&', "&amp;")
+            .replace('"', "&quot;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    }
+
+    fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+        haystack
+            .to_ascii_lowercase()
+            .find(&needle.to_ascii_lowercase())
+    }
+
+    // Index just after the `>` closing the tag starting at `start`, honoring quotes.
+    fn tag_end(html: &str, start: usize) -> Option<usize> {
+        let mut quote = None;
+        for (i, c) in html[start..].char_indices() {
+            match (quote, c) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => (),
+                (None, '"') | (None, '\'') => quote = Some(c),
+                (None, '>') => return Some(start + i + 1),
+                _ => (),
+            }
+        }
+        None
+    }
+
+    fn parse_attributes(s: &str) -> Vec<(String, String)> {
+        let mut result = vec![];
+        let mut rest = s.trim_start();
+        while !rest.is_empty() {
+            let name_end = rest
+                .find(|c: char| c.is_whitespace() || c == '=' || c == '/')
+                .unwrap_or(rest.len());
+            let name = rest[..name_end].to_ascii_lowercase();
+            rest = rest[name_end..].trim_start();
+
+            let mut value = String::new();
+            if rest.starts_with('=') {
+                rest = rest[1..].trim_start();
+                let quote = rest.chars().next().filter(|&c| c == '"' || c == '\'');
+                match quote {
+                    Some(q) => {
+                        let end = rest[1..].find(q).map_or(rest.len(), |i| i + 1);
+                        value = rest[1..end].to_string();
+                        rest = if end < rest.len() { &rest[end + 1..] } else { "" };
+                    }
+                    None => {
+                        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                        value = rest[..end].to_string();
+                        rest = &rest[end..];
+                    }
+                }
+            } else if rest.starts_with('/') {
+                rest = &rest[1..];
+            }
+
+            if !name.is_empty() {
+                result.push((name, url::decode_references(&value)));
+            }
+            rest = rest.trim_start();
+        }
+        result
+    }
+
+    impl AllowlistSanitizer {
+        pub fn new() -> AllowlistSanitizer {
+            AllowlistSanitizer::default()
+        }
+
+        pub fn allow_tag(mut self, tag: &str) -> AllowlistSanitizer {
+            self.tags.insert(tag.to_ascii_lowercase());
+            self
+        }
+
+        pub fn allow_attribute(mut self, name: &str) -> AllowlistSanitizer {
+            self.attributes.insert(name.to_ascii_lowercase());
+            self
+        }
+
+        pub fn allow_url_scheme(mut self, scheme: &str) -> AllowlistSanitizer {
+            self.url_schemes.insert(scheme.to_ascii_lowercase());
+            self
+        }
+
+        fn is_safe_url(&self, url: &str) -> bool {
+            url::scheme(url).map_or(true, |scheme| self.url_schemes.contains(&scheme))
+        }
+
+        fn write_open_tag(&self, out: &mut String, name: &str, attributes: &str) {
+            out.push('<');
+            out.push_str(name);
+            for (key, value) in parse_attributes(attributes) {
+                if !self.attributes.contains(&key) {
+                    continue;
+                }
+                if (key == "href" || key == "src") && !self.is_safe_url(&value) {
+                    continue;
+                }
+                out.push_str(&format!(" {}=\"{}\"", key, escape_attribute(&value)));
+            }
+            out.push('>');
+        }
+    }
+
+    impl Sanitizer for AllowlistSanitizer {
+        fn sanitize(&self, html: &str) -> String {
+            let mut out = String::with_capacity(html.len());
+            let mut i = 0;
+            while let Some(offset) = html[i..].find('<') {
+                let lt = i + offset;
+                out.push_str(&html[i..lt]);
+
+                if html[lt..].starts_with("<!--") {
+                    i = html[lt..].find("-->").map_or(html.len(), |j| lt + j + 3);
+                    continue;
+                }
+
+                let closing = html[lt + 1..].starts_with('/');
+                let name_start = if closing { lt + 2 } else { lt + 1 };
+                let name_len = html[name_start..]
+                    .find(|c: char| !c.is_ascii_alphanumeric())
+                    .unwrap_or(html.len() - name_start);
+                let end = match tag_end(html, lt) {
+                    Some(end) if name_len > 0 => end,
+                    _ => {
+                        out.push_str("&lt;");
+                        i = lt + 1;
+                        continue;
+                    }
+                };
+                let name = html[name_start..name_start + name_len].to_ascii_lowercase();
+
+                if !closing && DROP_WITH_CONTENT.contains(&name.as_str()) {
+                    let close = format!("</{}", name);
+                    i = match find_ignore_case(&html[end..], &close) {
+                        Some(j) => tag_end(html, end + j).unwrap_or(html.len()),
+                        None => html.len(),
+                    };
+                    continue;
+                }
+
+                if self.tags.contains(&name) {
+                    if closing {
+                        if !VOID.contains(&name.as_str()) {
+                            out.push_str(&format!("</{}>", name));
+                        }
+                    } else {
+                        let attributes = html[name_start + name_len..end - 1].trim_end_matches('/');
+                        self.write_open_tag(&mut out, &name, attributes);
+                    }
+                }
+                i = end;
+            }
+            out.push_str(&html[i..]);
+            out
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! `<select>` elements bound to a value.
+
+use {handler, keyed_list, Attribute, Child, HandlerArg, View};
+
+/// The selected values of a `change` event on a select, which runtimes
+/// report as a string or, for multiple selects, an array of strings.
+pub fn selected_values(arg: &HandlerArg) -> Vec<String> {
+    match *arg {
+        HandlerArg::String(ref value) => vec![value.clone()],
+        HandlerArg::Array(ref values) => values
+            .iter()
+            .filter_map(|value| value.as_str())
+            .map(|value| value.to_string())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn option_views<A, I, V, L, F>(options: I, is_selected: F) -> Child<A>
+where
+    I: IntoIterator<Item = (V, L)>,
+    V: ToString,
+    L: Into<String>,
+    F: Fn(&str) -> bool,
+{
+    keyed_list(options, |(value, label)| {
+        let value = value.to_string();
+        let attributes = vec![
+            ("value".to_string(), value.clone().into()),
+            ("selected".to_string(), is_selected(&value).into()),
+        ];
+        let option = View::new(
+            "option",
+            attributes,
+            vec![],
+            vec![View::text(label.into()).into()],
+        );
+        (value, option)
+    })
+}
+
+/// A select of `(value, label)` pairs with `selected` chosen, calling `f`
+/// with the value chosen instead.
+pub fn select<A, I, V, L, F>(
+    attributes: Vec<Attribute>,
+    options: I,
+    selected: &str,
+    f: F,
+) -> View<A>
+where
+    I: IntoIterator<Item = (V, L)>,
+    V: ToString,
+    L: Into<String>,
+    F: Fn(String) -> A + 'static,
+{
+    let on_change = handler(move |arg| selected_values(&arg).into_iter().next().map(&f));
+    View::new(
+        "select",
+        attributes,
+        vec![("change".to_string(), on_change)],
+        vec![option_views(options, |value| value == selected)],
+    )
+}
+
+/// Like `select`, allowing several values to be chosen.
+pub fn multi_select<A, I, V, L, F>(
+    attributes: Vec<Attribute>,
+    options: I,
+    selected: &[String],
+    f: F,
+) -> View<A>
+where
+    I: IntoIterator<Item = (V, L)>,
+    V: ToString,
+    L: Into<String>,
+    F: Fn(Vec<String>) -> A + 'static,
+{
+    let mut attributes = attributes;
+    attributes.push(("multiple".to_string(), true.into()));
+    let on_change = handler(move |arg| f(selected_values(&arg)));
+    View::new(
+        "select",
+        attributes,
+        vec![("change".to_string(), on_change)],
+        vec![option_views(options, |value| {
+            selected.iter().any(|s| s == value)
+        })],
+    )
+}
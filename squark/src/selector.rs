@@ -0,0 +1,46 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+type SelectFunction<S, T> = Rc<dyn Fn(&S) -> Rc<T>>;
+
+/// Derived value of a state which is recomputed only when the inputs it reads change.
+pub struct Selector<S, T> {
+    select: SelectFunction<S, T>,
+}
+
+impl<S, T> Clone for Selector<S, T> {
+    fn clone(&self) -> Self {
+        Selector {
+            select: self.select.clone(),
+        }
+    }
+}
+
+impl<S: 'static, T: 'static> Selector<S, T> {
+    pub fn new<I, F, G>(input: F, compute: G) -> Selector<S, T>
+    where
+        I: PartialEq + 'static,
+        F: Fn(&S) -> I + 'static,
+        G: Fn(&I) -> T + 'static,
+    {
+        let cache: RefCell<Option<(I, Rc<T>)>> = RefCell::new(None);
+        let select = move |state: &S| {
+            let input = input(state);
+            if let Some((ref cached, ref value)) = *cache.borrow() {
+                if cached == &input {
+                    return value.clone();
+                }
+            }
+            let value = Rc::new(compute(&input));
+            *cache.borrow_mut() = Some((input, value.clone()));
+            value
+        };
+        Selector {
+            select: Rc::new(select),
+        }
+    }
+
+    pub fn select(&self, state: &S) -> Rc<T> {
+        (self.select)(state)
+    }
+}
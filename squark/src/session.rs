@@ -0,0 +1,188 @@
+//! Server-driven apps whose clients share part of the state and keep the
+//! rest to themselves.
+
+use cmd::Cmd;
+use remote::{DatagramTransport, RemoteRuntime};
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::rc::{Rc, Weak};
+use {App, Task, View};
+
+/// The state of one client: what every client sees and what only it does.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SessionState<S, P> {
+    pub shared: S,
+    pub session: P,
+}
+
+/// An app served to many clients by a `SessionServer`, like a shared
+/// document with a selection of each client's own.
+pub trait SessionApp: 'static + Clone + Default {
+    type Shared: Clone + Debug + PartialEq + 'static;
+    type Session: Clone + Debug + Default + PartialEq + 'static;
+    type Action: Clone + Debug + 'static;
+
+    /// Reduces the actions of every client.
+    fn reduce_shared(&self, shared: Self::Shared, action: &Self::Action) -> Self::Shared;
+
+    /// Reduces the actions of the client the session belongs to, after
+    /// `reduce_shared`.
+    fn reduce_session(
+        &self,
+        session: Self::Session,
+        shared: &Self::Shared,
+        action: Self::Action,
+    ) -> Self::Session;
+
+    fn command(
+        &self,
+        _state: &SessionState<Self::Shared, Self::Session>,
+        _action: &Self::Action,
+    ) -> Cmd<Self::Action> {
+        Cmd::none()
+    }
+
+    fn view(&self, state: SessionState<Self::Shared, Self::Session>) -> View<Self::Action>;
+}
+
+#[derive(Clone, Default)]
+struct Sessioned<A>(A);
+
+impl<A: SessionApp> App for Sessioned<A> {
+    type State = SessionState<A::Shared, A::Session>;
+    type Action = A::Action;
+
+    fn reducer(&self, state: Self::State, action: A::Action) -> Self::State {
+        let shared = self.0.reduce_shared(state.shared, &action);
+        let session = self.0.reduce_session(state.session, &shared, action);
+        SessionState { shared, session }
+    }
+
+    fn command(&self, state: &Self::State, action: &A::Action) -> Cmd<A::Action> {
+        self.0.command(state, action)
+    }
+
+    fn view(&self, state: Self::State) -> View<A::Action> {
+        self.0.view(state)
+    }
+}
+
+struct Sessions<A: SessionApp, T> {
+    shared: RefCell<A::Shared>,
+    runtimes: RefCell<BTreeMap<u64, RemoteRuntime<Sessioned<A>, T>>>,
+    next_id: Cell<u64>,
+}
+
+impl<A: SessionApp, T: DatagramTransport + 'static> Sessions<A, T> {
+    // Adopts the shared state a client's render ended with and renders it
+    // for the others.
+    fn share(&self, from: u64, shared: A::Shared) {
+        if *self.shared.borrow() == shared {
+            return;
+        }
+        *self.shared.borrow_mut() = shared.clone();
+        let others: Vec<_> = self
+            .runtimes
+            .borrow()
+            .iter()
+            .filter(|&(&id, _)| id != from)
+            .map(|(_, runtime)| runtime.clone())
+            .collect();
+        for runtime in others {
+            let session = runtime.state().session;
+            runtime.replace_state(SessionState {
+                shared: shared.clone(),
+                session,
+            });
+        }
+    }
+}
+
+/// Serves a `SessionApp`, rendering each client's view of the shared state
+/// and its session in a `RemoteRuntime` of its own. Changes to the shared
+/// state are rendered for every client.
+pub struct SessionServer<A: SessionApp, T> {
+    sessions: Rc<Sessions<A, T>>,
+}
+
+impl<A: SessionApp, T: DatagramTransport + 'static> SessionServer<A, T> {
+    pub fn new(shared: A::Shared) -> SessionServer<A, T> {
+        SessionServer {
+            sessions: Rc::new(Sessions {
+                shared: RefCell::new(shared),
+                runtimes: RefCell::new(BTreeMap::new()),
+                next_id: Cell::new(0),
+            }),
+        }
+    }
+
+    /// Starts a session with a new client, sends it the initial render and
+    /// returns its id.
+    pub fn connect(&self, transport: T) -> u64 {
+        let id = self.sessions.next_id.get();
+        self.sessions.next_id.set(id + 1);
+        let state = SessionState {
+            shared: self.shared(),
+            session: A::Session::default(),
+        };
+        let runtime = RemoteRuntime::new(state, transport);
+        let sessions: Weak<Sessions<A, T>> = Rc::downgrade(&self.sessions);
+        runtime.observe(move |state: SessionState<A::Shared, A::Session>| {
+            if let Some(sessions) = sessions.upgrade() {
+                sessions.share(id, state.shared);
+            }
+        });
+        self.sessions.runtimes.borrow_mut().insert(id, runtime);
+        id
+    }
+
+    /// Ends the client's session, returning whether it had one.
+    pub fn disconnect(&self, client: u64) -> bool {
+        self.sessions
+            .runtimes
+            .borrow_mut()
+            .remove(&client)
+            .is_some()
+    }
+
+    /// Ids of the connected clients.
+    pub fn clients(&self) -> Vec<u64> {
+        self.sessions.runtimes.borrow().keys().cloned().collect()
+    }
+
+    pub fn shared(&self) -> A::Shared {
+        self.sessions.shared.borrow().clone()
+    }
+
+    pub fn session(&self, client: u64) -> Option<A::Session> {
+        self.runtime(client).map(|runtime| runtime.state().session)
+    }
+
+    /// Handles a datagram from the client.
+    pub fn receive(&self, client: u64, datagram: &str) {
+        if let Some(runtime) = self.runtime(client) {
+            runtime.receive(0, datagram);
+        }
+    }
+
+    /// Resends what the clients have not acknowledged.
+    pub fn retransmit(&self) {
+        for runtime in self.sessions.runtimes.borrow().values() {
+            runtime.retransmit();
+        }
+    }
+
+    /// Tasks spawned by any session since the last call.
+    pub fn take_tasks(&self) -> Vec<Task> {
+        let runtimes: Vec<_> = self.sessions.runtimes.borrow().values().cloned().collect();
+        runtimes
+            .iter()
+            .flat_map(|runtime| runtime.take_tasks())
+            .collect()
+    }
+
+    fn runtime(&self, client: u64) -> Option<RemoteRuntime<Sessioned<A>, T>> {
+        self.sessions.runtimes.borrow().get(&client).cloned()
+    }
+}
@@ -0,0 +1,152 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use subscription::{Subscription, SubscriptionKind};
+use HandlerArg;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Chord {
+    key: String,
+    ctrl: bool,
+    alt: bool,
+    shift: bool,
+    meta: bool,
+}
+
+impl Chord {
+    // `ctrl+shift+k`: modifiers in any order, then the key.
+    fn parse(chord: &str) -> Chord {
+        let mut parts: Vec<&str> = chord.split('+').collect();
+        let key = parts.pop().unwrap_or("");
+        let mut parsed = Chord {
+            key: key.to_lowercase(),
+            ctrl: false,
+            alt: false,
+            shift: false,
+            meta: false,
+        };
+        for modifier in parts {
+            match &*modifier.to_lowercase() {
+                "ctrl" | "control" => parsed.ctrl = true,
+                "alt" | "option" => parsed.alt = true,
+                "shift" => parsed.shift = true,
+                "meta" | "cmd" | "super" => parsed.meta = true,
+                _ => panic!("unknown modifier `{}` in shortcut `{}`", modifier, chord),
+            }
+        }
+        parsed
+    }
+
+    fn from_arg(arg: &HandlerArg) -> Option<Chord> {
+        let flag = |name| arg[name].as_bool().unwrap_or(false);
+        let key = arg["key"].as_str()?;
+        match key {
+            "Control" | "Alt" | "Shift" | "Meta" => None,
+            _ => Some(Chord {
+                key: key.to_lowercase(),
+                ctrl: flag("ctrl"),
+                alt: flag("alt"),
+                shift: flag("shift"),
+                meta: flag("meta"),
+            }),
+        }
+    }
+}
+
+struct Binding<A> {
+    scope: Option<String>,
+    sequence: Vec<Chord>,
+    action: A,
+}
+
+/// Keyboard shortcuts, as a single `KeyDown` subscription.
+///
+/// Shortcuts are chords like `ctrl+k` or sequences of chords separated by
+/// spaces like `g g`. Scoped bindings apply only while the element with that
+/// id or one of its descendants has focus, and take precedence over global
+/// ones; otherwise the first binding wins.
+///
+/// Runtimes keep one subscription per kind, so an app binds all of its
+/// shortcuts in one `Shortcuts`.
+pub struct Shortcuts<A> {
+    bindings: Vec<Binding<A>>,
+}
+
+impl<A: Clone + 'static> Default for Shortcuts<A> {
+    fn default() -> Self {
+        Shortcuts::new()
+    }
+}
+
+impl<A: Clone + 'static> Shortcuts<A> {
+    pub fn new() -> Shortcuts<A> {
+        Shortcuts { bindings: vec![] }
+    }
+
+    /// Binds `shortcut` everywhere in the document.
+    pub fn bind(self, shortcut: &str, action: A) -> Shortcuts<A> {
+        self.add(None, shortcut, action)
+    }
+
+    /// Binds `shortcut` while focus is within the element with id `scope`.
+    pub fn bind_in<S: Into<String>>(self, scope: S, shortcut: &str, action: A) -> Shortcuts<A> {
+        self.add(Some(scope.into()), shortcut, action)
+    }
+
+    // Panics when the shortcut was bound already in the same scope, or when
+    // one of the two is a prefix of the other, as the longer one could never
+    // fire.
+    fn add(mut self, scope: Option<String>, shortcut: &str, action: A) -> Shortcuts<A> {
+        let sequence: Vec<Chord> = shortcut.split_whitespace().map(Chord::parse).collect();
+        assert!(!sequence.is_empty(), "empty shortcut");
+        for binding in self.bindings.iter().filter(|b| b.scope == scope) {
+            let shared = binding.sequence.len().min(sequence.len());
+            if binding.sequence[..shared] == sequence[..shared] {
+                panic!("shortcut `{}` conflicts with an earlier binding", shortcut);
+            }
+        }
+        self.bindings.push(Binding {
+            scope,
+            sequence,
+            action,
+        });
+        self
+    }
+
+    pub fn into_subscription(self) -> Subscription<A> {
+        let mut bindings = self.bindings;
+        // Stable, so bindings keep their order within each group.
+        bindings.sort_by_key(|b| b.scope.is_none());
+        let pending = Rc::new(RefCell::new(vec![]));
+        Subscription::new(SubscriptionKind::KeyDown, move |arg| {
+            let chord = Chord::from_arg(&arg)?;
+            let scopes: Vec<&str> = arg["scopes"]
+                .as_array()
+                .map(|ids| ids.iter().filter_map(|id| id.as_str()).collect())
+                .unwrap_or_default();
+            let active: Vec<&Binding<A>> = bindings
+                .iter()
+                .filter(|b| b.scope.as_ref().map_or(true, |s| scopes.contains(&&**s)))
+                .collect();
+            let mut pending = pending.borrow_mut();
+            pending.push(chord);
+            // A chord ending no sequence may still start one.
+            for _ in 0..2 {
+                if let Some(binding) = active.iter().find(|b| b.sequence == *pending) {
+                    pending.clear();
+                    return Some(binding.action.clone());
+                }
+                if active
+                    .iter()
+                    .any(|b| b.sequence.len() > pending.len() && b.sequence.starts_with(&pending))
+                {
+                    return None;
+                }
+                let last = pending.pop();
+                pending.clear();
+                pending.extend(last);
+            }
+            pending.clear();
+            None
+        })
+    }
+}
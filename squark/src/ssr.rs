@@ -0,0 +1,364 @@
+//! Rendering on the server and taking the markup over on the client.
+//!
+//! The server renders `prerender(state)`; the client builds a runtime from the
+//! same state and calls `Runtime::hydrate` instead of `run`, which attaches
+//! handlers to the existing markup rather than recreating it.
+
+use super::{App, AttributeValue, Element, Env, Node, Normalize, CUSTOM_ATTRIBUTE, URL_ATTRIBUTES};
+use std::io;
+use std::mem;
+use url;
+
+/// Marks elements whose handlers were stripped, as space separated
+/// `<id>:<kind>` pairs.
+pub const HANDLER_ATTRIBUTE: &str = "data-squark-h";
+
+/// Removes every handler, recording them in `HANDLER_ATTRIBUTE` instead.
+pub fn strip_handlers(node: Node) -> Node {
+    node.map_transform(|node| match node {
+        Node::Element(mut el) => {
+            if el.handlers().is_empty() {
+                return Node::Element(el);
+            }
+            let handlers: Vec<String> = el
+                .handlers_mut()
+                .drain(..)
+                .map(|(kind, id)| format!("{}:{}", id, kind))
+                .collect();
+            el.attributes_mut()
+                .push((HANDLER_ATTRIBUTE.to_string(), handlers.join(" ").into()));
+            Node::Element(el)
+        }
+        node => node,
+    })
+}
+
+fn escape(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+}
+
+// Elements whose text is not parsed as markup but runs up to their end tag.
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+// Writes the text of the raw text element `name` as it is, but for end tags
+// of the element, which would close it early and are written as `<\/name`.
+fn escape_raw_text(text: &str, name: &str, out: &mut String) {
+    let mut rest = text;
+    while let Some(i) = rest.find("</") {
+        out.push_str(&rest[..=i]);
+        rest = &rest[i + 1..];
+        let tag = rest.as_bytes().get(1..=name.len());
+        if tag.is_some_and(|tag| tag.eq_ignore_ascii_case(name.as_bytes())) {
+            out.push('\\');
+        }
+    }
+    out.push_str(rest);
+}
+
+// Names that would end the attribute or the tag early are not rendered.
+fn is_valid_attribute_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.chars().any(|c| {
+            c.is_whitespace() || c.is_control() || ['"', '\'', '<', '>', '/', '='].contains(&c)
+        })
+}
+
+/// Inline code in rendered markup, which a strict Content-Security-Policy
+/// has to allow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Inline {
+    /// The contents of a `<script>` without `src`.
+    Script,
+    /// The contents of a `<style>`.
+    Style,
+    /// A `style` attribute.
+    StyleAttribute,
+}
+
+/// How a policy allows a piece of inline code.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CspSource {
+    /// A hash of the code as `<algorithm>-<base64>`, like `sha256-…`.
+    Hash(String),
+    /// A nonce rendered onto the element. Style attributes can only be
+    /// allowed by hash, so nonces for them are left out.
+    Nonce(String),
+}
+
+/// The quoted sources to add to `script-src` and `style-src`, without
+/// duplicates.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CspSources {
+    pub script_src: Vec<String>,
+    pub style_src: Vec<String>,
+}
+
+type SourceFn<'a> = &'a mut dyn FnMut(Inline, &str) -> CspSource;
+
+struct Csp<'a> {
+    source: SourceFn<'a>,
+    sources: CspSources,
+}
+
+impl<'a> Csp<'a> {
+    // Records how `code` is allowed, returning the nonce to render if any.
+    fn allow(&mut self, inline: Inline, code: &str) -> Option<String> {
+        let source = (self.source)(inline, code);
+        let list = match inline {
+            Inline::Script => &mut self.sources.script_src,
+            Inline::Style | Inline::StyleAttribute => &mut self.sources.style_src,
+        };
+        let (expression, nonce) = match source {
+            CspSource::Hash(hash) => (format!("'{}'", hash), None),
+            CspSource::Nonce(_) if inline == Inline::StyleAttribute => return None,
+            CspSource::Nonce(nonce) => (format!("'nonce-{}'", nonce), Some(nonce)),
+        };
+        if inline == Inline::StyleAttribute && !list.iter().any(|s| s == "'unsafe-hashes'") {
+            list.push("'unsafe-hashes'".to_string());
+        }
+        if !list.contains(&expression) {
+            list.push(expression);
+        }
+        nonce
+    }
+}
+
+/// About how many bytes `render_to_writer` writes at once.
+pub const CHUNK_SIZE: usize = 8192;
+
+type Sink<'a> = &'a mut dyn FnMut(&str) -> io::Result<()>;
+
+struct Renderer<'a> {
+    out: String,
+    csp: Option<Csp<'a>>,
+    // Takes the markup rendered so far in chunks, unless `capturing`.
+    sink: Option<Sink<'a>>,
+    capturing: bool,
+    error: Option<io::Error>,
+}
+
+impl<'a> Renderer<'a> {
+    fn new(csp: Option<Csp<'a>>, sink: Option<Sink<'a>>) -> Renderer<'a> {
+        Renderer {
+            out: String::new(),
+            csp,
+            sink,
+            capturing: false,
+            error: None,
+        }
+    }
+
+    fn node(&mut self, node: &Node) {
+        if self.error.is_some() {
+            return;
+        }
+        self.write(node);
+        if self.out.len() >= CHUNK_SIZE && !self.capturing {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Some(ref mut sink) = self.sink {
+            if let Err(error) = sink(&self.out) {
+                self.error = Some(error);
+            }
+            self.out.clear();
+        }
+    }
+
+    fn write(&mut self, node: &Node) {
+        let out = &mut self.out;
+        match *node {
+            Node::Null => (),
+            Node::Text(ref text) => escape(text, out),
+            Node::Raw(ref html) => {
+                out.push_str("<span>");
+                out.push_str(html);
+                out.push_str("</span>");
+            }
+            // Empty until the client mounts it.
+            Node::Custom(ref custom) => {
+                out.push_str(&format!("<{} {}=\"", custom.tag(), CUSTOM_ATTRIBUTE));
+                escape(custom.name(), out);
+                out.push_str(&format!("\"></{}>", custom.tag()));
+            }
+            Node::Element(ref el) => self.element(el),
+        }
+    }
+
+    fn element(&mut self, el: &Element) {
+        let inline = match el.name() {
+            "script" if el.attributes().iter().all(|(name, _)| name != "src") => {
+                Some(Inline::Script)
+            }
+            "style" => Some(Inline::Style),
+            _ => None,
+        };
+        let mut nonce = None;
+        let mut contents = None;
+        if let (Some(inline), true) = (inline, self.csp.is_some()) {
+            let out = mem::take(&mut self.out);
+            let capturing = mem::replace(&mut self.capturing, true);
+            self.children(el);
+            self.capturing = capturing;
+            let code = mem::replace(&mut self.out, out);
+            nonce = self.csp.as_mut().and_then(|csp| csp.allow(inline, &code));
+            contents = Some(code);
+        }
+
+        self.out.push('<');
+        self.out.push_str(el.name());
+        for (name, value) in el.attributes() {
+            if !is_valid_attribute_name(name) {
+                continue;
+            }
+            match *value {
+                AttributeValue::String(ref s)
+                    if URL_ATTRIBUTES.contains(&name.as_str()) && url::is_blocked(s) => {}
+                AttributeValue::String(ref s) => {
+                    if name == "style" {
+                        if let Some(ref mut csp) = self.csp {
+                            csp.allow(Inline::StyleAttribute, s);
+                        }
+                    }
+                    self.attribute(name, s);
+                }
+                AttributeValue::Bool(true) => {
+                    self.out.push(' ');
+                    self.out.push_str(name);
+                }
+                // Properties only exist on live elements.
+                AttributeValue::Bool(false) | AttributeValue::Property(_) => (),
+            }
+        }
+        if let Some(nonce) = nonce {
+            self.attribute("nonce", &nonce);
+        }
+        self.out.push('>');
+        if el.tag().is_void() {
+            return;
+        }
+        match contents {
+            Some(code) => self.out.push_str(&code),
+            None => self.children(el),
+        }
+        self.out.push_str("</");
+        self.out.push_str(el.name());
+        self.out.push('>');
+    }
+
+    fn attribute(&mut self, name: &str, value: &str) {
+        self.out.push(' ');
+        self.out.push_str(name);
+        self.out.push_str("=\"");
+        escape(value, &mut self.out);
+        self.out.push('"');
+    }
+
+    fn children(&mut self, el: &Element) {
+        if el.has_shadow_root() {
+            self.out.push_str("<template shadowrootmode=\"open\">");
+        }
+        let raw = RAW_TEXT_ELEMENTS.contains(&el.name());
+        for child in el.children() {
+            match *child {
+                Node::Text(ref text) if raw => escape_raw_text(text, el.name(), &mut self.out),
+                ref child => self.node(child),
+            }
+        }
+        if el.has_shadow_root() {
+            self.out.push_str("</template>");
+        }
+    }
+}
+
+/// Serializes `node` as HTML. Handlers are left out; see `strip_handlers`.
+/// So are URL attributes with blocked schemes, see `set_blocked_url_schemes`,
+/// and attributes whose names are not valid in markup. The text of `script`
+/// and `style` elements is written unescaped, but for their end tags.
+pub fn render_to_string(node: &Node) -> String {
+    let mut renderer = Renderer::new(None, None);
+    renderer.node(node);
+    renderer.out
+}
+
+/// Like `render_to_string`, writing the markup to `out` in chunks of about
+/// `CHUNK_SIZE` bytes as it is rendered.
+pub fn render_to_writer<W: io::Write>(node: &Node, out: W) -> io::Result<()> {
+    render_to_writer_with(node, out, |_| Ok(()))
+}
+
+/// Like `render_to_writer`, calling `flushed` with the writer after every
+/// chunk, e.g. to flush a streamed HTTP response.
+pub fn render_to_writer_with<W, F>(node: &Node, mut out: W, mut flushed: F) -> io::Result<()>
+where
+    W: io::Write,
+    F: FnMut(&mut W) -> io::Result<()>,
+{
+    let mut sink = |chunk: &str| {
+        out.write_all(chunk.as_bytes())?;
+        flushed(&mut out)
+    };
+    let mut renderer = Renderer::new(None, Some(&mut sink));
+    renderer.node(node);
+    if renderer.error.is_none() && !renderer.out.is_empty() {
+        renderer.flush();
+    }
+    match renderer.error {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+/// Like `render_to_string`, calling `source` with every inline script, style
+/// element and style attribute, for the sources a strict policy sent along
+/// with the markup has to allow.
+pub fn render_with_csp<F>(node: &Node, mut source: F) -> (String, CspSources)
+where
+    F: FnMut(Inline, &str) -> CspSource,
+{
+    let csp = Csp {
+        source: &mut source,
+        sources: CspSources::default(),
+    };
+    let mut renderer = Renderer::new(Some(csp), None);
+    renderer.node(node);
+    let sources = renderer.csp.map(|csp| csp.sources).unwrap_or_default();
+    (renderer.out, sources)
+}
+
+/// The markup of the view of `A` for `state`, ready for `Runtime::hydrate`.
+pub fn prerender<A: App>(state: A::State) -> String {
+    prerender_with::<A>(state, Normalize::default())
+}
+
+/// Like `prerender`, with commands tracked under `keys` still in flight: the
+/// fallbacks of `pending` are marked with `PLACEHOLDER_ATTRIBUTE`. Clients
+/// hydrate with `Env::set_placeholders` and `Env::begin_pending` for the
+/// same keys, then fill in each with `Runtime::fill_placeholder`.
+pub fn prerender_pending<A: App>(state: A::State, keys: &[&str]) -> String {
+    let env = Env::<A>::new(state);
+    env.set_placeholders(true);
+    for key in keys {
+        env.begin_pending(key);
+    }
+    let (view, _) = env.build_view();
+    render_to_string(&strip_handlers(view.node))
+}
+
+/// Like `prerender`, for clients hydrating with `Env::set_normalize`.
+pub fn prerender_with<A: App>(state: A::State, normalize: Normalize) -> String {
+    let env = Env::<A>::new(state);
+    env.set_normalize(normalize);
+    let (view, _) = env.build_view();
+    render_to_string(&strip_handlers(view.node))
+}
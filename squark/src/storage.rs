@@ -0,0 +1,38 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// Capability to persist string values by key, supplied by the runtime.
+pub trait KeyValueStorage {
+    fn get(&self, key: &str) -> Option<String>;
+
+    fn set(&self, key: &str, value: &str);
+
+    fn remove(&self, key: &str);
+}
+
+#[derive(Default)]
+pub struct MemoryStorage {
+    map: RefCell<HashMap<String, String>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> MemoryStorage {
+        MemoryStorage::default()
+    }
+}
+
+impl KeyValueStorage for MemoryStorage {
+    fn get(&self, key: &str) -> Option<String> {
+        self.map.borrow().get(key).cloned()
+    }
+
+    fn set(&self, key: &str, value: &str) {
+        self.map
+            .borrow_mut()
+            .insert(key.to_string(), value.to_string());
+    }
+
+    fn remove(&self, key: &str) {
+        self.map.borrow_mut().remove(key);
+    }
+}
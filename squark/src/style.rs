@@ -0,0 +1,99 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::rc::Rc;
+
+thread_local! {
+    // Rules registered outside the renders of an env, injected by every env.
+    static SHARED: StyleRegistry = Rc::new(RefCell::new(Registry::default()));
+    static CURRENT: RefCell<Option<StyleRegistry>> = const { RefCell::new(None) };
+}
+
+#[derive(Default)]
+pub(crate) struct Registry {
+    rules: Vec<(String, String)>,
+    // The declarations of every class name, to tell hash collisions apart.
+    declarations: HashMap<String, String>,
+}
+
+pub(crate) type StyleRegistry = Rc<RefCell<Registry>>;
+
+impl Registry {
+    fn register(&mut self, declarations: &str) -> String {
+        let mut class_name = String::from("sq-");
+        let _ = write!(class_name, "{:08x}", fnv1a(declarations));
+        let hash_len = class_name.len();
+        let mut collisions = 0;
+        while let Some(registered) = self.declarations.get(&class_name) {
+            if registered == declarations {
+                return class_name;
+            }
+            collisions += 1;
+            class_name.truncate(hash_len);
+            let _ = write!(class_name, "-{}", collisions);
+        }
+        let rule = scope(&class_name, declarations);
+        self.declarations
+            .insert(class_name.clone(), declarations.to_string());
+        self.rules.push((class_name.clone(), rule));
+        class_name
+    }
+
+    // Rules registered after the first `from` ones, in registration order.
+    pub(crate) fn rules_since(&self, from: usize) -> Vec<(String, String)> {
+        self.rules.iter().skip(from).cloned().collect()
+    }
+}
+
+// Has `css` register into `registry` while `f` runs.
+pub(crate) fn with_registry<T, F>(registry: StyleRegistry, f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    let previous = CURRENT.with(|r| r.replace(Some(registry)));
+    let result = f();
+    CURRENT.with(|r| *r.borrow_mut() = previous);
+    result
+}
+
+fn fnv1a(s: &str) -> u32 {
+    s.bytes().fold(0x811c_9dc5, |hash, b| {
+        (hash ^ u32::from(b)).wrapping_mul(0x0100_0193)
+    })
+}
+
+fn scope(class_name: &str, declarations: &str) -> String {
+    let selector = format!(".{}", class_name);
+    if declarations.contains('{') {
+        return declarations.replace('&', &selector);
+    }
+    format!("{} {{ {} }}", selector, declarations.trim())
+}
+
+/// Registers CSS declarations and returns a class name derived from their content.
+///
+/// Plain declarations are scoped to the class. Declarations containing blocks
+/// use `&` as the placeholder for the class selector, e.g. `& { color: red } &:hover { color: blue }`.
+///
+/// Rules are kept by the env rendering the view, or shared by all envs when
+/// registered outside of a render. Declarations whose hashes collide get
+/// class names told apart by a suffix.
+pub fn css(declarations: &str) -> String {
+    let registry = CURRENT
+        .with(|r| r.borrow().clone())
+        .unwrap_or_else(|| SHARED.with(|r| r.clone()));
+    let class_name = registry.borrow_mut().register(declarations);
+    class_name
+}
+
+// Rules registered outside of renders after the first `from` ones.
+pub(crate) fn shared_rules_since(from: usize) -> Vec<(String, String)> {
+    SHARED.with(|r| r.borrow().rules_since(from))
+}
+
+#[macro_export]
+macro_rules! style {
+    ($($declaration:expr),* $(,)*) => {
+        $crate::css(concat!($($declaration, " "),*))
+    };
+}
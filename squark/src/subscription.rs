@@ -0,0 +1,161 @@
+use std::rc::Rc;
+use router::Location;
+use std::time::Duration;
+use HandlerArg;
+
+/// Names an element for subscriptions observing it, like `Subscription::on_visible`
+/// and `Subscription::on_resize`.
+pub const OBSERVE_KEY: &str = "data-observe-key";
+
+type SubscriptionHandler<A> = Rc<dyn Fn(HandlerArg) -> Option<A>>;
+
+/// What a subscription listens to. Runtimes start one source per distinct kind
+/// and report its events back as `HandlerArg` payloads.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SubscriptionKind {
+    WebSocket(String),
+    Every(Duration),
+    UrlChange,
+    /// Every keydown in the document, as `{"key", "ctrl", "alt", "shift",
+    /// "meta", "scopes"}` where `scopes` lists the ids of the focused element
+    /// and its ancestors. See `Shortcuts`.
+    KeyDown,
+    /// Whether elements tagged with this `OBSERVE_KEY` intersect the viewport,
+    /// as a bool whenever that changes.
+    Visible(String),
+    /// The content box of elements tagged with this `OBSERVE_KEY`, as
+    /// `[width, height]` when observed and on every change.
+    Resize(String),
+    /// Once per frame before painting, with the frame's timestamp in
+    /// milliseconds. See `Runtime::dispatch_frame`.
+    AnimationFrame,
+    /// Whether the browser is online, as a bool when subscribed and whenever
+    /// that changes.
+    Online,
+}
+
+pub struct Subscription<A> {
+    kind: SubscriptionKind,
+    handler: SubscriptionHandler<A>,
+}
+
+impl<A> Clone for Subscription<A> {
+    fn clone(&self) -> Self {
+        Subscription {
+            kind: self.kind.clone(),
+            handler: self.handler.clone(),
+        }
+    }
+}
+
+impl<A: 'static> Subscription<A> {
+    pub fn new<F>(kind: SubscriptionKind, handler: F) -> Subscription<A>
+    where
+        F: Fn(HandlerArg) -> Option<A> + 'static,
+    {
+        Subscription {
+            kind,
+            handler: Rc::new(handler),
+        }
+    }
+
+    pub fn web_socket<S, F>(url: S, decoder: F) -> Subscription<A>
+    where
+        S: Into<String>,
+        F: Fn(String) -> Option<A> + 'static,
+    {
+        Subscription::new(SubscriptionKind::WebSocket(url.into()), move |arg| {
+            arg.as_str().and_then(|msg| decoder(msg.to_string()))
+        })
+    }
+
+    /// Ticks carry the runtime's current time in milliseconds, on the clock
+    /// animation frames are timed with rather than since the epoch.
+    pub fn every<F>(interval: Duration, f: F) -> Subscription<A>
+    where
+        F: Fn(f64) -> A + 'static,
+    {
+        Subscription::new(SubscriptionKind::Every(interval), move |arg| {
+            arg.as_f64().map(&f)
+        })
+    }
+
+    /// Ticks for animations, carrying the frame's timestamp in milliseconds,
+    /// comparable with those of `every`.
+    pub fn animation_frame<F>(f: F) -> Subscription<A>
+    where
+        F: Fn(f64) -> A + 'static,
+    {
+        Subscription::new(SubscriptionKind::AnimationFrame, move |arg| {
+            arg.as_f64().map(&f)
+        })
+    }
+
+    /// Fires with the current location when subscribed and on every history change.
+    pub fn url_change<F>(f: F) -> Subscription<A>
+    where
+        F: Fn(Location) -> A + 'static,
+    {
+        Subscription::new(SubscriptionKind::UrlChange, move |arg| {
+            arg.as_str().map(|url| f(Location::parse(url)))
+        })
+    }
+
+    /// Fires with whether the browser is online when subscribed and whenever
+    /// that changes. See `Outbox`.
+    pub fn online<F>(f: F) -> Subscription<A>
+    where
+        F: Fn(bool) -> A + 'static,
+    {
+        Subscription::new(SubscriptionKind::Online, move |arg| arg.as_bool().map(&f))
+    }
+
+    /// Fires `action` whenever an element tagged with `key` scrolls into view,
+    /// e.g. to load the next page of an infinite list.
+    pub fn on_visible<K: Into<String>>(key: K, action: A) -> Subscription<A>
+    where
+        A: Clone,
+    {
+        Subscription::new(SubscriptionKind::Visible(key.into()), move |arg| {
+            if arg.as_bool() == Some(true) {
+                Some(action.clone())
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Reports the size of elements tagged with `key`, e.g. to fit a canvas
+    /// to its container.
+    pub fn on_resize<K, F>(key: K, f: F) -> Subscription<A>
+    where
+        K: Into<String>,
+        F: Fn(f64, f64) -> A + 'static,
+    {
+        Subscription::new(SubscriptionKind::Resize(key.into()), move |arg| {
+            match (arg[0].as_f64(), arg[1].as_f64()) {
+                (Some(width), Some(height)) => Some(f(width, height)),
+                _ => None,
+            }
+        })
+    }
+
+    pub fn kind(&self) -> &SubscriptionKind {
+        &self.kind
+    }
+
+    pub fn handle(&self, arg: HandlerArg) -> Option<A> {
+        (self.handler)(arg)
+    }
+
+    pub fn map<B, F>(self, f: F) -> Subscription<B>
+    where
+        F: Fn(A) -> B + 'static,
+    {
+        let handler = self.handler;
+        Subscription {
+            kind: self.kind,
+            handler: Rc::new(move |arg| handler(arg).map(&f)),
+        }
+    }
+}
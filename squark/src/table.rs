@@ -0,0 +1,176 @@
+//! Tables of rows described by column definitions.
+
+use std::cmp::Ordering;
+use std::rc::Rc;
+use {handler, keyed_list, View};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+/// Which column rows are sorted by, kept in the state.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Sort {
+    pub column: String,
+    pub direction: SortDirection,
+}
+
+type Cell<T, A> = Rc<dyn Fn(&T) -> View<A>>;
+type Compare<T> = Rc<dyn Fn(&T, &T) -> Ordering>;
+type RowAction<T, A> = Rc<dyn Fn(&T) -> A>;
+
+pub struct Column<T, A> {
+    key: String,
+    header: String,
+    cell: Cell<T, A>,
+    compare: Option<Compare<T>>,
+}
+
+impl<T, A> Column<T, A> {
+    /// A column called `key` in `Sort`, rendering each row with `cell`.
+    pub fn new<K, H, F>(key: K, header: H, cell: F) -> Column<T, A>
+    where
+        K: Into<String>,
+        H: Into<String>,
+        F: Fn(&T) -> View<A> + 'static,
+    {
+        Column {
+            key: key.into(),
+            header: header.into(),
+            cell: Rc::new(cell),
+            compare: None,
+        }
+    }
+
+    /// Lets the column be sorted by, ascending in the order of `compare`.
+    pub fn sortable<F>(mut self, compare: F) -> Column<T, A>
+    where
+        F: Fn(&T, &T) -> Ordering + 'static,
+    {
+        self.compare = Some(Rc::new(compare));
+        self
+    }
+}
+
+/// A `<table>` with a header row and one keyed row per item, so rows keep
+/// their elements when sorted or filtered.
+///
+/// Clicking the header of a sortable column asks for rows sorted by it, in
+/// the opposite direction if they are already.
+pub struct DataTable<T, A> {
+    columns: Vec<Column<T, A>>,
+    row_key: Rc<dyn Fn(&T) -> String>,
+    on_sort: Option<Rc<dyn Fn(Sort) -> A>>,
+    on_row_click: Option<RowAction<T, A>>,
+}
+
+impl<T, A: Clone + 'static> DataTable<T, A> {
+    /// `row_key` must tell rows apart, see `keyed_list`.
+    pub fn new<K, F>(row_key: F) -> DataTable<T, A>
+    where
+        K: ToString,
+        F: Fn(&T) -> K + 'static,
+    {
+        DataTable {
+            columns: vec![],
+            row_key: Rc::new(move |row| row_key(row).to_string()),
+            on_sort: None,
+            on_row_click: None,
+        }
+    }
+
+    pub fn column(mut self, column: Column<T, A>) -> DataTable<T, A> {
+        self.columns.push(column);
+        self
+    }
+
+    pub fn on_sort<F: Fn(Sort) -> A + 'static>(mut self, f: F) -> DataTable<T, A> {
+        self.on_sort = Some(Rc::new(f));
+        self
+    }
+
+    pub fn on_row_click<F: Fn(&T) -> A + 'static>(mut self, f: F) -> DataTable<T, A> {
+        self.on_row_click = Some(Rc::new(f));
+        self
+    }
+
+    /// Renders `rows`, sorted by `sort` if its column is sortable.
+    pub fn view(&self, rows: &[T], sort: Option<&Sort>) -> View<A> {
+        let mut rows: Vec<&T> = rows.iter().collect();
+        let compare = sort.and_then(|sort| {
+            self.columns
+                .iter()
+                .find(|c| c.key == sort.column)
+                .and_then(|c| c.compare.clone())
+                .map(|compare| (compare, sort.direction))
+        });
+        if let Some((compare, direction)) = compare {
+            rows.sort_by(|a, b| match direction {
+                SortDirection::Ascending => compare(a, b),
+                SortDirection::Descending => compare(b, a),
+            });
+        }
+
+        let headers: Vec<View<A>> = self.columns.iter().map(|c| self.header(c, sort)).collect();
+        let header_row = View::new("tr", vec![], vec![], vec![headers.into()]);
+        let header = View::new("thead", vec![], vec![], vec![header_row.into()]);
+        let body = View::new(
+            "tbody",
+            vec![],
+            vec![],
+            vec![keyed_list(rows, |row| ((self.row_key)(row), self.row(row)))],
+        );
+        View::new("table", vec![], vec![], vec![header.into(), body.into()])
+    }
+
+    fn header(&self, column: &Column<T, A>, sort: Option<&Sort>) -> View<A> {
+        let direction = sort
+            .filter(|sort| sort.column == column.key)
+            .map(|sort| sort.direction);
+        let mut attributes = vec![
+            ("scope".to_string(), "col".into()),
+            ("data-column".to_string(), column.key.clone().into()),
+        ];
+        if let Some(direction) = direction {
+            let aria_sort = match direction {
+                SortDirection::Ascending => "ascending",
+                SortDirection::Descending => "descending",
+            };
+            attributes.push(("aria-sort".to_string(), aria_sort.into()));
+        }
+        let mut handlers = vec![];
+        let on_sort = self.on_sort.as_ref().filter(|_| column.compare.is_some());
+        if let Some(on_sort) = on_sort {
+            let action = on_sort(Sort {
+                column: column.key.clone(),
+                direction: match direction {
+                    Some(SortDirection::Ascending) => SortDirection::Descending,
+                    _ => SortDirection::Ascending,
+                },
+            });
+            handlers.push(("click".to_string(), handler(move |_| action.clone())));
+        }
+        View::new(
+            "th",
+            attributes,
+            handlers,
+            vec![View::text(column.header.clone()).into()],
+        )
+    }
+
+    fn row(&self, row: &T) -> View<A> {
+        let mut handlers = vec![];
+        if let Some(ref on_row_click) = self.on_row_click {
+            let action = on_row_click(row);
+            handlers.push(("click".to_string(), handler(move |_| action.clone())));
+        }
+        let cells: Vec<View<A>> = self
+            .columns
+            .iter()
+            .map(|c| View::new("td", vec![], vec![], vec![(c.cell)(row).into()]))
+            .collect();
+        View::new("tr", vec![], handlers, vec![cells.into()])
+    }
+}
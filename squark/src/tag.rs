@@ -0,0 +1,210 @@
+use std::fmt;
+
+macro_rules! tags {
+    ($($variant:ident => $name:expr,)*) => {
+        /// Standard HTML and SVG elements, plus `Custom` for any other name.
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum Tag {
+            $($variant,)*
+            Custom(String),
+        }
+
+        impl Tag {
+            pub fn name(&self) -> &str {
+                match *self {
+                    $(Tag::$variant => $name,)*
+                    Tag::Custom(ref name) => name,
+                }
+            }
+
+            /// The known tag called `name`, or `Custom`.
+            pub fn from_name(name: &str) -> Tag {
+                match name {
+                    $($name => Tag::$variant,)*
+                    _ => Tag::Custom(name.to_string()),
+                }
+            }
+        }
+    };
+}
+
+tags! {
+    A => "a",
+    Abbr => "abbr",
+    Address => "address",
+    Area => "area",
+    Article => "article",
+    Aside => "aside",
+    Audio => "audio",
+    B => "b",
+    Base => "base",
+    Bdi => "bdi",
+    Bdo => "bdo",
+    Blockquote => "blockquote",
+    Body => "body",
+    Br => "br",
+    Button => "button",
+    Canvas => "canvas",
+    Caption => "caption",
+    Cite => "cite",
+    Code => "code",
+    Col => "col",
+    Colgroup => "colgroup",
+    Data => "data",
+    Datalist => "datalist",
+    Dd => "dd",
+    Del => "del",
+    Details => "details",
+    Dfn => "dfn",
+    Dialog => "dialog",
+    Div => "div",
+    Dl => "dl",
+    Dt => "dt",
+    Em => "em",
+    Embed => "embed",
+    Fieldset => "fieldset",
+    Figcaption => "figcaption",
+    Figure => "figure",
+    Footer => "footer",
+    Form => "form",
+    H1 => "h1",
+    H2 => "h2",
+    H3 => "h3",
+    H4 => "h4",
+    H5 => "h5",
+    H6 => "h6",
+    Head => "head",
+    Header => "header",
+    Hgroup => "hgroup",
+    Hr => "hr",
+    Html => "html",
+    I => "i",
+    Iframe => "iframe",
+    Img => "img",
+    Input => "input",
+    Ins => "ins",
+    Kbd => "kbd",
+    Label => "label",
+    Legend => "legend",
+    Li => "li",
+    Link => "link",
+    Main => "main",
+    Map => "map",
+    Mark => "mark",
+    Menu => "menu",
+    Meta => "meta",
+    Meter => "meter",
+    Nav => "nav",
+    Noscript => "noscript",
+    Object => "object",
+    Ol => "ol",
+    Optgroup => "optgroup",
+    Option => "option",
+    Output => "output",
+    P => "p",
+    Param => "param",
+    Picture => "picture",
+    Pre => "pre",
+    Progress => "progress",
+    Q => "q",
+    Rp => "rp",
+    Rt => "rt",
+    Ruby => "ruby",
+    S => "s",
+    Samp => "samp",
+    Script => "script",
+    Search => "search",
+    Section => "section",
+    Select => "select",
+    Slot => "slot",
+    Small => "small",
+    Source => "source",
+    Span => "span",
+    Strong => "strong",
+    Style => "style",
+    Sub => "sub",
+    Summary => "summary",
+    Sup => "sup",
+    Table => "table",
+    Tbody => "tbody",
+    Td => "td",
+    Template => "template",
+    Textarea => "textarea",
+    Tfoot => "tfoot",
+    Th => "th",
+    Thead => "thead",
+    Time => "time",
+    Title => "title",
+    Tr => "tr",
+    Track => "track",
+    U => "u",
+    Ul => "ul",
+    Var => "var",
+    Video => "video",
+    Wbr => "wbr",
+    Svg => "svg",
+    Circle => "circle",
+    ClipPath => "clipPath",
+    Defs => "defs",
+    Desc => "desc",
+    Ellipse => "ellipse",
+    ForeignObject => "foreignObject",
+    G => "g",
+    Image => "image",
+    Line => "line",
+    LinearGradient => "linearGradient",
+    Marker => "marker",
+    Mask => "mask",
+    Path => "path",
+    Pattern => "pattern",
+    Polygon => "polygon",
+    Polyline => "polyline",
+    RadialGradient => "radialGradient",
+    Rect => "rect",
+    Stop => "stop",
+    Symbol => "symbol",
+    Text => "text",
+    Tspan => "tspan",
+    Use => "use",
+}
+
+impl Tag {
+    /// Elements which cannot have children, written without a closing tag.
+    pub fn is_void(&self) -> bool {
+        matches!(
+            *self,
+            Tag::Area
+                | Tag::Base
+                | Tag::Br
+                | Tag::Col
+                | Tag::Embed
+                | Tag::Hr
+                | Tag::Img
+                | Tag::Input
+                | Tag::Link
+                | Tag::Meta
+                | Tag::Param
+                | Tag::Source
+                | Tag::Track
+                | Tag::Wbr
+        )
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl From<String> for Tag {
+    fn from(name: String) -> Tag {
+        Tag::from_name(&name)
+    }
+}
+
+impl<'a> From<&'a str> for Tag {
+    fn from(name: &'a str) -> Tag {
+        Tag::from_name(name)
+    }
+}
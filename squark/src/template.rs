@@ -0,0 +1,295 @@
+use super::{
+    diff_attributes, diff_children, Attribute, AttributeValue, Diff, Element, Node, View,
+};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+pub(crate) type ConstantCache = Rc<RefCell<HashMap<String, Node>>>;
+
+thread_local! {
+    static CONSTANTS: RefCell<Option<ConstantCache>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn with_constants<T, F>(cache: ConstantCache, f: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    let previous = CONSTANTS.with(|c| c.replace(Some(cache)));
+    let result = f();
+    CONSTANTS.with(|c| *c.borrow_mut() = previous);
+    result
+}
+
+impl<A> View<A> {
+    /// Builds a subtree of static markup once per `Env` and reuses it on
+    /// later renders, where diffing skips it without walking it. `key` must
+    /// identify the subtree. Subtrees with handlers are rebuilt every time.
+    pub fn constant<F>(key: &str, build: F) -> View<A>
+    where
+        F: FnOnce() -> View<A>,
+    {
+        let cached = CONSTANTS.with(|c| {
+            c.borrow()
+                .as_ref()
+                .and_then(|cache| cache.borrow().get(key).cloned())
+        });
+        if let Some(node) = cached {
+            return View {
+                node,
+                handler_map: HashMap::new(),
+            };
+        }
+
+        let view = build();
+        if !view.handler_map.is_empty() {
+            #[cfg(all(debug_assertions, feature = "debug-checks"))]
+            super::warn(&format!("constant `{}` has handlers and is not cached", key));
+            return view;
+        }
+        CONSTANTS.with(|c| {
+            if let Some(ref cache) = *c.borrow() {
+                cache
+                    .borrow_mut()
+                    .insert(key.to_string(), view.node.clone());
+            }
+        });
+        view
+    }
+}
+
+const HOLE_PREFIX: &str = "\u{0}squark-hole-";
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Slot {
+    Text,
+    Attribute(String),
+    Children,
+}
+
+pub(crate) type Slots = Rc<Vec<(Vec<usize>, Slot)>>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum HoleKind {
+    Text,
+    Attribute,
+    Children,
+}
+
+/// Hands out the placeholders of a template while it is built.
+#[derive(Default)]
+pub struct Holes {
+    kinds: Vec<HoleKind>,
+}
+
+impl Holes {
+    fn marker(&mut self, kind: HoleKind) -> String {
+        self.kinds.push(kind);
+        format!("{}{}", HOLE_PREFIX, self.kinds.len() - 1)
+    }
+
+    pub fn text<A>(&mut self) -> View<A> {
+        View::text(self.marker(HoleKind::Text))
+    }
+
+    pub fn attribute(&mut self) -> AttributeValue {
+        AttributeValue::String(self.marker(HoleKind::Attribute))
+    }
+
+    /// Must be the only child of its parent.
+    pub fn children<A>(&mut self) -> View<A> {
+        View::text(self.marker(HoleKind::Children))
+    }
+}
+
+/// The value filling a hole, in the order the holes were handed out.
+pub enum Binding<A> {
+    Text(String),
+    Attribute(AttributeValue),
+    Children(Vec<View<A>>),
+}
+
+fn hole_index(s: &str) -> Option<usize> {
+    if !s.starts_with(HOLE_PREFIX) {
+        return None;
+    }
+    s[HOLE_PREFIX.len()..].parse().ok()
+}
+
+/// Static markup with holes for text, attribute values and child lists.
+///
+/// Rendering an instance only touches the holes, and diffing two instances
+/// of the same template compares the holes alone, patching them by path.
+#[derive(Clone, Debug)]
+pub struct Template {
+    root: Element,
+}
+
+impl Template {
+    /// Builds the markup once. Panics when the markup has handlers or a
+    /// children hole has siblings.
+    pub fn new<A, F>(build: F) -> Template
+    where
+        F: FnOnce(&mut Holes) -> View<A>,
+    {
+        let mut holes = Holes::default();
+        let view = build(&mut holes);
+        assert!(view.handler_map.is_empty(), "template markup has handlers");
+        let mut root = match view.node {
+            Node::Element(el) => el,
+            _ => panic!("template root must be an element"),
+        };
+
+        let mut slots = vec![None; holes.kinds.len()];
+        collect_slots(&mut root, &mut vec![], &holes.kinds, &mut slots);
+        let slots = slots
+            .into_iter()
+            .map(|slot| slot.expect("template hole is not used"))
+            .collect();
+        Rc::make_mut(&mut root.data).template = Some(Rc::new(slots));
+        Template { root }
+    }
+
+    pub fn render<A>(&self, bindings: Vec<Binding<A>>) -> View<A> {
+        let slots = self.root.data.template.clone().expect("template slots");
+        assert_eq!(slots.len(), bindings.len(), "template binding count");
+
+        let mut node = Node::Element(self.root.clone());
+        let mut handler_map = HashMap::new();
+        for ((path, slot), binding) in slots.iter().zip(bindings) {
+            let target = descendant_mut(&mut node, path);
+            match (slot, binding) {
+                (Slot::Text, Binding::Text(text)) => *target = Node::Text(text.into()),
+                (Slot::Attribute(name), Binding::Attribute(value)) => {
+                    if let Node::Element(ref mut el) = *target {
+                        Rc::make_mut(&mut el.data)
+                            .attributes
+                            .push((name.clone(), value));
+                    }
+                }
+                (Slot::Children, Binding::Children(views)) => {
+                    if let Node::Element(ref mut el) = *target {
+                        let data = Rc::make_mut(&mut el.data);
+                        for view in views {
+                            handler_map.extend(view.handler_map);
+                            data.children.push(view.node);
+                        }
+                    }
+                }
+                _ => panic!("template binding does not match its hole"),
+            }
+        }
+        View { node, handler_map }
+    }
+}
+
+fn collect_slots(
+    el: &mut Element,
+    path: &mut Vec<usize>,
+    kinds: &[HoleKind],
+    slots: &mut [Option<(Vec<usize>, Slot)>],
+) {
+    let data = Rc::make_mut(&mut el.data);
+    // Paths address rendered children, which null nodes are not.
+    data.children.retain(|child| !child.is_null());
+    data.attributes.retain(|(name, value)| {
+        let index = match *value {
+            AttributeValue::String(ref s) => hole_index(s),
+            _ => None,
+        };
+        match index {
+            Some(index) if kinds[index] == HoleKind::Attribute => {
+                slots[index] = Some((path.clone(), Slot::Attribute(name.clone())));
+                false
+            }
+            _ => true,
+        }
+    });
+
+    let mut i = 0;
+    while i < data.children.len() {
+        let index = match data.children[i] {
+            Node::Text(ref s) => hole_index(s),
+            _ => None,
+        };
+        match index.map(|index| (index, kinds[index])) {
+            Some((index, HoleKind::Text)) => {
+                data.children[i] = Node::Text("".into());
+                path.push(i);
+                slots[index] = Some((path.clone(), Slot::Text));
+                path.pop();
+            }
+            Some((index, HoleKind::Children)) => {
+                assert_eq!(data.children.len(), 1, "children hole must be the only child");
+                data.children.clear();
+                slots[index] = Some((path.clone(), Slot::Children));
+                break;
+            }
+            _ => {
+                if let Node::Element(ref mut child) = data.children[i] {
+                    path.push(i);
+                    collect_slots(child, path, kinds, slots);
+                    path.pop();
+                }
+            }
+        }
+        i += 1;
+    }
+}
+
+fn descendant_mut<'a>(node: &'a mut Node, path: &[usize]) -> &'a mut Node {
+    path.iter().fold(node, |node, &i| match *node {
+        Node::Element(ref mut el) => &mut Rc::make_mut(&mut el.data).children[i],
+        _ => panic!("template path leads through a non-element"),
+    })
+}
+
+fn descendant<'a>(el: &'a Element, path: &[usize]) -> Option<&'a Element> {
+    path.iter().try_fold(el, |el, &i| match el.children().get(i) {
+        Some(Node::Element(child)) => Some(child),
+        _ => None,
+    })
+}
+
+// Diffs two instances of one template by their holes.
+pub(crate) fn diff_holes(a: &Element, b: &Element, slots: &Slots) -> Vec<Diff> {
+    let mut result = vec![];
+    for (path, slot) in slots.iter() {
+        let mut diffs = vec![];
+        match *slot {
+            Slot::Text => {
+                let (&i, parent) = path.split_last().expect("text hole path");
+                let old = descendant(a, parent).and_then(|el| el.children().get(i));
+                let new = descendant(b, parent).and_then(|el| el.children().get(i));
+                if let (Some(Node::Text(old)), Some(Node::Text(new))) = (old, new) {
+                    if old != new {
+                        diffs.push(Diff::SetText(new.to_string()));
+                    }
+                }
+            }
+            Slot::Attribute(ref name) => {
+                let (old, new) = match (descendant(a, path), descendant(b, path)) {
+                    (Some(old), Some(new)) => (old, new),
+                    _ => continue,
+                };
+                let named = |el: &Element| -> Vec<Attribute> {
+                    el.attributes()
+                        .iter()
+                        .filter(|&(k, _)| k == name)
+                        .cloned()
+                        .collect()
+                };
+                diffs = diff_attributes(&mut named(old), &named(new));
+            }
+            Slot::Children => {
+                if let (Some(old), Some(new)) = (descendant(a, path), descendant(b, path)) {
+                    diffs = diff_children(&mut old.children().to_vec(), new.children(), &mut 0);
+                }
+            }
+        }
+        if !diffs.is_empty() {
+            result.push(Diff::PatchPath(path.clone(), diffs));
+        }
+    }
+    result
+}
@@ -0,0 +1,282 @@
+//! A runtime without a document, for driving apps from tests.
+
+use super::{
+    log, App, Diff, Env, Fetch, HandlerArg, KeyValueStorage, MediaControl, Node, Politeness,
+    Runtime, ScrollBehavior, SubscriptionKind, Task,
+};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+type Scrolls = Rc<RefCell<Vec<(Vec<usize>, ScrollBehavior)>>>;
+
+/// Renders synchronously on every state change and records what a real
+/// runtime would receive. Spawned tasks are kept, not polled.
+/// Warnings go to stderr unless a log sink is set.
+pub struct TestRuntime<A: App> {
+    env: Env<A>,
+    diffs: Rc<RefCell<Vec<Diff>>>,
+    root_diffs: Rc<RefCell<Vec<(String, Diff)>>>,
+    tasks: Rc<RefCell<Vec<Task>>>,
+    announcements: Rc<RefCell<Vec<(String, Politeness)>>>,
+    media_controls: Rc<RefCell<Vec<(String, MediaControl)>>>,
+    scrolls: Scrolls,
+    subscriptions: Rc<RefCell<HashMap<String, SubscriptionKind>>>,
+    fetcher: Rc<RefCell<Option<Rc<dyn Fetch>>>>,
+    storage: Rc<RefCell<Option<Rc<dyn KeyValueStorage>>>>,
+    history: Rc<RefCell<Vec<String>>>,
+    title: Rc<RefCell<String>>,
+    favicon: Rc<RefCell<Option<String>>>,
+}
+
+impl<A: App> Clone for TestRuntime<A> {
+    fn clone(&self) -> TestRuntime<A> {
+        TestRuntime {
+            env: self.env.clone(),
+            diffs: self.diffs.clone(),
+            root_diffs: self.root_diffs.clone(),
+            tasks: self.tasks.clone(),
+            announcements: self.announcements.clone(),
+            media_controls: self.media_controls.clone(),
+            scrolls: self.scrolls.clone(),
+            subscriptions: self.subscriptions.clone(),
+            fetcher: self.fetcher.clone(),
+            storage: self.storage.clone(),
+            history: self.history.clone(),
+            title: self.title.clone(),
+            favicon: self.favicon.clone(),
+        }
+    }
+}
+
+impl<A: App> TestRuntime<A> {
+    /// Creates the runtime and renders the initial view.
+    pub fn new(state: A::State) -> TestRuntime<A> {
+        TestRuntime::start(Env::new(state))
+    }
+
+    /// Like `new`, with ids generated from `seed` so that trees and diffs
+    /// are the same on every run.
+    pub fn with_seed(state: A::State, seed: u64) -> TestRuntime<A> {
+        let env = Env::new(state);
+        env.seed_ids(seed);
+        TestRuntime::start(env)
+    }
+
+    /// Like `new`, with an env configured by `Env::builder()`.
+    pub fn with_env(env: Env<A>) -> TestRuntime<A> {
+        TestRuntime::start(env)
+    }
+
+    /// Like `new`, but hydrates markup as `ssr::prerender` would produce
+    /// it instead of rendering from scratch.
+    pub fn hydrated(state: A::State) -> TestRuntime<A> {
+        let runtime = TestRuntime::build(Env::new(state));
+        runtime.hydrate();
+        runtime
+    }
+
+    fn start(env: Env<A>) -> TestRuntime<A> {
+        let runtime = TestRuntime::build(env);
+        runtime.mount();
+        runtime
+    }
+
+    fn build(env: Env<A>) -> TestRuntime<A> {
+        if !log::has_sink() {
+            log::set_sink(log::log_to_stderr);
+        }
+        TestRuntime {
+            env,
+            diffs: Rc::new(RefCell::new(vec![])),
+            root_diffs: Rc::new(RefCell::new(vec![])),
+            tasks: Rc::new(RefCell::new(vec![])),
+            announcements: Rc::new(RefCell::new(vec![])),
+            media_controls: Rc::new(RefCell::new(vec![])),
+            scrolls: Rc::new(RefCell::new(vec![])),
+            subscriptions: Rc::new(RefCell::new(HashMap::new())),
+            fetcher: Rc::new(RefCell::new(None)),
+            storage: Rc::new(RefCell::new(None)),
+            history: Rc::new(RefCell::new(vec![])),
+            title: Rc::new(RefCell::new(String::new())),
+            favicon: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    pub fn state(&self) -> A::State {
+        self.env.get_state()
+    }
+
+    pub fn node(&self) -> Node {
+        self.env.get_node()
+    }
+
+    /// Diffs handled since the last call.
+    pub fn take_diffs(&self) -> Vec<Diff> {
+        self.diffs.borrow_mut().drain(..).collect()
+    }
+
+    /// Diffs of additional roots, tagged by root id, since the last call.
+    pub fn take_root_diffs(&self) -> Vec<(String, Diff)> {
+        self.root_diffs.borrow_mut().drain(..).collect()
+    }
+
+    /// Tasks spawned since the last call.
+    pub fn take_tasks(&self) -> Vec<Task> {
+        self.tasks.borrow_mut().drain(..).collect()
+    }
+
+    /// Messages passed to `Cmd::announce` since the last call.
+    pub fn take_announcements(&self) -> Vec<(String, Politeness)> {
+        self.announcements.borrow_mut().drain(..).collect()
+    }
+
+    /// Media controls by element id since the last call.
+    pub fn take_media_controls(&self) -> Vec<(String, MediaControl)> {
+        self.media_controls.borrow_mut().drain(..).collect()
+    }
+
+    /// Paths of elements scrolled into view since the last call.
+    pub fn take_scrolls(&self) -> Vec<(Vec<usize>, ScrollBehavior)> {
+        self.scrolls.borrow_mut().drain(..).collect()
+    }
+
+    /// Serves `Cmd::fetch` from `fetcher`. Without one, fetches fail with
+    /// `FetchError::Unavailable`. Requests run as tasks, see `take_tasks`.
+    pub fn set_fetcher(&self, fetcher: Rc<dyn Fetch>) {
+        *self.fetcher.borrow_mut() = Some(fetcher);
+    }
+
+    /// Backs the storage commands with `storage`, e.g. a `MemoryStorage`.
+    pub fn set_storage(&self, storage: Rc<dyn KeyValueStorage>) {
+        *self.storage.borrow_mut() = Some(storage);
+    }
+
+    /// URLs pushed to the history, the last one as replaced since.
+    pub fn history(&self) -> Vec<String> {
+        self.history.borrow().clone()
+    }
+
+    /// The title last set by `Cmd::set_title`, empty before.
+    pub fn title(&self) -> String {
+        self.title.borrow().clone()
+    }
+
+    pub fn favicon(&self) -> Option<String> {
+        self.favicon.borrow().clone()
+    }
+
+    /// Active subscriptions by id.
+    pub fn subscriptions(&self) -> HashMap<String, SubscriptionKind> {
+        self.subscriptions.borrow().clone()
+    }
+
+    /// Dispatches to every active subscription of `kind`.
+    pub fn emit(&self, kind: &SubscriptionKind, arg: HandlerArg) {
+        let ids: Vec<String> = self
+            .subscriptions
+            .borrow()
+            .iter()
+            .filter(|&(_, k)| k == kind)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in ids {
+            self.dispatch_subscription(&id, arg.clone());
+        }
+    }
+
+    /// Panics when no element matching `selector` handles clicks.
+    pub fn click(&self, selector: &str) {
+        self.expect_trigger(selector, "click", HandlerArg::Null);
+    }
+
+    /// Fires an input event carrying `value`, like the web runtime does.
+    pub fn input(&self, selector: &str, value: &str) {
+        self.expect_trigger(selector, "input", value.into());
+    }
+
+    /// Fires a keydown event carrying the key name.
+    pub fn keydown(&self, selector: &str, key: &str) {
+        self.expect_trigger(selector, "keydown", key.into());
+    }
+
+    fn expect_trigger(&self, selector: &str, kind: &str, arg: HandlerArg) {
+        if !self.trigger(selector, kind, arg) {
+            panic!("no `{}` handler on `{}`", kind, selector);
+        }
+    }
+}
+
+impl<A: App> Runtime<A> for TestRuntime<A> {
+    fn get_env(&self) -> &Env<A> {
+        &self.env
+    }
+
+    fn handle_diff(&self, diff: Diff) {
+        self.diffs.borrow_mut().push(diff);
+    }
+
+    fn handle_root_diff(&self, root: &str, diff: Diff) {
+        self.root_diffs.borrow_mut().push((root.to_string(), diff));
+    }
+
+    fn schedule_render(&self) {
+        self.run();
+    }
+
+    fn subscribe(&self, id: &str, kind: &SubscriptionKind) {
+        self.subscriptions
+            .borrow_mut()
+            .insert(id.to_string(), kind.clone());
+    }
+
+    fn unsubscribe(&self, id: &str) {
+        self.subscriptions.borrow_mut().remove(id);
+    }
+
+    fn announce(&self, message: &str, politeness: Politeness) {
+        self.announcements
+            .borrow_mut()
+            .push((message.to_string(), politeness));
+    }
+
+    fn scroll_into_view(&self, path: &[usize], behavior: ScrollBehavior) {
+        self.scrolls.borrow_mut().push((path.to_vec(), behavior));
+    }
+
+    fn set_title(&self, title: &str) {
+        *self.title.borrow_mut() = title.to_string();
+    }
+
+    fn set_favicon(&self, url: &str) {
+        *self.favicon.borrow_mut() = Some(url.to_string());
+    }
+
+    fn push_url(&self, url: &str) {
+        self.history.borrow_mut().push(url.to_string());
+    }
+
+    fn replace_url(&self, url: &str) {
+        let mut history = self.history.borrow_mut();
+        history.pop();
+        history.push(url.to_string());
+    }
+
+    fn fetcher(&self) -> Option<Rc<dyn Fetch>> {
+        self.fetcher.borrow().clone()
+    }
+
+    fn storage(&self) -> Option<Rc<dyn KeyValueStorage>> {
+        self.storage.borrow().clone()
+    }
+
+    fn control_media(&self, id: &str, control: MediaControl) {
+        self.media_controls
+            .borrow_mut()
+            .push((id.to_string(), control));
+    }
+
+    fn spawn(&self, task: Task) {
+        self.tasks.borrow_mut().push(task);
+    }
+}
@@ -0,0 +1,174 @@
+//! Short-lived notifications stacked above the page.
+
+use std::time::Duration;
+use subscription::Subscription;
+use {handler, keyed_list, View};
+
+/// Where a toast is in its life, shown as its `data-state` so that CSS
+/// transitions can animate it in and out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ToastState {
+    /// Rendered once before the next tick, to transition from.
+    Entering,
+    Shown,
+    /// Kept for the leave transition, then removed.
+    Leaving,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Toast {
+    id: u64,
+    message: String,
+    state: ToastState,
+    // Tick time the current state started at, unknown until the next tick.
+    since: Option<f64>,
+}
+
+impl Toast {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn state(&self) -> ToastState {
+        self.state
+    }
+}
+
+/// The queue of toasts, kept in the state and advanced by the ticks of
+/// `Toasts::subscription`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Toasts {
+    toasts: Vec<Toast>,
+    next_id: u64,
+    duration: f64,
+    transition: f64,
+}
+
+impl Toasts {
+    /// Toasts are shown for `duration`, then leave over `transition`, which
+    /// should match the CSS transition.
+    pub fn new(duration: Duration, transition: Duration) -> Toasts {
+        Toasts {
+            toasts: vec![],
+            next_id: 0,
+            duration: duration.as_secs_f64() * 1000.0,
+            transition: transition.as_secs_f64() * 1000.0,
+        }
+    }
+
+    /// Toasts in the order they were pushed, including leaving ones.
+    pub fn toasts(&self) -> &[Toast] {
+        &self.toasts
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.toasts.is_empty()
+    }
+
+    /// Queues `message` and returns the toast's id.
+    pub fn push<S: Into<String>>(&mut self, message: S) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.toasts.push(Toast {
+            id,
+            message: message.into(),
+            state: ToastState::Entering,
+            since: None,
+        });
+        id
+    }
+
+    /// Has the toast leave before its time is up.
+    pub fn dismiss(&mut self, id: u64) {
+        for toast in self.toasts.iter_mut() {
+            if toast.id == id && toast.state != ToastState::Leaving {
+                toast.state = ToastState::Leaving;
+                toast.since = None;
+            }
+        }
+    }
+
+    /// Advances the toasts to `now`, the time in milliseconds carried by a
+    /// tick of `subscription`.
+    pub fn tick(&mut self, now: f64) {
+        let (duration, transition) = (self.duration, self.transition);
+        for toast in self.toasts.iter_mut() {
+            let since = *toast.since.get_or_insert(now);
+            match toast.state {
+                ToastState::Entering => {
+                    toast.state = ToastState::Shown;
+                    toast.since = Some(now);
+                }
+                ToastState::Shown if now - since >= duration => {
+                    toast.state = ToastState::Leaving;
+                    toast.since = Some(now);
+                }
+                _ => {}
+            }
+        }
+        self.toasts.retain(|toast| {
+            toast.state != ToastState::Leaving
+                || toast.since.is_some_and(|since| now - since < transition)
+        });
+    }
+
+    /// Ticks for `tick`: every frame while a toast enters or leaves, every
+    /// `resolution` while toasts are shown, none without toasts.
+    pub fn subscription<A, F>(&self, resolution: Duration, f: F) -> Option<Subscription<A>>
+    where
+        A: 'static,
+        F: Fn(f64) -> A + 'static,
+    {
+        if self.toasts.is_empty() {
+            None
+        } else if self.toasts.iter().all(|t| t.state == ToastState::Shown) {
+            Some(Subscription::every(resolution, f))
+        } else {
+            Some(Subscription::animation_frame(f))
+        }
+    }
+}
+
+/// The toasts as a polite live region, each with a button calling `dismiss`
+/// with its id. Meant for an `App::roots` mount point so that it stacks
+/// above the page.
+pub fn toast_stack<A, F>(toasts: &Toasts, dismiss: F) -> View<A>
+where
+    A: 'static,
+    F: Fn(u64) -> A + Clone + 'static,
+{
+    let items = keyed_list(toasts.toasts(), |toast| {
+        let state = match toast.state {
+            ToastState::Entering => "entering",
+            ToastState::Shown => "shown",
+            ToastState::Leaving => "leaving",
+        };
+        let (id, dismiss) = (toast.id, dismiss.clone());
+        let close = View::new(
+            "button",
+            vec![("aria-label".to_string(), "Dismiss".into())],
+            vec![("click".to_string(), handler(move |_| dismiss(id)))],
+            vec![View::static_text("×").into()],
+        );
+        let item = View::new(
+            "li",
+            vec![("data-state".to_string(), state.into())],
+            vec![],
+            vec![View::text(toast.message.clone()).into(), close.into()],
+        );
+        (toast.id, item)
+    });
+    View::new(
+        "ol",
+        vec![
+            ("role".to_string(), "status".into()),
+            ("aria-live".to_string(), "polite".into()),
+        ],
+        vec![],
+        vec![items],
+    )
+}
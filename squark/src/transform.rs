@@ -0,0 +1,60 @@
+//! Passes over whole trees.
+
+use super::{Attribute, Element, Handler, Node};
+use std::mem;
+use std::rc::Rc;
+
+impl Node {
+    /// Calls `visit` with this node and every descendant, parents first.
+    pub fn walk<F: FnMut(&Node)>(&self, mut visit: F) {
+        walk(self, &mut visit);
+    }
+
+    /// Rebuilds the tree bottom-up: children are transformed before the
+    /// element holding them is passed to `f`.
+    pub fn map_transform<F: FnMut(Node) -> Node>(self, mut f: F) -> Node {
+        transform(self, &mut f)
+    }
+}
+
+fn walk(node: &Node, visit: &mut dyn FnMut(&Node)) {
+    visit(node);
+    if let Node::Element(ref el) = *node {
+        for child in el.children() {
+            walk(child, visit);
+        }
+    }
+}
+
+fn transform(node: Node, f: &mut dyn FnMut(Node) -> Node) -> Node {
+    let node = match node {
+        Node::Element(mut el) => {
+            if !el.children().is_empty() {
+                let data = el.data_mut();
+                let children = mem::take(&mut data.children);
+                data.children = children.into_iter().map(|c| transform(c, f)).collect();
+            }
+            Node::Element(el)
+        }
+        node => node,
+    };
+    f(node)
+}
+
+impl Element {
+    // Shared data is copied first. Edited elements lose their template, whose
+    // holes would no longer describe them.
+    pub(crate) fn data_mut(&mut self) -> &mut super::ElementData {
+        let data = Rc::make_mut(&mut self.data);
+        data.template = None;
+        data
+    }
+
+    pub fn attributes_mut(&mut self) -> &mut Vec<Attribute> {
+        &mut self.data_mut().attributes
+    }
+
+    pub fn handlers_mut(&mut self) -> &mut Vec<Handler> {
+        &mut self.data_mut().handlers
+    }
+}
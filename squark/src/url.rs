@@ -0,0 +1,150 @@
+//! URLs for attributes that navigate or load, like `href` and `src`.
+
+use std::cell::RefCell;
+use std::fmt;
+use AttributeValue;
+
+/// Attributes holding a URL, checked by `ssr` against the blocked schemes.
+pub const URL_ATTRIBUTES: &[&str] = &["href", "src", "action", "formaction", "poster", "cite"];
+
+thread_local! {
+    static BLOCKED_SCHEMES: RefCell<Vec<String>> = RefCell::new(vec!["javascript".to_string()]);
+}
+
+/// Sets the schemes `Url::new` rejects and `ssr` drops from URL attributes,
+/// `javascript` by default. Schemes are compared ignoring case.
+pub fn set_blocked_url_schemes(schemes: &[&str]) {
+    let schemes = schemes.iter().map(|s| s.to_ascii_lowercase()).collect();
+    BLOCKED_SCHEMES.with(|blocked| *blocked.borrow_mut() = schemes);
+}
+
+// Named references which may spell out a scheme or end one.
+const NAMED_REFERENCES: &[(&str, char)] = &[
+    ("colon", ':'),
+    ("Tab", '\t'),
+    ("NewLine", '\n'),
+    ("sol", '/'),
+    ("quest", '?'),
+    ("num", '#'),
+    ("amp", '&'),
+    ("lt", '<'),
+    ("gt", '>'),
+    ("quot", '"'),
+    ("apos", '\''),
+    ("nbsp", '\u{a0}'),
+];
+
+// The character referenced at the start of `s`, which starts with `&`, and
+// the length of the reference. Numeric ones may omit the `;`.
+fn reference(s: &str) -> Option<(char, usize)> {
+    let body = &s[1..];
+    if let Some(number) = body.strip_prefix('#') {
+        let (radix, digits) = match number.strip_prefix(&['x', 'X'][..]) {
+            Some(hex) => (16, hex),
+            None => (10, number),
+        };
+        let len = digits
+            .find(|c: char| !c.is_digit(radix))
+            .unwrap_or(digits.len());
+        if len == 0 {
+            return None;
+        }
+        let c = u32::from_str_radix(&digits[..len], radix)
+            .ok()
+            .and_then(std::char::from_u32)
+            .filter(|&c| c != '\0')
+            .unwrap_or('\u{fffd}');
+        let end = s.len() - digits.len() + len;
+        let end = if s[end..].starts_with(';') {
+            end + 1
+        } else {
+            end
+        };
+        return Some((c, end));
+    }
+    NAMED_REFERENCES
+        .iter()
+        .find(|&&(name, _)| body.starts_with(name) && body[name.len()..].starts_with(';'))
+        .map(|&(name, c)| (c, name.len() + 2))
+}
+
+/// Decodes the character references of markup, as in `&#58;` or `&colon;`.
+/// Unknown named references are kept as they are.
+pub(crate) fn decode_references(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        match reference(rest) {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The lowercased scheme of `url` as browsers read it, or `None` for
+/// relative URLs. Character references are decoded, as the URL may come
+/// from markup, and whitespace and control characters skipped, as in
+/// `java\tscript:` or `javascript&colon;`.
+pub(crate) fn scheme(url: &str) -> Option<String> {
+    let url: String = decode_references(url)
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect();
+    match url.find(&[':', '/', '?', '#'][..]) {
+        Some(i) if url[i..].starts_with(':') => Some(url[..i].to_ascii_lowercase()),
+        _ => None,
+    }
+}
+
+pub(crate) fn is_blocked(url: &str) -> bool {
+    match scheme(url) {
+        Some(scheme) => BLOCKED_SCHEMES.with(|blocked| blocked.borrow().contains(&scheme)),
+        None => false,
+    }
+}
+
+/// A URL whose scheme is not blocked. See `href` and `src`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Url(String);
+
+impl Url {
+    pub fn new<S: Into<String>>(url: S) -> Result<Url, BlockedUrl> {
+        let url = url.into();
+        if is_blocked(&url) {
+            return Err(BlockedUrl(url));
+        }
+        Ok(Url(url))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<Url> for AttributeValue {
+    fn from(url: Url) -> AttributeValue {
+        AttributeValue::String(url.0)
+    }
+}
+
+/// A URL with a blocked scheme, holding the URL.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockedUrl(pub String);
+
+impl fmt::Display for BlockedUrl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the scheme of \"{}\" is blocked", self.0)
+    }
+}
+
+impl std::error::Error for BlockedUrl {}
@@ -0,0 +1,153 @@
+#[cfg(all(debug_assertions, feature = "debug-checks"))]
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt;
+#[cfg(all(debug_assertions, feature = "debug-checks"))]
+use warn;
+use {Element, Node, Tag};
+
+/// Markup browsers would not take as it is. Debug builds check views as
+/// they are built and warn about each violation the first time it is found,
+/// rather than on every render.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Violation {
+    /// `child` is not permitted directly inside `parent`, browsers would move it.
+    InvalidChild(String, String),
+    DuplicateId(String),
+    /// A void element such as `<br>` was given children, which are dropped.
+    VoidChildren(String),
+    /// Siblings share a key, so diffing cannot tell them apart.
+    DuplicateKey(String),
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Violation::InvalidChild(parent, child) => {
+                write!(f, "<{}> is not allowed inside <{}>", child, parent)
+            }
+            Violation::DuplicateId(id) => write!(f, "id \"{}\" is used more than once", id),
+            Violation::VoidChildren(name) => write!(f, "<{}> cannot have children", name),
+            Violation::DuplicateKey(key) => write!(f, "key \"{}\" is used by siblings", key),
+        }
+    }
+}
+
+#[cfg(all(debug_assertions, feature = "debug-checks"))]
+thread_local! {
+    static REPORTED: RefCell<HashSet<Violation>> = RefCell::new(HashSet::new());
+}
+
+#[cfg(all(debug_assertions, feature = "debug-checks"))]
+pub(crate) fn warn_once(violation: Violation) {
+    let message = violation.to_string();
+    if REPORTED.with(|reported| reported.borrow_mut().insert(violation)) {
+        warn(&message);
+    }
+}
+
+const BLOCKS: &[&str] = &[
+    "address", "article", "aside", "blockquote", "details", "div", "dl", "fieldset",
+    "figcaption", "figure", "footer", "form", "h1", "h2", "h3", "h4", "h5", "h6", "header",
+    "hgroup", "hr", "main", "menu", "nav", "ol", "p", "pre", "section", "table", "ul",
+];
+
+// Parents an element must be placed in, for elements which have such a restriction.
+fn required_parents(child: &str) -> Option<&'static [&'static str]> {
+    let parents: &'static [&'static str] = match child {
+        "li" => &["ul", "ol", "menu"],
+        "dt" | "dd" => &["dl", "div"],
+        "tr" => &["thead", "tbody", "tfoot"],
+        "td" | "th" => &["tr"],
+        "thead" | "tbody" | "tfoot" | "caption" | "colgroup" => &["table"],
+        "col" => &["colgroup"],
+        "option" => &["select", "datalist", "optgroup"],
+        "optgroup" => &["select"],
+        "summary" => &["details"],
+        "figcaption" => &["figure"],
+        "legend" => &["fieldset"],
+        _ => return None,
+    };
+    Some(parents)
+}
+
+fn is_allowed(parent: &str, child: &str) -> bool {
+    if let Some(parents) = required_parents(child) {
+        if !parents.contains(&parent) {
+            return false;
+        }
+    }
+    match parent {
+        "p" => !BLOCKS.contains(&child),
+        "a" => child != "a",
+        "button" => child != "button",
+        "form" => child != "form",
+        "ul" | "ol" | "menu" => child == "li" || child == "script" || child == "template",
+        "table" => !["tr", "td", "th"].contains(&child),
+        "select" => ["option", "optgroup", "hr"].contains(&child),
+        _ => true,
+    }
+}
+
+/// Checks the direct children of an element named `parent`.
+pub fn check_children(parent: &str, children: &[Node]) -> Vec<Violation> {
+    if Tag::from_name(parent).is_void() && children.iter().any(|c| !c.is_null()) {
+        return vec![Violation::VoidChildren(parent.to_string())];
+    }
+    let mut keys = HashSet::new();
+    let mut violations: Vec<Violation> = children
+        .iter()
+        .filter_map(|child| match child {
+            Node::Element(el) if !is_allowed(parent, el.name()) => Some(
+                Violation::InvalidChild(parent.to_string(), el.name().to_string()),
+            ),
+            _ => None,
+        })
+        .collect();
+    for key in children.iter().filter_map(Node::get_key) {
+        if !keys.insert(key.clone()) {
+            violations.push(Violation::DuplicateKey(key));
+        }
+    }
+    violations
+}
+
+pub fn check_duplicate_ids(node: &Node) -> Vec<Violation> {
+    fn collect(el: &Element, seen: &mut HashSet<String>, result: &mut Vec<Violation>) {
+        if let Some(id) = el.get_string_attribute("id") {
+            if !seen.insert(id.clone()) {
+                result.push(Violation::DuplicateId(id));
+            }
+        }
+        for child in el.children() {
+            if let Node::Element(child) = child {
+                collect(child, seen, result);
+            }
+        }
+    }
+
+    let mut result = vec![];
+    if let Node::Element(el) = node {
+        collect(el, &mut HashSet::new(), &mut result);
+    }
+    result
+}
+
+/// Checks a whole tree for nesting browsers would rewrite and for duplicate ids.
+pub fn validate(node: &Node) -> Vec<Violation> {
+    fn walk(el: &Element, result: &mut Vec<Violation>) {
+        result.append(&mut check_children(el.name(), el.children()));
+        for child in el.children() {
+            if let Node::Element(child) = child {
+                walk(child, result);
+            }
+        }
+    }
+
+    let mut result = vec![];
+    if let Node::Element(el) = node {
+        walk(el, &mut result);
+    }
+    result.append(&mut check_duplicate_ids(node));
+    result
+}
@@ -0,0 +1,213 @@
+//! JSON encoding of trees and diffs, for runtimes applying them elsewhere.
+//!
+//! Nodes are `null`, `{"text"}`, `{"raw"}`, `{"custom", "tag"}` or
+//! `{"id", "key", "tag", "attributes", "handlers", "children", "shadow", "external"}`, and
+//! diffs are arrays of the variant's name followed by its fields.
+
+use custom::Custom;
+use serde_json::Value;
+use std::rc::Rc;
+use {AttributeValue, Diff, Element, HandlerArg, Node, Tag};
+
+fn encode_attribute(value: &AttributeValue) -> Value {
+    match *value {
+        AttributeValue::String(ref s) => Value::from(s.as_str()),
+        AttributeValue::Bool(b) => Value::from(b),
+        AttributeValue::Property(ref v) => json!({ "property": v }),
+    }
+}
+
+fn decode_attribute(value: &Value) -> Option<AttributeValue> {
+    match *value {
+        Value::String(ref s) => Some(AttributeValue::String(s.clone())),
+        Value::Bool(b) => Some(AttributeValue::Bool(b)),
+        _ => value
+            .get("property")
+            .map(|v| AttributeValue::Property(v.clone())),
+    }
+}
+
+fn string(value: &Value) -> Option<String> {
+    value.as_str().map(|s| s.to_string())
+}
+
+fn index(value: &Value) -> Option<usize> {
+    value.as_u64().map(|i| i as usize)
+}
+
+fn pairs(value: &Value) -> Option<Vec<(String, String)>> {
+    value
+        .as_array()?
+        .iter()
+        .map(|pair| Some((string(&pair[0])?, string(&pair[1])?)))
+        .collect()
+}
+
+fn encode_diffs(diffs: &[Diff]) -> Value {
+    Value::from(diffs.iter().map(Diff::to_json).collect::<Vec<_>>())
+}
+
+fn decode_diffs(value: &Value) -> Option<Vec<Diff>> {
+    value.as_array()?.iter().map(Diff::from_json).collect()
+}
+
+impl Node {
+    pub fn to_json(&self) -> HandlerArg {
+        match *self {
+            Node::Null => Value::Null,
+            Node::Text(ref text) => json!({ "text": text }),
+            Node::Raw(ref html) => json!({ "raw": html }),
+            Node::Custom(ref custom) => {
+                json!({ "custom": custom.name(), "tag": custom.tag().name() })
+            }
+            Node::Element(ref el) => {
+                let attributes: Vec<Value> = el
+                    .attributes()
+                    .iter()
+                    .map(|(k, v)| json!([k, encode_attribute(v)]))
+                    .collect();
+                let children: Vec<Value> = el.children().iter().map(Node::to_json).collect();
+                json!({
+                    "id": el.node_id(),
+                    "key": el.key(),
+                    "tag": el.name(),
+                    "attributes": attributes,
+                    "handlers": el.handlers(),
+                    "children": children,
+                    "shadow": el.has_shadow_root(),
+                    "external": el.is_external(),
+                })
+            }
+        }
+    }
+
+    /// Reads a node written by `to_json`, `None` if it is malformed.
+    pub fn from_json(value: &HandlerArg) -> Option<Node> {
+        if value.is_null() {
+            return Some(Node::Null);
+        }
+        if let Some(text) = value.get("text") {
+            return Some(Node::Text(string(text)?.into()));
+        }
+        if let Some(html) = value.get("raw") {
+            return Some(Node::Raw(string(html)?));
+        }
+        let tag = Tag::from_name(value.get("tag")?.as_str()?);
+        if let Some(name) = value.get("custom") {
+            return Some(Node::Custom(Custom::new(tag, string(name)?)));
+        }
+        let attributes = value
+            .get("attributes")?
+            .as_array()?
+            .iter()
+            .map(|pair| Some((string(&pair[0])?, decode_attribute(&pair[1])?)))
+            .collect::<Option<Vec<_>>>()?;
+        let handlers = pairs(value.get("handlers")?)?;
+        let children = value
+            .get("children")?
+            .as_array()?
+            .iter()
+            .map(Node::from_json)
+            .collect::<Option<Vec<_>>>()?;
+        let mut el = Element::new(tag, attributes, handlers, children);
+        if value.get("shadow")?.as_bool()? {
+            Rc::make_mut(&mut el.data).shadow = true;
+        }
+        if let Some(external) = value.get("external") {
+            Rc::make_mut(&mut el.data).external = external.as_bool()?;
+        }
+        if let Some(key) = value.get("key").filter(|key| !key.is_null()) {
+            Rc::make_mut(&mut el.data).key = Some(string(key)?);
+        }
+        if let Some(id) = value.get("id") {
+            el.data.id.set(id.as_u64()?);
+        }
+        Some(Node::Element(el))
+    }
+}
+
+impl Diff {
+    pub fn to_json(&self) -> HandlerArg {
+        match *self {
+            Diff::SetAttribute(ref name, ref value) => {
+                json!(["SetAttribute", name, encode_attribute(value)])
+            }
+            Diff::RemoveAttribute(ref name) => json!(["RemoveAttribute", name]),
+            Diff::AddChild(i, ref node) => json!(["AddChild", i, node.to_json()]),
+            Diff::AddChildren(i, ref nodes) => {
+                let nodes: Vec<Value> = nodes.iter().map(Node::to_json).collect();
+                json!(["AddChildren", i, nodes])
+            }
+            Diff::ReplaceChild(i, ref node) => json!(["ReplaceChild", i, node.to_json()]),
+            Diff::RemoveChild(i) => json!(["RemoveChild", i]),
+            Diff::RemoveChildren(i, count) => json!(["RemoveChildren", i, count]),
+            Diff::ClearChildren => json!(["ClearChildren"]),
+            Diff::PatchChild(i, ref diffs) => json!(["PatchChild", i, encode_diffs(diffs)]),
+            Diff::PatchNode(id, ref diffs) => json!(["PatchNode", id, encode_diffs(diffs)]),
+            Diff::PatchKeyed(ref key, ref diffs) => {
+                json!(["PatchKeyed", key, encode_diffs(diffs)])
+            }
+            Diff::ReplaceKeyed(ref key, ref node) => json!(["ReplaceKeyed", key, node.to_json()]),
+            Diff::RemoveKeyed(ref key) => json!(["RemoveKeyed", key]),
+            Diff::ReplaceRoot(ref node) => json!(["ReplaceRoot", node.to_json()]),
+            Diff::PatchRoot(ref diffs) => json!(["PatchRoot", encode_diffs(diffs)]),
+            Diff::PatchShadow(ref diffs) => json!(["PatchShadow", encode_diffs(diffs)]),
+            Diff::PatchPath(ref path, ref diffs) => {
+                json!(["PatchPath", path, encode_diffs(diffs)])
+            }
+            Diff::SetText(ref text) => json!(["SetText", text]),
+            Diff::SetHandler(ref kind, ref id) => json!(["SetHandler", kind, id]),
+            Diff::RemoveHandler(ref kind, ref id) => json!(["RemoveHandler", kind, id]),
+            Diff::SaveScroll(ref key) => json!(["SaveScroll", key]),
+            Diff::RestoreScroll(ref key) => json!(["RestoreScroll", key]),
+            Diff::InjectStyle(ref class, ref rule) => json!(["InjectStyle", class, rule]),
+            Diff::DelegateEvents(ref kinds) => json!(["DelegateEvents", kinds]),
+            Diff::SetProperty(ref name, ref value) => json!(["SetProperty", name, value]),
+            Diff::RemoveProperty(ref name) => json!(["RemoveProperty", name]),
+        }
+    }
+
+    /// Reads a diff written by `to_json`, `None` if it is malformed.
+    pub fn from_json(value: &HandlerArg) -> Option<Diff> {
+        let a = &value[1];
+        let b = &value[2];
+        let diff = match value[0].as_str()? {
+            "SetAttribute" => Diff::SetAttribute(string(a)?, decode_attribute(b)?),
+            "RemoveAttribute" => Diff::RemoveAttribute(string(a)?),
+            "AddChild" => Diff::AddChild(index(a)?, Node::from_json(b)?),
+            "AddChildren" => {
+                let nodes = b.as_array()?.iter().map(Node::from_json).collect::<Option<_>>()?;
+                Diff::AddChildren(index(a)?, nodes)
+            }
+            "ReplaceChild" => Diff::ReplaceChild(index(a)?, Node::from_json(b)?),
+            "RemoveChild" => Diff::RemoveChild(index(a)?),
+            "RemoveChildren" => Diff::RemoveChildren(index(a)?, index(b)?),
+            "ClearChildren" => Diff::ClearChildren,
+            "PatchChild" => Diff::PatchChild(index(a)?, decode_diffs(b)?),
+            "PatchNode" => Diff::PatchNode(a.as_u64()?, decode_diffs(b)?),
+            "PatchKeyed" => Diff::PatchKeyed(string(a)?, decode_diffs(b)?),
+            "ReplaceKeyed" => Diff::ReplaceKeyed(string(a)?, Node::from_json(b)?),
+            "RemoveKeyed" => Diff::RemoveKeyed(string(a)?),
+            "ReplaceRoot" => Diff::ReplaceRoot(Node::from_json(a)?),
+            "PatchRoot" => Diff::PatchRoot(decode_diffs(a)?),
+            "PatchShadow" => Diff::PatchShadow(decode_diffs(a)?),
+            "PatchPath" => {
+                let path = a.as_array()?.iter().map(index).collect::<Option<_>>()?;
+                Diff::PatchPath(path, decode_diffs(b)?)
+            }
+            "SetText" => Diff::SetText(string(a)?),
+            "SetHandler" => Diff::SetHandler(string(a)?, string(b)?),
+            "RemoveHandler" => Diff::RemoveHandler(string(a)?, string(b)?),
+            "SaveScroll" => Diff::SaveScroll(string(a)?),
+            "RestoreScroll" => Diff::RestoreScroll(string(a)?),
+            "InjectStyle" => Diff::InjectStyle(string(a)?, string(b)?),
+            "DelegateEvents" => {
+                Diff::DelegateEvents(a.as_array()?.iter().map(string).collect::<Option<_>>()?)
+            }
+            "SetProperty" => Diff::SetProperty(string(a)?, b.clone()),
+            "RemoveProperty" => Diff::RemoveProperty(string(a)?),
+            _ => return None,
+        };
+        Some(diff)
+    }
+}
@@ -0,0 +1,159 @@
+extern crate squark;
+
+use squark::a11y::{audit, A11yWarning, Issue};
+use squark::ssr::render_to_string;
+use squark::{focus_trap, handler, Aria, Attribute, View};
+
+fn view(name: &str, attributes: &[(&str, &str)], children: Vec<View<()>>) -> View<()> {
+    let attributes: Vec<Attribute> = attributes
+        .iter()
+        .map(|&(name, value)| (name.to_string(), value.into()))
+        .collect();
+    let children = children.into_iter().map(View::into).collect();
+    View::new(name, attributes, vec![], children)
+}
+
+// The issues audited on the only child of a `main`, at path `[0]`.
+fn issues(child: View<()>) -> Vec<Issue> {
+    let page = view("main", &[], vec![child]);
+    audit(page.node())
+        .into_iter()
+        .map(|warning| {
+            assert_eq!(warning.path, vec![0]);
+            warning.issue
+        })
+        .collect()
+}
+
+#[test]
+fn focus_traps_mark_their_element() {
+    let close = View::new("button", vec![], vec![], vec![]);
+    let dialog = focus_trap(View::<()>::new(
+        "div",
+        vec![("role".to_string(), "dialog".into())],
+        vec![],
+        vec![close.into()],
+    ));
+    assert_eq!(
+        render_to_string(dialog.node()),
+        "<div role=\"dialog\" data-focus-trap><button></button></div>"
+    );
+}
+
+#[test]
+#[should_panic(expected = "focus_trap needs an element")]
+fn focus_traps_need_an_element() {
+    focus_trap(View::<()>::text("Saved"));
+}
+
+#[test]
+fn audits_accessible_trees_cleanly() {
+    let page = view(
+        "main",
+        &[],
+        vec![
+            view("img", &[("alt", "Logo")], vec![]),
+            view("button", &[("aria-pressed", "true")], vec!["Bold".into()]),
+            view(
+                "div",
+                &[("role", "button"), ("aria-label", "Close")],
+                vec![],
+            ),
+            view("button", &[], vec![view("img", &[("alt", "Menu")], vec![])]),
+            view(
+                "div",
+                &[("role", "checkbox"), ("aria-checked", "false")],
+                vec![],
+            ),
+            view("p", &[("aria-hidden", "true")], vec!["Decoration".into()]),
+        ],
+    );
+    assert_eq!(audit(page.node()), vec![]);
+}
+
+#[test]
+fn finds_images_without_alt_text() {
+    assert_eq!(issues(view("img", &[], vec![])), vec![Issue::MissingAlt]);
+    assert_eq!(issues(view("img", &[("alt", "")], vec![])), vec![]);
+}
+
+#[test]
+fn finds_buttons_without_names() {
+    assert_eq!(
+        issues(view("button", &[], vec![" ".into()])),
+        vec![Issue::MissingAccessibleName]
+    );
+    assert_eq!(
+        issues(view("span", &[("role", "button"), ("title", "")], vec![])),
+        vec![Issue::MissingAccessibleName]
+    );
+}
+
+#[test]
+fn finds_unknown_roles_and_attributes() {
+    assert_eq!(
+        issues(view("div", &[("role", "buton")], vec![])),
+        vec![Issue::UnknownRole("buton".to_string())]
+    );
+    assert_eq!(
+        issues(view("div", &[("aria-lable", "Close")], vec![])),
+        vec![Issue::UnknownAriaAttribute("aria-lable".to_string())]
+    );
+}
+
+#[test]
+fn finds_attributes_the_role_does_not_support() {
+    assert_eq!(
+        issues(view(
+            "div",
+            &[("role", "tab"), ("aria-pressed", "true")],
+            vec![]
+        )),
+        vec![Issue::UnsupportedAriaAttribute(
+            "tab".to_string(),
+            Aria::Pressed
+        )]
+    );
+}
+
+#[test]
+fn finds_click_handlers_on_non_interactive_elements() {
+    let clickable = |attributes: Vec<Attribute>| {
+        View::<()>::new(
+            "div",
+            attributes,
+            vec![("click".to_string(), handler(|_| ()))],
+            vec![],
+        )
+    };
+    assert_eq!(
+        issues(clickable(vec![])),
+        vec![Issue::NonInteractiveClickHandler]
+    );
+    assert_eq!(
+        issues(clickable(vec![("tabindex".to_string(), "0".into())])),
+        vec![]
+    );
+}
+
+#[test]
+fn warnings_point_at_the_element() {
+    let page = view(
+        "main",
+        &[],
+        vec![view("p", &[], vec![view("img", &[], vec![])])],
+    );
+    let warnings = audit(page.node());
+    assert_eq!(
+        warnings,
+        vec![A11yWarning {
+            path: vec![0, 0],
+            element: "img".to_string(),
+            issue: Issue::MissingAlt,
+        }]
+    );
+    assert_eq!(
+        warnings[0].to_string(),
+        "<img> at [0, 0]: image has no alt text"
+    );
+}
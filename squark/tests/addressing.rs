@@ -0,0 +1,100 @@
+extern crate squark;
+
+use squark::testing::TestRuntime;
+use squark::{Addressing, App, Diff, Env, Node, Runtime, View};
+
+#[derive(Clone, Debug)]
+enum Action {
+    Remove(&'static str),
+    Rename(&'static str, &'static str),
+}
+
+#[derive(Clone, Default)]
+struct List;
+
+impl App for List {
+    type State = Vec<(&'static str, &'static str)>;
+    type Action = Action;
+
+    fn reducer(&self, mut items: Self::State, action: Action) -> Self::State {
+        match action {
+            Action::Remove(key) => items.retain(|&(k, _)| k != key),
+            Action::Rename(key, label) => {
+                for item in items.iter_mut().filter(|(k, _)| *k == key) {
+                    item.1 = label;
+                }
+            }
+        }
+        items
+    }
+
+    fn view(&self, items: Self::State) -> View<Action> {
+        let items: Vec<View<Action>> = items
+            .into_iter()
+            .map(|(key, label)| {
+                View::new(
+                    "li",
+                    vec![("key".to_string(), key.into())],
+                    vec![],
+                    vec![View::text(label).into()],
+                )
+            })
+            .collect();
+        View::new("ul", vec![], vec![], vec![items.into()])
+    }
+}
+
+#[test]
+fn keyed_children_are_addressed_by_key() {
+    let env = Env::<List>::builder()
+        .addressing(Addressing::Keyed)
+        .build(vec![("a", "apple"), ("b", "banana"), ("c", "cherry")]);
+    let runtime = TestRuntime::with_env(env);
+    runtime.take_diffs();
+
+    runtime.dispatch(Action::Remove("a"));
+    assert_eq!(
+        runtime.take_diffs(),
+        vec![Diff::PatchRoot(vec![Diff::RemoveKeyed("a".to_string())])]
+    );
+
+    runtime.dispatch(Action::Rename("c", "cranberry"));
+    let text = Diff::ReplaceChild(0, View::<Action>::text("cranberry").node().clone());
+    assert_eq!(
+        runtime.take_diffs(),
+        vec![Diff::PatchRoot(vec![Diff::PatchKeyed(
+            "c".to_string(),
+            vec![text]
+        )])]
+    );
+}
+
+#[test]
+fn patched_elements_keep_their_ids() {
+    let env = Env::<List>::builder()
+        .addressing(Addressing::StableIds)
+        .build(vec![("a", "apple"), ("b", "banana")]);
+    let runtime = TestRuntime::with_env(env);
+    runtime.take_diffs();
+    let ids = |node: Node| match node {
+        Node::Element(ul) => {
+            let li = match ul.children()[1] {
+                Node::Element(ref li) => li.node_id(),
+                _ => panic!("not an element"),
+            };
+            (ul.node_id(), li)
+        }
+        _ => panic!("not an element"),
+    };
+    let (ul, li) = ids(runtime.get_env().snapshot_node());
+
+    for label in &["blueberry", "blackberry"] {
+        runtime.dispatch(Action::Rename("b", label));
+        let text = Diff::ReplaceChild(0, View::<Action>::text(*label).node().clone());
+        assert_eq!(
+            runtime.take_diffs(),
+            vec![Diff::PatchNode(ul, vec![Diff::PatchNode(li, vec![text])])]
+        );
+        assert_eq!(ids(runtime.get_env().snapshot_node()), (ul, li));
+    }
+}
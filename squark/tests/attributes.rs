@@ -0,0 +1,80 @@
+extern crate squark;
+
+use squark::{aria, attr_if, data, Aria, Attribute, AttributeValue, Node, View};
+
+#[test]
+fn data_attributes_are_prefixed() {
+    assert_eq!(
+        data("user-id", "42"),
+        ("data-user-id".to_string(), "42".into())
+    );
+    assert_eq!(
+        data("open", true),
+        ("data-open".to_string(), AttributeValue::Bool(true))
+    );
+}
+
+#[test]
+fn aria_attributes_use_their_html_names() {
+    assert_eq!(
+        aria(Aria::Label, "Close"),
+        ("aria-label".to_string(), "Close".into())
+    );
+    assert_eq!(
+        aria(Aria::DescribedBy, "hint"),
+        ("aria-describedby".to_string(), "hint".into())
+    );
+    assert_eq!(
+        aria(Aria::Expanded, false),
+        ("aria-expanded".to_string(), AttributeValue::Bool(false))
+    );
+
+    assert_eq!(Aria::from_name("aria-valuenow"), Some(Aria::ValueNow));
+    assert_eq!(Aria::from_name("aria-nothing"), None);
+    assert_eq!(Aria::from_name("label"), None);
+}
+
+#[test]
+fn helpers_render_as_attributes() {
+    let view: View<()> = View::new(
+        "button",
+        vec![data("action", "close"), aria(Aria::Label, "Close")],
+        vec![],
+        vec![],
+    );
+    assert_eq!(
+        squark::ssr::render_to_string(view.node()),
+        r#"<button data-action="close" aria-label="Close"></button>"#
+    );
+}
+
+#[test]
+fn attr_if_gives_the_attribute_only_when_the_condition_holds() {
+    assert_eq!(
+        attr_if(true, "class", "done"),
+        Some(("class".to_string(), "done".into()))
+    );
+    assert_eq!(attr_if(false, "class", "done"), None);
+
+    let attributes = |done: bool| -> Vec<Attribute> {
+        vec![data("id", "1")]
+            .into_iter()
+            .chain(attr_if(done, "class", "done"))
+            .collect()
+    };
+    assert_eq!(attributes(false), vec![data("id", "1")]);
+    assert_eq!(
+        attributes(true),
+        vec![data("id", "1"), ("class".to_string(), "done".into())]
+    );
+}
+
+#[test]
+fn when_builds_the_view_only_when_the_condition_holds() {
+    let banner = || View::new("p", vec![], vec![], vec!["Saved".into()]);
+    let shown: View<()> = View::when(true, banner);
+    assert_eq!(squark::ssr::render_to_string(shown.node()), "<p>Saved</p>");
+
+    let hidden: View<()> = View::when(false, || -> View<()> { panic!("built") });
+    assert_eq!(*hidden.node(), Node::Null);
+}
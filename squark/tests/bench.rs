@@ -0,0 +1,19 @@
+extern crate squark;
+
+use squark::bench;
+use squark::proptest_support::diff;
+use squark::View;
+
+#[test]
+fn workloads_expect_what_diffing_produces() {
+    for workload in bench::workloads(50) {
+        let (before, after): (View<()>, View<()>) = workload.views();
+        assert_eq!(
+            diff(before.node(), after.node()),
+            workload.expected,
+            "{}",
+            workload.name
+        );
+    }
+    assert_eq!(bench::rows(bench::ROWS).len(), bench::ROWS);
+}
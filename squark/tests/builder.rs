@@ -0,0 +1,106 @@
+extern crate squark;
+
+use squark::testing::TestRuntime;
+use squark::{
+    handler, App, Cmd, Env, KeyValueStorage, MemoryStorage, RenderSchedule, Runtime, View,
+};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[derive(Clone, Debug)]
+enum Action {
+    Increment,
+    Decrement,
+}
+
+#[derive(Clone, Default)]
+struct Counter;
+
+impl App for Counter {
+    type State = i32;
+    type Action = Action;
+
+    fn reducer(&self, count: i32, action: Action) -> i32 {
+        match action {
+            Action::Increment => count + 1,
+            Action::Decrement => count - 1,
+        }
+    }
+
+    fn command(&self, count: &i32, _: &Action) -> Cmd<Action> {
+        Cmd::storage_set("count", count.to_string())
+    }
+
+    fn view(&self, count: i32) -> View<Action> {
+        let button = |class: &str, action: fn() -> Action| {
+            View::new(
+                "button",
+                vec![("class".to_string(), class.into())],
+                vec![("click".to_string(), handler(move |_| action()))],
+                vec![],
+            )
+            .into()
+        };
+        View::new(
+            "div",
+            vec![],
+            vec![],
+            vec![
+                View::text(count.to_string()).into(),
+                button("inc", || Action::Increment),
+                button("dec", || Action::Decrement),
+            ],
+        )
+    }
+}
+
+#[test]
+fn built_envs_apply_their_options() {
+    let storage = Rc::new(MemoryStorage::new());
+    let env = Env::<Counter>::builder()
+        .seed_ids(7)
+        .middleware(|count, action| match action {
+            Action::Decrement if *count == 0 => None,
+            action => Some(action),
+        })
+        .storage(storage.clone())
+        .schedule(RenderSchedule::Manual)
+        .build(0);
+    let runtime = TestRuntime::with_env(env);
+    runtime.take_diffs();
+
+    runtime.click("button.dec");
+    assert_eq!(runtime.state(), 0);
+    assert_eq!(storage.get("count"), None);
+
+    runtime.click("button.inc");
+    assert_eq!(runtime.state(), 1);
+    assert_eq!(storage.get("count"), Some("1".to_string()));
+    assert!(runtime.get_env().is_render_scheduled());
+    assert!(runtime.take_diffs().is_empty());
+
+    runtime.run();
+    assert!(!runtime.get_env().is_render_scheduled());
+    assert_eq!(runtime.take_diffs().len(), 1);
+}
+
+#[test]
+fn global_log_sinks_take_the_warnings_of_every_env() {
+    use squark::log::{self, Level};
+
+    let logged = Rc::new(RefCell::new(vec![]));
+    let sink = |name: &'static str| {
+        let logged = logged.clone();
+        move |_: Level, message: &str| logged.borrow_mut().push(format!("{}: {}", name, message))
+    };
+    let _first = Env::<Counter>::builder()
+        .global_log_sink(sink("first"))
+        .build(0);
+    let _second = Env::<Counter>::builder()
+        .global_log_sink(sink("second"))
+        .build(0);
+    log::log(Level::Warn, "hello");
+    log::reset_sink();
+
+    assert_eq!(*logged.borrow(), vec!["second: hello".to_string()]);
+}
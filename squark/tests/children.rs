@@ -0,0 +1,112 @@
+extern crate squark;
+
+use squark::{App, Diff, Env, Node, Runtime, SubscriptionKind, Task, View};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+type Items = Vec<Option<&'static str>>;
+
+#[derive(Clone, Default)]
+struct List;
+
+impl App for List {
+    type State = Items;
+    type Action = Items;
+
+    fn reducer(&self, _: Items, items: Items) -> Items {
+        items
+    }
+
+    fn view(&self, items: Items) -> View<Items> {
+        let children = items
+            .into_iter()
+            .map(|item| match item {
+                Some(text) => View::text(text.to_string()).into(),
+                None => View::null().into(),
+            })
+            .collect();
+        View::new("ul".to_string(), vec![], vec![], children)
+    }
+}
+
+#[derive(Clone)]
+struct Recorder {
+    env: Env<List>,
+    diffs: Rc<RefCell<Vec<Diff>>>,
+}
+
+impl Runtime<List> for Recorder {
+    fn get_env(&self) -> &Env<List> {
+        &self.env
+    }
+
+    fn handle_diff(&self, diff: Diff) {
+        self.diffs.borrow_mut().push(diff);
+    }
+
+    fn schedule_render(&self) {}
+
+    fn subscribe(&self, _: &str, _: &SubscriptionKind) {}
+
+    fn unsubscribe(&self, _: &str) {}
+
+    fn spawn(&self, _: Task) {}
+}
+
+// The ops on the children of the list when going from `from` to `to`.
+fn child_ops(from: Items, to: Items) -> Vec<String> {
+    let runtime = Recorder {
+        env: Env::new(from),
+        diffs: Rc::new(RefCell::new(vec![])),
+    };
+    runtime.run();
+    runtime.diffs.borrow_mut().clear();
+    runtime.dispatch(to);
+    runtime.run();
+
+    let diffs = runtime.diffs.borrow_mut().drain(..).collect::<Vec<_>>();
+    let ops = match diffs.as_slice() {
+        [Diff::PatchRoot(ops)] => ops.clone(),
+        diffs => panic!("one patch of the list expected, got {:?}", diffs),
+    };
+    let add = |i: usize, node: &Node| match node {
+        Node::Text(text) => format!("add {} {}", i, text),
+        node => panic!("unexpected node {:?}", node),
+    };
+    ops.iter()
+        .flat_map(|op| match op {
+            Diff::AddChild(i, node) => vec![add(*i, node)],
+            Diff::AddChildren(i, nodes) => nodes
+                .iter()
+                .enumerate()
+                .map(|(k, node)| add(i + k, node))
+                .collect(),
+            Diff::RemoveChild(i) => vec![format!("remove {}", i)],
+            op => panic!("unexpected op {:?}", op),
+        })
+        .collect()
+}
+
+#[test]
+fn added_children_get_consecutive_indices() {
+    assert_eq!(
+        child_ops(vec![Some("a")], vec![Some("a"), Some("b"), Some("c")]),
+        vec!["add 1 b", "add 2 c"]
+    );
+}
+
+#[test]
+fn null_children_are_not_counted_when_adding() {
+    assert_eq!(
+        child_ops(vec![Some("a"), None], vec![Some("a"), None, Some("b")]),
+        vec!["add 1 b"]
+    );
+}
+
+#[test]
+fn null_children_are_not_counted_when_removing() {
+    assert_eq!(
+        child_ops(vec![Some("a"), None, Some("b")], vec![Some("a"), None]),
+        vec!["remove 1"]
+    );
+}
@@ -0,0 +1,87 @@
+extern crate squark;
+
+use squark::{App, Diff, Env, RenderToken, Runtime, SubscriptionKind, Task, View};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[derive(Clone, Default)]
+struct Counter;
+
+impl App for Counter {
+    type State = i32;
+    type Action = i32;
+
+    fn reducer(&self, count: i32, by: i32) -> i32 {
+        count + by
+    }
+
+    fn view(&self, count: i32) -> View<i32> {
+        View::new(
+            "p",
+            vec![],
+            vec![],
+            vec![View::text(count.to_string()).into()],
+        )
+    }
+}
+
+// Applies renders whenever the test says so.
+#[derive(Clone)]
+struct Deferred {
+    env: Env<Counter>,
+    renders: Rc<RefCell<Vec<(RenderToken, Diff)>>>,
+}
+
+impl Runtime<Counter> for Deferred {
+    fn get_env(&self) -> &Env<Counter> {
+        &self.env
+    }
+
+    fn handle_diff(&self, _: Diff) {}
+
+    fn handle_render(&self, diff: Diff) -> Option<RenderToken> {
+        let token = self.env.render_token();
+        self.renders.borrow_mut().push((token, diff));
+        Some(token)
+    }
+
+    fn schedule_render(&self) {
+        self.run();
+    }
+
+    fn subscribe(&self, _: &str, _: &SubscriptionKind) {}
+
+    fn unsubscribe(&self, _: &str) {}
+
+    fn spawn(&self, _: Task) {}
+}
+
+#[test]
+fn rejected_renders_are_diffed_again_from_the_applied_tree() {
+    let runtime = Deferred {
+        env: Env::new(0),
+        renders: Rc::new(RefCell::new(vec![])),
+    };
+    runtime.mount();
+    let (mounted, _) = runtime.renders.borrow_mut().remove(0);
+    runtime.env.confirm_render(mounted);
+    assert!(!runtime.env.has_pending_renders());
+
+    runtime.dispatch(1);
+    runtime.dispatch(1);
+    let renders = runtime.renders.replace(vec![]);
+    assert_eq!(renders.len(), 2);
+    assert!(runtime.env.has_pending_renders());
+
+    // The first applies, the second fails: the runtime shows "1".
+    assert!(runtime.env.reject_render(renders[1].0));
+    assert!(!runtime.env.has_pending_renders());
+    runtime.run();
+    let retried = runtime.renders.replace(vec![]);
+    assert_eq!(retried.len(), 1);
+    assert_eq!(retried[0].1, renders[1].1);
+
+    runtime.env.confirm_render(retried[0].0);
+    assert!(!runtime.env.has_pending_renders());
+    assert!(!runtime.env.reject_render(retried[0].0));
+}
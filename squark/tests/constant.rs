@@ -0,0 +1,85 @@
+extern crate squark;
+
+use squark::testing::TestRuntime;
+use squark::{handler, App, Diff, Node, Runtime, View};
+use std::cell::Cell;
+
+thread_local! {
+    static BUILDS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn builds() -> usize {
+    BUILDS.with(|b| b.replace(0))
+}
+
+#[derive(Clone, Default)]
+struct Page;
+
+impl App for Page {
+    // The count and whether the footer has a handler.
+    type State = (i32, bool);
+    type Action = ();
+
+    fn reducer(&self, (count, handled): (i32, bool), _: ()) -> (i32, bool) {
+        (count + 1, handled)
+    }
+
+    fn view(&self, (count, handled): (i32, bool)) -> View<()> {
+        let footer = View::constant("footer", || {
+            BUILDS.with(|b| b.set(b.get() + 1));
+            let handlers = if handled {
+                vec![("click".to_string(), handler(|_| ()))]
+            } else {
+                vec![]
+            };
+            View::new("footer", vec![], handlers, vec!["static".into()])
+        });
+        View::new(
+            "div",
+            vec![],
+            vec![],
+            vec![View::text(count.to_string()).into(), footer.into()],
+        )
+    }
+}
+
+#[test]
+fn builds_constants_once_per_env() {
+    let runtime = TestRuntime::<Page>::new((0, false));
+    runtime.dispatch(());
+    runtime.run();
+    runtime.dispatch(());
+    runtime.run();
+    assert_eq!(builds(), 1);
+    assert_eq!(
+        squark::ssr::render_to_string(&runtime.node()),
+        "<div>2<footer>static</footer></div>"
+    );
+
+    TestRuntime::<Page>::new((0, false));
+    assert_eq!(builds(), 1);
+}
+
+#[test]
+fn diffs_skip_constants() {
+    let runtime = TestRuntime::<Page>::new((0, false));
+    runtime.take_diffs();
+    runtime.dispatch(());
+    runtime.run();
+    assert_eq!(
+        runtime.take_diffs(),
+        vec![Diff::PatchRoot(vec![Diff::ReplaceChild(
+            0,
+            Node::Text("1".into())
+        )])]
+    );
+}
+
+#[test]
+fn rebuilds_constants_with_handlers() {
+    let runtime = TestRuntime::<Page>::new((0, true));
+    runtime.click("footer");
+    runtime.click("footer");
+    assert_eq!(runtime.state(), (2, true));
+    assert_eq!(builds(), 3);
+}
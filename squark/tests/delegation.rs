@@ -0,0 +1,132 @@
+extern crate squark;
+
+use squark::testing::TestRuntime;
+use squark::{handler, App, Diff, HandlerArg, Runtime, View};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct State {
+    picked: Option<&'static str>,
+    typing: bool,
+}
+
+#[derive(Clone, Debug)]
+enum Action {
+    Pick(&'static str),
+    Type,
+}
+
+#[derive(Clone, Default)]
+struct Menu;
+
+impl App for Menu {
+    type State = State;
+    type Action = Action;
+
+    fn reducer(&self, mut state: State, action: Action) -> State {
+        match action {
+            Action::Pick(item) => state.picked = Some(item),
+            Action::Type => state.typing = true,
+        }
+        state
+    }
+
+    fn view(&self, state: State) -> View<Action> {
+        let item = |name: &'static str| {
+            let label = View::new("span", vec![], vec![], vec![name.into()]);
+            View::new(
+                "li",
+                vec![],
+                vec![("click".to_string(), handler(move |_| Action::Pick(name)))],
+                vec![label.into()],
+            )
+        };
+        let handlers = if state.typing {
+            vec![]
+        } else {
+            vec![("keydown".to_string(), handler(|_| Action::Type))]
+        };
+        View::new(
+            "div",
+            vec![],
+            handlers,
+            vec![
+                View::null().into(),
+                View::new("p", vec![], vec![], vec!["Pick one".into()]).into(),
+                View::new(
+                    "ul",
+                    vec![],
+                    vec![],
+                    vec![item("tea").into(), item("coffee").into()],
+                )
+                .into(),
+            ],
+        )
+    }
+}
+
+fn resolve(runtime: &TestRuntime<Menu>, path: &[usize], kind: &str, arg: HandlerArg) {
+    let id = runtime
+        .get_env()
+        .handler_at(path, kind)
+        .expect("a handler on the path");
+    runtime.pop_handler(&id).expect("a live handler")(arg);
+}
+
+#[test]
+fn resolves_the_deepest_handler_on_the_path() {
+    let runtime = TestRuntime::<Menu>::new(State::default());
+
+    // The span inside the second item, null children left out of the path.
+    resolve(&runtime, &[1, 1, 0], "click", HandlerArg::Null);
+    assert_eq!(runtime.state().picked, Some("coffee"));
+    resolve(&runtime, &[1, 0], "click", HandlerArg::Null);
+    assert_eq!(runtime.state().picked, Some("tea"));
+
+    // Bubbles up to the root from anywhere below it.
+    resolve(&runtime, &[0], "keydown", "a".into());
+    assert!(runtime.state().typing);
+}
+
+#[test]
+fn finds_nothing_without_a_handler_on_the_path() {
+    let runtime = TestRuntime::<Menu>::new(State::default());
+    let env = runtime.get_env();
+    assert_eq!(env.handler_at(&[0], "click"), None);
+    assert_eq!(env.handler_at(&[1], "click"), None);
+    assert_eq!(env.handler_at(&[1, 0, 0], "input"), None);
+    // Paths running past the tree stop at the last element.
+    assert_eq!(env.handler_at(&[1, 5, 0], "click"), None);
+    assert_eq!(
+        env.handler_at(&[1, 0, 0, 3], "click"),
+        env.handler_at(&[1, 0], "click")
+    );
+}
+
+#[test]
+fn delegates_the_kinds_in_the_tree() {
+    let delegated = |diffs: Vec<Diff>| -> Vec<Vec<String>> {
+        diffs
+            .into_iter()
+            .filter_map(|diff| match diff {
+                Diff::DelegateEvents(kinds) => Some(kinds),
+                _ => None,
+            })
+            .collect()
+    };
+    let runtime = TestRuntime::<Menu>::new(State::default());
+    assert_eq!(
+        delegated(runtime.take_diffs()),
+        vec![vec!["click".to_string(), "keydown".to_string()]]
+    );
+
+    runtime.dispatch(Action::Pick("tea"));
+    runtime.run();
+    assert!(delegated(runtime.take_diffs()).is_empty());
+
+    runtime.dispatch(Action::Type);
+    runtime.run();
+    assert_eq!(
+        delegated(runtime.take_diffs()),
+        vec![vec!["click".to_string()]]
+    );
+}
@@ -0,0 +1,100 @@
+extern crate squark;
+
+use squark::testing::TestRuntime;
+use squark::{handler, App, DevtoolsEvent, Env, Runtime, View};
+use std::rc::Rc;
+use std::sync::mpsc::{channel, Receiver};
+
+#[derive(Clone, Debug)]
+enum Action {
+    Increment,
+    Nothing,
+}
+
+#[derive(Clone, Default)]
+struct Counter;
+
+impl App for Counter {
+    type State = i32;
+    type Action = Action;
+
+    fn reducer(&self, count: i32, action: Action) -> i32 {
+        match action {
+            Action::Increment => count + 1,
+            Action::Nothing => count,
+        }
+    }
+
+    fn view(&self, count: i32) -> View<Action> {
+        View::new(
+            "button",
+            vec![],
+            vec![("click".to_string(), handler(|_| Action::Increment))],
+            vec![View::text(count.to_string()).into()],
+        )
+    }
+}
+
+fn inspected(count: i32) -> (TestRuntime<Counter>, Receiver<DevtoolsEvent<i32, Action>>) {
+    let (sender, events) = channel();
+    let env = Env::builder().devtools(Rc::new(sender)).build(count);
+    (TestRuntime::with_env(env), events)
+}
+
+#[test]
+fn snapshots_show_the_last_render() {
+    let (runtime, _events) = inspected(1);
+    let env = runtime.get_env();
+    assert_eq!(*env.state_ref(), 1);
+    assert_eq!(env.snapshot_node(), runtime.node());
+    assert_eq!(
+        squark::ssr::render_to_string(&env.snapshot_node()),
+        "<button>1</button>"
+    );
+    assert_eq!(env.handler_ids().len(), 1);
+
+    runtime.click("button");
+    assert_eq!(*env.state_ref(), 2);
+    assert_eq!(
+        squark::ssr::render_to_string(&env.snapshot_node()),
+        "<button>2</button>"
+    );
+}
+
+#[test]
+fn reports_actions_state_changes_and_diffs() {
+    let (runtime, events) = inspected(0);
+    // The first render.
+    match events.try_recv() {
+        Ok(DevtoolsEvent::Diff(_)) => {}
+        event => panic!("unexpected {:?}", event),
+    }
+
+    let before = runtime.node();
+    runtime.click("button");
+    match events.try_recv() {
+        Ok(DevtoolsEvent::Action(Action::Increment)) => {}
+        event => panic!("unexpected {:?}", event),
+    }
+    match events.try_recv() {
+        Ok(DevtoolsEvent::State(1)) => {}
+        event => panic!("unexpected {:?}", event),
+    }
+    match events.try_recv() {
+        Ok(DevtoolsEvent::Diff(diff)) => {
+            assert_eq!(
+                Some(diff),
+                squark::proptest_support::diff(&before, &runtime.node())
+            );
+        }
+        event => panic!("unexpected {:?}", event),
+    }
+
+    // Actions leaving the state as it was are logged alone.
+    runtime.dispatch(Action::Nothing);
+    match events.try_recv() {
+        Ok(DevtoolsEvent::Action(Action::Nothing)) => {}
+        event => panic!("unexpected {:?}", event),
+    }
+    assert!(events.try_recv().is_err());
+}
@@ -0,0 +1,70 @@
+#[macro_use]
+extern crate serde_json;
+extern crate squark;
+
+use squark::testing::TestRuntime;
+use squark::{
+    dialog_stack, query, query_all, App, Dialogs, Runtime, Shortcuts, Subscription,
+    SubscriptionKind, View, FOCUS_TRAP,
+};
+
+#[derive(Clone, Debug)]
+enum Action {
+    Open(&'static str),
+    Close(u64),
+}
+
+#[derive(Clone, Default)]
+struct Modals;
+
+impl App for Modals {
+    type State = Dialogs<&'static str>;
+    type Action = Action;
+
+    fn reducer(&self, mut dialogs: Self::State, action: Action) -> Self::State {
+        match action {
+            Action::Open(title) => {
+                dialogs.open(title);
+            }
+            Action::Close(id) => {
+                dialogs.close(id);
+            }
+        }
+        dialogs
+    }
+
+    fn subscriptions(&self, dialogs: &Self::State) -> Vec<Subscription<Action>> {
+        let shortcuts = Shortcuts::new().bind("ctrl+o", Action::Open("Open file"));
+        vec![dialogs
+            .bind_escape(shortcuts, Action::Close)
+            .into_subscription()]
+    }
+
+    fn view(&self, dialogs: Self::State) -> View<Action> {
+        dialog_stack(&dialogs, |title| View::static_text(title), Action::Close)
+    }
+}
+
+fn escape(runtime: &TestRuntime<Modals>) {
+    let arg = json!({ "key": "Escape", "ctrl": false, "alt": false, "shift": false, "meta": false, "scopes": [] });
+    runtime.emit(&SubscriptionKind::KeyDown, arg);
+}
+
+#[test]
+fn stacks_modal_dialogs_closed_by_escape_or_the_backdrop() {
+    let runtime = TestRuntime::<Modals>::new(Dialogs::new());
+    escape(&runtime);
+    runtime.dispatch(Action::Open("Settings"));
+    runtime.dispatch(Action::Open("Confirm"));
+    let node = runtime.node();
+    let selector = format!("[role=dialog][{}]", FOCUS_TRAP);
+    assert_eq!(query_all(&node, &selector).len(), 2);
+    assert!(query(&node, "[key='0'][inert]").is_some());
+    assert!(query(&node, "[key='1'][inert]").is_none());
+
+    escape(&runtime);
+    assert_eq!(runtime.state().top(), Some((0, &"Settings")));
+    assert!(query(&runtime.node(), "[inert]").is_none());
+    runtime.click("[data-backdrop]");
+    assert!(!runtime.state().is_open());
+}
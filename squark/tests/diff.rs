@@ -0,0 +1,284 @@
+extern crate squark;
+
+use squark::proptest_support::diff;
+use squark::{scroll_key, AttributeValue, Diff, Location, Node, View, SCROLL_KEY};
+
+fn checkbox(checked: Option<bool>) -> View<()> {
+    let attribute = checked.map(|checked| ("checked".to_string(), checked.into()));
+    View::new("input", attribute, vec![], vec![])
+}
+
+fn attribute_diffs(a: Option<bool>, b: Option<bool>) -> Vec<Diff> {
+    match diff(checkbox(a).node(), checkbox(b).node()) {
+        Some(Diff::PatchRoot(diffs)) => diffs,
+        None => vec![],
+        Some(diff) => panic!("unexpected {:?}", diff),
+    }
+}
+
+#[test]
+fn false_attributes_are_removed() {
+    let diffs = attribute_diffs(Some(true), Some(false));
+    assert!(matches!(diffs[..], [Diff::RemoveAttribute(ref name)] if name == "checked"));
+
+    let diffs = attribute_diffs(Some(false), Some(true));
+    assert!(matches!(
+        diffs[..],
+        [Diff::SetAttribute(ref name, AttributeValue::Bool(true))] if name == "checked"
+    ));
+}
+
+#[test]
+fn false_and_missing_attributes_are_the_same() {
+    assert!(attribute_diffs(None, Some(false)).is_empty());
+    assert!(attribute_diffs(Some(false), None).is_empty());
+    assert!(matches!(
+        attribute_diffs(Some(true), None)[..],
+        [Diff::RemoveAttribute(_)]
+    ));
+}
+
+#[test]
+fn repeated_attributes_keep_the_last_value() {
+    let view: View<()> = View::new(
+        "div",
+        vec![
+            ("class".to_string(), "a".into()),
+            ("id".to_string(), "x".into()),
+            ("class".to_string(), "b".into()),
+        ],
+        vec![],
+        vec![],
+    );
+    match view.node() {
+        squark::Node::Element(el) => assert_eq!(
+            el.attributes(),
+            &[
+                ("class".to_string(), "b".into()),
+                ("id".to_string(), "x".into()),
+            ][..]
+        ),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn prints_trees_and_diffs() {
+    let a: View<()> = View::new("ul", vec![], vec![], vec![]);
+    let b: View<()> = View::new(
+        "ul",
+        vec![("class".to_string(), "list".into())],
+        vec![],
+        vec![View::new("li", vec![], vec![], vec!["one".into()]).into()],
+    );
+    assert_eq!(
+        b.node().to_string(),
+        "<ul class=\"list\">\n  <li>\n    one\n  </li>\n</ul>\n"
+    );
+    let diff = diff(a.node(), b.node()).unwrap();
+    assert_eq!(
+        squark::format_diffs(&[diff]),
+        "patch root\n  set class=\"list\"\n  add 0 <li>…</li>\n"
+    );
+}
+
+#[test]
+fn explains_diffs_with_their_paths() {
+    let a: View<()> = View::new(
+        "ul",
+        vec![],
+        vec![],
+        vec![View::new("li", vec![], vec![], vec![]).into()],
+    );
+    let b: View<()> = View::new(
+        "ul",
+        vec![],
+        vec![],
+        vec![View::new(
+            "li",
+            vec![("class".to_string(), "active".into())],
+            vec![],
+            vec![],
+        )
+        .into()],
+    );
+    assert_eq!(
+        diff(a.node(), b.node()).unwrap().explain(),
+        "PatchRoot -> PatchChild[0] -> SetAttribute class=\"active\""
+    );
+}
+
+#[test]
+fn custom_nodes_are_only_replaced() {
+    let chart = |name: &str| -> View<()> {
+        View::new(
+            "div",
+            vec![],
+            vec![],
+            vec![View::custom("canvas", name).into()],
+        )
+    };
+    assert_eq!(diff(chart("chart").node(), chart("chart").node()), None);
+    assert!(matches!(
+        diff(chart("chart").node(), chart("map").node()),
+        Some(Diff::PatchRoot(ref diffs)) if matches!(diffs[..], [Diff::ReplaceChild(0, _)])
+    ));
+    assert_eq!(
+        squark::ssr::render_to_string(chart("chart").node()),
+        "<div><canvas data-squark-custom=\"chart\"></canvas></div>"
+    );
+}
+
+fn list(items: &[&'static str]) -> View<()> {
+    let items: Vec<View<()>> = items.iter().map(|item| View::text(*item)).collect();
+    View::new("ul", vec![], vec![], vec![items.into()])
+}
+
+#[test]
+fn appended_children_are_added_at_once() {
+    let before = list(&["a"]);
+    let after = list(&["a", "b", "c", "d"]);
+    let diffs = match diff(before.node(), after.node()) {
+        Some(Diff::PatchRoot(diffs)) => diffs,
+        diff => panic!("unexpected {:?}", diff),
+    };
+    let added: Vec<_> = ["b", "c", "d"]
+        .iter()
+        .map(|item| View::<()>::text(*item).node().clone())
+        .collect();
+    assert_eq!(diffs, vec![Diff::AddChildren(1, added)]);
+    let applied = squark::proptest_support::apply(before.node(), &Diff::PatchRoot(diffs));
+    assert_eq!(&applied, after.node());
+}
+
+fn list_diffs(before: &View<()>, after: &View<()>) -> Vec<Diff> {
+    match diff(before.node(), after.node()) {
+        Some(Diff::PatchRoot(diffs)) => diffs,
+        diff => panic!("unexpected {:?}", diff),
+    }
+}
+
+#[test]
+fn removed_runs_of_children_are_ranges() {
+    let before = list(&["a", "b", "c", "d", "e"]);
+    let after = list(&["a", "b"]);
+    let diffs = list_diffs(&before, &after);
+    assert_eq!(diffs, vec![Diff::RemoveChildren(2, 3)]);
+    let applied = squark::proptest_support::apply(before.node(), &Diff::PatchRoot(diffs));
+    assert_eq!(&applied, after.node());
+
+    let diffs = list_diffs(&before, &list(&[]));
+    assert_eq!(diffs, vec![Diff::ClearChildren]);
+    let applied = squark::proptest_support::apply(before.node(), &Diff::PatchRoot(diffs));
+    assert_eq!(&applied, list(&[]).node());
+}
+
+#[test]
+fn removed_attributes_keep_their_order() {
+    let names = [
+        "id", "class", "title", "lang", "dir", "role", "hidden", "style",
+    ];
+    let before: View<()> = View::new(
+        "div",
+        names
+            .iter()
+            .map(|name| (name.to_string(), "x".into()))
+            .collect::<Vec<_>>(),
+        vec![],
+        vec![],
+    );
+    let after: View<()> = View::new("div", vec![], vec![], vec![]);
+    let removed: Vec<Diff> = names
+        .iter()
+        .map(|name| Diff::RemoveAttribute(name.to_string()))
+        .collect();
+    assert_eq!(list_diffs(&before, &after), removed);
+}
+
+#[test]
+fn diffs_trees_outside_apps() {
+    let (a, b) = (list(&["a"]), list(&["a", "b"]));
+    assert!(squark::diff(a.node(), a.node()).is_empty());
+    assert!(matches!(
+        squark::diff(&squark::Node::Null, a.node())[..],
+        [Diff::AddChild(0, _)]
+    ));
+    assert!(matches!(
+        squark::diff(a.node(), &squark::Node::Null)[..],
+        [Diff::ReplaceRoot(squark::Node::Null)]
+    ));
+    assert!(matches!(
+        squark::diff(a.node(), checkbox(None).node())[..],
+        [Diff::ReplaceRoot(_)]
+    ));
+    assert!(matches!(
+        squark::diff(a.node(), b.node())[..],
+        [Diff::PatchRoot(ref diffs)] if diffs.len() == 1
+    ));
+}
+
+#[test]
+fn external_elements_are_not_diffed_into() {
+    let page = |chart: View<()>, text: &'static str| {
+        View::new(
+            "main",
+            vec![],
+            vec![],
+            vec![chart.into(), View::text(text).into()],
+        )
+    };
+    let before = page(View::external("chart"), "a");
+
+    let after = page(View::external("chart"), "b");
+    let text = Diff::ReplaceChild(1, squark::Node::Text("b".into()));
+    assert_eq!(
+        squark::diff(before.node(), after.node()),
+        vec![Diff::PatchRoot(vec![text])]
+    );
+
+    let id = vec![("id".to_string(), "chart".into())];
+    let after = page(View::new("div", id, vec![], vec![]), "a");
+    assert!(matches!(
+        squark::diff(before.node(), after.node())[..],
+        [Diff::PatchRoot(ref diffs)] if matches!(diffs[..], [Diff::ReplaceChild(0, _)])
+    ));
+}
+
+fn scroller(url: &str, text: &'static str) -> View<()> {
+    let key = scroll_key("list", &Location::parse(url));
+    View::new(
+        "ul",
+        vec![(SCROLL_KEY.to_string(), key.into())],
+        vec![],
+        vec![View::new("li", vec![], vec![], vec![text.into()]).into()],
+    )
+}
+
+#[test]
+fn scroll_offsets_are_saved_before_and_restored_after_the_patch() {
+    let diffs = diff(
+        scroller("/a", "a").node(),
+        scroller("/b?page=2", "b").node(),
+    );
+    assert_eq!(
+        diffs,
+        Some(Diff::PatchRoot(vec![
+            Diff::SaveScroll("list@/a".to_string()),
+            Diff::SetAttribute(SCROLL_KEY.to_string(), "list@/b".into()),
+            Diff::PatchChild(0, vec![Diff::ReplaceChild(0, Node::Text("b".into()))]),
+            Diff::RestoreScroll("list@/b".to_string()),
+        ]))
+    );
+
+    let diffs = diff(
+        scroller("/a", "a").node(),
+        scroller("/a?page=2", "b").node(),
+    );
+    assert_eq!(
+        diffs,
+        Some(Diff::PatchRoot(vec![Diff::PatchChild(
+            0,
+            vec![Diff::ReplaceChild(0, Node::Text("b".into()))]
+        )]))
+    );
+}
@@ -0,0 +1,140 @@
+extern crate squark;
+
+use squark::testing::TestRuntime;
+use squark::{embed, handler, App, Cmd, Embed, Subscription, SubscriptionKind, View};
+use std::time::Duration;
+
+#[derive(Clone, Debug)]
+enum CounterAction {
+    Increment,
+}
+
+#[derive(Clone, Default)]
+struct Counter;
+
+impl App for Counter {
+    type State = i32;
+    type Action = CounterAction;
+
+    fn reducer(&self, count: i32, _: CounterAction) -> i32 {
+        count + 1
+    }
+
+    fn command(&self, count: &i32, _: &CounterAction) -> Cmd<CounterAction> {
+        Cmd::set_title(format!("counted {}", count))
+    }
+
+    fn subscriptions(&self, _: &i32) -> Vec<Subscription<CounterAction>> {
+        vec![Subscription::every(Duration::from_secs(1), |_| {
+            CounterAction::Increment
+        })]
+    }
+
+    fn view(&self, count: i32) -> View<CounterAction> {
+        View::new(
+            "button",
+            vec![],
+            vec![("click".to_string(), handler(|_| CounterAction::Increment))],
+            vec![View::text(count.to_string()).into()],
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct State {
+    title: String,
+    count: i32,
+}
+
+#[derive(Clone, Debug)]
+enum Action {
+    Counter(CounterAction),
+}
+
+#[derive(Clone)]
+struct Page {
+    counter: Embed<Counter, State, Action>,
+}
+
+impl Default for Page {
+    fn default() -> Page {
+        Page {
+            counter: embed(
+                Action::Counter,
+                |state: &State| state.count,
+                |state, count| State { count, ..state },
+            ),
+        }
+    }
+}
+
+impl App for Page {
+    type State = State;
+    type Action = Action;
+
+    fn reducer(&self, state: State, action: Action) -> State {
+        match action {
+            Action::Counter(action) => self.counter.reducer(state, action),
+        }
+    }
+
+    fn command(&self, state: &State, action: &Action) -> Cmd<Action> {
+        match *action {
+            Action::Counter(ref action) => self.counter.command(state, action),
+        }
+    }
+
+    fn subscriptions(&self, state: &State) -> Vec<Subscription<Action>> {
+        self.counter.subscriptions(state)
+    }
+
+    fn view(&self, state: State) -> View<Action> {
+        View::new(
+            "div",
+            vec![],
+            vec![],
+            vec![
+                View::text(state.title.clone()).into(),
+                self.counter.view(&state).into(),
+            ],
+        )
+    }
+}
+
+fn page() -> State {
+    State {
+        title: "page".to_string(),
+        count: 0,
+    }
+}
+
+#[test]
+fn embedded_apps_render_their_slice_of_the_state() {
+    let runtime = TestRuntime::<Page>::new(State { count: 3, ..page() });
+    assert_eq!(
+        squark::ssr::render_to_string(&runtime.node()),
+        "<div>page<button>3</button></div>"
+    );
+}
+
+#[test]
+fn embedded_actions_update_the_slice_only() {
+    let runtime = TestRuntime::<Page>::new(page());
+    runtime.click("button");
+    runtime.click("button");
+    assert_eq!(runtime.state(), State { count: 2, ..page() });
+    assert_eq!(runtime.title(), "counted 2");
+}
+
+#[test]
+fn embedded_subscriptions_dispatch_mapped_actions() {
+    let runtime = TestRuntime::<Page>::new(page());
+    let every = SubscriptionKind::Every(Duration::from_secs(1));
+    assert_eq!(
+        runtime.subscriptions().values().collect::<Vec<_>>(),
+        vec![&every]
+    );
+
+    runtime.emit(&every, 0.into());
+    assert_eq!(runtime.state().count, 1);
+}
@@ -0,0 +1,173 @@
+extern crate squark;
+
+use squark::testing::TestRuntime;
+use squark::{App, Diff, Env, Node, PatchStep, Runtime, View};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[derive(Clone, Default)]
+struct Badge;
+
+impl App for Badge {
+    type State = i32;
+    type Action = i32;
+
+    fn reducer(&self, _: i32, count: i32) -> i32 {
+        count
+    }
+
+    fn view(&self, count: i32) -> View<i32> {
+        let class = format!("badge-{}", count);
+        View::new(
+            "div",
+            vec![],
+            vec![],
+            vec![
+                View::new(
+                    "span",
+                    vec![("class".to_string(), class.into())],
+                    vec![],
+                    vec![],
+                )
+                .into(),
+                View::new(
+                    "p",
+                    vec![],
+                    vec![],
+                    vec![View::text(count.to_string()).into()],
+                )
+                .into(),
+            ],
+        )
+    }
+}
+
+#[test]
+fn filters_drop_ops_in_protected_subtrees() {
+    let seen = Rc::new(RefCell::new(vec![]));
+    let recorded = seen.clone();
+    let env = Env::<Badge>::builder()
+        .diff_filter(move |path: &[PatchStep], diff: Diff| {
+            recorded.borrow_mut().push(path.to_vec());
+            match diff {
+                Diff::SetAttribute(..) if path.first() == Some(&PatchStep::Child(0)) => None,
+                diff => Some(diff),
+            }
+        })
+        .build(0);
+    let runtime = TestRuntime::with_env(env);
+    runtime.take_diffs();
+    seen.borrow_mut().clear();
+
+    runtime.dispatch(1);
+    runtime.run();
+    let text = Diff::ReplaceChild(0, Node::Text("1".into()));
+    assert_eq!(
+        runtime.take_diffs(),
+        vec![Diff::PatchRoot(vec![Diff::PatchChild(1, vec![text])])]
+    );
+    assert_eq!(
+        *seen.borrow(),
+        vec![
+            vec![],
+            vec![],
+            vec![PatchStep::Child(0)],
+            vec![],
+            vec![PatchStep::Child(1)],
+        ]
+    );
+}
+
+#[test]
+fn filters_get_patches_before_the_ops_they_hold() {
+    let seen = Rc::new(RefCell::new(vec![]));
+    let recorded = seen.clone();
+    let env = Env::<Badge>::builder()
+        .diff_filter(move |_: &[PatchStep], diff: Diff| {
+            recorded.borrow_mut().push(diff.clone());
+            Some(diff)
+        })
+        .build(0);
+    let runtime = TestRuntime::with_env(env);
+    runtime.take_diffs();
+    seen.borrow_mut().clear();
+
+    runtime.dispatch(1);
+    runtime.run();
+    let class = Diff::SetAttribute("class".to_string(), "badge-1".into());
+    let text = Diff::ReplaceChild(0, Node::Text("1".into()));
+    assert_eq!(
+        *seen.borrow(),
+        vec![
+            Diff::PatchRoot(vec![]),
+            Diff::PatchChild(0, vec![]),
+            class.clone(),
+            Diff::PatchChild(1, vec![]),
+            text.clone(),
+        ]
+    );
+    assert_eq!(
+        runtime.take_diffs(),
+        vec![Diff::PatchRoot(vec![
+            Diff::PatchChild(0, vec![class]),
+            Diff::PatchChild(1, vec![text]),
+        ])]
+    );
+}
+
+#[derive(Clone, Default)]
+struct List;
+
+impl App for List {
+    type State = Vec<&'static str>;
+    type Action = Vec<&'static str>;
+
+    fn reducer(&self, _: Vec<&'static str>, items: Vec<&'static str>) -> Vec<&'static str> {
+        items
+    }
+
+    fn view(&self, items: Vec<&'static str>) -> View<Vec<&'static str>> {
+        let items = items
+            .into_iter()
+            .map(|item| View::new("li", vec![], vec![], vec![View::text(item).into()]))
+            .collect();
+        View::new("ul", vec![], vec![], vec![items])
+    }
+}
+
+#[test]
+fn filters_cannot_drop_ops_changing_the_tree() {
+    use squark::log;
+
+    let warnings = Rc::new(RefCell::new(0));
+    let sink = warnings.clone();
+    log::set_sink(move |_, _: &str| *sink.borrow_mut() += 1);
+    let env = Env::<List>::builder()
+        .diff_filter(|_: &[PatchStep], diff: Diff| match diff {
+            Diff::AddChild(..) | Diff::AddChildren(..) => None,
+            diff => Some(diff),
+        })
+        .build(vec!["a"]);
+    let runtime = TestRuntime::with_env(env);
+    assert!(matches!(runtime.take_diffs()[..], [Diff::AddChild(0, _)]));
+    *warnings.borrow_mut() = 0;
+
+    runtime.dispatch(vec!["a", "b"]);
+    runtime.run();
+    assert!(matches!(
+        runtime.take_diffs()[..],
+        [Diff::PatchRoot(ref diffs)] if matches!(diffs[..], [Diff::AddChild(1, _)])
+    ));
+    if cfg!(all(debug_assertions, feature = "debug-checks")) {
+        assert_eq!(*warnings.borrow(), 1);
+    }
+
+    runtime.dispatch(vec!["a", "c"]);
+    runtime.run();
+    log::reset_sink();
+    let text = Diff::ReplaceChild(0, Node::Text("c".into()));
+    assert_eq!(
+        runtime.take_diffs(),
+        vec![Diff::PatchRoot(vec![Diff::PatchChild(1, vec![text])])]
+    );
+}
@@ -0,0 +1,73 @@
+#[macro_use]
+extern crate squark;
+
+use squark::testing::TestRuntime;
+use squark::{handler, App, Catalog, Translator, View};
+use std::rc::Rc;
+
+#[derive(Clone, Default)]
+struct Greeting;
+
+impl App for Greeting {
+    // The locale, if any.
+    type State = Option<&'static str>;
+    type Action = &'static str;
+
+    fn reducer(&self, _: Option<&'static str>, locale: &'static str) -> Option<&'static str> {
+        Some(locale)
+    }
+
+    fn translator(&self, locale: &Option<&'static str>) -> Option<Rc<dyn Translator>> {
+        let catalog = match (*locale)? {
+            "de" => Catalog::new("de").message("greeting", "Hallo, {name}!"),
+            locale => Catalog::new(locale).message("greeting", "Hello, {name}!"),
+        };
+        Some(Rc::new(catalog))
+    }
+
+    fn view(&self, _: Option<&'static str>) -> View<&'static str> {
+        View::new(
+            "p",
+            vec![],
+            vec![("click".to_string(), handler(|_| "de"))],
+            vec![
+                View::text(t!("greeting", name = "Ada")).into(),
+                View::text(t!("missing")).into(),
+            ],
+        )
+    }
+}
+
+fn text(runtime: &TestRuntime<Greeting>) -> String {
+    squark::ssr::render_to_string(&runtime.node())
+}
+
+#[test]
+fn translates_with_the_translator_of_the_state() {
+    let runtime = TestRuntime::<Greeting>::new(Some("en"));
+    assert_eq!(text(&runtime), "<p>Hello, Ada!missing</p>");
+
+    runtime.click("p");
+    assert_eq!(text(&runtime), "<p>Hallo, Ada!missing</p>");
+}
+
+#[test]
+fn falls_back_to_the_key_without_a_translator() {
+    let runtime = TestRuntime::<Greeting>::new(None);
+    assert_eq!(text(&runtime), "<p>greetingmissing</p>");
+    assert_eq!(squark::translate("greeting", &[]), "greeting");
+}
+
+#[test]
+fn catalogs_interpolate_arguments() {
+    let catalog = Catalog::new("en").message("items", "{count} items in {place}");
+    assert_eq!(catalog.locale(), "en");
+    assert_eq!(
+        catalog.translate(
+            "items",
+            &[("count", "3".to_string()), ("place", "cart".to_string())]
+        ),
+        Some("3 items in cart".to_string())
+    );
+    assert_eq!(catalog.translate("other", &[]), None);
+}
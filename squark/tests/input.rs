@@ -0,0 +1,102 @@
+#[macro_use]
+extern crate serde_json;
+extern crate squark;
+
+use squark::testing::TestRuntime;
+use squark::{composed_input, App, Runtime, View};
+
+#[derive(Clone, Default)]
+struct Search;
+
+impl App for Search {
+    type State = Vec<String>;
+    type Action = String;
+
+    fn reducer(&self, mut state: Vec<String>, query: String) -> Vec<String> {
+        state.push(query);
+        state
+    }
+
+    fn view(&self, _: Vec<String>) -> View<String> {
+        View::new("input", vec![], composed_input(|value| value), vec![])
+    }
+}
+
+#[test]
+fn composed_input_skips_text_being_composed() {
+    let runtime = TestRuntime::<Search>::new(vec![]);
+    runtime.input("input", "a");
+    runtime.trigger("input", "input", json!({ "value": "aにほ", "composing": true }));
+    runtime.trigger("input", "compositionend", json!({ "data": "日本", "value": "a日本" }));
+    assert_eq!(runtime.state(), vec!["a", "a日本"]);
+}
+
+fn phone(raw: &str) -> String {
+    let digits: String = raw.chars().filter(|c| c.is_ascii_digit()).take(7).collect();
+    if digits.len() > 3 {
+        format!("{}-{}", &digits[..3], &digits[3..])
+    } else {
+        digits
+    }
+}
+
+#[test]
+fn masks_keep_the_caret_after_the_same_digits() {
+    use squark::Masked;
+
+    assert_eq!(
+        Masked::format("5551", 4, phone),
+        Masked { value: "555-1".to_string(), caret: 5 }
+    );
+    // A digit typed before the dash.
+    assert_eq!(
+        Masked::format("5505-12", 3, phone),
+        Masked { value: "550-512".to_string(), caret: 3 }
+    );
+    assert_eq!(Masked::format("55", 2, phone).caret, 2);
+}
+
+#[test]
+fn masked_inputs_dispatch_formatted_values() {
+    use squark::{masked_input, Masked};
+
+    #[derive(Clone, Default)]
+    struct Form;
+
+    impl App for Form {
+        type State = Masked;
+        type Action = Masked;
+
+        fn reducer(&self, _: Masked, masked: Masked) -> Masked {
+            masked
+        }
+
+        fn view(&self, state: Masked) -> View<Masked> {
+            masked_input(vec![], &state, phone, |masked| masked)
+        }
+    }
+
+    let runtime = TestRuntime::<Form>::new(Masked::default());
+    runtime.trigger("input", "input", json!({ "value": "5551234", "caret": 7 }));
+    assert_eq!(runtime.state().value, "555-1234");
+    assert_eq!(runtime.state().caret, 8);
+    let html = format!("{}", runtime.node());
+    assert!(html.contains(".value=\"555-1234\" .selectionStart=8"));
+}
+
+#[test]
+fn selects_mark_and_report_selected_values() {
+    use squark::{multi_select, select, selected_values};
+
+    let sizes = vec![("s", "Small"), ("m", "Medium"), ("l", "Large")];
+    let view: View<String> = select(vec![], sizes.clone(), "m", |value| value);
+    let selected = squark::query_all(view.node(), "option[selected]");
+    assert_eq!(selected.len(), 1);
+    assert_eq!(selected[0].attributes()[0].1, "m".into());
+
+    let chosen = vec!["s".to_string(), "l".to_string()];
+    let view: View<Vec<String>> = multi_select(vec![], sizes, &chosen, |values| values);
+    assert_eq!(squark::query_all(view.node(), "select[multiple] option[selected]").len(), 2);
+    assert_eq!(selected_values(&json!(["s", "l"])), chosen);
+    assert_eq!(selected_values(&json!("m")), vec!["m"]);
+}
@@ -0,0 +1,44 @@
+extern crate squark;
+
+use squark::ssr::render_to_string;
+use squark::{set_key_attribute, Node, View};
+
+fn item(attributes: Vec<(&str, &str)>) -> View<()> {
+    let attributes: Vec<_> = attributes
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.into()))
+        .collect();
+    View::new("li", attributes, vec![], vec![])
+}
+
+fn key(view: &View<()>) -> Option<String> {
+    match view.node() {
+        Node::Element(el) => el.key().map(String::from),
+        _ => None,
+    }
+}
+
+#[test]
+fn keys_are_not_rendered() {
+    let view = item(vec![("key", "a"), ("class", "x")]);
+    assert_eq!(key(&view), Some("a".to_string()));
+    assert_eq!(render_to_string(view.node()), "<li class=\"x\"></li>");
+
+    let view = View::<()>::new("li", vec![], vec![], vec![]).with_key("b");
+    assert_eq!(key(&view), Some("b".to_string()));
+    assert_eq!(render_to_string(view.node()), "<li></li>");
+}
+
+#[test]
+fn the_key_attribute_is_configurable() {
+    set_key_attribute(Some("data-key"));
+    let view = item(vec![("key", "k"), ("data-key", "a")]);
+    set_key_attribute(None);
+    let unkeyed = item(vec![("key", "k")]);
+    set_key_attribute(Some("key"));
+
+    assert_eq!(key(&view), Some("a".to_string()));
+    assert_eq!(render_to_string(view.node()), "<li key=\"k\"></li>");
+    assert_eq!(key(&unkeyed), None);
+    assert_eq!(render_to_string(unkeyed.node()), "<li key=\"k\"></li>");
+}
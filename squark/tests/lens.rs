@@ -0,0 +1,48 @@
+#[macro_use]
+extern crate squark;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Todo {
+    title: String,
+    done: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct State {
+    todos: Vec<Todo>,
+    filter: String,
+    edits: u32,
+}
+
+fn initial() -> State {
+    State {
+        todos: vec![Todo::default(), Todo::default()],
+        ..State::default()
+    }
+}
+
+#[test]
+fn update_assigns_one_field_of_an_owned_state() {
+    let (state, i) = (initial(), 1);
+    let state = update!(state.todos[i].done = true);
+    assert!(state.todos[1].done && !state.todos[0].done);
+    let state = update!(state.edits = state.edits + 1);
+    assert_eq!(state.edits, 1);
+}
+
+#[test]
+fn lenses_get_set_and_compose() {
+    let todo = lens!(State, todos[1]);
+    let title = todo.then(lens!(Todo, title));
+    let state = title.set(initial(), "Write tests".to_string());
+    assert_eq!(title.get(&state), "Write tests");
+    let state = todo.update(state, |todo| todo.done = true);
+    assert_eq!(
+        todo.get(&state),
+        &Todo {
+            title: "Write tests".to_string(),
+            done: true
+        }
+    );
+    assert_eq!(state.todos[0], Todo::default());
+}
@@ -0,0 +1,67 @@
+extern crate squark;
+
+use squark::log::{self, Level};
+use squark::View;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[test]
+fn warnings_go_to_the_sink() {
+    let messages = Rc::new(RefCell::new(vec![]));
+    let sink = messages.clone();
+    log::set_sink(move |level, message: &str| sink.borrow_mut().push((level, message.to_string())));
+    assert!(log::has_sink());
+
+    let item = || View::<()>::new("li", vec![("key".to_string(), "a".into())], vec![], vec![]);
+    View::new("ul", vec![], vec![], vec![item().into(), item().into()]);
+    View::<()>::new(
+        "p",
+        vec![],
+        vec![],
+        vec![View::new("div", vec![], vec![], vec![]).into()],
+    );
+
+    let messages = messages.borrow().clone();
+    if cfg!(all(debug_assertions, feature = "debug-checks")) {
+        assert_eq!(
+            messages,
+            vec![
+                (Level::Warn, "key \"a\" is used by siblings".to_string()),
+                (Level::Warn, "<div> is not allowed inside <p>".to_string()),
+            ]
+        );
+    }
+    log::reset_sink();
+    assert!(!log::has_sink());
+}
+
+#[derive(Clone, Default)]
+struct Empty;
+
+impl squark::App for Empty {
+    type State = ();
+    type Action = ();
+
+    fn reducer(&self, _: (), _: ()) {}
+
+    fn view(&self, _: ()) -> View<()> {
+        View::new("div", vec![], vec![], vec![])
+    }
+}
+
+#[test]
+fn test_runtimes_log_to_stderr_unless_a_sink_is_set() {
+    use squark::testing::TestRuntime;
+
+    assert!(!log::has_sink());
+    TestRuntime::<Empty>::new(());
+    assert!(log::has_sink());
+
+    let messages = Rc::new(RefCell::new(vec![]));
+    let sink = messages.clone();
+    log::set_sink(move |_, message: &str| sink.borrow_mut().push(message.to_string()));
+    TestRuntime::<Empty>::new(());
+    log::log(Level::Info, "kept");
+    log::reset_sink();
+    assert_eq!(*messages.borrow(), vec!["kept".to_string()]);
+}
@@ -0,0 +1,73 @@
+#![cfg(feature = "markdown")]
+
+extern crate squark;
+
+use squark::{AttributeValue, Node, View};
+
+fn render(node: &Node) -> String {
+    match *node {
+        Node::Text(ref text) => text.to_string(),
+        Node::Element(ref el) => {
+            let mut out = format!("<{}", el.name());
+            for (key, value) in el.attributes() {
+                match value {
+                    AttributeValue::String(s) => out.push_str(&format!(" {}=\"{}\"", key, s)),
+                    _ => out.push_str(&format!(" {}", key)),
+                }
+            }
+            out.push('>');
+            for child in el.children() {
+                out.push_str(&render(child));
+            }
+            out.push_str(&format!("</{}>", el.name()));
+            out
+        }
+        _ => String::new(),
+    }
+}
+
+fn markdown(source: &str) -> String {
+    render(View::<()>::from_markdown(source).node())
+}
+
+#[test]
+fn builds_elements_instead_of_markup() {
+    assert_eq!(
+        markdown("# Title\n\nSome *emphasis* and `code`.\n\n- [x] done"),
+        "<div><h1>Title</h1><p>Some <em>emphasis</em> and <code>code</code>.</p>\
+         <ul><li><input type=\"checkbox\" disabled checked></input>done</li></ul></div>"
+    );
+}
+
+#[test]
+fn drops_embedded_html_and_unsafe_links() {
+    assert_eq!(
+        markdown("<script>alert(1)</script>\n\n[x](javascript:alert(1)) ![a *b*](/i.png)"),
+        "<div><p><a>x</a> <img src=\"/i.png\" alt=\"a b\"></img></p></div>"
+    );
+}
+
+#[test]
+fn drops_links_hiding_their_scheme_in_character_references() {
+    for url in &[
+        "javascript&#58;alert(1)",
+        "javascript&colon;alert(1)",
+        "&#106;avascript:alert(1)",
+    ] {
+        assert_eq!(
+            markdown(&format!("[x]({})", url)),
+            "<div><p><a>x</a></p></div>"
+        );
+    }
+}
+
+#[test]
+fn wraps_table_rows_in_tbody() {
+    assert_eq!(
+        markdown("| a | b |\n|:--|--:|\n| 1 | 2 |"),
+        "<div><table><thead><tr><th style=\"text-align: left\">a</th>\
+         <th style=\"text-align: right\">b</th></tr></thead><tbody><tr>\
+         <td style=\"text-align: left\">1</td><td style=\"text-align: right\">2</td>\
+         </tr></tbody></table></div>"
+    );
+}
@@ -0,0 +1,69 @@
+#[macro_use]
+extern crate serde_json;
+extern crate squark;
+
+use squark::testing::TestRuntime;
+use squark::{media_handler, App, Cmd, MediaControl, Runtime, View};
+
+#[derive(Clone, Default)]
+struct Player;
+
+#[derive(Clone, Debug)]
+enum Action {
+    Progress(f64),
+    Ended,
+}
+
+impl App for Player {
+    type State = f64;
+    type Action = Action;
+
+    fn reducer(&self, state: f64, action: Action) -> f64 {
+        match action {
+            Action::Progress(t) => t,
+            Action::Ended => state,
+        }
+    }
+
+    fn command(&self, _: &f64, action: &Action) -> Cmd<Action> {
+        match *action {
+            Action::Ended => Cmd::batch(vec![Cmd::seek("video", 0.0), Cmd::play("video")]),
+            Action::Progress(_) => Cmd::none(),
+        }
+    }
+
+    fn view(&self, _: f64) -> View<Action> {
+        View::new(
+            "video",
+            vec![("id".to_string(), "video".into())],
+            vec![
+                (
+                    "timeupdate".to_string(),
+                    media_handler(|e| Action::Progress(e.current_time)),
+                ),
+                ("ended".to_string(), media_handler(|_| Action::Ended)),
+            ],
+            vec![],
+        )
+    }
+}
+
+#[test]
+fn dispatches_playback_state_and_controls_media() {
+    let runtime = TestRuntime::<Player>::new(0.0);
+    let playing = json!({ "currentTime": 12.5, "duration": null, "paused": false, "ended": false });
+    assert!(runtime.trigger("video", "timeupdate", playing));
+    assert_eq!(runtime.state(), 12.5);
+    assert!(runtime.trigger("video", "timeupdate", json!(null)));
+    assert_eq!(runtime.state(), 12.5);
+
+    let ended = json!({ "currentTime": 60.0, "duration": 60.0, "paused": true, "ended": true });
+    runtime.trigger("video", "ended", ended);
+    assert_eq!(
+        runtime.take_media_controls(),
+        vec![
+            ("video".to_string(), MediaControl::Seek(0.0)),
+            ("video".to_string(), MediaControl::Play),
+        ]
+    );
+}
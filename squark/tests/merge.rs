@@ -0,0 +1,61 @@
+extern crate squark;
+
+use squark::ssr::render_to_string;
+use squark::{merge, Conflict, Node, View};
+
+fn list(class: &str, items: &[(&str, &'static str)]) -> Node {
+    let items: Vec<View<()>> = items
+        .iter()
+        .map(|&(key, text)| {
+            View::new("li", vec![], vec![], vec![View::text(text).into()]).with_key(key)
+        })
+        .collect();
+    View::new(
+        "ul",
+        vec![("class".to_string(), class.into())],
+        vec![],
+        vec![items.into()],
+    )
+    .node()
+    .clone()
+}
+
+#[test]
+fn changes_of_both_sides_are_combined() {
+    let base = list("a", &[("1", "one"), ("2", "two")]);
+    let mine = list("b", &[("1", "one"), ("2", "two"), ("3", "three")]);
+    let theirs = list("a", &[("0", "zero"), ("1", "ONE")]);
+
+    let (merged, conflicts) = merge(&base, &mine, &theirs);
+    assert!(conflicts.is_empty());
+    assert_eq!(
+        render_to_string(&merged),
+        "<ul class=\"b\"><li>zero</li><li>ONE</li><li>three</li></ul>"
+    );
+}
+
+#[test]
+fn conflicting_changes_keep_theirs() {
+    let base = list("a", &[("1", "one")]);
+    let mine = list("b", &[("1", "uno")]);
+    let theirs = list("c", &[("1", "ONE")]);
+
+    let (merged, conflicts) = merge(&base, &mine, &theirs);
+    assert_eq!(merged, theirs);
+    assert_eq!(
+        conflicts,
+        vec![
+            Conflict::Attribute {
+                path: vec![],
+                name: "class".to_string(),
+                mine: Some("b".into()),
+                theirs: Some("c".into()),
+            },
+            Conflict::Node {
+                path: vec![0, 0],
+                mine: Node::Text("uno".into()),
+                theirs: Node::Text("ONE".into()),
+            },
+        ]
+    );
+}
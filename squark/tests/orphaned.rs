@@ -0,0 +1,84 @@
+extern crate squark;
+
+use squark::testing::TestRuntime;
+use squark::{handler, App, HandlerArg, Node, Runtime, View};
+
+#[derive(Clone, Default)]
+struct Counter;
+
+impl App for Counter {
+    type State = i32;
+    type Action = ();
+
+    fn reducer(&self, count: i32, _: ()) -> i32 {
+        count + 1
+    }
+
+    fn view(&self, count: i32) -> View<()> {
+        View::new(
+            "button",
+            vec![],
+            vec![("click".to_string(), handler(|_| ()))],
+            vec![View::text(count.to_string()).into()],
+        )
+    }
+}
+
+fn click_id(runtime: &TestRuntime<Counter>) -> String {
+    match runtime.node() {
+        Node::Element(el) => el.handler("click").unwrap().to_string(),
+        node => panic!("unexpected {:?}", node),
+    }
+}
+
+// Takes the handler of the button, as a runtime attaching listeners does.
+fn attach(runtime: &TestRuntime<Counter>) -> Box<dyn Fn(HandlerArg)> {
+    runtime
+        .pop_handler(&click_id(runtime))
+        .expect("a live handler")
+}
+
+#[test]
+fn reports_nothing_once_every_handler_is_attached() {
+    let runtime = TestRuntime::<Counter>::new(0);
+    assert_eq!(
+        runtime.get_env().orphaned_handlers(),
+        vec![click_id(&runtime)]
+    );
+
+    attach(&runtime)(HandlerArg::Null);
+    let _listener = attach(&runtime);
+    assert_eq!(runtime.state(), 1);
+    assert!(runtime.get_env().orphaned_handlers().is_empty());
+}
+
+#[test]
+fn reports_handlers_replacing_attached_ones() {
+    use squark::log;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let runtime = TestRuntime::<Counter>::new(0);
+    let listener = attach(&runtime);
+    let attached = click_id(&runtime);
+
+    // The listener keeps the handler the re-render retired.
+    let warnings = Rc::new(RefCell::new(vec![]));
+    let sink = warnings.clone();
+    log::set_sink(move |_, message: &str| sink.borrow_mut().push(message.to_string()));
+    listener(HandlerArg::Null);
+    log::reset_sink();
+
+    let replacement = click_id(&runtime);
+    assert_ne!(replacement, attached);
+    assert_eq!(
+        runtime.get_env().orphaned_handlers(),
+        vec![replacement.clone()]
+    );
+    if cfg!(all(debug_assertions, feature = "debug-checks")) {
+        assert_eq!(
+            *warnings.borrow(),
+            vec![format!("handlers never attached: {}", replacement)]
+        );
+    }
+}
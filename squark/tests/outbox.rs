@@ -0,0 +1,103 @@
+#[macro_use]
+extern crate serde_json;
+extern crate squark;
+
+use squark::testing::TestRuntime;
+use squark::{
+    App, Cmd, Fetch, FetchFuture, HandlerArg, KeyValueStorage, MemoryStorage, Outbox, Request,
+    Runtime, Subscription, SubscriptionKind, View,
+};
+use std::cell::RefCell;
+use std::future;
+use std::rc::Rc;
+
+#[derive(Default)]
+struct Server {
+    received: RefCell<Vec<String>>,
+}
+
+impl Fetch for Server {
+    fn request(&self, request: Request) -> FetchFuture {
+        let body = request.get_body().unwrap_or("").to_string();
+        self.received.borrow_mut().push(body);
+        Box::pin(future::pending())
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Action {
+    Start,
+    Restored(Vec<HandlerArg>),
+    Online(bool),
+    Send(&'static str),
+}
+
+fn send(message: &HandlerArg) -> Cmd<Action> {
+    Cmd::fetch(Request::post("/messages", message.to_string()), |_| None)
+}
+
+#[derive(Clone, Default)]
+struct Messenger;
+
+impl App for Messenger {
+    type State = Outbox;
+    type Action = Action;
+
+    fn reducer(&self, mut outbox: Outbox, action: Action) -> Outbox {
+        match action {
+            Action::Start => {}
+            Action::Restored(items) => outbox.restored(items),
+            Action::Online(online) => outbox.set_online(online),
+            Action::Send(text) => {
+                outbox.push(json!(text));
+            }
+        }
+        outbox
+    }
+
+    fn command(&self, outbox: &Outbox, action: &Action) -> Cmd<Action> {
+        match *action {
+            Action::Start => outbox.restore(Action::Restored),
+            Action::Online(_) => outbox.flush(send),
+            Action::Send(text) if outbox.is_online() => send(&json!(text)),
+            Action::Send(_) => outbox.save(),
+            Action::Restored(_) => Cmd::none(),
+        }
+    }
+
+    fn subscriptions(&self, _: &Outbox) -> Vec<Subscription<Action>> {
+        vec![Subscription::online(Action::Online)]
+    }
+
+    fn view(&self, outbox: Outbox) -> View<Action> {
+        View::text_of(outbox.queue().len())
+    }
+}
+
+#[test]
+fn queues_while_offline_and_flushes_on_reconnect() {
+    let storage = Rc::new(MemoryStorage::new());
+    storage.set("outbox", "[\"saved\"]");
+    let server = Rc::new(Server::default());
+    let runtime = TestRuntime::<Messenger>::new(Outbox::new("outbox"));
+    runtime.set_storage(storage.clone());
+    runtime.set_fetcher(server.clone());
+
+    runtime.dispatch(Action::Start);
+    runtime.emit(&SubscriptionKind::Online, json!(false));
+    runtime.dispatch(Action::Send("offline"));
+    assert_eq!(runtime.state().queue(), &[json!("saved"), json!("offline")]);
+    assert_eq!(
+        storage.get("outbox"),
+        Some("[\"saved\",\"offline\"]".to_string())
+    );
+    assert!(server.received.borrow().is_empty());
+
+    runtime.emit(&SubscriptionKind::Online, json!(true));
+    runtime.dispatch(Action::Send("online"));
+    assert_eq!(
+        *server.received.borrow(),
+        vec!["\"saved\"", "\"offline\"", "\"online\""]
+    );
+    assert_eq!(storage.get("outbox"), None);
+}
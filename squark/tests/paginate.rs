@@ -0,0 +1,86 @@
+extern crate squark;
+
+use squark::testing::TestRuntime;
+use squark::{
+    load_more_sentinel, pager, App, Paginated, Runtime, Subscription, SubscriptionKind, View,
+};
+
+#[derive(Clone, Debug)]
+enum Action {
+    LoadMore,
+    Loaded(Vec<u32>),
+    Show(usize),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct State {
+    list: Paginated<u32>,
+    shown: usize,
+}
+
+#[derive(Clone, Default)]
+struct Feed;
+
+impl App for Feed {
+    type State = State;
+    type Action = Action;
+
+    fn reducer(&self, mut state: State, action: Action) -> State {
+        match action {
+            Action::LoadMore => {
+                state.list.request_next();
+            }
+            Action::Loaded(items) => state.list.receive(items),
+            Action::Show(page) => state.shown = page,
+        }
+        state
+    }
+
+    fn subscriptions(&self, state: &State) -> Vec<Subscription<Action>> {
+        state
+            .list
+            .load_more("more", Action::LoadMore)
+            .into_iter()
+            .collect()
+    }
+
+    fn view(&self, state: State) -> View<Action> {
+        View::new(
+            "main",
+            vec![],
+            vec![],
+            vec![
+                pager(state.shown, state.list.loaded_pages(), Action::Show).into(),
+                load_more_sentinel("more").into(),
+            ],
+        )
+    }
+}
+
+#[test]
+fn loads_pages_while_the_sentinel_is_visible() {
+    let runtime = TestRuntime::<Feed>::new(State {
+        list: Paginated::new(2),
+        shown: 0,
+    });
+    let visible = SubscriptionKind::Visible("more".to_string());
+    runtime.emit(&visible, true.into());
+    assert!(runtime.state().list.is_loading());
+    assert!(runtime.subscriptions().is_empty());
+
+    runtime.dispatch(Action::Loaded(vec![1, 2]));
+    runtime.emit(&visible, true.into());
+    runtime.dispatch(Action::Loaded(vec![3]));
+    let list = runtime.state().list;
+    assert_eq!(list.page(1), &[3]);
+    assert!(!list.has_more());
+    assert!(runtime.subscriptions().is_empty());
+
+    runtime.click("button[aria-label='Next page']");
+    assert_eq!(runtime.state().shown, 1);
+    assert_eq!(
+        squark::query_all(&runtime.node(), "button[aria-current=page]").len(),
+        1
+    );
+    assert_eq!(squark::query_all(&runtime.node(), "button[disabled]").len(), 1);
+}
@@ -0,0 +1,244 @@
+extern crate squark;
+
+use squark::testing::TestRuntime;
+use squark::{
+    pending, App, Cmd, Env, Fetch, FetchFuture, KeyValueStorage, Request, Response, Runtime, View,
+};
+use std::cell::{Cell, RefCell};
+use std::future;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::task::{Context, Wake, Waker};
+
+struct Respond(u16, &'static str);
+
+impl Fetch for Respond {
+    fn request(&self, _: Request) -> FetchFuture {
+        Box::pin(future::ready(Ok(Response {
+            status: self.0,
+            headers: vec![],
+            body: self.1.to_string(),
+        })))
+    }
+}
+
+struct Noop;
+
+impl Wake for Noop {
+    fn wake(self: Arc<Self>) {}
+}
+
+fn complete_tasks<A: App>(runtime: &TestRuntime<A>) {
+    let waker = Waker::from(Arc::new(Noop));
+    let mut cx = Context::from_waker(&waker);
+    for mut task in runtime.take_tasks() {
+        assert!(task.as_mut().poll(&mut cx).is_ready());
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Action {
+    Load,
+    Loaded(String),
+}
+
+#[derive(Clone, Default)]
+struct Profile;
+
+impl App for Profile {
+    type State = String;
+    type Action = Action;
+
+    fn reducer(&self, state: String, action: Action) -> String {
+        match action {
+            Action::Load => state,
+            Action::Loaded(name) => name,
+        }
+    }
+
+    fn command(&self, _: &String, action: &Action) -> Cmd<Action> {
+        match *action {
+            Action::Load => Cmd::fetch(Request::get("/me"), |res| {
+                res.ok().map(|res| Action::Loaded(res.body))
+            })
+            .track("profile"),
+            Action::Loaded(_) => Cmd::none(),
+        }
+    }
+
+    fn view(&self, state: String) -> View<Action> {
+        pending("profile", View::static_text("Loading"), || {
+            View::text(state)
+        })
+    }
+}
+
+#[test]
+fn shows_the_fallback_while_tracked_commands_are_in_flight() {
+    let runtime = TestRuntime::<Profile>::new(String::new());
+    runtime.set_fetcher(Rc::new(Respond(200, "Ada")));
+    runtime.dispatch(Action::Load);
+    assert!(runtime.get_env().is_pending("profile"));
+    assert_eq!(format!("{}", runtime.node()), "Loading\n");
+
+    complete_tasks(&runtime);
+    assert!(!runtime.get_env().is_pending("profile"));
+    assert_eq!(format!("{}", runtime.node()), "Ada\n");
+}
+
+#[derive(Clone, Debug)]
+enum Theme {
+    Load,
+    Loaded(Option<String>),
+    Title,
+}
+
+#[derive(Clone, Default)]
+struct Settings;
+
+impl App for Settings {
+    type State = String;
+    type Action = Theme;
+
+    fn reducer(&self, state: String, action: Theme) -> String {
+        match action {
+            Theme::Loaded(theme) => theme.unwrap_or(state),
+            _ => state,
+        }
+    }
+
+    fn command(&self, _: &String, action: &Theme) -> Cmd<Theme> {
+        match *action {
+            Theme::Load => Cmd::storage_get("theme", Theme::Loaded).track("theme"),
+            Theme::Loaded(_) => Cmd::none(),
+            Theme::Title => Cmd::set_title("Settings").track("theme"),
+        }
+    }
+
+    fn view(&self, state: String) -> View<Theme> {
+        pending("theme", View::static_text("Loading"), || View::text(state))
+    }
+}
+
+// Remembers whether the theme was pending while it was read.
+struct Watched(Env<Settings>, Rc<Cell<bool>>);
+
+impl KeyValueStorage for Watched {
+    fn get(&self, _: &str) -> Option<String> {
+        self.1.set(self.0.is_pending("theme"));
+        Some("dark".to_string())
+    }
+
+    fn set(&self, _: &str, _: &str) {}
+
+    fn remove(&self, _: &str) {}
+}
+
+#[test]
+fn tracks_storage_reads() {
+    let runtime = TestRuntime::<Settings>::new("light".to_string());
+    let pending_while_read = Rc::new(Cell::new(false));
+    let storage = Watched(runtime.get_env().clone(), pending_while_read.clone());
+    runtime.set_storage(Rc::new(storage));
+
+    runtime.dispatch(Theme::Load);
+    assert!(pending_while_read.get());
+    assert!(!runtime.get_env().is_pending("theme"));
+    assert_eq!(format!("{}", runtime.node()), "dark\n");
+}
+
+#[test]
+fn leaves_commands_done_once_run_untracked() {
+    use squark::log;
+
+    let warnings = Rc::new(RefCell::new(vec![]));
+    let sink = warnings.clone();
+    log::set_sink(move |_, message: &str| sink.borrow_mut().push(message.to_string()));
+    let runtime = TestRuntime::<Settings>::new("light".to_string());
+    runtime.dispatch(Theme::Title);
+    log::reset_sink();
+
+    assert_eq!(runtime.title(), "Settings");
+    assert!(!runtime.get_env().is_pending("theme"));
+    if cfg!(all(debug_assertions, feature = "debug-checks")) {
+        assert_eq!(
+            *warnings.borrow(),
+            vec!["only fetches and storage reads are tracked by \"theme\""]
+        );
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Like {
+    Like,
+    Unlike,
+}
+
+#[derive(Clone, Default)]
+struct Likes;
+
+impl App for Likes {
+    type State = u32;
+    type Action = Like;
+
+    fn reducer(&self, likes: u32, action: Like) -> u32 {
+        match action {
+            Like::Like => likes + 1,
+            Like::Unlike => likes - 1,
+        }
+    }
+
+    fn command(&self, _: &u32, action: &Like) -> Cmd<Like> {
+        match *action {
+            Like::Like => {
+                Cmd::optimistic("like", Request::post("/like", ""), Like::Unlike, |_| None)
+            }
+            Like::Unlike => Cmd::none(),
+        }
+    }
+
+    fn view(&self, likes: u32) -> View<Like> {
+        View::text_of(likes)
+    }
+}
+
+#[test]
+fn rolls_back_optimistic_changes_when_the_request_fails() {
+    let runtime = TestRuntime::<Likes>::new(0);
+    runtime.set_fetcher(Rc::new(Respond(200, "")));
+    runtime.dispatch(Like::Like);
+    assert_eq!(runtime.state(), 1);
+    assert!(runtime.get_env().is_pending("like"));
+    complete_tasks(&runtime);
+    assert_eq!(runtime.state(), 1);
+
+    runtime.set_fetcher(Rc::new(Respond(503, "")));
+    runtime.dispatch(Like::Like);
+    assert_eq!(runtime.state(), 2);
+    complete_tasks(&runtime);
+    assert_eq!(runtime.state(), 1);
+    assert!(!runtime.get_env().is_pending("like"));
+}
+
+#[test]
+fn server_renders_placeholders_the_client_fills() {
+    use squark::ssr::{prerender_pending, render_to_string};
+    use squark::Env;
+
+    let html = prerender_pending::<Profile>(String::new(), &["profile"]);
+    assert_eq!(
+        html,
+        "<span data-squark-placeholder=\"profile\">Loading</span>"
+    );
+
+    let env = Env::<Profile>::builder()
+        .placeholders(true)
+        .build(String::new());
+    env.begin_pending("profile");
+    let runtime = TestRuntime::with_env(env);
+    assert_eq!(render_to_string(&runtime.node()), html);
+
+    runtime.fill_placeholder("profile", Action::Loaded("Ada".to_string()));
+    runtime.run();
+    assert_eq!(render_to_string(&runtime.node()), "Ada");
+}
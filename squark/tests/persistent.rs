@@ -0,0 +1,69 @@
+#![cfg(feature = "im")]
+
+extern crate squark;
+
+use squark::persistent::{Shared, Vector};
+use squark::testing::TestRuntime;
+use squark::{keyed_list, App, Runtime, View};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct State {
+    items: Vector<String>,
+    catalog: Shared<Vec<String>>,
+}
+
+#[derive(Clone, Debug)]
+enum Action {
+    Add(usize),
+    Rename(usize, &'static str),
+}
+
+#[derive(Clone, Default)]
+struct List;
+
+impl App for List {
+    type State = State;
+    type Action = Action;
+
+    fn reducer(&self, mut state: State, action: Action) -> State {
+        match action {
+            Action::Add(i) => state.items.push_back(state.catalog[i].clone()),
+            Action::Rename(i, name) => state.items[i] = name.to_string(),
+        }
+        state
+    }
+
+    fn view(&self, state: State) -> View<Action> {
+        let items = keyed_list(state.items.iter().enumerate(), |(i, item)| {
+            (
+                i,
+                View::new("li", vec![], vec![], vec![View::text(item.clone()).into()]),
+            )
+        });
+        View::new("ul", vec![], vec![], vec![items])
+    }
+}
+
+#[test]
+fn persistent_collections_work_as_state() {
+    let catalog = Shared::new(vec!["apple".to_string(), "pear".to_string()]);
+    let state = State {
+        items: Vector::new(),
+        catalog: catalog.clone(),
+    };
+    let runtime = TestRuntime::<List>::new(state);
+    runtime.dispatch(Action::Add(0));
+    runtime.dispatch(Action::Add(1));
+    runtime.dispatch(Action::Rename(0, "plum"));
+    let state = runtime.get_env().state_ref().clone();
+    assert_eq!(
+        state.items,
+        Vector::from(vec!["plum".to_string(), "pear".to_string()])
+    );
+    assert!(state.catalog.ptr_eq(&catalog));
+
+    let mut copy = state.catalog.clone();
+    assert_eq!(copy, catalog);
+    copy.make_mut().push("fig".to_string());
+    assert!(!copy.ptr_eq(&catalog) && catalog.len() == 2);
+}
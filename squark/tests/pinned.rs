@@ -0,0 +1,145 @@
+extern crate squark;
+
+use squark::testing::TestRuntime;
+use squark::{handler, App, Diff, Runtime, View};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct State {
+    version: u64,
+    renames: u32,
+}
+
+#[derive(Clone, Debug)]
+enum Action {
+    Rename,
+    Publish,
+}
+
+#[derive(Clone, Default)]
+struct Panel;
+
+impl App for Panel {
+    type State = State;
+    type Action = Action;
+
+    fn reducer(&self, mut state: State, action: Action) -> State {
+        match action {
+            Action::Rename => state.renames += 1,
+            Action::Publish => state.version += 1,
+        }
+        state
+    }
+
+    fn view(&self, state: State) -> View<Action> {
+        let rename = View::new(
+            "button",
+            vec![("class".to_string(), "rename".into())],
+            vec![("click".to_string(), handler(|_| Action::Rename))],
+            vec![View::text(format!("renamed {} times", state.renames)).into()],
+        );
+        let panel = View::new(
+            "section",
+            vec![("key".to_string(), "panel".into())],
+            vec![],
+            vec![rename.into()],
+        );
+        let publish = View::new(
+            "button",
+            vec![("class".to_string(), "publish".into())],
+            vec![("click".to_string(), handler(|_| Action::Publish))],
+            vec![],
+        );
+        View::new(
+            "div",
+            vec![],
+            vec![],
+            vec![publish.into(), View::pinned(state.version, panel).into()],
+        )
+    }
+}
+
+fn touches_panel(diffs: &[Diff]) -> bool {
+    diffs.iter().any(|diff| match *diff {
+        Diff::PatchRoot(ref diffs) => diffs
+            .iter()
+            .any(|d| matches!(*d, Diff::PatchChild(1, _) | Diff::ReplaceChild(1, _))),
+        _ => false,
+    })
+}
+
+#[test]
+fn pinned_views_change_only_with_their_version() {
+    let runtime = TestRuntime::<Panel>::new(State::default());
+    runtime.run();
+    runtime.take_diffs();
+
+    runtime.click("button.rename");
+    runtime.click("button.rename");
+    assert_eq!(runtime.state().renames, 2);
+    assert!(!touches_panel(&runtime.take_diffs()));
+
+    runtime.click("button.publish");
+    let diffs = runtime.take_diffs();
+    assert!(touches_panel(&diffs));
+    assert!(format!("{:?}", diffs).contains("renamed 2 times"));
+
+    runtime.click("button.rename");
+    assert_eq!(runtime.state().renames, 3);
+    assert!(!touches_panel(&runtime.take_diffs()));
+}
+
+#[test]
+fn hydrating_attaches_the_handlers_of_pinned_views() {
+    let runtime = TestRuntime::<Panel>::hydrated(State::default());
+    let diffs = format!("{:?}", runtime.take_diffs());
+    assert_eq!(diffs.matches("SetHandler(\"click\"").count(), 2);
+
+    runtime.click("button.rename");
+    assert_eq!(runtime.state().renames, 1);
+    assert!(!touches_panel(&runtime.take_diffs()));
+}
+
+#[derive(Clone, Default)]
+struct Unkeyed;
+
+impl App for Unkeyed {
+    type State = i32;
+    type Action = i32;
+
+    fn reducer(&self, _: i32, count: i32) -> i32 {
+        count
+    }
+
+    fn view(&self, count: i32) -> View<i32> {
+        let text = View::new(
+            "p",
+            vec![],
+            vec![],
+            vec![View::text(count.to_string()).into()],
+        );
+        View::new("div", vec![], vec![], vec![View::pinned(0, text).into()])
+    }
+}
+
+#[test]
+fn unkeyed_views_are_diffed_as_usual_with_a_warning() {
+    use squark::log;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let warnings = Rc::new(RefCell::new(vec![]));
+    let sink = warnings.clone();
+    log::set_sink(move |_, message: &str| sink.borrow_mut().push(message.to_string()));
+    let runtime = TestRuntime::<Unkeyed>::new(0);
+    runtime.take_diffs();
+    runtime.dispatch(1);
+    runtime.run();
+    log::reset_sink();
+
+    assert!(format!("{:?}", runtime.take_diffs()).contains("Text(\"1\")"));
+    if cfg!(all(debug_assertions, feature = "debug-checks")) {
+        assert!(warnings
+            .borrow()
+            .contains(&"pinned views need a key, diffing one without as usual".to_string()));
+    }
+}
@@ -0,0 +1,16 @@
+extern crate rand;
+extern crate squark;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use squark::proptest_support::{arbitrary_node, check_diff};
+
+#[test]
+fn patching_with_diffs_reproduces_the_new_tree() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..2000 {
+        let a = arbitrary_node(&mut rng, 3);
+        let b = arbitrary_node(&mut rng, 3);
+        check_diff(&a, &b).unwrap();
+    }
+}
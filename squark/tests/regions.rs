@@ -0,0 +1,102 @@
+#[macro_use]
+extern crate squark;
+
+use squark::testing::TestRuntime;
+use squark::{handler, App, Diff, Runtime, StateDiff, View};
+use std::cell::Cell;
+
+thread_local! {
+    static BUILDS: Cell<(u32, u32)> = const { Cell::new((0, 0)) };
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct State {
+    todos: Vec<String>,
+    filter: String,
+}
+
+state_diff!(State { todos, filter });
+
+#[derive(Clone, Debug)]
+enum Action {
+    Add,
+    Filter(&'static str),
+}
+
+#[derive(Clone, Default)]
+struct Todos;
+
+impl App for Todos {
+    type State = State;
+    type Action = Action;
+
+    fn reducer(&self, mut state: State, action: Action) -> State {
+        match action {
+            Action::Add => state.todos.push(format!("todo {}", state.todos.len())),
+            Action::Filter(filter) => state.filter = filter.to_string(),
+        }
+        state
+    }
+
+    fn changed_fields(&self, old: &State, new: &State) -> Option<Vec<&'static str>> {
+        Some(new.changed_fields(old))
+    }
+
+    fn view(&self, state: State) -> View<Action> {
+        let list = View::region("list", &["todos"], || {
+            BUILDS.with(|b| b.set((b.get().0 + 1, b.get().1)));
+            let items = state.todos.iter().map(|todo| {
+                View::new("li", vec![], vec![], vec![View::text(todo.clone()).into()]).into()
+            });
+            View::new("ul", vec![], vec![], items.collect())
+        });
+        let footer = View::region("footer", &["filter"], || {
+            BUILDS.with(|b| b.set((b.get().0, b.get().1 + 1)));
+            let add = View::new(
+                "button",
+                vec![("class".to_string(), "add".into())],
+                vec![("click".to_string(), handler(|_| Action::Add))],
+                vec![],
+            );
+            let active = View::new(
+                "button",
+                vec![("class".to_string(), "active".into())],
+                vec![("click".to_string(), handler(|_| Action::Filter("active")))],
+                vec![View::text(state.filter.clone()).into()],
+            );
+            View::new("footer", vec![], vec![], vec![add.into(), active.into()])
+        });
+        View::new("div", vec![], vec![], vec![list.into(), footer.into()])
+    }
+}
+
+fn builds() -> (u32, u32) {
+    BUILDS.with(Cell::get)
+}
+
+#[test]
+fn regions_are_rebuilt_only_when_their_fields_change() {
+    let runtime = TestRuntime::<Todos>::new(State::default());
+    runtime.run();
+    runtime.take_diffs();
+    assert_eq!(builds(), (1, 1));
+
+    runtime.click("button.add");
+    runtime.click("button.add");
+    assert_eq!(runtime.state().todos.len(), 2);
+    assert_eq!(builds(), (3, 1));
+    match runtime.take_diffs()[..] {
+        [Diff::PatchRoot(ref diffs), ..] => {
+            assert!(diffs.iter().all(|d| !matches!(*d, Diff::PatchChild(1, _))))
+        }
+        ref other => panic!("unexpected {:?}", other),
+    }
+
+    runtime.click("button.active");
+    assert_eq!(runtime.state().filter, "active");
+    assert_eq!(builds(), (3, 2));
+
+    runtime.click("button.add");
+    assert_eq!(runtime.state().todos.len(), 3);
+    assert_eq!(builds(), (4, 2));
+}
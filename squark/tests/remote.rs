@@ -0,0 +1,298 @@
+#[macro_use]
+extern crate serde_json;
+extern crate squark;
+
+use squark::remote;
+use squark::remote::{
+    DatagramChannel, DatagramTransport, Message, PatchCompressor, PatchDecompressor, PatchSequence,
+    Received, RemoteRuntime,
+};
+use squark::{handler, App, AttributeValue, Diff, Node, View};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[derive(Clone, Default)]
+struct Wire(Rc<RefCell<Vec<String>>>);
+
+impl Wire {
+    fn take(&self) -> Vec<String> {
+        self.0.borrow_mut().drain(..).collect()
+    }
+}
+
+impl DatagramTransport for Wire {
+    fn send(&self, datagram: &str) {
+        self.0.borrow_mut().push(datagram.to_string());
+    }
+}
+
+#[derive(Clone, Default)]
+struct Counter;
+
+impl App for Counter {
+    type State = i32;
+    type Action = i32;
+
+    fn reducer(&self, count: i32, n: i32) -> i32 {
+        count + n
+    }
+
+    fn view(&self, count: i32) -> View<i32> {
+        View::new(
+            "button",
+            vec![
+                ("class".to_string(), "counter".into()),
+                ("disabled".to_string(), false.into()),
+                (
+                    "value".to_string(),
+                    AttributeValue::Property(json!({ "n": count })),
+                ),
+            ],
+            vec![("click".to_string(), handler(|_| 1))],
+            vec![
+                View::text_of(count).into(),
+                View::custom("canvas", "chart").into(),
+            ],
+        )
+        .shadow_root()
+    }
+}
+
+#[test]
+fn trees_and_diffs_round_trip_through_json() {
+    let runtime = RemoteRuntime::<Counter, Wire>::new(0, Wire::default());
+    let node = runtime.node();
+    let decoded = Node::from_json(&node.to_json()).unwrap();
+    assert!(decoded.eq_with_handler_ids(&node));
+
+    let diff = Diff::PatchPath(vec![0, 2], vec![Diff::SetText("a".to_string())]);
+    assert_eq!(Diff::from_json(&diff.to_json()), Some(diff));
+    assert_eq!(Diff::from_json(&json!(["Unknown"])), None);
+}
+
+#[test]
+fn channels_deliver_in_order_despite_loss_and_reordering() {
+    let (a, b) = (Wire::default(), Wire::default());
+    let sender = DatagramChannel::new(a.clone());
+    let receiver = DatagramChannel::new(b.clone());
+    for n in 0..3 {
+        sender.send(json!(n));
+    }
+    let sent = a.take();
+    assert!(receiver.receive(&sent[2]).is_empty());
+    assert_eq!(receiver.receive(&sent[0]), vec![json!(0)]);
+    for ack in b.take() {
+        sender.receive(&ack);
+    }
+    assert_eq!(sender.unacknowledged(), 2);
+
+    sender.retransmit();
+    let delivered: Vec<_> = a.take().iter().flat_map(|d| receiver.receive(d)).collect();
+    assert_eq!(delivered, vec![json!(1), json!(2)]);
+    for ack in b.take() {
+        sender.receive(&ack);
+    }
+    assert_eq!(sender.unacknowledged(), 0);
+}
+
+#[test]
+fn remote_runtimes_send_patches_and_handle_events() {
+    let (server, client) = (Wire::default(), Wire::default());
+    let runtime = RemoteRuntime::<Counter, Wire>::new(0, server.clone());
+    let peer = DatagramChannel::new(client.clone());
+
+    let messages: Vec<Message> = server
+        .take()
+        .iter()
+        .flat_map(|d| peer.receive(d))
+        .filter_map(|m| Message::from_json(&m))
+        .collect();
+    let handler = messages
+        .iter()
+        .find_map(|message| match *message {
+            Message::Patch(_, None, ref diffs) => diffs.iter().find_map(|diff| match *diff {
+                Diff::AddChild(0, Node::Element(ref el)) => el.handler("click").map(String::from),
+                _ => None,
+            }),
+            _ => None,
+        })
+        .unwrap();
+
+    peer.send(Message::Event(handler, json!(null)).to_json());
+    for datagram in client.take() {
+        runtime.receive(0, &datagram);
+    }
+    assert_eq!(runtime.state(), 1);
+    assert!(server
+        .take()
+        .iter()
+        .flat_map(|d| peer.receive(d))
+        .any(|m| m.to_string().contains(r#"{"text":"1"}"#)));
+}
+
+#[test]
+fn handlers_of_the_previous_render_still_handle_events() {
+    let (server, client) = (Wire::default(), Wire::default());
+    let runtime = RemoteRuntime::<Counter, Wire>::new(0, server.clone());
+    let peer = DatagramChannel::new(client.clone());
+    let handler = runtime.node().to_json()["handlers"][0][1]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let click = || {
+        peer.send(Message::Event(handler.clone(), json!(null)).to_json());
+        for datagram in client.take() {
+            runtime.receive(0, &datagram);
+        }
+    };
+
+    click();
+    assert_eq!(runtime.state(), 1);
+    click();
+    assert_eq!(runtime.state(), 2);
+    click();
+    assert_eq!(runtime.state(), 2);
+}
+
+#[test]
+fn compressed_patches_restore_to_the_originals() {
+    let runtime = RemoteRuntime::<Counter, Wire>::new(0, Wire::default());
+    let set_text = |path: Vec<usize>, text: &str| {
+        Message::Patch(
+            2,
+            None,
+            vec![Diff::PatchPath(path, vec![Diff::SetText(text.to_string())])],
+        )
+    };
+    let messages = [
+        Message::Patch(0, None, vec![Diff::AddChild(0, runtime.node())]),
+        Message::Patch(1, None, vec![Diff::AddChild(1, runtime.node())]),
+        set_text(vec![1, 0, 3], "a"),
+        set_text(vec![1, 0, 4], "b"),
+        Message::Event("1".to_string(), json!(null)),
+    ];
+    let (mut compressor, mut decompressor) = (PatchCompressor::new(), PatchDecompressor::new());
+    let compressed: Vec<_> = messages
+        .iter()
+        .map(|message| compressor.compress(message.to_json()))
+        .collect();
+    assert!(compressed[1].to_string().len() < compressed[0].to_string().len());
+    assert_eq!(compressed[3]["patch"][0][1], json!([2, 4]));
+    for (message, compressed) in messages.iter().zip(compressed) {
+        let restored = decompressor.decompress(compressed).unwrap();
+        assert_eq!(restored, message.to_json());
+    }
+}
+
+#[test]
+fn peers_missing_patches_resync_from_the_full_tree() {
+    let (server, client) = (Wire::default(), Wire::default());
+    let runtime = RemoteRuntime::<Counter, Wire>::new(0, server.clone());
+    let peer = DatagramChannel::new(client.clone());
+    let mut sequence = PatchSequence::new();
+    let receive = |sequence: &mut PatchSequence| -> Vec<Received> {
+        server
+            .take()
+            .iter()
+            .flat_map(|d| peer.receive(d))
+            .filter_map(|m| Message::from_json(&m))
+            .map(|m| sequence.receive(m))
+            .collect()
+    };
+    let initial = receive(&mut sequence);
+    assert!(initial
+        .iter()
+        .all(|r| matches!(*r, Received::Apply(None, _))));
+
+    let gap = Message::Patch(9, None, vec![Diff::RemoveChild(0)]);
+    assert_eq!(sequence.receive(gap.clone()), Received::Resync);
+    assert_eq!(sequence.receive(gap), Received::Ignore);
+    assert!(sequence.is_resyncing());
+
+    peer.send(Message::RequestFullTree.to_json());
+    for datagram in client.take() {
+        runtime.receive(0, &datagram);
+    }
+    match receive(&mut sequence)[..] {
+        [Received::Replace(ref node, ref roots)] => {
+            assert!(node.eq_with_handler_ids(&runtime.node()));
+            assert!(roots.is_empty());
+        }
+        ref other => panic!("unexpected {:?}", other),
+    }
+    assert!(!sequence.is_resyncing());
+    assert_eq!(
+        sequence.receive(Message::Patch(initial.len() as u64, None, vec![])),
+        Received::Apply(None, vec![])
+    );
+}
+
+#[derive(Clone, Default)]
+struct Lobby;
+
+impl App for Lobby {
+    type State = Vec<u64>;
+    type Action = u64;
+
+    fn reducer(&self, mut joined: Vec<u64>, client: u64) -> Vec<u64> {
+        joined.push(client);
+        joined
+    }
+
+    fn view(&self, joined: Vec<u64>) -> View<u64> {
+        View::new(
+            "button",
+            vec![],
+            vec![("click".to_string(), handler(|_| remote::client()))],
+            vec![View::text(format!("{:?}", joined)).into()],
+        )
+    }
+}
+
+#[test]
+fn servers_broadcast_to_every_client_and_tell_handlers_the_sender() {
+    let runtime = RemoteRuntime::<Lobby, Wire>::serve(vec![], false);
+    let wires = [Wire::default(), Wire::default()];
+    let ids: Vec<u64> = wires.iter().map(|w| runtime.connect(w.clone())).collect();
+    assert_eq!(runtime.clients(), ids);
+
+    let peers: Vec<_> = wires
+        .iter()
+        .map(|_| DatagramChannel::new(Wire::default()))
+        .collect();
+    let handlers: Vec<_> = wires
+        .iter()
+        .zip(&peers)
+        .map(|(wire, peer)| {
+            let messages: Vec<_> = wire.take().iter().flat_map(|d| peer.receive(d)).collect();
+            match Message::from_json(&messages[0]) {
+                Some(Message::FullTree(0, Node::Element(el), _)) => {
+                    el.handler("click").map(String::from)
+                }
+                other => panic!("unexpected {:?}", other),
+            }
+        })
+        .collect();
+    assert_eq!(handlers[0], handlers[1]);
+    let handler = handlers[0].clone().unwrap();
+
+    peers[1].send(Message::Event(handler, json!(null)).to_json());
+    let sent = peers[1].transport().take();
+    for datagram in sent {
+        runtime.receive(ids[1], &datagram);
+    }
+    assert_eq!(runtime.state(), vec![ids[1]]);
+    assert_eq!(remote::client(), None);
+    for (wire, peer) in wires.iter().zip(&peers) {
+        let patches: Vec<_> = wire
+            .take()
+            .iter()
+            .flat_map(|d| peer.receive(d))
+            .filter_map(|m| Message::from_json(&m))
+            .collect();
+        assert!(matches!(patches[..], [Message::Patch(0, None, _), ..]));
+    }
+
+    assert!(runtime.disconnect(ids[0]));
+    assert_eq!(runtime.clients(), vec![ids[1]]);
+}
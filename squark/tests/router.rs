@@ -0,0 +1,280 @@
+extern crate squark;
+
+use squark::testing::TestRuntime;
+use squark::{
+    App, Cmd, Fetch, FetchFuture, Location, QueryParams, QuerySync, Request, Response, Route,
+    RouteLoader, RouteStatus, Routing, Runtime, Subscription, SubscriptionKind, View,
+};
+use std::future;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::task::{Context, Wake, Waker};
+
+struct Echo;
+
+impl Fetch for Echo {
+    fn request(&self, request: Request) -> FetchFuture {
+        let status = if request.url().ends_with("/0") {
+            404
+        } else {
+            200
+        };
+        Box::pin(future::ready(Ok(Response {
+            status,
+            headers: vec![],
+            body: request.url().to_string(),
+        })))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Page {
+    Home,
+    Article(u32),
+}
+
+impl Route for Page {
+    fn from_location(location: &Location) -> Option<Page> {
+        match location.segments()[..] {
+            [] => Some(Page::Home),
+            ["articles", id] => id.parse().ok().map(Page::Article),
+            _ => None,
+        }
+    }
+
+    fn to_url(&self) -> String {
+        match *self {
+            Page::Home => "/".to_string(),
+            Page::Article(id) => format!("/articles/{}", id),
+        }
+    }
+}
+
+impl RouteLoader for Page {
+    type Data = String;
+
+    fn loader(&self) -> Option<Cmd<Result<String, String>>> {
+        match *self {
+            Page::Home => None,
+            Page::Article(id) => Some(Cmd::fetch(
+                Request::get(format!("/api/articles/{}", id)),
+                |res| {
+                    Some(match res {
+                        Ok(ref res) if res.is_success() => Ok(res.body.clone()),
+                        _ => Err("Article not found".to_string()),
+                    })
+                },
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Action {
+    Go(Page),
+    Loaded(u64, Result<String, String>),
+}
+
+#[derive(Clone, Default)]
+struct Blog;
+
+impl App for Blog {
+    type State = Routing<Page>;
+    type Action = Action;
+
+    fn reducer(&self, mut routing: Routing<Page>, action: Action) -> Routing<Page> {
+        match action {
+            Action::Go(page) => routing.navigate(page),
+            Action::Loaded(generation, result) => routing.receive(generation, result),
+        }
+        routing
+    }
+
+    fn command(&self, routing: &Routing<Page>, action: &Action) -> Cmd<Action> {
+        match *action {
+            Action::Go(_) => routing.load(Action::Loaded),
+            Action::Loaded(..) => Cmd::none(),
+        }
+    }
+
+    fn view(&self, routing: Routing<Page>) -> View<Action> {
+        routing.view(
+            View::static_text("Loading"),
+            |message| View::text(message.to_string()),
+            |page, body| View::text(format!("{:?} {}", page, body.cloned().unwrap_or_default())),
+        )
+    }
+}
+
+struct Noop;
+
+impl Wake for Noop {
+    fn wake(self: Arc<Self>) {}
+}
+
+fn complete_tasks(runtime: &TestRuntime<Blog>) {
+    let waker = Waker::from(Arc::new(Noop));
+    let mut cx = Context::from_waker(&waker);
+    for mut task in runtime.take_tasks() {
+        assert!(task.as_mut().poll(&mut cx).is_ready());
+    }
+}
+
+#[test]
+fn routes_render_once_their_loader_resolves() {
+    let runtime = TestRuntime::<Blog>::new(Routing::new(Page::Home));
+    runtime.set_fetcher(Rc::new(Echo));
+    assert_eq!(format!("{}", runtime.node()), "Home \n");
+
+    runtime.dispatch(Action::Go(Page::Article(1)));
+    runtime.dispatch(Action::Go(Page::Article(2)));
+    assert_eq!(format!("{}", runtime.node()), "Loading\n");
+    complete_tasks(&runtime);
+    assert_eq!(
+        runtime.state().status(),
+        &RouteStatus::Ready(Some("/api/articles/2".to_string()))
+    );
+
+    runtime.dispatch(Action::Go(Page::Article(0)));
+    complete_tasks(&runtime);
+    assert_eq!(format!("{}", runtime.node()), "Article not found\n");
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Filters {
+    search: String,
+    page: u32,
+}
+
+impl QueryParams for Filters {
+    fn from_query(location: &Location) -> Filters {
+        Filters {
+            search: location.query_value("q").unwrap_or("").to_string(),
+            page: location
+                .query_value("page")
+                .and_then(|page| page.parse().ok())
+                .unwrap_or(0),
+        }
+    }
+
+    fn to_query(&self) -> Vec<(String, String)> {
+        let mut query = vec![];
+        if !self.search.is_empty() {
+            query.push(("q".to_string(), self.search.clone()));
+        }
+        if self.page > 0 {
+            query.push(("page".to_string(), self.page.to_string()));
+        }
+        query
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Search {
+    UrlChanged(Location),
+    Filter(Filters),
+}
+
+#[derive(Clone, Default)]
+struct Catalog;
+
+impl App for Catalog {
+    type State = QuerySync<Filters>;
+    type Action = Search;
+
+    fn reducer(&self, mut sync: QuerySync<Filters>, action: Search) -> QuerySync<Filters> {
+        match action {
+            Search::UrlChanged(location) => sync.navigate(location),
+            Search::Filter(filters) => sync.set(filters),
+        }
+        sync
+    }
+
+    fn command(&self, sync: &QuerySync<Filters>, action: &Search) -> Cmd<Search> {
+        match *action {
+            Search::Filter(_) => sync.replace_url(),
+            Search::UrlChanged(_) => Cmd::none(),
+        }
+    }
+
+    fn view(&self, sync: QuerySync<Filters>) -> View<Search> {
+        View::text(sync.get().search.clone())
+    }
+}
+
+#[test]
+fn query_sync_parses_navigations_and_replaces_the_url() {
+    let location = Location::parse("/shop?lang=en&q=tea&page=2");
+    let runtime = TestRuntime::<Catalog>::new(QuerySync::new(location));
+    assert_eq!(
+        runtime.state().get(),
+        &Filters {
+            search: "tea".to_string(),
+            page: 2,
+        }
+    );
+
+    runtime.dispatch(Search::Filter(Filters {
+        search: "green tea".to_string(),
+        page: 0,
+    }));
+    assert_eq!(runtime.history(), vec!["/shop?lang=en&q=green%20tea"]);
+
+    runtime.dispatch(Search::UrlChanged(Location::parse("/shop?page=3")));
+    assert_eq!(runtime.state().get().page, 3);
+    assert_eq!(format!("{}", runtime.node()), "\n");
+}
+
+#[derive(Clone, Debug)]
+enum Visit {
+    Go(&'static str),
+    UrlChanged(Location),
+}
+
+#[derive(Clone, Default)]
+struct Links;
+
+impl App for Links {
+    // The paths visited so far.
+    type State = Vec<String>;
+    type Action = Visit;
+
+    fn reducer(&self, mut visited: Vec<String>, action: Visit) -> Vec<String> {
+        if let Visit::UrlChanged(location) = action {
+            visited.push(location.path().to_string());
+        }
+        visited
+    }
+
+    fn command(&self, _: &Vec<String>, action: &Visit) -> Cmd<Visit> {
+        match *action {
+            Visit::Go(url) => Cmd::push_url(url),
+            Visit::UrlChanged(_) => Cmd::none(),
+        }
+    }
+
+    fn subscriptions(&self, _: &Vec<String>) -> Vec<Subscription<Visit>> {
+        vec![Subscription::url_change(Visit::UrlChanged)]
+    }
+
+    fn view(&self, visited: Vec<String>) -> View<Visit> {
+        View::text(visited.join(" "))
+    }
+}
+
+#[test]
+fn pushed_urls_reach_url_change_subscriptions() {
+    let runtime = TestRuntime::<Links>::new(vec![]);
+    assert_eq!(
+        runtime.subscriptions().values().collect::<Vec<_>>(),
+        vec![&SubscriptionKind::UrlChange]
+    );
+
+    runtime.dispatch(Visit::Go("/posts/1?draft"));
+    assert_eq!(runtime.history(), vec!["/posts/1?draft"]);
+    // The test runtime leaves notifying subscriptions to the test.
+    assert!(runtime.state().is_empty());
+
+    runtime.emit(&SubscriptionKind::UrlChange, "/posts/1?draft".into());
+    assert_eq!(runtime.state(), vec!["/posts/1"]);
+}
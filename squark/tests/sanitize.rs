@@ -0,0 +1,48 @@
+#![cfg(feature = "sanitize")]
+
+extern crate squark;
+
+use squark::{AllowlistSanitizer, Sanitizer};
+
+#[test]
+fn strips_scripts_and_event_handlers() {
+    let sanitizer = AllowlistSanitizer::new();
+    assert_eq!(
+        sanitizer.sanitize("<p onclick=\"x()\">Hi <b>there</b><script>alert(1)</script></p>"),
+        "<p>Hi <b>there</b></p>"
+    );
+}
+
+#[test]
+fn rejects_unsafe_url_schemes() {
+    let sanitizer = AllowlistSanitizer::new();
+    assert_eq!(
+        sanitizer.sanitize("<a href='javascript:alert(1)'>x</a><a href=\"/ok\">ok</a>"),
+        "<a>x</a><a href=\"/ok\">ok</a>"
+    );
+}
+
+#[test]
+fn decodes_character_references_before_checking_schemes() {
+    let sanitizer = AllowlistSanitizer::new();
+    for href in &[
+        "javascript&#58;alert(1)",
+        "javascript&colon;alert(1)",
+        "&#106;avascript:alert(1)",
+        "&#x6A;avascript&#x3a;alert(1)",
+    ] {
+        assert_eq!(
+            sanitizer.sanitize(&format!("<a href=\"{}\">x</a>", href)),
+            "<a>x</a>"
+        );
+    }
+}
+
+#[test]
+fn escapes_ampersands_in_attributes() {
+    let sanitizer = AllowlistSanitizer::new();
+    assert_eq!(
+        sanitizer.sanitize("<a href=\"/search?q=a&amp;page=2\" title='R&D'>x</a>"),
+        "<a href=\"/search?q=a&amp;page=2\" title=\"R&amp;D\">x</a>"
+    );
+}
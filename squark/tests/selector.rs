@@ -0,0 +1,44 @@
+extern crate squark;
+
+use squark::Selector;
+use std::cell::Cell;
+use std::rc::Rc;
+
+// Items and a filter the selector does not read.
+type State = (Vec<i32>, &'static str);
+
+fn counting_selector(computed: Rc<Cell<usize>>) -> Selector<State, i32> {
+    Selector::new(
+        |state: &State| state.0.clone(),
+        move |items: &Vec<i32>| {
+            computed.set(computed.get() + 1);
+            items.iter().sum()
+        },
+    )
+}
+
+#[test]
+fn recomputes_only_when_the_input_changes() {
+    let computed = Rc::new(Cell::new(0));
+    let total = counting_selector(computed.clone());
+    let state = (vec![1, 2], "");
+
+    let first = total.select(&state);
+    assert_eq!(*first, 3);
+    assert!(Rc::ptr_eq(&first, &total.select(&(vec![1, 2], "a"))));
+    assert_eq!(computed.get(), 1);
+
+    assert_eq!(*total.select(&(vec![1, 2, 3], "a")), 6);
+    assert_eq!(computed.get(), 2);
+}
+
+#[test]
+fn clones_share_the_cache() {
+    let computed = Rc::new(Cell::new(0));
+    let total = counting_selector(computed.clone());
+    let state = (vec![4], "");
+
+    total.select(&state);
+    total.clone().select(&state);
+    assert_eq!(computed.get(), 1);
+}
@@ -0,0 +1,156 @@
+#[macro_use]
+extern crate serde_json;
+extern crate squark;
+
+use squark::remote::{DatagramChannel, DatagramTransport, Message};
+use squark::session::{SessionApp, SessionServer, SessionState};
+use squark::{handler, Diff, Node, View};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[derive(Clone, Default)]
+struct Wire(Rc<RefCell<Vec<String>>>);
+
+impl Wire {
+    fn take(&self) -> Vec<String> {
+        self.0.borrow_mut().drain(..).collect()
+    }
+}
+
+impl DatagramTransport for Wire {
+    fn send(&self, datagram: &str) {
+        self.0.borrow_mut().push(datagram.to_string());
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Action {
+    Add,
+    Select(usize),
+}
+
+#[derive(Clone, Default)]
+struct Document;
+
+impl SessionApp for Document {
+    type Shared = Vec<String>;
+    type Session = Option<usize>;
+    type Action = Action;
+
+    fn reduce_shared(&self, mut lines: Vec<String>, action: &Action) -> Vec<String> {
+        if let Action::Add = *action {
+            lines.push(format!("line {}", lines.len()));
+        }
+        lines
+    }
+
+    fn reduce_session(
+        &self,
+        selected: Option<usize>,
+        lines: &Vec<String>,
+        action: Action,
+    ) -> Option<usize> {
+        match action {
+            Action::Select(i) if i < lines.len() => Some(i),
+            _ => selected,
+        }
+    }
+
+    fn view(&self, state: SessionState<Vec<String>, Option<usize>>) -> View<Action> {
+        let lines = state.shared.iter().enumerate().map(|(i, line)| {
+            View::new(
+                "p",
+                vec![(
+                    "aria-selected".to_string(),
+                    (state.session == Some(i)).into(),
+                )],
+                vec![("click".to_string(), handler(move |_| Action::Select(i)))],
+                vec![View::text(line.clone()).into()],
+            )
+            .into()
+        });
+        let add = View::new(
+            "button",
+            vec![],
+            vec![("click".to_string(), handler(|_| Action::Add))],
+            vec![],
+        );
+        View::new(
+            "div",
+            vec![],
+            vec![],
+            Some(add.into()).into_iter().chain(lines).collect(),
+        )
+    }
+}
+
+struct Peer {
+    wire: Wire,
+    channel: DatagramChannel<Wire>,
+}
+
+impl Peer {
+    fn diffs(&self) -> Vec<Diff> {
+        self.wire
+            .take()
+            .iter()
+            .flat_map(|d| self.channel.receive(d))
+            .filter_map(|m| match Message::from_json(&m) {
+                Some(Message::Patch(_, _, diffs)) => Some(diffs),
+                _ => None,
+            })
+            .flatten()
+            .collect()
+    }
+
+    fn click(&self, server: &SessionServer<Document, Wire>, client: u64, handler: &str) {
+        self.channel
+            .send(Message::Event(handler.to_string(), json!(null)).to_json());
+        for datagram in self.channel.transport().take() {
+            server.receive(client, &datagram);
+        }
+    }
+}
+
+fn handler_of(diffs: &[Diff], at: usize) -> String {
+    let root = diffs.iter().find_map(|diff| match *diff {
+        Diff::AddChild(0, Node::Element(ref el)) => Some(el.clone()),
+        _ => None,
+    });
+    match root.unwrap().children()[at] {
+        Node::Element(ref el) => el.handler("click").unwrap().to_string(),
+        ref other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn shared_changes_reach_every_client_and_sessions_stay_private() {
+    let server = SessionServer::<Document, Wire>::new(vec!["title".to_string()]);
+    let peers: Vec<Peer> = (0..2)
+        .map(|_| Peer {
+            wire: Wire::default(),
+            channel: DatagramChannel::new(Wire::default()),
+        })
+        .collect();
+    let ids: Vec<u64> = peers
+        .iter()
+        .map(|p| server.connect(p.wire.clone()))
+        .collect();
+    assert_eq!(server.clients(), ids);
+    let initial: Vec<Vec<Diff>> = peers.iter().map(Peer::diffs).collect();
+
+    peers[1].click(&server, ids[1], &handler_of(&initial[1], 1));
+    assert_eq!(server.session(ids[1]), Some(Some(0)));
+    assert_eq!(server.session(ids[0]), Some(None));
+    assert!(!peers[1].diffs().is_empty());
+    assert!(peers[0].diffs().is_empty());
+
+    peers[0].click(&server, ids[0], &handler_of(&initial[0], 0));
+    assert_eq!(server.shared(), vec!["title", "line 1"]);
+    assert!(!peers[0].diffs().is_empty());
+    assert!(!peers[1].diffs().is_empty());
+    assert_eq!(server.session(ids[1]), Some(Some(0)));
+
+    assert!(server.disconnect(ids[0]));
+    assert_eq!(server.session(ids[0]), None);
+}
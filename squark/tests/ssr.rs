@@ -0,0 +1,130 @@
+extern crate squark;
+
+use squark::bench::{rows, table};
+use squark::ssr::{
+    render_to_string, render_to_writer, render_to_writer_with, render_with_csp, CspSource,
+    CspSources, Inline, CHUNK_SIZE,
+};
+use squark::{Attribute, View};
+
+fn element(name: &str, attributes: &[(&str, &str)], text: Option<&'static str>) -> View<()> {
+    let attributes: Vec<Attribute> = attributes
+        .iter()
+        .map(|&(name, value)| (name.to_string(), value.into()))
+        .collect();
+    let children = text
+        .map(|text| View::text(text).into())
+        .into_iter()
+        .collect();
+    View::new(name, attributes, vec![], children)
+}
+
+#[test]
+fn inline_code_is_collected_for_csp() {
+    let page = View::<()>::new(
+        "div",
+        vec![],
+        vec![],
+        vec![
+            element("style", &[], Some("p{}")).into(),
+            element("script", &[], Some("go()")).into(),
+            element("script", &[("src", "/app.js")], None).into(),
+            element("p", &[("style", "color:red")], None).into(),
+            element("script", &[], Some("go()")).into(),
+        ],
+    );
+    let mut seen = vec![];
+    let (html, sources) = render_with_csp(page.node(), |inline, code| {
+        seen.push((inline, code.to_string()));
+        match inline {
+            Inline::Script => CspSource::Nonce("abc".to_string()),
+            _ => CspSource::Hash(format!("sha256-{}", code.len())),
+        }
+    });
+
+    assert_eq!(
+        html,
+        "<div><style>p{}</style><script nonce=\"abc\">go()</script>\
+         <script src=\"/app.js\"></script><p style=\"color:red\"></p>\
+         <script nonce=\"abc\">go()</script></div>"
+    );
+    assert_eq!(
+        seen,
+        vec![
+            (Inline::Style, "p{}".to_string()),
+            (Inline::Script, "go()".to_string()),
+            (Inline::StyleAttribute, "color:red".to_string()),
+            (Inline::Script, "go()".to_string()),
+        ]
+    );
+    assert_eq!(
+        sources,
+        CspSources {
+            script_src: vec!["'nonce-abc'".to_string()],
+            style_src: vec![
+                "'sha256-3'".to_string(),
+                "'unsafe-hashes'".to_string(),
+                "'sha256-9'".to_string(),
+            ],
+        }
+    );
+}
+
+#[test]
+fn raw_text_is_not_escaped() {
+    let page = View::<()>::new(
+        "div",
+        vec![],
+        vec![],
+        vec![
+            element("script", &[], Some("if (a < b && c > \"d\") {}")).into(),
+            element("style", &[], Some("a > b { content: '&' }")).into(),
+            element("script", &[], Some("s = '</SCRIPT>'; t = '</p>'")).into(),
+            element("p", &[], Some("a < b")).into(),
+        ],
+    );
+    let mut seen = vec![];
+    let (html, _) = render_with_csp(page.node(), |_, code| {
+        seen.push(code.to_string());
+        CspSource::Hash(format!("sha256-{}", code.len()))
+    });
+
+    assert_eq!(
+        html,
+        "<div><script>if (a < b && c > \"d\") {}</script>\
+         <style>a > b { content: '&' }</style>\
+         <script>s = '<\\/SCRIPT>'; t = '</p>'</script><p>a &lt; b</p></div>"
+    );
+    assert_eq!(html, render_to_string(page.node()));
+    assert_eq!(
+        seen,
+        vec![
+            "if (a < b && c > \"d\") {}",
+            "a > b { content: '&' }",
+            "s = '<\\/SCRIPT>'; t = '</p>'",
+        ]
+    );
+}
+
+#[test]
+fn large_pages_are_written_in_chunks() {
+    let page = table::<()>(&rows(1000));
+    let html = render_to_string(page.node());
+
+    let mut flushes = vec![];
+    let mut out = vec![];
+    render_to_writer_with(page.node(), &mut out, |out: &mut &mut Vec<u8>| {
+        flushes.push(out.len());
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), html);
+    assert!(flushes.len() > 1);
+    assert!(flushes[..flushes.len() - 1]
+        .windows(2)
+        .all(|w| w[1] - w[0] >= CHUNK_SIZE));
+
+    let mut out = vec![];
+    render_to_writer(page.node(), &mut out).unwrap();
+    assert_eq!(out, html.into_bytes());
+}
@@ -0,0 +1,57 @@
+extern crate squark;
+
+use squark::testing::TestRuntime;
+use squark::{css, App, Diff, View};
+
+#[derive(Clone, Default)]
+struct Styled;
+
+impl App for Styled {
+    type State = &'static str;
+    type Action = ();
+
+    fn reducer(&self, declarations: &'static str, _: ()) -> &'static str {
+        declarations
+    }
+
+    fn view(&self, declarations: &'static str) -> View<()> {
+        View::new(
+            "div",
+            vec![("class".to_string(), css(declarations).into())],
+            vec![],
+            vec![],
+        )
+    }
+}
+
+fn injected(runtime: &TestRuntime<Styled>) -> Vec<String> {
+    runtime
+        .take_diffs()
+        .into_iter()
+        .filter_map(|diff| match diff {
+            Diff::InjectStyle(_, rule) => Some(rule),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn envs_inject_the_rules_of_their_own_renders() {
+    let red = TestRuntime::<Styled>::new("color: red;");
+    let blue = TestRuntime::<Styled>::new("color: blue;");
+    assert_eq!(injected(&red).len(), 1);
+    let rules = injected(&blue);
+    assert_eq!(rules.len(), 1);
+    assert!(rules[0].contains("color: blue;"));
+}
+
+#[test]
+fn colliding_declarations_get_distinct_classes() {
+    // Both hash to 757f4523.
+    let narrow = css("width: 129599px;");
+    let wide = css("width: 732382px;");
+    assert_eq!(narrow, "sq-757f4523");
+    assert_eq!(wide, "sq-757f4523-1");
+    assert_eq!(css("width: 732382px;"), wide);
+    assert_eq!(css("width: 129599px;"), narrow);
+}
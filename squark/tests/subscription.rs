@@ -0,0 +1,95 @@
+#[macro_use]
+extern crate serde_json;
+extern crate squark;
+
+use serde_json::Value;
+use squark::testing::TestRuntime;
+use squark::{App, Shortcuts, Subscription, SubscriptionKind, View};
+use std::time::Duration;
+
+fn key(key: &str, ctrl: bool, scopes: &[&str]) -> Value {
+    json!({ "key": key, "ctrl": ctrl, "alt": false, "shift": false, "meta": false, "scopes": scopes })
+}
+
+#[test]
+fn maps_chords_and_sequences_to_actions() {
+    let sub = Shortcuts::new()
+        .bind("ctrl+k", "search")
+        .bind("g g", "top")
+        .bind_in("editor", "ctrl+k", "link")
+        .into_subscription();
+    assert_eq!(sub.kind(), &SubscriptionKind::KeyDown);
+
+    assert_eq!(sub.handle(key("k", false, &[])), None);
+    assert_eq!(sub.handle(key("K", true, &[])), Some("search"));
+    assert_eq!(sub.handle(key("k", true, &["editor", "main"])), Some("link"));
+
+    assert_eq!(sub.handle(key("g", false, &[])), None);
+    assert_eq!(sub.handle(key("Control", true, &[])), None);
+    assert_eq!(sub.handle(key("x", false, &[])), None);
+    assert_eq!(sub.handle(key("g", false, &[])), None);
+    assert_eq!(sub.handle(key("g", false, &[])), Some("top"));
+}
+
+#[test]
+#[should_panic(expected = "conflicts")]
+fn rejects_shortcuts_shadowing_each_other() {
+    Shortcuts::new().bind("g", 1).bind("g g", 2);
+}
+
+#[test]
+fn fires_when_tagged_elements_become_visible() {
+    let sub = Subscription::on_visible("more", "load");
+    assert_eq!(sub.kind(), &SubscriptionKind::Visible("more".to_string()));
+    assert_eq!(sub.handle(json!(true)), Some("load"));
+    assert_eq!(sub.handle(json!(false)), None);
+}
+
+#[test]
+fn reports_element_sizes() {
+    let sub = Subscription::on_resize("chart", |width, height| (width, height));
+    assert_eq!(sub.handle(json!([320.0, 200.5])), Some((320.0, 200.5)));
+    assert_eq!(sub.handle(json!(null)), None);
+}
+
+#[test]
+fn ticks_carry_frame_timestamps() {
+    let sub = Subscription::animation_frame(|t| t / 1000.0);
+    assert_eq!(sub.kind(), &SubscriptionKind::AnimationFrame);
+    assert_eq!(sub.handle(json!(1500.0)), Some(1.5));
+}
+
+#[derive(Clone, Default)]
+struct Clocks;
+
+impl App for Clocks {
+    type State = Vec<&'static str>;
+    type Action = &'static str;
+
+    fn reducer(&self, mut ticks: Vec<&'static str>, clock: &'static str) -> Vec<&'static str> {
+        ticks.push(clock);
+        ticks
+    }
+
+    fn subscriptions(&self, _: &Vec<&'static str>) -> Vec<Subscription<&'static str>> {
+        let second = Duration::from_secs(1);
+        vec![
+            Subscription::every(second, |_| "clock"),
+            Subscription::every(second, |_| "autosave"),
+        ]
+    }
+
+    fn view(&self, _: Vec<&'static str>) -> View<&'static str> {
+        View::null()
+    }
+}
+
+#[test]
+fn ticks_reach_every_subscription_of_a_kind() {
+    let runtime = TestRuntime::<Clocks>::new(vec![]);
+    let kind = SubscriptionKind::Every(Duration::from_secs(1));
+    assert_eq!(runtime.subscriptions().len(), 1);
+
+    runtime.emit(&kind, json!(1000.0));
+    assert_eq!(runtime.state(), vec!["clock", "autosave"]);
+}
@@ -0,0 +1,100 @@
+extern crate squark;
+
+use squark::testing::TestRuntime;
+use squark::{App, Column, DataTable, Diff, Runtime, Sort, SortDirection, View};
+
+#[derive(Clone, Debug, PartialEq)]
+struct User {
+    id: u32,
+    name: &'static str,
+}
+
+#[derive(Clone, Debug)]
+enum Action {
+    Sort(Sort),
+    Open(u32),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct State {
+    users: Vec<User>,
+    sort: Option<Sort>,
+    open: Option<u32>,
+}
+
+impl Default for State {
+    fn default() -> State {
+        State {
+            users: vec![User { id: 1, name: "Bo" }, User { id: 2, name: "Al" }],
+            sort: None,
+            open: None,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+struct Users;
+
+impl App for Users {
+    type State = State;
+    type Action = Action;
+
+    fn reducer(&self, mut state: State, action: Action) -> State {
+        match action {
+            Action::Sort(sort) => state.sort = Some(sort),
+            Action::Open(id) => state.open = Some(id),
+        }
+        state
+    }
+
+    fn view(&self, state: State) -> View<Action> {
+        DataTable::new(|user: &User| user.id)
+            .column(Column::new("id", "Id", |user: &User| {
+                View::text_of(user.id)
+            }))
+            .column(
+                Column::new("name", "Name", |user: &User| View::static_text(user.name))
+                    .sortable(|a: &User, b: &User| a.name.cmp(b.name)),
+            )
+            .on_sort(Action::Sort)
+            .on_row_click(|user| Action::Open(user.id))
+            .view(&state.users, state.sort.as_ref())
+    }
+}
+
+fn row_keys(runtime: &TestRuntime<Users>) -> Vec<String> {
+    squark::query_all(&runtime.node(), "tbody tr")
+        .iter()
+        .map(|tr| tr.key().expect("rows are keyed").to_string())
+        .collect()
+}
+
+#[test]
+fn sorts_by_clicked_headers_and_moves_keyed_rows() {
+    let runtime = TestRuntime::<Users>::new(State::default());
+    runtime.click("tbody tr[key='2']");
+    assert_eq!(runtime.state().open, Some(2));
+    assert!(!runtime.trigger("th[data-column=id]", "click", squark::HandlerArg::Null));
+
+    runtime.take_diffs();
+    runtime.click("th[data-column=name]");
+    assert_eq!(
+        runtime.state().sort,
+        Some(Sort {
+            column: "name".to_string(),
+            direction: SortDirection::Ascending,
+        })
+    );
+    let diffs: Vec<String> = runtime.take_diffs().iter().map(Diff::explain).collect();
+    assert!(diffs
+        .join("\n")
+        .contains("SetAttribute aria-sort=\"ascending\""));
+    assert_eq!(row_keys(&runtime), vec!["2", "1"]);
+
+    runtime.click("th[aria-sort]");
+    assert_eq!(
+        runtime.state().sort.unwrap().direction,
+        SortDirection::Descending
+    );
+    assert_eq!(row_keys(&runtime), vec!["1", "2"]);
+}
@@ -0,0 +1,60 @@
+extern crate squark;
+
+use squark::{Node, Tag, View};
+
+fn tag(view: View<()>) -> Tag {
+    match *view.node() {
+        Node::Element(ref el) => el.tag().clone(),
+        ref node => panic!("unexpected {:?}", node),
+    }
+}
+
+#[test]
+fn names_map_to_known_tags_or_custom_ones() {
+    assert_eq!(Tag::from_name("div"), Tag::Div);
+    assert_eq!(Tag::from_name("linearGradient"), Tag::LinearGradient);
+    assert_eq!(
+        Tag::from_name("my-widget"),
+        Tag::Custom("my-widget".to_string())
+    );
+    // Names are matched as written.
+    assert_eq!(Tag::from_name("DIV"), Tag::Custom("DIV".to_string()));
+
+    for name in &["ul", "h1", "foreignObject", "my-widget"] {
+        assert_eq!(Tag::from_name(name).name(), *name);
+    }
+    assert_eq!(Tag::Svg.to_string(), "svg");
+}
+
+#[test]
+fn only_void_elements_are_void() {
+    assert!(Tag::Br.is_void());
+    assert!(Tag::Input.is_void());
+    assert!(!Tag::Div.is_void());
+    assert!(!Tag::Custom("br-like".to_string()).is_void());
+}
+
+#[test]
+fn views_take_tags_and_names_alike() {
+    let element = |name: Tag| -> View<()> { View::new(name, vec![], vec![], vec![]) };
+    assert_eq!(tag(element(Tag::Ul)), Tag::Ul);
+    assert_eq!(tag(View::new("ul", vec![], vec![], vec![])), Tag::Ul);
+    assert_eq!(
+        tag(View::new("my-widget".to_string(), vec![], vec![], vec![])),
+        Tag::Custom("my-widget".to_string())
+    );
+
+    let page: View<()> = View::new(
+        Tag::Div,
+        vec![],
+        vec![],
+        vec![
+            element(Tag::Br).into(),
+            View::new("my-widget", vec![], vec![], vec![]).into(),
+        ],
+    );
+    assert_eq!(
+        squark::ssr::render_to_string(page.node()),
+        "<div><br><my-widget></my-widget></div>"
+    );
+}
@@ -0,0 +1,95 @@
+extern crate squark;
+
+use squark::proptest_support::diff;
+use squark::{Binding, Diff, Node, Template, View};
+
+fn element(name: &str, children: Vec<View<()>>) -> View<()> {
+    View::new(
+        name.to_string(),
+        vec![],
+        vec![],
+        children.into_iter().map(|v| v.into()).collect(),
+    )
+}
+
+#[test]
+fn fills_holes_in_order() {
+    let template = Template::new(|holes| {
+        View::new(
+            "div".to_string(),
+            vec![("class".to_string(), holes.attribute())],
+            vec![],
+            vec![
+                element("h1", vec![holes.text()]).into(),
+                element("ul", vec![holes.children()]).into(),
+            ],
+        )
+    });
+    let view: View<()> = template.render(vec![
+        Binding::Attribute("card".into()),
+        Binding::Text("Title".to_string()),
+        Binding::Children(vec![element("li", vec![]), element("li", vec![])]),
+    ]);
+
+    let root = match view.node() {
+        Node::Element(el) => el,
+        _ => panic!("element expected"),
+    };
+    assert_eq!(root.attributes()[0].0, "class");
+    match (&root.children()[0], &root.children()[1]) {
+        (Node::Element(h1), Node::Element(ul)) => {
+            assert!(matches!(h1.children()[0], Node::Text(ref s) if s == "Title"));
+            assert_eq!(ul.children().len(), 2);
+        }
+        _ => panic!("elements expected"),
+    }
+}
+
+#[test]
+fn diffs_instances_by_their_holes() {
+    let template = Template::new(|holes| {
+        View::new(
+            "div".to_string(),
+            vec![("class".to_string(), holes.attribute())],
+            vec![],
+            vec![
+                element("h1", vec![holes.text()]).into(),
+                element("ul", vec![holes.children()]).into(),
+            ],
+        )
+    });
+    let card = |class: &str, title: &str, items: usize| -> View<()> {
+        template.render(vec![
+            Binding::Attribute(class.to_string().into()),
+            Binding::Text(title.to_string()),
+            Binding::Children((0..items).map(|_| element("li", vec![])).collect()),
+        ])
+    };
+
+    let a = card("card", "Title", 1);
+    assert!(diff(a.node(), card("card", "Title", 1).node()).is_none());
+
+    let b = card("card", "Other", 1);
+    assert_eq!(
+        diff(a.node(), b.node()),
+        Some(Diff::PatchRoot(vec![Diff::PatchPath(
+            vec![0, 0],
+            vec![Diff::SetText("Other".to_string())]
+        )]))
+    );
+
+    let c = card("card wide", "Title", 2);
+    assert_eq!(
+        diff(a.node(), c.node()),
+        Some(Diff::PatchRoot(vec![
+            Diff::PatchPath(
+                vec![],
+                vec![Diff::SetAttribute("class".to_string(), "card wide".into())]
+            ),
+            Diff::PatchPath(
+                vec![1],
+                vec![Diff::AddChild(1, element("li", vec![]).node().clone())]
+            ),
+        ]))
+    );
+}
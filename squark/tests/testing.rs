@@ -0,0 +1,328 @@
+extern crate squark;
+
+use squark::testing::TestRuntime;
+use squark::{handler, App, Cmd, Politeness, Runtime, ScrollBehavior, View};
+
+#[derive(Clone, Default)]
+struct Counter;
+
+#[derive(Clone, Debug)]
+enum Action {
+    Add(isize),
+    Set(String),
+}
+
+impl App for Counter {
+    type State = (isize, String);
+    type Action = Action;
+
+    fn reducer(&self, state: Self::State, action: Action) -> Self::State {
+        match action {
+            Action::Add(n) => (state.0 + n, state.1),
+            Action::Set(s) => (state.0, s),
+        }
+    }
+
+    fn command(&self, state: &Self::State, action: &Action) -> Cmd<Action> {
+        match *action {
+            Action::Add(_) => Cmd::announce(format!("count is {}", state.0), Politeness::Polite),
+            Action::Set(_) => Cmd::none(),
+        }
+    }
+
+    fn roots(&self, state: &Self::State) -> Vec<(String, View<Action>)> {
+        let header = View::new(
+            "header".to_string(),
+            vec![],
+            vec![(
+                "click".to_string(),
+                handler(|_| Some(Action::Add(10))),
+            )],
+            vec![View::text_of(state.0).into()],
+        );
+        vec![("header".to_string(), header)]
+    }
+
+    fn view(&self, state: Self::State) -> View<Action> {
+        let count = state.0;
+        let button = |test: &str, n: isize| -> View<Action> {
+            View::new(
+                "button".to_string(),
+                vec![("data-test".to_string(), test.into())],
+                vec![(
+                    "click".to_string(),
+                    handler(move |_| Some(Action::Add(n))),
+                )],
+                vec![],
+            )
+        };
+        View::new(
+            "div".to_string(),
+            vec![("class".to_string(), "counter".into())],
+            vec![],
+            vec![
+                button("inc", 1).into(),
+                button("dec", -1).into(),
+                View::new(
+                    "button".to_string(),
+                    vec![("data-test".to_string(), "reset".into())],
+                    vec![(
+                        "click".to_string(),
+                        handler(move |_| vec![Action::Add(-count), Action::Set(String::new())]),
+                    )],
+                    vec![],
+                )
+                .into(),
+                View::new(
+                    "input".to_string(),
+                    vec![],
+                    vec![(
+                        "input".to_string(),
+                        handler(|arg| arg.as_str().map(|s| Action::Set(s.to_string()))),
+                    )],
+                    vec![],
+                )
+                .into(),
+                state.0.into(),
+            ],
+        )
+    }
+}
+
+#[test]
+fn dispatches_handlers_found_by_selector() {
+    let runtime = TestRuntime::<Counter>::new((0, String::new()));
+    runtime.click(".counter > button[data-test=inc]");
+    runtime.click("button[data-test='inc']");
+    runtime.click("div button[data-test=dec]");
+    runtime.input("input", "hello");
+    assert_eq!(runtime.state(), (1, "hello".to_string()));
+
+    runtime.take_diffs();
+    runtime.click("button[data-test=reset]");
+    assert_eq!(runtime.state(), (0, String::new()));
+    assert_eq!(runtime.take_diffs().len(), 1);
+}
+
+#[test]
+fn seeded_runtimes_generate_the_same_ids() {
+    let a = TestRuntime::<Counter>::with_seed((0, String::new()), 7);
+    let b = TestRuntime::<Counter>::with_seed((0, String::new()), 7);
+    assert!(a.node().eq_with_handler_ids(&b.node()));
+}
+
+#[test]
+fn content_ids_repeat_across_renders() {
+    use squark::{ContentIds, IdGen};
+
+    let ids = ContentIds::new();
+    let first = (ids.next_id("click"), ids.next_id("click"));
+    ids.reset();
+    let second = (ids.next_id("click"), ids.next_id("click"));
+    assert_eq!(first, second);
+    assert_ne!(first.0, first.1);
+}
+
+#[test]
+fn unmount_removes_the_tree_and_ignores_later_actions() {
+    use squark::{Diff, Node, Runtime};
+
+    let runtime = TestRuntime::<Counter>::new((0, String::new()));
+    runtime.take_diffs();
+    runtime.unmount();
+    assert!(matches!(
+        runtime.take_diffs()[0],
+        Diff::ReplaceRoot(Node::Null)
+    ));
+    assert!(matches!(runtime.node(), Node::Null));
+
+    runtime.dispatch(Action::Add(1));
+    assert_eq!(runtime.state().0, 0);
+}
+
+#[test]
+fn mounting_adds_whole_trees() {
+    use squark::Diff;
+
+    let runtime = TestRuntime::<Counter>::new((0, String::new()));
+    assert!(matches!(
+        runtime.take_diffs().last(),
+        Some(Diff::AddChild(0, _))
+    ));
+    runtime.take_root_diffs();
+
+    runtime.mount();
+    assert!(matches!(runtime.take_diffs()[..], [Diff::AddChild(0, _)]));
+    assert!(matches!(
+        runtime.take_root_diffs()[..],
+        [(_, Diff::AddChild(0, _))]
+    ));
+}
+
+#[test]
+fn rendering_from_scratch_replaces_whole_trees() {
+    use squark::Diff;
+
+    let runtime = TestRuntime::<Counter>::new((0, String::new()));
+    runtime.take_diffs();
+    runtime.take_root_diffs();
+
+    runtime.render_from_scratch();
+    let node = runtime.get_env().snapshot_node();
+    assert_eq!(runtime.take_diffs(), vec![Diff::ReplaceRoot(node)]);
+    assert!(matches!(
+        runtime.take_root_diffs()[..],
+        [(_, Diff::ReplaceRoot(_))]
+    ));
+
+    // Only the render asked for is a full one.
+    runtime.run();
+    assert!(matches!(runtime.take_diffs()[..], [Diff::PatchRoot(_)]));
+}
+
+#[test]
+fn renders_additional_roots_from_the_same_state() {
+    use squark::Diff;
+
+    let runtime = TestRuntime::<Counter>::new((0, String::new()));
+    let diffs = runtime.take_root_diffs();
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].0, "header");
+    assert!(matches!(diffs[0].1, Diff::AddChild(0, _)));
+
+    runtime.click("header");
+    assert_eq!(runtime.state().0, 10);
+    let diffs = runtime.take_root_diffs();
+    assert_eq!(diffs.len(), 1);
+    assert!(matches!(diffs[0].1, Diff::PatchRoot(_)));
+
+    runtime.click("button[data-test=inc]");
+    assert_eq!(runtime.state().0, 11);
+}
+
+#[test]
+fn builds_children_from_lists_in_variables() {
+    use squark::{Child, Node};
+
+    let mut items: Child<Action> = vec![View::text_of(1), View::text_of(2)].into();
+    items.extend(vec![View::text_of(3)]);
+    let none: Option<Vec<View<Action>>> = None;
+    let view = View::new("ul".to_string(), vec![], vec![], vec![items, none.into()]);
+    match view.node() {
+        Node::Element(el) => assert_eq!(el.children().len(), 3),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn keyed_list_sets_keys() {
+    use squark::{keyed_list, query_all};
+
+    let items = vec![(1, "one"), (2, "two")];
+    let list = keyed_list(items, |(id, label)| {
+        (id, View::new("li".to_string(), vec![], vec![], vec![label.into()]))
+    });
+    let view: View<Action> = View::new("ul".to_string(), vec![], vec![], vec![list]);
+    assert_eq!(query_all(view.node(), "li[key='2']").len(), 1);
+}
+
+#[test]
+#[should_panic(expected = "duplicate key `1`")]
+fn keyed_list_rejects_duplicate_keys() {
+    squark::keyed_list(vec![1, 1], |id| {
+        (id, View::<Action>::new("li".to_string(), vec![], vec![], vec![]))
+    });
+}
+
+#[test]
+fn trees_compare_without_handler_ids() {
+    let a = TestRuntime::<Counter>::new((0, String::new()));
+    let b = TestRuntime::<Counter>::new((0, String::new()));
+    assert_eq!(a.node(), b.node());
+    assert!(!a.node().eq_with_handler_ids(&b.node()));
+}
+
+#[test]
+fn records_announcements() {
+    let runtime = TestRuntime::<Counter>::new((0, String::new()));
+    runtime.click("button[data-test=inc]");
+    assert_eq!(
+        runtime.take_announcements(),
+        vec![("count is 1".to_string(), Politeness::Polite)]
+    );
+}
+
+#[derive(Clone, Default)]
+struct Chat;
+
+impl App for Chat {
+    type State = Vec<u32>;
+    type Action = u32;
+
+    fn reducer(&self, mut messages: Vec<u32>, id: u32) -> Vec<u32> {
+        messages.push(id);
+        messages
+    }
+
+    fn command(&self, _: &Vec<u32>, id: &u32) -> Cmd<u32> {
+        Cmd::scroll_into_view(id.to_string(), ScrollBehavior::Smooth)
+    }
+
+    fn view(&self, messages: Vec<u32>) -> View<u32> {
+        let list = squark::keyed_list(messages, |id| {
+            (id, View::new("li".to_string(), vec![], vec![], vec![]))
+        });
+        View::new(
+            "main".to_string(),
+            vec![],
+            vec![],
+            vec![
+                View::new("h1".to_string(), vec![], vec![], vec![]).into(),
+                View::new("ol".to_string(), vec![], vec![], vec![list]).into(),
+            ],
+        )
+    }
+}
+
+#[test]
+fn scrolls_keyed_elements_into_view_once_rendered() {
+    let runtime = TestRuntime::<Chat>::new(vec![1, 2]);
+    runtime.dispatch(3);
+    assert_eq!(
+        runtime.take_scrolls(),
+        vec![(vec![1, 2], ScrollBehavior::Smooth)]
+    );
+}
+
+#[derive(Clone, Default)]
+struct Inbox;
+
+impl App for Inbox {
+    type State = usize;
+    type Action = ();
+
+    fn reducer(&self, unread: usize, _: ()) -> usize {
+        unread + 1
+    }
+
+    fn command(&self, unread: &usize, _: &()) -> Cmd<()> {
+        Cmd::batch(vec![
+            Cmd::set_title(format!("Inbox ({})", unread)),
+            Cmd::set_favicon("/unread.png"),
+        ])
+    }
+
+    fn view(&self, _: usize) -> View<()> {
+        View::text("")
+    }
+}
+
+#[test]
+fn sets_the_title_and_favicon() {
+    let runtime = TestRuntime::<Inbox>::new(0);
+    assert_eq!(runtime.title(), "");
+    runtime.dispatch(());
+    runtime.dispatch(());
+    assert_eq!(runtime.title(), "Inbox (2)");
+    assert_eq!(runtime.favicon(), Some("/unread.png".to_string()));
+}
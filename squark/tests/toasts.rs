@@ -0,0 +1,123 @@
+extern crate squark;
+
+use squark::testing::TestRuntime;
+use squark::{
+    query, toast_stack, App, HandlerArg, Runtime, Subscription, SubscriptionKind, Toasts, View,
+};
+use std::time::Duration;
+
+#[derive(Clone, Debug)]
+enum Action {
+    Notify(&'static str),
+    Dismiss(u64),
+    Tick(f64),
+}
+
+#[derive(Clone, Default)]
+struct Notifier;
+
+impl App for Notifier {
+    type State = Toasts;
+    type Action = Action;
+
+    fn reducer(&self, mut toasts: Toasts, action: Action) -> Toasts {
+        match action {
+            Action::Notify(message) => {
+                toasts.push(message);
+            }
+            Action::Dismiss(id) => toasts.dismiss(id),
+            Action::Tick(now) => toasts.tick(now),
+        }
+        toasts
+    }
+
+    fn subscriptions(&self, toasts: &Toasts) -> Vec<Subscription<Action>> {
+        toasts
+            .subscription(Duration::from_millis(250), Action::Tick)
+            .into_iter()
+            .collect()
+    }
+
+    fn view(&self, toasts: Toasts) -> View<Action> {
+        toast_stack(&toasts, Action::Dismiss)
+    }
+}
+
+fn states(runtime: &TestRuntime<Notifier>) -> Vec<String> {
+    runtime
+        .state()
+        .toasts()
+        .iter()
+        .map(|toast| format!("{} {:?}", toast.message(), toast.state()))
+        .collect()
+}
+
+fn kinds(runtime: &TestRuntime<Notifier>) -> Vec<SubscriptionKind> {
+    runtime.subscriptions().into_values().collect()
+}
+
+#[test]
+fn toasts_enter_expire_and_leave() {
+    let runtime = TestRuntime::<Notifier>::new(Toasts::new(
+        Duration::from_secs(3),
+        Duration::from_millis(200),
+    ));
+    let frame = SubscriptionKind::AnimationFrame;
+    runtime.dispatch(Action::Notify("Saved"));
+    assert!(query(&runtime.node(), "li[data-state=entering]").is_some());
+    assert_eq!(kinds(&runtime), vec![frame.clone()]);
+
+    runtime.emit(&frame, HandlerArg::from(0.0));
+    assert_eq!(states(&runtime), vec!["Saved Shown"]);
+    let every = SubscriptionKind::Every(Duration::from_millis(250));
+    assert_eq!(kinds(&runtime), vec![every.clone()]);
+
+    runtime.emit(&every, HandlerArg::from(3000.0));
+    assert!(query(&runtime.node(), "li[data-state=leaving]").is_some());
+    runtime.emit(&frame, HandlerArg::from(3100.0));
+    assert_eq!(states(&runtime), vec!["Saved Leaving"]);
+    runtime.emit(&frame, HandlerArg::from(3200.0));
+    assert!(states(&runtime).is_empty());
+    assert!(kinds(&runtime).is_empty());
+}
+
+#[test]
+fn dismissed_toasts_leave_early() {
+    let runtime = TestRuntime::<Notifier>::new(Toasts::new(
+        Duration::from_secs(3),
+        Duration::from_millis(200),
+    ));
+    runtime.dispatch(Action::Notify("Saved"));
+    runtime.dispatch(Action::Notify("Sent"));
+    runtime.emit(&SubscriptionKind::AnimationFrame, HandlerArg::from(0.0));
+    runtime.click("li[key='0'] button[aria-label=Dismiss]");
+    assert_eq!(states(&runtime), vec!["Saved Leaving", "Sent Shown"]);
+    assert_eq!(kinds(&runtime), vec![SubscriptionKind::AnimationFrame]);
+}
+
+// Ticks as a page a minute after load sees them: frames carry their start
+// time and intervals the time they fire at, read from the same clock.
+#[test]
+fn interval_and_frame_ticks_share_a_clock() {
+    let runtime = TestRuntime::<Notifier>::new(Toasts::new(
+        Duration::from_secs(3),
+        Duration::from_millis(200),
+    ));
+    let frame = SubscriptionKind::AnimationFrame;
+    let every = SubscriptionKind::Every(Duration::from_millis(250));
+    runtime.dispatch(Action::Notify("Saved"));
+    runtime.emit(&frame, HandlerArg::from(61_016.6));
+    assert_eq!(kinds(&runtime), vec![every.clone()]);
+
+    runtime.emit(&every, HandlerArg::from(61_268.1));
+    runtime.emit(&every, HandlerArg::from(63_767.9));
+    assert_eq!(states(&runtime), vec!["Saved Shown"]);
+    runtime.emit(&every, HandlerArg::from(64_018.2));
+    assert_eq!(states(&runtime), vec!["Saved Leaving"]);
+    assert_eq!(kinds(&runtime), vec![frame.clone()]);
+
+    runtime.emit(&frame, HandlerArg::from(64_116.6));
+    assert_eq!(states(&runtime), vec!["Saved Leaving"]);
+    runtime.emit(&frame, HandlerArg::from(64_233.3));
+    assert!(states(&runtime).is_empty());
+}
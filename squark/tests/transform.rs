@@ -0,0 +1,135 @@
+extern crate squark;
+
+use squark::{handler, Node, View};
+
+fn list() -> View<()> {
+    View::new(
+        "ul",
+        vec![],
+        vec![],
+        vec![
+            View::new(
+                "li",
+                vec![],
+                vec![("click".to_string(), handler(|_| None))],
+                vec!["a".into()],
+            )
+            .into(),
+            View::new("li", vec![], vec![], vec!["b".into()]).into(),
+        ],
+    )
+}
+
+#[test]
+fn walks_and_transforms_every_node() {
+    let mut texts = vec![];
+    list().node().walk(|node| {
+        if let Node::Text(text) = node {
+            texts.push(text.to_string());
+        }
+    });
+    assert_eq!(texts, vec!["a", "b"]);
+
+    let node = list().node().clone().map_transform(|node| match node {
+        Node::Element(mut el) => {
+            el.handlers_mut().clear();
+            if el.name() == "li" {
+                el.attributes_mut()
+                    .push(("data-test".to_string(), "item".into()));
+            }
+            Node::Element(el)
+        }
+        node => node,
+    });
+    assert_eq!(squark::query_all(&node, "li[data-test=item]").len(), 2);
+    assert!(squark::query_all(&node, "li")
+        .iter()
+        .all(|li| li.handlers().is_empty()));
+}
+
+#[test]
+fn hydration_attaches_handlers_to_prerendered_markup() {
+    use squark::testing::TestRuntime;
+    use squark::{ssr, App, Diff};
+
+    #[derive(Clone, Default)]
+    struct Page;
+
+    impl App for Page {
+        type State = ();
+        type Action = ();
+
+        fn reducer(&self, _: (), _: ()) {}
+
+        fn view(&self, _: ()) -> View<()> {
+            list()
+        }
+    }
+
+    assert!(ssr::prerender::<Page>(()).starts_with("<ul><li data-squark-h=\""));
+
+    let node = ssr::strip_handlers(list().node().clone());
+    let html = ssr::render_to_string(&node);
+    assert!(html.ends_with(":click\">a</li><li>b</li></ul>"));
+
+    let runtime = TestRuntime::<Page>::hydrated(());
+    let diffs: Vec<String> = runtime.take_diffs().iter().map(Diff::explain).collect();
+    let explained = diffs.join("\n");
+    assert!(!explained.contains("AddChild"));
+    assert!(explained.contains("PatchRoot -> PatchChild[0] -> SetHandler click="));
+    assert!(explained.contains("PatchRoot -> PatchChild[0] -> RemoveAttribute data-squark-h"));
+}
+
+#[test]
+fn normalizes_text_like_a_parser() {
+    use squark::Normalize;
+
+    let view: View<()> = View::new(
+        "p",
+        vec![],
+        vec![],
+        vec![
+            "a".into(),
+            View::null().into(),
+            "b".into(),
+            View::new("br", vec![], vec![], vec![]).into(),
+            "  ".into(),
+        ],
+    );
+    let node = Normalize::all().apply(view.node().clone());
+    assert_eq!(squark::ssr::render_to_string(&node), "<p>ab<br></p>");
+    assert_eq!(Normalize::all().apply(node.clone()), node);
+}
+
+#[test]
+fn keeps_whitespace_where_it_is_content() {
+    use squark::Normalize;
+
+    let code: View<()> = View::new("code", vec![], vec![], vec!["  ".into()]);
+    let view: View<()> = View::new(
+        "div",
+        vec![],
+        vec![],
+        vec![
+            " ".into(),
+            View::new("pre", vec![], vec![], vec![code.into(), "\n".into()]).into(),
+            View::new("textarea", vec![], vec![], vec![" ".into()]).into(),
+        ],
+    );
+    let node = Normalize::all().apply(view.node().clone());
+    assert_eq!(
+        squark::ssr::render_to_string(&node),
+        "<div><pre><code>  </code>\n</pre><textarea> </textarea></div>"
+    );
+    assert_eq!(Normalize::all().apply(node.clone()), node);
+    assert_eq!(
+        Normalize::default().apply(view.node().clone()),
+        *view.node()
+    );
+}
+
+#[test]
+fn void_elements_drop_children() {
+    let view: View<()> = View::new("input", vec![], vec![], vec!["ignored".into()]);
+    assert_eq!(squark::ssr::render_to_string(view.node()), "<input>");
+}
@@ -0,0 +1,53 @@
+extern crate squark;
+
+use squark::ssr::render_to_string;
+use squark::{href, set_blocked_url_schemes, src, Url, View};
+
+fn link(url: &str) -> View<()> {
+    View::new("a", vec![("href".to_string(), url.into())], vec![], vec![])
+}
+
+#[test]
+fn script_urls_are_rejected() {
+    assert!(Url::new("javascript:alert(1)").is_err());
+    assert!(Url::new(" Java\tScript:alert(1)").is_err());
+    assert!(Url::new("javascript&colon;alert(1)").is_err());
+    assert!(Url::new("&#106;avascript&#58alert(1)").is_err());
+    assert!(Url::new("/search?q=javascript:").is_ok());
+
+    let url = Url::new("https://example.com/").unwrap();
+    let image = View::<()>::new("img", vec![src(url.clone())], vec![], vec![]);
+    assert_eq!(
+        render_to_string(image.node()),
+        "<img src=\"https://example.com/\">"
+    );
+    let anchor = View::<()>::new("a", vec![href(url)], vec![], vec![]);
+    assert_eq!(
+        render_to_string(anchor.node()),
+        "<a href=\"https://example.com/\"></a>"
+    );
+}
+
+#[test]
+fn rendering_drops_blocked_urls_and_invalid_names() {
+    assert_eq!(
+        render_to_string(link("javascript:alert(1)").node()),
+        "<a></a>"
+    );
+    let view = View::<()>::new(
+        "p",
+        vec![("x onclick=\"alert(1)\"".to_string(), "".into())],
+        vec![],
+        vec![],
+    );
+    assert_eq!(render_to_string(view.node()), "<p></p>");
+
+    set_blocked_url_schemes(&["data"]);
+    assert!(Url::new("javascript:void(0)").is_ok());
+    assert!(Url::new("DATA:text/html,x").is_err());
+    assert_eq!(
+        render_to_string(link("javascript:void(0)").node()),
+        "<a href=\"javascript:void(0)\"></a>"
+    );
+    set_blocked_url_schemes(&["javascript"]);
+}
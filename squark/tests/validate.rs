@@ -0,0 +1,140 @@
+extern crate squark;
+
+use squark::validate::{check_children, validate, Violation};
+use squark::{Attribute, Node, View};
+
+fn view(name: &str, attributes: &[(&str, &str)], children: Vec<View<()>>) -> View<()> {
+    let attributes: Vec<Attribute> = attributes
+        .iter()
+        .map(|&(name, value)| (name.to_string(), value.into()))
+        .collect();
+    let children = children.into_iter().map(View::into).collect();
+    View::new(name, attributes, vec![], children)
+}
+
+fn el(name: &str, attributes: &[(&str, &str)], children: Vec<View<()>>) -> Node {
+    view(name, attributes, children).node().clone()
+}
+
+fn views(names: &[&str]) -> Vec<View<()>> {
+    names.iter().map(|name| view(name, &[], vec![])).collect()
+}
+
+fn children(names: &[&str]) -> Vec<Node> {
+    views(names)
+        .iter()
+        .map(|view| view.node().clone())
+        .collect()
+}
+
+fn invalid(parent: &str, child: &str) -> Violation {
+    Violation::InvalidChild(parent.to_string(), child.to_string())
+}
+
+#[test]
+fn elements_with_required_parents_are_allowed_only_there() {
+    for parent in &["ul", "ol", "menu"] {
+        assert_eq!(check_children(parent, &children(&["li", "li"])), vec![]);
+    }
+    assert_eq!(check_children("tr", &children(&["td", "th"])), vec![]);
+    assert_eq!(check_children("dl", &children(&["dt", "dd"])), vec![]);
+    assert_eq!(check_children("select", &children(&["option"])), vec![]);
+
+    assert_eq!(
+        check_children("div", &children(&["li"])),
+        vec![invalid("div", "li")]
+    );
+    assert_eq!(
+        check_children("table", &children(&["td"])),
+        vec![invalid("table", "td")]
+    );
+    assert_eq!(
+        check_children("section", &children(&["summary", "legend"])),
+        vec![invalid("section", "summary"), invalid("section", "legend")]
+    );
+}
+
+#[test]
+fn parents_reject_what_browsers_would_move() {
+    assert_eq!(check_children("p", &children(&["span", "em"])), vec![]);
+    assert_eq!(check_children("a", &children(&["span"])), vec![]);
+
+    assert_eq!(
+        check_children("p", &children(&["div"])),
+        vec![invalid("p", "div")]
+    );
+    assert_eq!(
+        check_children("a", &children(&["a"])),
+        vec![invalid("a", "a")]
+    );
+    assert_eq!(
+        check_children("ul", &children(&["div"])),
+        vec![invalid("ul", "div")]
+    );
+    assert_eq!(
+        check_children("select", &children(&["p"])),
+        vec![invalid("select", "p")]
+    );
+}
+
+#[test]
+fn void_elements_and_keys_are_checked() {
+    assert_eq!(
+        check_children("br", &[Node::Text("text".into())]),
+        vec![Violation::VoidChildren("br".to_string())]
+    );
+    assert_eq!(check_children("br", &[Node::Null]), vec![]);
+
+    let keyed = |key| el("li", &[("key", key)], vec![]);
+    assert_eq!(check_children("ul", &[keyed("a"), keyed("b")]), vec![]);
+    assert_eq!(
+        check_children("ul", &[keyed("a"), keyed("a")]),
+        vec![Violation::DuplicateKey("a".to_string())]
+    );
+}
+
+#[test]
+fn validates_whole_trees() {
+    let page = el(
+        "div",
+        &[("id", "page")],
+        vec![
+            view("p", &[("id", "intro")], views(&["div"])),
+            view("section", &[("id", "intro")], vec![]),
+        ],
+    );
+    assert_eq!(
+        validate(&page),
+        vec![
+            invalid("p", "div"),
+            Violation::DuplicateId("intro".to_string()),
+        ]
+    );
+    assert_eq!(validate(&el("ul", &[], views(&["li"]))), vec![]);
+}
+
+#[test]
+fn warns_about_each_violation_once() {
+    use squark::log;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let warnings = Rc::new(RefCell::new(vec![]));
+    let sink = warnings.clone();
+    log::set_sink(move |_, message: &str| sink.borrow_mut().push(message.to_string()));
+    for _ in 0..3 {
+        view("div", &[], views(&["li"]));
+    }
+    view("ol", &[], views(&["p"]));
+    log::reset_sink();
+
+    if cfg!(all(debug_assertions, feature = "debug-checks")) {
+        assert_eq!(
+            *warnings.borrow(),
+            vec![
+                "<li> is not allowed inside <div>",
+                "<p> is not allowed inside <ol>"
+            ]
+        );
+    }
+}